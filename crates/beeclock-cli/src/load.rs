@@ -0,0 +1,74 @@
+//! Clock definition loading.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use beeclock_core::ClockConfig;
+
+/// Supported clock definition formats.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    /// Pick a format from a file extension (`.toml` or `.json`).
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Ok(Format::Toml),
+            Some("json") => Ok(Format::Json),
+            _ => Err(format!(
+                "cannot infer format of '{}' (expected .toml or .json)",
+                path.display()
+            )
+            .into()),
+        }
+    }
+}
+
+/// Read and parse a clock definition file.
+pub fn load_config(path: &Path) -> Result<ClockConfig, Box<dyn Error>> {
+    let format = Format::from_path(path)?;
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("cannot read '{}': {err}", path.display()))?;
+    parse_config(&text, format).map_err(|err| format!("invalid '{}': {err}", path.display()).into())
+}
+
+/// Parse a clock definition from text.
+pub fn parse_config(text: &str, format: Format) -> Result<ClockConfig, Box<dyn Error>> {
+    match format {
+        Format::Toml => Ok(toml::from_str(text)?),
+        Format::Json => Ok(ClockConfig::from_json(text)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = include_str!("../examples/hms.toml");
+    const JSON: &str = include_str!("../examples/hms.json");
+
+    #[test]
+    fn parses_example_definitions() {
+        let toml = parse_config(TOML, Format::Toml).unwrap();
+        assert_eq!(toml.partitions.len(), 3);
+        assert_eq!(toml.pulses.len(), 3);
+        assert!(toml.build().is_ok());
+
+        let json = parse_config(JSON, Format::Json).unwrap();
+        assert_eq!(json.pulses[1].name, "minute");
+        assert!(json.build().is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_extension() {
+        assert!(Format::from_path(Path::new("clock.yaml")).is_err());
+        assert_eq!(
+            Format::from_path(Path::new("a/clock.toml")).unwrap(),
+            Format::Toml
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! # BeeClock CLI
+//!
+//! Loads a clock definition (TOML or JSON) and checks, runs, simulates, or
+//! queries it.
+//!
+//! ```text
+//! beeclock check clock.toml
+//! beeclock run clock.toml --rate 10
+//! beeclock simulate clock.toml --ticks 86400
+//! beeclock next clock.toml noon
+//! beeclock timeline clock.toml --ticks 3600 --svg timeline.svg
+//! beeclock repl clock.toml
+//! ```
+
+mod load;
+mod output;
+mod repl;
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use beeclock_core::Clock;
+use clap::{Parser, Subcommand};
+
+use crate::load::load_config;
+use crate::output::format_time;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "beeclock",
+    version,
+    about = "Run and inspect beeclock clock definitions"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check a clock definition without running it.
+    Check {
+        /// Clock definition (.toml or .json).
+        config: PathBuf,
+    },
+    /// Tick in real time and print fired pulses.
+    Run {
+        /// Clock definition (.toml or .json).
+        config: PathBuf,
+        /// Ticks per second.
+        #[arg(long, default_value_t = 1.0)]
+        rate: f64,
+        /// Stop after this many ticks (runs forever by default).
+        #[arg(long)]
+        ticks: Option<u64>,
+    },
+    /// Simulate ahead and summarize each pulse's schedule.
+    Simulate {
+        /// Clock definition (.toml or .json).
+        config: PathBuf,
+        /// Number of ticks to simulate.
+        #[arg(long)]
+        ticks: u64,
+    },
+    /// Print the tick at which a pulse next fires.
+    Next {
+        /// Clock definition (.toml or .json).
+        config: PathBuf,
+        /// Pulse name.
+        pulse: String,
+        /// Maximum ticks to search.
+        #[arg(long, default_value_t = 1_000_000)]
+        horizon: u64,
+    },
+    /// Draw each pulse's firing timeline as an ASCII chart or SVG file.
+    Timeline {
+        /// Clock definition (.toml or .json).
+        config: PathBuf,
+        /// Number of ticks to draw.
+        #[arg(long)]
+        ticks: u64,
+        /// Chart width in columns (ASCII output).
+        #[arg(long, default_value_t = 72)]
+        width: usize,
+        /// Write an SVG document to this path instead of printing ASCII.
+        #[arg(long)]
+        svg: Option<PathBuf>,
+    },
+    /// Step a clock interactively (type `help` for commands).
+    Repl {
+        /// Clock definition (.toml or .json).
+        config: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    match cli.command {
+        Command::Check { config } => {
+            load_config(&config)?.validate()?;
+            println!("{}: ok", config.display());
+            Ok(())
+        }
+        Command::Run {
+            config,
+            rate,
+            ticks,
+        } => {
+            let mut clock = load_config(&config)?.build()?;
+            run_realtime(&mut clock, rate, ticks)
+        }
+        Command::Simulate { config, ticks } => {
+            let clock = load_config(&config)?.build()?;
+            print_simulation(&clock, ticks);
+            Ok(())
+        }
+        Command::Next {
+            config,
+            pulse,
+            horizon,
+        } => {
+            let clock = load_config(&config)?.build()?;
+            if !clock.config().pulses.iter().any(|p| p.name == pulse) {
+                return Err(format!("unknown pulse '{pulse}'").into());
+            }
+            match clock.next_fire(&pulse, horizon) {
+                Some(tick) => println!("{pulse} fires next at tick {tick}"),
+                None => println!("{pulse} does not fire within {horizon} ticks"),
+            }
+            Ok(())
+        }
+        Command::Timeline {
+            config,
+            ticks,
+            width,
+            svg,
+        } => {
+            let report = load_config(&config)?.build()?.simulate(ticks);
+            match svg {
+                Some(path) => fs::write(&path, report.to_svg())
+                    .map_err(|err| format!("cannot write '{}': {err}", path.display()))?,
+                None => print!("{}", report.to_ascii(width)),
+            }
+            Ok(())
+        }
+        Command::Repl { config } => {
+            let clock = load_config(&config)?.build()?;
+            repl::run(clock)?;
+            Ok(())
+        }
+    }
+}
+
+fn run_realtime(clock: &mut Clock, rate: f64, limit: Option<u64>) -> Result<(), Box<dyn Error>> {
+    if !(rate.is_finite() && rate > 0.0) {
+        return Err("--rate must be a positive number".into());
+    }
+    let interval = Duration::from_secs_f64(1.0 / rate);
+    let start = Instant::now();
+    let order = clock.config().order;
+    let mut count = 0u64;
+
+    while limit.is_none_or(|limit| count < limit) {
+        count += 1;
+        // Schedule against the start time so sleep jitter doesn't accumulate.
+        let due = start + interval.mul_f64(count as f64);
+        if let Some(wait) = due.checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+
+        let outcome = clock.tick();
+        for pulse in outcome.pulses.iter() {
+            println!(
+                "[tick {}] {}  {}",
+                outcome.snapshot.tick,
+                format_time(&outcome.snapshot, order),
+                pulse.name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_simulation(clock: &Clock, ticks: u64) {
+    let report = clock.simulate(ticks);
+    let width = report
+        .pulses
+        .iter()
+        .map(|p| p.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    let show = |value: Option<u64>| value.map_or_else(|| String::from("-"), |v| v.to_string());
+
+    println!(
+        "simulated {} ticks from tick {}",
+        report.horizon, report.start_tick
+    );
+    println!(
+        "{:width$}  {:>8}  {:>10}  {:>10}  {:>8}  {:>8}",
+        "pulse", "fires", "first", "last", "min gap", "max gap"
+    );
+    for pulse in &report.pulses {
+        println!(
+            "{:width$}  {:>8}  {:>10}  {:>10}  {:>8}  {:>8}",
+            pulse.name,
+            pulse.count(),
+            show(pulse.first()),
+            show(pulse.last()),
+            show(pulse.min_gap()),
+            show(pulse.max_gap()),
+        );
+    }
+}
@@ -0,0 +1,18 @@
+//! Shared text formatting.
+
+use beeclock_core::{ClockSnapshot, PartitionOrder};
+
+/// Render partitions most-significant first, e.g. `hour=1 min=2 sec=3`.
+pub fn format_time(snapshot: &ClockSnapshot, order: PartitionOrder) -> String {
+    let parts: Vec<String> = snapshot
+        .partitions
+        .iter()
+        .map(|p| format!("{}={}", p.name, p.value))
+        .collect();
+    match order {
+        PartitionOrder::LeastSignificantFirst => {
+            parts.into_iter().rev().collect::<Vec<_>>().join(" ")
+        }
+        PartitionOrder::MostSignificantFirst => parts.join(" "),
+    }
+}
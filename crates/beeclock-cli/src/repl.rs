@@ -0,0 +1,157 @@
+//! Interactive REPL for stepping a clock.
+
+use std::io::{self, BufRead, Write};
+
+use beeclock_core::{Clock, PartitionOrder};
+
+use crate::output::format_time;
+
+const HELP: &str = "\
+commands:
+  tick [n]              advance n ticks (default 1), printing fired pulses
+  show                  print tick, epoch and partition values
+  set <partition> <v>   set a partition value
+  explain <pulse>       show how a pulse's condition evaluates now
+  pulses                list configured pulses
+  help                  show this help
+  quit                  exit";
+
+/// REPL session state.
+pub struct Repl {
+    clock: Clock,
+    order: PartitionOrder,
+}
+
+/// Whether the session should keep reading commands.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+impl Repl {
+    pub fn new(clock: Clock) -> Self {
+        let order = clock.config().order;
+        Self { clock, order }
+    }
+
+    /// Execute one command line, writing its output to `out`.
+    pub fn execute(&mut self, line: &str, out: &mut impl Write) -> io::Result<Flow> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Flow::Continue);
+        };
+        let args: Vec<&str> = words.collect();
+
+        match (command, args.as_slice()) {
+            ("tick", []) => self.tick(1, out)?,
+            ("tick", [n]) => match n.parse() {
+                Ok(n) => self.tick(n, out)?,
+                Err(_) => writeln!(out, "error: '{n}' is not a tick count")?,
+            },
+            ("show", []) => self.show(out)?,
+            ("set", [name, value]) => match value.parse() {
+                Ok(value) => match self.clock.set_partition(name, value) {
+                    Ok(()) => self.show(out)?,
+                    Err(err) => writeln!(out, "error: {err}")?,
+                },
+                Err(_) => writeln!(out, "error: '{value}' is not a value")?,
+            },
+            ("explain", [pulse]) => match self.clock.explain(pulse) {
+                Some(explanation) => write!(out, "{explanation}")?,
+                None => writeln!(out, "error: unknown pulse '{pulse}'")?,
+            },
+            ("pulses", []) => {
+                for idx in 0..self.clock.pulse_count() {
+                    writeln!(out, "{}", self.clock.pulse_name(idx).unwrap_or_default())?;
+                }
+            }
+            ("help", []) => writeln!(out, "{HELP}")?,
+            ("quit" | "exit", []) => return Ok(Flow::Quit),
+            _ => writeln!(out, "error: unrecognized command (try `help`)")?,
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn tick(&mut self, n: u64, out: &mut impl Write) -> io::Result<()> {
+        for _ in 0..n {
+            let outcome = self.clock.tick();
+            for pulse in outcome.pulses.iter() {
+                writeln!(
+                    out,
+                    "[tick {}] {}  {}",
+                    outcome.snapshot.tick,
+                    format_time(&outcome.snapshot, self.order),
+                    pulse.name
+                )?;
+            }
+        }
+        self.show(out)
+    }
+
+    fn show(&self, out: &mut impl Write) -> io::Result<()> {
+        let snapshot = self.clock.snapshot();
+        writeln!(
+            out,
+            "tick {} epoch {}  {}",
+            snapshot.tick,
+            snapshot.epoch,
+            format_time(&snapshot, self.order)
+        )
+    }
+}
+
+/// Run an interactive session on stdin/stdout.
+pub fn run(clock: Clock) -> io::Result<()> {
+    let mut repl = Repl::new(clock);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut line = String::new();
+
+    writeln!(stdout, "beeclock repl - type `help` for commands")?;
+    loop {
+        write!(stdout, "beeclock> ")?;
+        stdout.flush()?;
+        line.clear();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if repl.execute(&line, &mut stdout)? == Flow::Quit {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::{parse_config, Format};
+
+    fn session() -> Repl {
+        let config = parse_config(include_str!("../examples/hms.toml"), Format::Toml).unwrap();
+        Repl::new(config.build().unwrap())
+    }
+
+    fn run(repl: &mut Repl, line: &str) -> String {
+        let mut out = Vec::new();
+        repl.execute(line, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn steps_sets_and_explains() {
+        let mut repl = session();
+        assert_eq!(
+            run(&mut repl, "tick 15"),
+            "[tick 15] hour=0 min=0 sec=15  heartbeat\ntick 15 epoch 0  hour=0 min=0 sec=15\n"
+        );
+        assert_eq!(
+            run(&mut repl, "set hour 12"),
+            "tick 15 epoch 0  hour=12 min=0 sec=15\n"
+        );
+        assert!(run(&mut repl, "explain minute").contains("✗ sec == 0 (sec is 15)"));
+        assert!(run(&mut repl, "set hour 24").starts_with("error: value 24 out of range"));
+        assert!(run(&mut repl, "explain nope").starts_with("error: unknown pulse"));
+        assert_eq!(repl.execute("quit", &mut Vec::new()).unwrap(), Flow::Quit);
+    }
+}
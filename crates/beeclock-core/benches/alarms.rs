@@ -0,0 +1,72 @@
+//! Per-tick cost of pending alarms: timer wheel vs. a linear scan.
+//!
+//! Run with `cargo bench --bench alarms`. Each case keeps `n` one-shot
+//! alarms pending at pseudo-random future ticks and reports the mean time
+//! per tick.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use beeclock_core::{AlarmTarget, Clock};
+
+const TICKS: u64 = 20_000;
+
+fn due_ticks(n: usize) -> impl Iterator<Item = u64> {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    (0..n).map(move |_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        1 + state % (TICKS * 4)
+    })
+}
+
+/// The pre-wheel approach: check every pending alarm on every tick.
+fn linear_scan(n: usize) -> Duration {
+    let mut pending: Vec<(u64, usize)> = due_ticks(n).zip(0..).collect();
+    let start = Instant::now();
+    for tick in 1..=TICKS {
+        pending.retain(|&(at, id)| {
+            if at == tick {
+                black_box(id);
+            }
+            at != tick
+        });
+    }
+    start.elapsed()
+}
+
+fn timer_wheel(n: usize) -> Duration {
+    let mut clock = Clock::builder()
+        .least_significant_first()
+        .partition("sec", 60)
+        .build()
+        .unwrap();
+    for (id, at) in due_ticks(n).enumerate() {
+        clock
+            .set_alarm(format!("a{id}"), AlarmTarget::Tick(at))
+            .unwrap();
+    }
+    let mut buffer = clock.tick_buffer();
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        clock.tick_into(&mut buffer);
+        black_box(&buffer.alarms);
+    }
+    start.elapsed()
+}
+
+fn main() {
+    println!(
+        "{:>8}  {:>14}  {:>14}",
+        "alarms", "scan ns/tick", "wheel ns/tick"
+    );
+    for n in [100, 1_000, 10_000, 100_000] {
+        let per_tick = |d: Duration| d.as_nanos() / TICKS as u128;
+        println!(
+            "{n:>8}  {:>14}  {:>14}",
+            per_tick(linear_scan(n)),
+            per_tick(timer_wheel(n))
+        );
+    }
+}
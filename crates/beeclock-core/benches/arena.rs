@@ -0,0 +1,65 @@
+//! Bulk ticking: one `Clock` per agent vs. a shared `ClockArena`.
+//!
+//! Run with `cargo bench --bench arena`. Each case ticks `n` clocks with the
+//! same configuration and reports the mean time per clock tick.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use beeclock_core::{Clock, ClockArena, ClockConfig};
+
+const TICKS: u64 = 200;
+
+fn config() -> ClockConfig {
+    Clock::builder()
+        .least_significant_first()
+        .partition("sec", 60)
+        .partition("min", 60)
+        .partition("hour", 24)
+        .pulse_when("minute", "changed(min)".parse().unwrap())
+        .pulse_when("shift", "hour % 8 == 0 && changed(hour)".parse().unwrap())
+        .build()
+        .unwrap()
+        .config()
+}
+
+fn separate_clocks(n: usize) -> Duration {
+    let config = config();
+    let mut clocks: Vec<Clock> = (0..n).map(|_| config.clone().build().unwrap()).collect();
+    let mut buffer = clocks[0].tick_buffer();
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        for clock in &mut clocks {
+            clock.tick_into(&mut buffer);
+            black_box(&buffer.fired);
+        }
+    }
+    start.elapsed()
+}
+
+fn arena(n: usize) -> Duration {
+    let mut arena = ClockArena::new(config()).unwrap();
+    for _ in 0..n {
+        arena.push();
+    }
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        black_box(arena.tick_all());
+    }
+    start.elapsed()
+}
+
+fn main() {
+    println!(
+        "{:>8}  {:>14}  {:>14}",
+        "clocks", "clock ns/tick", "arena ns/tick"
+    );
+    for n in [1_000, 10_000, 100_000] {
+        let per_tick = |d: Duration| d.as_nanos() / (TICKS as u128 * n as u128);
+        println!(
+            "{n:>8}  {:>14}  {:>14}",
+            per_tick(separate_clocks(n)),
+            per_tick(arena(n))
+        );
+    }
+}
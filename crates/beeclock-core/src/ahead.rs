@@ -0,0 +1,210 @@
+//! Run-ahead cache of upcoming outcomes.
+//!
+//! [`Clock::precompute`] steps a detached copy of the clock through the next
+//! ticks ahead of time, on a background thread under std, and [`Clock::tick`]
+//! hands out the results in order instead of evaluating pulses itself. Each
+//! entry carries the clock state after its tick, which the clock takes on as
+//! the cache drains, so stateful pulses, alarms and machines end up exactly
+//! where ticking would have left them.
+//!
+//! Any change that could alter later outcomes (partitions, pulses, alarms,
+//! machines, skips) throws the cache away and starts a new one for the ticks
+//! still owed.
+
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::collections::VecDeque;
+
+use crate::{Clock, TickOutcome};
+
+/// An outcome and the clock state right after it.
+type Ahead = (TickOutcome, Box<Clock>);
+
+/// Outcomes computed ahead of the clock, oldest first.
+#[derive(Debug)]
+pub(crate) struct RunAhead {
+    /// Ticks asked for and not yet handed out.
+    remaining: usize,
+    /// Behind a mutex only so the clock stays `Sync`; reached through
+    /// `get_mut`, it is never locked.
+    #[cfg(feature = "std")]
+    ready: std::sync::Mutex<std::sync::mpsc::Receiver<Ahead>>,
+    #[cfg(not(feature = "std"))]
+    ready: VecDeque<Ahead>,
+}
+
+impl RunAhead {
+    /// Step `clock`, a detached copy, through `ticks` ticks or until it
+    /// halts.
+    fn start(mut clock: Clock, ticks: usize) -> Self {
+        #[cfg(feature = "std")]
+        let ready = {
+            let (sender, ready) = std::sync::mpsc::channel();
+            std::thread::spawn(move || {
+                for _ in 0..ticks {
+                    let outcome = clock.step();
+                    let next = Box::new(clock.detached());
+                    // A dropped receiver means the cache was invalidated.
+                    if sender.send((outcome, next)).is_err() || clock.halted {
+                        break;
+                    }
+                }
+            });
+            std::sync::Mutex::new(ready)
+        };
+        #[cfg(not(feature = "std"))]
+        let ready = {
+            let mut ready = VecDeque::with_capacity(ticks);
+            for _ in 0..ticks {
+                let outcome = clock.step();
+                ready.push_back((outcome, Box::new(clock.detached())));
+                if clock.halted {
+                    break;
+                }
+            }
+            ready
+        };
+        Self {
+            remaining: ticks,
+            ready,
+        }
+    }
+
+    /// The next entry, waiting for the background thread if it is behind;
+    /// `None` once the clock halted ahead.
+    fn next(&mut self) -> Option<Ahead> {
+        #[cfg(feature = "std")]
+        let next = self
+            .ready
+            .get_mut()
+            .ok()
+            .and_then(|ready| ready.recv().ok());
+        #[cfg(not(feature = "std"))]
+        let next = self.ready.pop_front();
+        self.remaining -= usize::from(next.is_some());
+        next
+    }
+}
+
+impl Clock {
+    /// Compute the outcomes of the next `ticks` ticks ahead of time, on a
+    /// background thread under std, for [`Clock::tick`] to hand out.
+    ///
+    /// Ticking drains the cache in order, still notifying subscribers,
+    /// running [epoch hooks](Clock::on_epoch_change) and recording metrics; if the thread is behind, the tick waits for it.
+    /// Anything that could change later outcomes, like
+    /// [`Clock::set_partition`], an alarm or [`Clock::skip`], recomputes the
+    /// ticks still owed. [`Clock::tick_into`] bypasses and discards the
+    /// cache, and ticks served from it are not [profiled](Clock::set_profiling).
+    /// Replaces any earlier cache; `0`, a halted clock or one with child
+    /// clocks (whose own subscribers would miss the ticks) caches nothing.
+    ///
+    /// ```
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::builder().pulse_every("beat", 4).build().unwrap();
+    /// clock.precompute(8);
+    /// let fired = (0..8).filter(|_| !clock.tick().pulses.is_empty()).count();
+    /// assert_eq!(fired, 2);
+    /// assert_eq!(clock.tick_count(), 8);
+    /// ```
+    pub fn precompute(&mut self, ticks: usize) {
+        self.ahead = (ticks > 0 && !self.halted && self.children.is_empty())
+            .then(|| RunAhead::start(self.detached(), ticks));
+    }
+
+    /// Next outcome from the run-ahead cache, advancing the clock to the
+    /// state after it.
+    pub(crate) fn take_ahead(&mut self) -> Option<TickOutcome> {
+        let mut ahead = self.ahead.take()?;
+        let (outcome, next) = ahead.next()?;
+        // The copy ran without hooks; run them for the tick taken on.
+        let epoch = self.epoch;
+        self.adopt(*next);
+        self.epoch_changed(epoch);
+        if ahead.remaining > 0 && !self.halted {
+            self.ahead = Some(ahead);
+        }
+        Some(outcome)
+    }
+
+    /// Recompute the run-ahead cache, if any, from the current state.
+    pub(crate) fn rerun_ahead(&mut self) {
+        if let Some(ahead) = self.ahead.take() {
+            self.precompute(ahead.remaining);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AlarmTarget;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    fn clock() -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("sec", 10)
+            .pulse_every("beat", 3)
+            .pulse_when("top", "sec == 0".parse().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn fired(outcomes: impl IntoIterator<Item = TickOutcome>) -> Vec<(u64, Vec<String>)> {
+        outcomes
+            .into_iter()
+            .map(|o| {
+                let names = o.pulses.iter().map(|p| p.name.clone()).collect();
+                (o.snapshot.tick, names)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cached_ticks_match_live_ones_across_mutations() {
+        let run = |clock: &mut Clock| {
+            let mut outcomes = Vec::new();
+            for _ in 0..5 {
+                outcomes.push(clock.tick());
+            }
+            clock.set_partition("sec", 7).unwrap();
+            clock.start_countdown("ding", 4).unwrap();
+            for _ in 0..6 {
+                outcomes.push(clock.tick());
+            }
+            clock.skip(5);
+            outcomes.extend((0..4).map(|_| clock.tick()));
+            clock.set_alarm("late", AlarmTarget::Tick(40)).unwrap();
+            outcomes.extend((0..30).map(|_| clock.tick()));
+            fired(outcomes)
+        };
+
+        let mut live = clock();
+        let mut cached = clock();
+        cached.precompute(45);
+        assert_eq!(run(&mut cached), run(&mut live));
+        assert_eq!(cached.tick_count(), live.tick_count());
+        assert_eq!(cached.snapshot().get("sec"), live.snapshot().get("sec"));
+        assert!(cached.ahead.is_none());
+    }
+
+    #[test]
+    fn stops_at_the_run_budget_and_keeps_subscribers() {
+        let mut clock = clock();
+        clock.set_run_for(4).unwrap();
+        #[cfg(feature = "std")]
+        let outcomes = clock.subscribe();
+        clock.precompute(10);
+        let ticks: Vec<u64> = (0..6).map(|_| clock.tick().snapshot.tick).collect();
+        assert_eq!(ticks, [1, 2, 3, 4, 4, 4]);
+        assert!(clock.is_halted());
+        #[cfg(feature = "std")]
+        assert_eq!(outcomes.try_iter().count(), 4);
+
+        clock.precompute(3);
+        assert!(clock.ahead.is_none());
+    }
+}
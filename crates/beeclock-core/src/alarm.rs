@@ -0,0 +1,404 @@
+//! One-shot alarms.
+//!
+//! An alarm fires once, as a pulse named after the alarm, on the first tick
+//! that reaches its [`AlarmTarget`], and then clears itself. Unlike pulses,
+//! alarms are set and cancelled at runtime and are not part of
+//! [`ClockConfig`](crate::ClockConfig).
+//!
+//! Countdowns are alarms at `now + ticks` that remember their length, so they
+//! can report the ticks remaining and be restarted.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::wheel::TimerWheel;
+
+/// When an alarm goes off.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AlarmTarget {
+    /// At this absolute tick.
+    Tick(u64),
+    /// On the first tick where every listed partition has the given value.
+    Partitions(Vec<(String, u64)>),
+}
+
+impl AlarmTarget {
+    /// Partition-value pattern from `(name, value)` pairs.
+    pub fn partitions<N: Into<String>>(values: impl IntoIterator<Item = (N, u64)>) -> Self {
+        AlarmTarget::Partitions(
+            values
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Alarm {
+    name: Arc<str>,
+    target: AlarmTarget,
+    /// Partition indices and values for [`AlarmTarget::Partitions`].
+    pattern: Vec<(usize, u64)>,
+    /// Length of a countdown, in ticks.
+    countdown: Option<u64>,
+}
+
+impl Alarm {
+    fn due_in(
+        &self,
+        now: u64,
+        mut until_pattern: impl FnMut(&[(usize, u64)]) -> Option<u64>,
+    ) -> Option<u64> {
+        match self.target {
+            AlarmTarget::Tick(at) => Some(at.saturating_sub(now).max(1)),
+            AlarmTarget::Partitions(_) => until_pattern(&self.pattern),
+        }
+    }
+}
+
+/// Pending alarms, in the order they were set.
+///
+/// Tick targets are scheduled on a [`TimerWheel`], so finding the alarms due
+/// on a tick doesn't scan every pending one; partition patterns are checked
+/// each tick. Cancelled and restarted alarms leave stale wheel entries that
+/// are skipped when they come due.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Alarms {
+    /// Keyed by a set-order id, so iteration follows set order.
+    alarms: BTreeMap<u64, Alarm>,
+    names: BTreeMap<Arc<str>, u64>,
+    wheel: TimerWheel,
+    /// Ids of [`AlarmTarget::Partitions`] alarms.
+    patterns: Vec<u64>,
+    next_id: u64,
+    /// Ids due on the current tick (reused across ticks).
+    due: Vec<u64>,
+}
+
+impl Alarms {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.alarms.is_empty()
+    }
+
+    /// Set an alarm (caller validates the target and resolves `pattern`),
+    /// replacing any pending alarm with the same name.
+    pub fn set(&mut self, name: String, now: u64, target: AlarmTarget, pattern: Vec<(usize, u64)>) {
+        self.insert(name, now, target, pattern, None);
+    }
+
+    /// Start a countdown of `ticks` from `now`, replacing any pending alarm
+    /// with the same name.
+    pub fn start_countdown(&mut self, name: String, now: u64, ticks: u64) {
+        let target = AlarmTarget::Tick(now.saturating_add(ticks));
+        self.insert(name, now, target, Vec::new(), Some(ticks));
+    }
+
+    /// Set an alarm with an optional countdown length, replacing any
+    /// pending alarm with the same name. Tick targets at or before `now`
+    /// fire on the next tick.
+    pub fn insert(
+        &mut self,
+        name: String,
+        now: u64,
+        target: AlarmTarget,
+        pattern: Vec<(usize, u64)>,
+        countdown: Option<u64>,
+    ) {
+        self.cancel(&name);
+        let id = self.next_id;
+        self.next_id += 1;
+        match target {
+            AlarmTarget::Tick(at) => self.wheel.insert(now, at, id),
+            AlarmTarget::Partitions(_) => self.patterns.push(id),
+        }
+        let name: Arc<str> = Arc::from(name);
+        self.names.insert(Arc::clone(&name), id);
+        self.alarms.insert(
+            id,
+            Alarm {
+                name,
+                target,
+                pattern,
+                countdown,
+            },
+        );
+    }
+
+    fn countdown_id(&self, name: &str) -> Option<u64> {
+        let id = *self.names.get(name)?;
+        self.alarms[&id].countdown.map(|_| id)
+    }
+
+    /// Reset a pending countdown to its full length from `now`.
+    pub fn restart_countdown(&mut self, name: &str, now: u64) -> bool {
+        let Some(id) = self.countdown_id(name) else {
+            return false;
+        };
+        let alarm = self.alarms.get_mut(&id).expect("named alarm exists");
+        let at = now.saturating_add(alarm.countdown.unwrap_or_default());
+        alarm.target = AlarmTarget::Tick(at);
+        self.wheel.insert(now, at, id);
+        true
+    }
+
+    /// Cancel a pending countdown, returning whether one was running.
+    pub fn cancel_countdown(&mut self, name: &str) -> bool {
+        self.countdown_id(name).is_some() && self.cancel(name)
+    }
+
+    /// Ticks until a pending countdown fires, counted from `now`.
+    pub fn remaining(&self, name: &str, now: u64) -> Option<u64> {
+        let id = self.countdown_id(name)?;
+        match self.alarms[&id].target {
+            AlarmTarget::Tick(at) => Some(at.saturating_sub(now)),
+            AlarmTarget::Partitions(_) => None,
+        }
+    }
+
+    /// Cancel a pending alarm, returning whether one was set.
+    pub fn cancel(&mut self, name: &str) -> bool {
+        match self.names.get(name) {
+            Some(&id) => {
+                self.remove(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AlarmTarget> {
+        let id = self.names.get(name)?;
+        Some(&self.alarms[id].target)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AlarmTarget)> {
+        self.alarms
+            .values()
+            .map(|alarm| (&*alarm.name, &alarm.target))
+    }
+
+    /// Pending alarms with their countdown lengths, in set order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &AlarmTarget, Option<u64>)> {
+        self.alarms
+            .values()
+            .map(|alarm| (&*alarm.name, &alarm.target, alarm.countdown))
+    }
+
+    /// Report and clear every alarm due at `tick`, in set order. Tick targets
+    /// skipped over (see [`Clock::observe`](crate::Clock::observe)) are due
+    /// on the next tick.
+    pub fn fire_due(&mut self, tick: u64, values: &[u64], mut fired: impl FnMut(&Arc<str>)) {
+        let (alarms, due) = (&self.alarms, &mut self.due);
+        due.clear();
+        self.wheel.advance(tick, |at, id| {
+            if alarms
+                .get(&id)
+                .is_some_and(|a| a.target == AlarmTarget::Tick(at))
+            {
+                due.push(id);
+            }
+        });
+        due.extend(self.patterns.iter().copied().filter(|id| {
+            alarms[id]
+                .pattern
+                .iter()
+                .all(|&(idx, value)| values[idx] == value)
+        }));
+        if self.due.is_empty() {
+            return;
+        }
+        self.due.sort_unstable();
+        self.due.dedup();
+        for i in 0..self.due.len() {
+            let alarm = self.remove(self.due[i]);
+            fired(&alarm.name);
+        }
+    }
+
+    /// Ticks from `now` until the earliest alarm fires, given the ticks
+    /// until a partition pattern next matches.
+    pub fn earliest(
+        &self,
+        now: u64,
+        mut until_pattern: impl FnMut(&[(usize, u64)]) -> Option<u64>,
+    ) -> Option<u64> {
+        self.alarms
+            .values()
+            .filter_map(|alarm| alarm.due_in(now, &mut until_pattern))
+            .min()
+    }
+
+    /// Ticks from `now` until the named alarm fires, as for
+    /// [`earliest`](Self::earliest).
+    pub fn due_in(
+        &self,
+        name: &str,
+        now: u64,
+        until_pattern: impl FnMut(&[(usize, u64)]) -> Option<u64>,
+    ) -> Option<u64> {
+        let id = self.names.get(name)?;
+        self.alarms[id].due_in(now, until_pattern)
+    }
+
+    fn remove(&mut self, id: u64) -> Alarm {
+        let alarm = self.alarms.remove(&id).expect("alarm id is live");
+        self.names.remove(&alarm.name);
+        if matches!(alarm.target, AlarmTarget::Partitions(_)) {
+            self.patterns.retain(|&p| p != id);
+        }
+        if self.alarms.is_empty() {
+            self.wheel.clear();
+        }
+        alarm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, ClockError, PartitionOrder};
+
+    fn fires(clock: &mut Clock, ticks: u64) -> Vec<(u64, String)> {
+        (0..ticks)
+            .flat_map(|_| {
+                let outcome = clock.tick();
+                let tick = outcome.snapshot.tick;
+                outcome
+                    .pulses
+                    .iter()
+                    .map(|p| (tick, p.name.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn alarms_fire_once_and_clear() {
+        let mut clock = Clock::default();
+        clock.set_alarm("wake", AlarmTarget::Tick(3)).unwrap();
+        clock
+            .set_alarm("top", AlarmTarget::partitions([("sec", 0), ("min", 1)]))
+            .unwrap();
+        clock.set_alarm("never", AlarmTarget::Tick(5)).unwrap();
+        assert!(clock.cancel_alarm("never"));
+        assert!(!clock.cancel_alarm("never"));
+        assert_eq!(clock.alarms().count(), 2);
+
+        assert_eq!(
+            fires(&mut clock, 200),
+            [(3, "wake".into()), (60, "top".into())]
+        );
+        assert!(clock.alarm("top").is_none());
+
+        clock.set_alarm("again", AlarmTarget::Tick(201)).unwrap();
+        let mut buffer = clock.tick_buffer();
+        clock.tick_into(&mut buffer);
+        assert_eq!(buffer.alarms.len(), 1);
+        assert_eq!(&*buffer.alarms[0], "again");
+    }
+
+    #[test]
+    fn pattern_alarms_know_their_tick() {
+        let mut clock = Clock::default();
+        clock.observe(8 * 3600 - 1);
+        clock
+            .set_alarm("wake", AlarmTarget::partitions([("hour", 7), ("min", 30)]))
+            .unwrap();
+        // Wraps to tomorrow, at the first second of 07:30.
+        let due = 24 * 3600 + 7 * 3600 + 30 * 60;
+        assert_eq!(clock.alarm_due("wake"), Some(due));
+        clock
+            .set_alarm("soon", AlarmTarget::Tick(9 * 3600))
+            .unwrap();
+        assert_eq!(clock.alarm_due("soon"), Some(9 * 3600));
+        assert_eq!(clock.alarm_due("missing"), None);
+
+        clock.observe(due - 2);
+        assert_eq!(fires(&mut clock, 61), [(due, "wake".into())]);
+    }
+
+    #[test]
+    fn pattern_due_ticks_match_brute_force() {
+        let patterns = [
+            vec![("a", 2)],
+            vec![("b", 0), ("a", 1)],
+            vec![("c", 1), ("a", 0)],
+            vec![("a", 2), ("b", 3), ("c", 0)],
+            vec![],
+        ];
+        for order in [
+            PartitionOrder::LeastSignificantFirst,
+            PartitionOrder::MostSignificantFirst,
+        ] {
+            for start in 0..24 {
+                for pattern in &patterns {
+                    let mut clock = Clock::builder()
+                        .partition_order(order)
+                        .partition("a", 3)
+                        .partition("b", 4)
+                        .partition("c", 2)
+                        .build()
+                        .unwrap();
+                    clock.skip(start);
+                    let target = AlarmTarget::partitions(pattern.iter().cloned());
+                    clock.set_alarm("x", target).unwrap();
+                    let due = clock.alarm_due("x").unwrap();
+                    let fired = fires(&mut clock, 30);
+                    assert_eq!(fired, [(due, "x".into())], "{order:?} {start} {pattern:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn countdowns_restart_and_cancel() {
+        let mut clock = Clock::default();
+        clock.start_countdown("boil", 5).unwrap();
+        clock.start_countdown("rest", 3).unwrap();
+        assert_eq!(fires(&mut clock, 2), []);
+        assert_eq!(clock.remaining("boil"), Some(3));
+
+        assert!(clock.restart_countdown("boil"));
+        assert_eq!(clock.remaining("boil"), Some(5));
+        assert!(clock.cancel_countdown("rest"));
+        assert!(!clock.cancel_countdown("rest"));
+        assert_eq!(fires(&mut clock, 10), [(7, "boil".into())]);
+        assert_eq!(clock.remaining("boil"), None);
+        assert!(!clock.restart_countdown("boil"));
+
+        clock.set_alarm("plain", AlarmTarget::Tick(20)).unwrap();
+        assert_eq!(clock.remaining("plain"), None);
+        assert!(!clock.cancel_countdown("plain"));
+        assert!(matches!(
+            clock.start_countdown("zero", 0),
+            Err(ClockError::ZeroDelay { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_unreachable_targets() {
+        let mut clock = Clock::default();
+        clock.tick();
+        assert!(matches!(
+            clock.set_alarm("late", AlarmTarget::Tick(1)),
+            Err(ClockError::AlarmInPast { .. })
+        ));
+        assert!(matches!(
+            clock.set_alarm("x", AlarmTarget::partitions([("day", 1)])),
+            Err(ClockError::PartitionNotFound { .. })
+        ));
+        assert!(matches!(
+            clock.set_alarm("x", AlarmTarget::partitions([("hour", 24)])),
+            Err(ClockError::ValueOutOfRange { .. })
+        ));
+    }
+}
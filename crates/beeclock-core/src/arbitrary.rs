@@ -0,0 +1,193 @@
+//! Proptest `Arbitrary` implementations (requires the `proptest` feature).
+//!
+//! Generated clock builders are always valid: conditions only reference
+//! generated partitions, use non-zero periods/moduli, and ordered tick ranges.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec::Vec;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use crate::{ClockBuilder, PartitionOrder, PartitionSpec, PulseCondition, PulseSpec};
+
+/// Parameters for generating [`PulseCondition`]s.
+#[derive(Clone, Debug)]
+pub struct ConditionParams {
+    /// Partitions that generated conditions may reference.
+    pub partitions: Vec<PartitionSpec>,
+    /// Upper bound for periods and tick range endpoints.
+    pub max_tick: u64,
+    /// Maximum nesting depth of `Not`/`And`/`Or`.
+    pub depth: u32,
+}
+
+impl Default for ConditionParams {
+    fn default() -> Self {
+        Self {
+            partitions: ["sec", "min", "hour"]
+                .into_iter()
+                .zip([60, 60, 24])
+                .map(|(name, modulus)| PartitionSpec::new(name, modulus))
+                .collect(),
+            max_tick: 10_000,
+            depth: 3,
+        }
+    }
+}
+
+impl Arbitrary for PartitionOrder {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(PartitionOrder::LeastSignificantFirst),
+            Just(PartitionOrder::MostSignificantFirst),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for PartitionSpec {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        ("[a-z]{1,8}", 1u64..=1000)
+            .prop_map(|(name, modulus)| PartitionSpec::new(name, modulus))
+            .boxed()
+    }
+}
+
+impl Arbitrary for PulseCondition {
+    type Parameters = ConditionParams;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(params: ConditionParams) -> Self::Strategy {
+        let max_tick = params.max_tick.max(1);
+        let every = (1..=max_tick).prop_map(PulseCondition::Every).boxed();
+        let range = (0..=max_tick, 0..=max_tick)
+            .prop_map(|(a, b)| PulseCondition::TickRange {
+                start: a.min(b),
+                end: a.max(b),
+            })
+            .boxed();
+
+        let leaf = if params.partitions.is_empty() {
+            prop_oneof![every, range].boxed()
+        } else {
+            let equals = select(params.partitions.clone())
+                .prop_flat_map(|spec| {
+                    (0..spec.modulus).prop_map(move |value| PulseCondition::PartitionEquals {
+                        name: spec.name.clone(),
+                        value,
+                    })
+                })
+                .boxed();
+            let modulo = select(params.partitions)
+                .prop_flat_map(|spec| {
+                    (1..=spec.modulus).prop_flat_map(move |modulus| {
+                        let name = spec.name.clone();
+                        (0..modulus).prop_map(move |remainder| PulseCondition::PartitionModulo {
+                            name: name.clone(),
+                            modulus,
+                            remainder,
+                        })
+                    })
+                })
+                .boxed();
+            prop_oneof![every, range, equals, modulo].boxed()
+        };
+
+        leaf.prop_recursive(params.depth, 32, 4, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|c| PulseCondition::Not(Box::new(c))),
+                vec(inner.clone(), 0..4).prop_map(PulseCondition::And),
+                vec(inner, 0..4).prop_map(PulseCondition::Or),
+            ]
+        })
+        .boxed()
+    }
+}
+
+impl Arbitrary for ClockBuilder {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Up to 4 partitions (`p0`..) with moduli 1..=12 and up to 6 pulses.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (any::<PartitionOrder>(), vec(1u64..=12, 0..=4))
+            .prop_flat_map(|(order, moduli)| {
+                let partitions: Vec<PartitionSpec> = moduli
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, modulus)| PartitionSpec::new(format!("p{idx}"), modulus))
+                    .collect();
+                let params = ConditionParams {
+                    partitions: partitions.clone(),
+                    max_tick: 200,
+                    depth: 2,
+                };
+                vec(PulseCondition::arbitrary_with(params), 0..6).prop_map(move |conditions| {
+                    let mut builder =
+                        ClockBuilder::new().partition_chain(order, partitions.clone());
+                    builder.pulses = conditions
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, condition)| PulseSpec::new(format!("pulse{idx}"), condition))
+                        .collect();
+                    builder
+                })
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PartitionOrder;
+
+    /// Reference model: decompose `tick` into mixed-radix digits.
+    fn model(tick: u64, specs: &[PartitionSpec], order: PartitionOrder) -> Vec<u64> {
+        let mut digits = alloc::vec![0; specs.len()];
+        let mut rest = tick;
+        let mut assign = |idx: usize| {
+            digits[idx] = rest % specs[idx].modulus;
+            rest /= specs[idx].modulus;
+        };
+        match order {
+            PartitionOrder::LeastSignificantFirst => (0..specs.len()).for_each(&mut assign),
+            PartitionOrder::MostSignificantFirst => (0..specs.len()).rev().for_each(&mut assign),
+        }
+        digits
+    }
+
+    proptest! {
+        #[test]
+        fn clock_matches_mixed_radix_model(builder in any::<ClockBuilder>(), ticks in 0u64..300) {
+            let specs = builder.partitions.clone();
+            let order = builder.order.unwrap();
+            let mut clock = builder.build().unwrap();
+
+            for _ in 0..ticks {
+                let outcome = clock.tick();
+                let tick = outcome.snapshot.tick;
+                let expected_values = model(tick, &specs, order);
+                prop_assert_eq!(clock.partition_values(), expected_values.as_slice());
+
+                let fired: Vec<&str> = outcome.pulses.iter().map(|p| p.name.as_str()).collect();
+                let expected: Vec<&str> = clock
+                    .pulses
+                    .iter()
+                    .filter(|p| p.condition.is_met(tick, &outcome.snapshot))
+                    .map(|p| p.name.as_str())
+                    .collect();
+                prop_assert_eq!(fired, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,553 @@
+//! Bulk evaluation of many clocks sharing one configuration.
+//!
+//! A [`ClockArena`] keeps the tick, epoch and partition values of every
+//! clock in flat arrays, and evaluates pulses from conditions compiled
+//! against partition indices, so ticking a hundred thousand clocks walks a
+//! few contiguous buffers instead of as many heap-allocated [`Clock`]s.
+//!
+//! The arena holds no per-pulse runtime state, so it accepts configurations
+//! whose pulses need none: no delayed or expiring pulses, no `Jitter`,
+//! `RateLimit` or `Debounce` wrappers, no quiet windows and no run budget.
+//! Alarms, child clocks, state machines and subscribers are per-clock
+//! attachments and have no place in it either; convert a clock out with
+//! [`ClockArena::to_clock`] when one needs them.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{
+    CivilDate, Clock, ClockConfig, ClockError, ClockSnapshot, DateField, PartitionOrder,
+    PulseCondition, DAY_PARTITION,
+};
+
+/// A pulse condition with partition names resolved.
+#[derive(Clone, Debug)]
+pub(crate) enum Compiled {
+    Every(u64),
+    Equals {
+        idx: usize,
+        value: u64,
+    },
+    Modulo {
+        idx: usize,
+        modulus: u64,
+        remainder: u64,
+    },
+    TickRange {
+        start: u64,
+        end: u64,
+    },
+    /// `pos` is the partition's significance, 0 for the fastest.
+    Changed {
+        pos: usize,
+    },
+    Decreased {
+        pos: usize,
+    },
+    /// `idx` is the day partition.
+    Date {
+        idx: usize,
+        field: DateField,
+        value: u64,
+    },
+    Not(Box<Compiled>),
+    And(Vec<Compiled>),
+    Or(Vec<Compiled>),
+}
+
+impl Compiled {
+    /// Whether the condition holds, where `wrapped` partitions (counted
+    /// from the fastest) rolled over to 0 on this tick and the next one was
+    /// incremented.
+    pub(crate) fn holds(&self, tick: u64, values: &[u64], wrapped: usize) -> bool {
+        match self {
+            Compiled::Every(period) => tick != 0 && tick.is_multiple_of(*period),
+            Compiled::Equals { idx, value } => values[*idx] == *value,
+            Compiled::Modulo {
+                idx,
+                modulus,
+                remainder,
+            } => values[*idx] % modulus == *remainder,
+            Compiled::TickRange { start, end } => tick >= *start && tick <= *end,
+            Compiled::Changed { pos } => *pos <= wrapped,
+            Compiled::Decreased { pos } => *pos < wrapped,
+            Compiled::Date { idx, field, value } => i64::try_from(values[*idx]).is_ok_and(|days| {
+                u64::try_from(field.of(CivilDate::from_days(days))) == Ok(*value)
+            }),
+            Compiled::Not(inner) => !inner.holds(tick, values, wrapped),
+            Compiled::And(conditions) => {
+                !conditions.is_empty() && conditions.iter().all(|c| c.holds(tick, values, wrapped))
+            }
+            Compiled::Or(conditions) => conditions.iter().any(|c| c.holds(tick, values, wrapped)),
+        }
+    }
+}
+
+/// Many clocks with one configuration, stored and ticked in bulk.
+///
+/// Every clock evaluates the same pulses as a [`Clock`] built from the
+/// configuration would, from its own tick and partition values. After
+/// [`tick_all`](Self::tick_all), each clock's fired pulses are read back as
+/// a bit set: bit `i` for pulse `i` and bit [`pulse_count`](Self::pulse_count)
+/// when its tick counter overflowed. Unlike the raw binding layout's
+/// overflow bit, it is set even if the configuration fires no overflow pulse.
+///
+/// ```
+/// use beeclock_core::{Clock, ClockArena};
+///
+/// let config = Clock::builder()
+///     .least_significant_first()
+///     .partition("sec", 60)
+///     .partition("min", 60)
+///     .pulse_when("minute", "changed(min)".parse().unwrap())
+///     .build()
+///     .unwrap()
+///     .config();
+/// let mut arena = ClockArena::new(config).unwrap();
+/// for offset in 0..1000 {
+///     let agent = arena.push();
+///     arena.set_partition(agent, "sec", offset % 60).unwrap();
+/// }
+///
+/// // The 16 agents at second 59 roll over into minute 1.
+/// assert_eq!(arena.tick_all(), 16);
+/// assert_eq!(arena.fired(59).collect::<Vec<_>>(), [0]);
+/// assert_eq!(arena.values(59), [0, 1]);
+/// ```
+#[derive(Debug)]
+pub struct ClockArena {
+    /// Clock built from the configuration, for names and conversions.
+    template: Clock,
+    /// Partition indices from least to most significant.
+    carry: Vec<usize>,
+    moduli: Vec<u64>,
+    conditions: Vec<Compiled>,
+    /// Fired-bit words per clock.
+    words: usize,
+    ticks: Vec<u64>,
+    epochs: Vec<u64>,
+    /// Partition values, one row of `moduli.len()` per clock.
+    values: Vec<u64>,
+    /// Fired bits from the last [`tick_all`](Self::tick_all), one row of
+    /// `words` per clock.
+    fired: Vec<u64>,
+}
+
+impl ClockArena {
+    /// Create an empty arena for clocks built from `config`.
+    ///
+    /// Fails as [`ClockConfig::build`] would, or with
+    /// [`ClockError::UnsupportedInArena`] if the configuration needs
+    /// per-pulse state or scales ticks per unit.
+    pub fn new(config: ClockConfig) -> Result<Self, ClockError> {
+        if let Some(reason) = stateful_feature(&config) {
+            return Err(ClockError::UnsupportedInArena { reason });
+        }
+        let template = config.build()?;
+
+        let len = template.partitions.len();
+        let carry = carry_order(&template);
+        let moduli: Vec<u64> = (0..len)
+            .map(|idx| template.partitions.modulus(idx))
+            .collect();
+        let conditions = template
+            .pulses
+            .iter()
+            .map(|pulse| compile(&pulse.condition, &template, &carry))
+            .collect();
+        Ok(Self {
+            words: (template.pulses.len() + 1).div_ceil(64),
+            template,
+            carry,
+            moduli,
+            conditions,
+            ticks: Vec::new(),
+            epochs: Vec::new(),
+            values: Vec::new(),
+            fired: Vec::new(),
+        })
+    }
+
+    /// Add a clock at tick 0 with every partition at 0, returning its index.
+    pub fn push(&mut self) -> usize {
+        self.ticks.push(0);
+        self.epochs.push(0);
+        self.values
+            .extend(core::iter::repeat_n(0, self.moduli.len()));
+        self.fired.extend(core::iter::repeat_n(0, self.words));
+        self.ticks.len() - 1
+    }
+
+    /// Add a clock where `clock` stands: its tick, epoch and partition
+    /// values. `clock` must have the arena's shape (see
+    /// [`Clock::config_hash`]).
+    pub fn push_clock(&mut self, clock: &Clock) -> Result<usize, ClockError> {
+        if clock.config_hash() != self.template.config_hash() {
+            return Err(ClockError::SnapshotMismatch {
+                reason: String::from("clock configuration differs from the arena's"),
+            });
+        }
+        let idx = self.push();
+        self.ticks[idx] = clock.tick_count();
+        self.epochs[idx] = clock.epoch();
+        self.row_mut(idx).copy_from_slice(clock.partition_values());
+        Ok(idx)
+    }
+
+    /// Number of clocks.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    /// The shared configuration.
+    pub fn config(&self) -> ClockConfig {
+        self.template.config()
+    }
+
+    /// Number of configured pulses.
+    #[inline]
+    pub fn pulse_count(&self) -> usize {
+        self.conditions.len()
+    }
+
+    /// Name of the pulse at `index`.
+    pub fn pulse_name(&self, index: usize) -> Option<&str> {
+        self.template.pulse_name(index)
+    }
+
+    /// Advance every clock by one tick, returning how many fired a pulse
+    /// (not counting tick overflow).
+    pub fn tick_all(&mut self) -> usize {
+        let (parts, words) = (self.moduli.len(), self.words);
+        let overflow_bit = self.conditions.len();
+        let mut firing = 0;
+        for (idx, (tick, epoch)) in self.ticks.iter_mut().zip(&mut self.epochs).enumerate() {
+            let (next, overflowed) = tick.overflowing_add(1);
+            *tick = next;
+            if overflowed {
+                *epoch = epoch.wrapping_add(1);
+            }
+
+            let values = &mut self.values[idx * parts..(idx + 1) * parts];
+            let mut wrapped = 0;
+            for &part in &self.carry {
+                values[part] += 1;
+                if values[part] < self.moduli[part] {
+                    break;
+                }
+                values[part] = 0;
+                wrapped += 1;
+            }
+
+            let bits = &mut self.fired[idx * words..(idx + 1) * words];
+            bits.fill(0);
+            let mut any = false;
+            for (pulse, condition) in self.conditions.iter().enumerate() {
+                if condition.holds(next, values, wrapped) {
+                    bits[pulse / 64] |= 1 << (pulse % 64);
+                    any = true;
+                }
+            }
+            if overflowed {
+                bits[overflow_bit / 64] |= 1 << (overflow_bit % 64);
+            }
+            firing += usize::from(any);
+        }
+        firing
+    }
+
+    /// Tick counter of clock `idx`.
+    #[inline]
+    pub fn tick_count(&self, idx: usize) -> u64 {
+        self.ticks[idx]
+    }
+
+    #[inline]
+    pub fn epoch(&self, idx: usize) -> u64 {
+        self.epochs[idx]
+    }
+
+    /// Partition values of clock `idx`, in configured order.
+    #[inline]
+    pub fn values(&self, idx: usize) -> &[u64] {
+        let parts = self.moduli.len();
+        &self.values[idx * parts..(idx + 1) * parts]
+    }
+
+    /// Set a partition of clock `idx` without advancing time, as
+    /// [`Clock::set_partition`] does.
+    pub fn set_partition(&mut self, idx: usize, name: &str, value: u64) -> Result<(), ClockError> {
+        let part = self.template.partitions.position(name).ok_or_else(|| {
+            ClockError::PartitionNotFound {
+                name: String::from(name),
+            }
+        })?;
+        let modulus = self.moduli[part];
+        if value >= modulus {
+            return Err(ClockError::ValueOutOfRange {
+                name: String::from(name),
+                value,
+                modulus,
+            });
+        }
+        self.row_mut(idx)[part] = value;
+        Ok(())
+    }
+
+    /// Fired bits of clock `idx` from the last [`tick_all`](Self::tick_all).
+    #[inline]
+    pub fn fired_bits(&self, idx: usize) -> &[u64] {
+        &self.fired[idx * self.words..(idx + 1) * self.words]
+    }
+
+    /// Indices of the pulses clock `idx` fired on the last
+    /// [`tick_all`](Self::tick_all), in pulse order.
+    pub fn fired(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let bits = self.fired_bits(idx);
+        (0..self.conditions.len()).filter(move |&pulse| bits[pulse / 64] & (1 << (pulse % 64)) != 0)
+    }
+
+    /// Whether clock `idx`'s tick counter overflowed on the last
+    /// [`tick_all`](Self::tick_all).
+    pub fn overflowed(&self, idx: usize) -> bool {
+        let bit = self.conditions.len();
+        self.fired_bits(idx)[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    /// Snapshot of clock `idx`.
+    pub fn snapshot(&self, idx: usize) -> ClockSnapshot {
+        let mut snapshot = self.template.snapshot();
+        snapshot.tick = self.ticks[idx];
+        snapshot.epoch = self.epochs[idx];
+        for (part, &value) in snapshot.partitions.iter_mut().zip(self.values(idx)) {
+            part.value = value;
+        }
+        snapshot
+    }
+
+    /// A standalone [`Clock`] where clock `idx` stands, which continues
+    /// exactly as the arena would.
+    pub fn to_clock(&self, idx: usize) -> Clock {
+        let mut clock = self.template.detached();
+        clock.tick = self.ticks[idx];
+        clock.epoch = self.epochs[idx];
+        for (part, &value) in self.values(idx).iter().enumerate() {
+            clock.partitions.set(part, value);
+        }
+        clock
+    }
+
+    fn row_mut(&mut self, idx: usize) -> &mut [u64] {
+        let parts = self.moduli.len();
+        &mut self.values[idx * parts..(idx + 1) * parts]
+    }
+}
+
+/// The first part of `config` that needs per-pulse or per-clock runtime
+/// state, described for an error message.
+pub(crate) fn stateful_feature(config: &ClockConfig) -> Option<String> {
+    if !config.quiet_windows.is_empty() {
+        return Some(String::from("quiet windows"));
+    }
+    if config.run_for.is_some() {
+        return Some(String::from("run budget"));
+    }
+    if config.ticks_per_unit.is_some_and(|ticks| ticks > 1) {
+        return Some(String::from("ticks per unit scaling"));
+    }
+    config.pulses.iter().find_map(|pulse| {
+        if pulse.after.is_some() {
+            Some(format!("delayed pulse '{}'", pulse.name))
+        } else if pulse.can_expire() {
+            Some(format!("expiring pulse '{}'", pulse.name))
+        } else if pulse.condition.is_stateful() {
+            Some(format!("stateful condition on pulse '{}'", pulse.name))
+        } else {
+            None
+        }
+    })
+}
+
+/// Partition indices of `clock` from least to most significant.
+pub(crate) fn carry_order(clock: &Clock) -> Vec<usize> {
+    let len = clock.partitions.len();
+    match clock.partition_order {
+        PartitionOrder::LeastSignificantFirst => (0..len).collect(),
+        PartitionOrder::MostSignificantFirst => (0..len).rev().collect(),
+    }
+}
+
+/// Resolve partition names in `condition` against `clock`, whose conditions
+/// are already validated. `carry` lists partition indices by significance.
+pub(crate) fn compile(condition: &PulseCondition, clock: &Clock, carry: &[usize]) -> Compiled {
+    let index = |name: &str| {
+        clock
+            .partitions
+            .position(name)
+            .expect("validated partition name")
+    };
+    // A partition of modulus 1 never changes.
+    let moving = |name: &str| {
+        let idx = index(name);
+        (clock.partitions.modulus(idx) > 1).then(|| {
+            carry
+                .iter()
+                .position(|&part| part == idx)
+                .expect("every partition carries")
+        })
+    };
+    let never = || Compiled::Or(Vec::new());
+    match condition {
+        PulseCondition::Every(period) => Compiled::Every(*period),
+        PulseCondition::PartitionEquals { name, value } => Compiled::Equals {
+            idx: index(name),
+            value: *value,
+        },
+        PulseCondition::PartitionModulo {
+            name,
+            modulus,
+            remainder,
+        } => Compiled::Modulo {
+            idx: index(name),
+            modulus: *modulus,
+            remainder: *remainder,
+        },
+        PulseCondition::TickRange { start, end } => Compiled::TickRange {
+            start: *start,
+            end: *end,
+        },
+        PulseCondition::PartitionChanged { name } => {
+            moving(name).map_or_else(never, |pos| Compiled::Changed { pos })
+        }
+        PulseCondition::PartitionDecreased { name } => {
+            moving(name).map_or_else(never, |pos| Compiled::Decreased { pos })
+        }
+        PulseCondition::DateEquals { field, value } => Compiled::Date {
+            idx: index(DAY_PARTITION),
+            field: *field,
+            value: *value,
+        },
+        PulseCondition::Not(inner) => Compiled::Not(Box::new(compile(inner, clock, carry))),
+        PulseCondition::And(conditions) => Compiled::And(
+            conditions
+                .iter()
+                .map(|c| compile(c, clock, carry))
+                .collect(),
+        ),
+        PulseCondition::Or(conditions) => Compiled::Or(
+            conditions
+                .iter()
+                .map(|c| compile(c, clock, carry))
+                .collect(),
+        ),
+        PulseCondition::Jitter { .. }
+        | PulseCondition::RateLimit { .. }
+        | PulseCondition::Debounce { .. }
+        | PulseCondition::ElapsedAtLeast { .. } => unreachable!("stateful conditions are rejected"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PulseSpec, QuietWindow};
+
+    fn config(order: PartitionOrder) -> ClockConfig {
+        Clock::builder()
+            .partition_order(order)
+            .partition("sec", 7)
+            .partition("fixed", 1)
+            .partition("min", 5)
+            .pulse_every("beat", 3)
+            .pulse_when("new_min", "changed(min)".parse().unwrap())
+            .pulse_when("wrap", "decreased(min) || decreased(sec)".parse().unwrap())
+            .pulse_when("frozen", "changed(fixed)".parse().unwrap())
+            .pulse_when("mid", "min == 2 && !(sec % 2 == 0)".parse().unwrap())
+            .pulse_when("early", PulseCondition::TickRange { start: 4, end: 9 })
+            .build()
+            .unwrap()
+            .config()
+    }
+
+    #[test]
+    fn matches_standalone_clocks() {
+        for order in [
+            PartitionOrder::LeastSignificantFirst,
+            PartitionOrder::MostSignificantFirst,
+        ] {
+            let mut arena = ClockArena::new(config(order)).unwrap();
+            let mut clocks: Vec<Clock> = Vec::new();
+            for start in 0..12u64 {
+                let mut clock = config(order).build().unwrap();
+                clock.skip(start * 5);
+                arena.push_clock(&clock).unwrap();
+                clocks.push(clock);
+            }
+            for _ in 0..80 {
+                let firing = arena.tick_all();
+                let mut expected_firing = 0;
+                for (idx, clock) in clocks.iter_mut().enumerate() {
+                    let outcome = clock.tick();
+                    let names: Vec<&str> = outcome.pulses.iter().map(|p| p.name.as_str()).collect();
+                    let fired: Vec<&str> = arena
+                        .fired(idx)
+                        .map(|pulse| arena.pulse_name(pulse).unwrap())
+                        .collect();
+                    assert_eq!(fired, names, "{order:?} clock {idx}");
+                    assert_eq!(arena.snapshot(idx), *outcome.snapshot);
+                    expected_firing += usize::from(!names.is_empty());
+                }
+                assert_eq!(firing, expected_firing);
+            }
+            let mut resumed = arena.to_clock(3);
+            assert_eq!(resumed.tick().snapshot, clocks[3].tick().snapshot);
+        }
+    }
+
+    #[test]
+    fn reports_overflow_and_rejects_stateful_configs() {
+        let mut arena =
+            ClockArena::new(ClockConfig::new(PartitionOrder::LeastSignificantFirst)).unwrap();
+        let mut clock = Clock::builder().build().unwrap();
+        clock.skip(u64::MAX);
+        let idx = arena.push_clock(&clock).unwrap();
+        arena.tick_all();
+        assert!(arena.overflowed(idx));
+        assert_eq!((arena.tick_count(idx), arena.epoch(idx)), (0, 1));
+        arena.tick_all();
+        assert!(!arena.overflowed(idx));
+
+        assert!(matches!(
+            arena.push_clock(&Clock::default()),
+            Err(ClockError::SnapshotMismatch { .. })
+        ));
+
+        let mut delayed = config(PartitionOrder::LeastSignificantFirst);
+        delayed
+            .pulses
+            .push(PulseSpec::new("late", PulseCondition::Every(2)).after("beat", 1));
+        let mut capped = config(PartitionOrder::LeastSignificantFirst);
+        capped.pulses[0] = capped.pulses[0].clone().max_fires(3);
+        let mut quiet = config(PartitionOrder::LeastSignificantFirst);
+        quiet.quiet_windows.push(QuietWindow::ticks("hush", 1, 2));
+        let mut limited = config(PartitionOrder::LeastSignificantFirst);
+        limited.pulses[0].condition = PulseCondition::Debounce {
+            ticks: 2,
+            inner: Box::new(PulseCondition::Every(1)),
+        };
+        for config in [delayed, capped, quiet, limited] {
+            assert!(matches!(
+                ClockArena::new(config),
+                Err(ClockError::UnsupportedInArena { .. })
+            ));
+        }
+    }
+}
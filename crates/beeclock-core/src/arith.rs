@@ -0,0 +1,150 @@
+//! Pure snapshot arithmetic.
+//!
+//! Moves snapshots forward without a running clock: [`ClockConfig::add_ticks`]
+//! computes where a clock with that configuration would be `n` ticks later,
+//! and subtracting snapshots gives the ticks between them.
+
+use core::ops::Sub;
+
+use crate::{ClockConfig, ClockError, ClockSnapshot, PartitionOrder};
+
+impl ClockConfig {
+    /// The snapshot a clock with this configuration reaches `ticks` ticks
+    /// after `snapshot`, including partition carries and tick overflow into
+    /// the epoch. Past the last epoch it wraps to epoch 0, tick 0, as a
+    /// running clock does.
+    ///
+    /// For a [scaled](crate::ClockBuilder::ticks_per_unit) configuration the
+    /// snapshot is taken to stand `(epoch, tick)` ticks into the current
+    /// unit, counted from tick 0 of epoch 0, which holds for a clock started
+    /// there but not for one whose partitions were set by hand.
+    ///
+    /// Fails if the snapshot's partitions don't match this configuration.
+    pub fn add_ticks(
+        &self,
+        snapshot: &ClockSnapshot,
+        ticks: u64,
+    ) -> Result<ClockSnapshot, ClockError> {
+        snapshot.check_layout(self.partitions.iter().map(|p| (p.name.as_str(), p.modulus)))?;
+
+        let mut next = snapshot.clone();
+        let start = (snapshot.epoch as u128) << 64 | snapshot.tick as u128;
+        let position = start.wrapping_add(ticks as u128);
+        next.tick = position as u64;
+        next.epoch = (position >> 64) as u64;
+
+        let scale = self.ticks_per_unit.unwrap_or(1).max(1) as u128;
+        let mut carry = (start % scale + ticks as u128) / scale;
+        let mut add = |idx: usize| {
+            let part = &mut next.partitions[idx];
+            let total = part.value as u128 + carry;
+            part.value = (total % part.modulus as u128) as u64;
+            carry = total / part.modulus as u128;
+        };
+        let len = snapshot.partitions.len();
+        match self.order {
+            PartitionOrder::LeastSignificantFirst => (0..len).for_each(&mut add),
+            PartitionOrder::MostSignificantFirst => (0..len).rev().for_each(&mut add),
+        }
+        Ok(next)
+    }
+}
+
+impl ClockSnapshot {
+    /// Ticks from `earlier` to this snapshot, by epoch and tick.
+    ///
+    /// Returns `None` if `earlier` is actually later or the gap doesn't fit
+    /// in a `u64`. Partition values are not consulted.
+    pub fn ticks_since(&self, earlier: &ClockSnapshot) -> Option<u64> {
+        let position = |s: &ClockSnapshot| (s.epoch as u128) << 64 | s.tick as u128;
+        let gap = position(self).checked_sub(position(earlier))?;
+        u64::try_from(gap).ok()
+    }
+}
+
+/// Ticks between two snapshots, saturating to 0 (like `Instant - Instant`).
+impl Sub for &ClockSnapshot {
+    type Output = u64;
+
+    fn sub(self, earlier: &ClockSnapshot) -> u64 {
+        self.ticks_since(earlier).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clock;
+
+    #[test]
+    fn add_ticks_matches_running_clock() {
+        for order in [
+            PartitionOrder::LeastSignificantFirst,
+            PartitionOrder::MostSignificantFirst,
+        ] {
+            let mut config = ClockConfig::new(order);
+            config.partitions = alloc::vec![
+                crate::PartitionSpec::new("a", 3),
+                crate::PartitionSpec::new("b", 4),
+            ];
+            let mut clock = config.clone().build().unwrap();
+            let start = clock.snapshot();
+            for n in 1..=30 {
+                let live = (*clock.tick().snapshot).clone();
+                assert_eq!(config.add_ticks(&start, n).unwrap(), live);
+                assert_eq!(&live - &start, n);
+            }
+        }
+    }
+
+    #[test]
+    fn add_ticks_overflows_into_epoch_and_checks_config() {
+        let clock = Clock::default();
+        let config = clock.config();
+        let mut snapshot = clock.snapshot();
+        snapshot.tick = u64::MAX - 1;
+
+        let next = config.add_ticks(&snapshot, 3).unwrap();
+        assert_eq!((next.epoch, next.tick), (1, 1));
+        assert_eq!(next.ticks_since(&snapshot), Some(3));
+        assert_eq!(snapshot.ticks_since(&next), None);
+        assert_eq!(&snapshot - &next, 0);
+
+        // The last tick of the last epoch wraps to the very start.
+        snapshot.epoch = u64::MAX;
+        snapshot.tick = u64::MAX;
+        let next = config.add_ticks(&snapshot, 5).unwrap();
+        assert_eq!((next.epoch, next.tick), (0, 4));
+
+        snapshot.partitions.pop();
+        assert!(matches!(
+            config.add_ticks(&snapshot, 1),
+            Err(ClockError::SnapshotMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn scaled_add_ticks_keeps_the_phase_across_tick_overflow() {
+        // 2^64 is not a multiple of 3, so `tick % 3` misplaces the phase
+        // once the tick counter wraps.
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 5)
+            .ticks_per_unit(3)
+            .build()
+            .unwrap();
+        let config = clock.config();
+        clock.skip(u64::MAX - 3);
+        for _ in 0..6 {
+            clock.tick();
+        }
+        assert_eq!((clock.epoch(), clock.tick_count()), (1, 2));
+        let mut resumed = Clock::from_state(clock.save_state()).unwrap();
+        let start = clock.snapshot();
+        for n in 1..=12 {
+            let live = (*clock.tick().snapshot).clone();
+            assert_eq!(config.add_ticks(&start, n).unwrap(), live, "{n} ticks");
+            assert_eq!(*resumed.tick().snapshot, live, "{n} ticks restored");
+        }
+    }
+}
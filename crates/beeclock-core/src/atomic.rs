@@ -0,0 +1,282 @@
+//! Lock-free ticking from many threads (std only).
+//!
+//! An [`AtomicClock`] keeps nothing but a tick counter that changes: each
+//! [`tick`](AtomicClock::tick) claims the next tick with one atomic
+//! fetch-and-add, then derives that tick's partition values and pulses from
+//! the count, so concurrent producers never wait on each other. Like a
+//! [`ClockArena`](crate::ClockArena), it only accepts configurations whose
+//! pulses need no runtime state.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::arena::{carry_order, compile, stateful_feature, Compiled};
+use crate::{Clock, ClockConfig, ClockError, ClockSnapshot, PulseFired, PulseList, TickOutcome};
+
+/// A clock that any number of threads can tick through `&self`.
+///
+/// Every call to [`tick`](Self::tick) gets a distinct tick, and its outcome
+/// is exactly what a [`Clock`] built from the same configuration would
+/// produce on that tick. Threads racing to tick may receive their outcomes
+/// in a different order than the ticks were claimed.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use beeclock_core::{AtomicClock, Clock};
+///
+/// let config = Clock::builder()
+///     .least_significant_first()
+///     .partition("sec", 60)
+///     .partition("min", 60)
+///     .pulse_when("minute", "changed(min)".parse().unwrap())
+///     .build()
+///     .unwrap()
+///     .config();
+/// let clock = Arc::new(AtomicClock::new(config).unwrap());
+///
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let clock = Arc::clone(&clock);
+///         thread::spawn(move || (0..30).filter(|_| !clock.tick().pulses.is_empty()).count())
+///     })
+///     .collect();
+/// let minutes: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+///
+/// assert_eq!(minutes, 2);
+/// assert_eq!(clock.tick_count(), 120);
+/// assert_eq!(clock.snapshot().get("min"), 2);
+/// ```
+#[derive(Debug)]
+pub struct AtomicClock {
+    /// Clock built from the configuration, standing where counting started.
+    origin: Clock,
+    name: Option<Arc<str>>,
+    /// Partition indices from least to most significant.
+    carry: Vec<usize>,
+    conditions: Vec<Compiled>,
+    /// Ticks taken since `origin`.
+    taken: AtomicU64,
+}
+
+impl AtomicClock {
+    /// Create a clock at tick 0 with every partition at 0.
+    ///
+    /// Fails as [`ClockConfig::build`] would, or with
+    /// [`ClockError::UnsupportedInAtomic`] if the configuration needs
+    /// per-pulse state or scales ticks per unit.
+    pub fn new(config: ClockConfig) -> Result<Self, ClockError> {
+        if let Some(reason) = stateful_feature(&config) {
+            return Err(ClockError::UnsupportedInAtomic { reason });
+        }
+        let origin = config.build()?;
+        let carry = carry_order(&origin);
+        let conditions = origin
+            .pulses
+            .iter()
+            .map(|pulse| compile(&pulse.condition, &origin, &carry))
+            .collect();
+        Ok(Self {
+            name: origin.name().map(Arc::from),
+            origin,
+            carry,
+            conditions,
+            taken: AtomicU64::new(0),
+        })
+    }
+
+    /// Continue from where `clock` stands: its tick, epoch and partition
+    /// values. Alarms, child clocks, state machines and subscribers are not
+    /// carried over.
+    pub fn from_clock(clock: &Clock) -> Result<Self, ClockError> {
+        let mut atomic = Self::new(clock.config())?;
+        atomic.origin.tick = clock.tick;
+        atomic.origin.epoch = clock.epoch;
+        for (part, &value) in clock.partition_values().iter().enumerate() {
+            atomic.origin.partitions.set(part, value);
+        }
+        Ok(atomic)
+    }
+
+    /// Name stamped onto outcomes and fired pulses, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The configuration the clock was built from.
+    pub fn config(&self) -> ClockConfig {
+        self.origin.config()
+    }
+
+    /// Claim the next tick and evaluate it.
+    pub fn tick(&self) -> TickOutcome {
+        // The counter publishes no other memory, so only its own
+        // modification order matters.
+        let taken = self.taken.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        let (snapshot, wrapped) = self.at(taken);
+        let (tick, epoch) = (snapshot.tick, snapshot.epoch);
+        let overflowed = tick == 0;
+
+        let values: Vec<u64> = snapshot.partitions.iter().map(|p| p.value).collect();
+        let fire = |name: &str| PulseFired {
+            name: String::from(name),
+            tick,
+            epoch,
+            clock: self.name.clone(),
+        };
+        let mut pulses: PulseList = self
+            .conditions
+            .iter()
+            .zip(&self.origin.pulses)
+            .filter(|(condition, _)| condition.holds(tick, &values, wrapped))
+            .map(|(_, pulse)| fire(&pulse.name))
+            .collect();
+        if let Some(name) = self.origin.overflow_pulse().filter(|_| overflowed) {
+            pulses.push(fire(name));
+        }
+
+        TickOutcome {
+            clock: self.name.clone(),
+            snapshot: Arc::new(snapshot),
+            pulses,
+            overflowed,
+            quiet_reports: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Current tick count.
+    pub fn tick_count(&self) -> u64 {
+        self.snapshot().tick
+    }
+
+    /// Current epoch (increments on tick overflow).
+    pub fn epoch(&self) -> u64 {
+        self.snapshot().epoch
+    }
+
+    /// Snapshot as of the latest claimed tick.
+    pub fn snapshot(&self) -> ClockSnapshot {
+        self.at(self.taken.load(Ordering::Relaxed)).0
+    }
+
+    /// A standalone [`Clock`] as of the latest claimed tick, which continues
+    /// exactly as this one would.
+    pub fn to_clock(&self) -> Clock {
+        let snapshot = self.snapshot();
+        let mut clock = self.origin.detached();
+        clock.tick = snapshot.tick;
+        clock.epoch = snapshot.epoch;
+        for (part, state) in snapshot.partitions.iter().enumerate() {
+            clock.partitions.set(part, state.value);
+        }
+        clock
+    }
+
+    /// The snapshot `taken` ticks after the origin, and how many partitions
+    /// (from the fastest) rolled over on the last of those ticks.
+    fn at(&self, taken: u64) -> (ClockSnapshot, usize) {
+        let mut snapshot = self.origin.snapshot();
+        let position =
+            ((snapshot.epoch as u128) << 64 | snapshot.tick as u128).wrapping_add(taken as u128);
+        snapshot.tick = position as u64;
+        snapshot.epoch = (position >> 64) as u64;
+
+        let mut carry = taken as u128;
+        for &part in &self.carry {
+            let part = &mut snapshot.partitions[part];
+            let total = part.value as u128 + carry;
+            part.value = (total % part.modulus as u128) as u64;
+            carry = total / part.modulus as u128;
+        }
+        // A partition rolled over on the last tick if it and every faster
+        // one now read 0.
+        let wrapped = self
+            .carry
+            .iter()
+            .take_while(|&&part| snapshot.partitions[part].value == 0)
+            .count();
+        (snapshot, wrapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PartitionOrder;
+    use std::thread;
+
+    fn names(outcome: &TickOutcome) -> Vec<&str> {
+        outcome.pulses.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn matches_a_standalone_clock() {
+        for order in [
+            PartitionOrder::LeastSignificantFirst,
+            PartitionOrder::MostSignificantFirst,
+        ] {
+            let mut clock = Clock::builder()
+                .name("events")
+                .partition_order(order)
+                .partition("a", 3)
+                .partition("one", 1)
+                .partition("b", 4)
+                .pulse_every("even", 2)
+                .pulse_when("b", "changed(b) && !decreased(b)".parse().unwrap())
+                .pulse_when("wrap", "decreased(b) || changed(one)".parse().unwrap())
+                .pulse_when("mid", "a == 1 && b % 2 == 1".parse().unwrap())
+                .build()
+                .unwrap();
+            clock.set_partition("a", 2).unwrap();
+            let atomic = AtomicClock::from_clock(&clock).unwrap();
+            for _ in 0..40 {
+                let (expected, actual) = (clock.tick(), atomic.tick());
+                assert_eq!(*actual.snapshot, *expected.snapshot, "{order:?}");
+                assert_eq!(names(&actual), names(&expected), "{order:?}");
+                assert_eq!(actual.clock.as_deref(), Some("events"));
+            }
+            assert_eq!(atomic.to_clock().snapshot(), clock.snapshot());
+        }
+    }
+
+    #[test]
+    fn concurrent_ticks_are_distinct_and_overflow_once() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .build()
+            .unwrap();
+        clock.skip(u64::MAX - 400);
+        let atomic = AtomicClock::from_clock(&clock).unwrap();
+        let ticks: Vec<(u64, bool)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..200)
+                            .map(|_| {
+                                let outcome = atomic.tick();
+                                (outcome.snapshot.tick, !outcome.pulses.is_empty())
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().unwrap())
+                .collect()
+        });
+
+        let mut claimed: Vec<u64> = ticks.iter().map(|&(tick, _)| tick).collect();
+        claimed.sort_unstable();
+        claimed.dedup();
+        assert_eq!(claimed.len(), 800);
+        assert_eq!(ticks.iter().filter(|&&(_, overflow)| overflow).count(), 1);
+        assert_eq!((atomic.tick_count(), atomic.epoch()), (399, 1));
+        assert!(matches!(
+            AtomicClock::new(Clock::builder().run_for(5).build().unwrap().config()),
+            Err(ClockError::UnsupportedInAtomic { .. })
+        ));
+    }
+}
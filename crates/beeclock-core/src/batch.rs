@@ -0,0 +1,122 @@
+//! Batched evaluation of periodic pulses.
+//!
+//! Top-level `Every(n)` pulses are grouped by period at build time, so each
+//! tick performs one division per distinct period and records the due pulses
+//! in a bitmask instead of walking every condition tree.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{PulseCondition, PulseSpec};
+
+/// Pulses sharing a period.
+#[derive(Clone, Debug)]
+struct PeriodGroup {
+    period: u64,
+    members: Vec<usize>,
+}
+
+/// Period-grouped index over the `Every` pulses of a clock.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EveryIndex {
+    groups: Vec<PeriodGroup>,
+    /// Bit per pulse: set when the pulse is evaluated by this index.
+    batched: Vec<u64>,
+    /// Bit per pulse: set when the pulse is due at the prepared tick.
+    due: Vec<u64>,
+}
+
+impl EveryIndex {
+    /// Group the top-level `Every` pulses by period, skipping delayed pulses.
+    pub fn new(pulses: &[PulseSpec]) -> Self {
+        let words = pulses.len().div_ceil(64);
+        let mut index = Self {
+            groups: Vec::new(),
+            batched: vec![0; words],
+            due: vec![0; words],
+        };
+        for (idx, pulse) in pulses.iter().enumerate() {
+            if pulse.after.is_some() {
+                continue;
+            }
+            if let PulseCondition::Every(period) = pulse.condition {
+                match index.groups.iter_mut().find(|g| g.period == period) {
+                    Some(group) => group.members.push(idx),
+                    None => index.groups.push(PeriodGroup {
+                        period,
+                        members: vec![idx],
+                    }),
+                }
+                set_bit(&mut index.batched, idx);
+            }
+        }
+        index
+    }
+
+    /// Compute the due mask for `tick`.
+    pub fn prepare(&mut self, tick: u64) {
+        self.due.fill(0);
+        if tick == 0 {
+            return;
+        }
+        for group in &self.groups {
+            if tick.is_multiple_of(group.period) {
+                for &idx in &group.members {
+                    set_bit(&mut self.due, idx);
+                }
+            }
+        }
+    }
+
+    /// Whether the pulse at `idx` is handled by this index.
+    #[inline]
+    pub fn is_batched(&self, idx: usize) -> bool {
+        get_bit(&self.batched, idx)
+    }
+
+    /// Whether the batched pulse at `idx` is due at the prepared tick.
+    #[inline]
+    pub fn is_due(&self, idx: usize) -> bool {
+        get_bit(&self.due, idx)
+    }
+}
+
+#[inline]
+fn set_bit(words: &mut [u64], idx: usize) {
+    words[idx / 64] |= 1 << (idx % 64);
+}
+
+#[inline]
+fn get_bit(words: &[u64], idx: usize) -> bool {
+    words[idx / 64] & (1 << (idx % 64)) != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every(name: &str, period: u64) -> PulseSpec {
+        PulseSpec::new(name, PulseCondition::Every(period))
+    }
+
+    #[test]
+    fn groups_periods_and_matches_direct_evaluation() {
+        let mut pulses: Vec<PulseSpec> = (0..200).map(|i| every("p", 1 + i % 7)).collect();
+        pulses.push(PulseSpec::new(
+            "range",
+            PulseCondition::TickRange { start: 1, end: 3 },
+        ));
+
+        let mut index = EveryIndex::new(&pulses);
+        assert_eq!(index.groups.len(), 7);
+        assert!(!index.is_batched(200));
+
+        for tick in 0..50 {
+            index.prepare(tick);
+            for (idx, pulse) in pulses.iter().enumerate().take(200) {
+                let expected = pulse.condition.evaluate(tick, &[][..]);
+                assert_eq!(index.is_due(idx), expected, "pulse {idx} at tick {tick}");
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! Calendar preset and Gregorian dates.
+//!
+//! Partitions have a fixed modulus, so months of 28 to 31 days and leap
+//! years can't be partitions of their own. The [`ClockBuilder::calendar`]
+//! preset counts seconds, minutes and hours as partitions plus a running
+//! `day` count from 1970-01-01, and [`CivilDate`] derives the year, month,
+//! day and weekday from that count using the proleptic Gregorian rules.
+//! Conditions match the derived fields with
+//! [`PulseCondition::DateEquals`](crate::PulseCondition::DateEquals),
+//! written `date(month) == 2` in text.
+
+use core::fmt;
+
+use crate::partition::PartitionLookup;
+use crate::{ClockBuilder, ClockSnapshot, PartitionOrder};
+
+/// Name of the day-count partition in the [calendar](ClockBuilder::calendar)
+/// preset.
+pub const DAY_PARTITION: &str = "day";
+
+/// A date in the proleptic Gregorian calendar.
+///
+/// ```
+/// use beeclock_core::{CivilDate, Weekday};
+///
+/// let date = CivilDate::new(2024, 2, 29).unwrap();
+/// assert_eq!(date.weekday(), Weekday::Thursday);
+/// assert_eq!(CivilDate::from_days(date.to_days() + 1).to_string(), "2024-03-01");
+/// assert!(CivilDate::new(2023, 2, 29).is_none());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CivilDate {
+    pub year: i64,
+    /// 1 to 12.
+    pub month: u8,
+    /// 1 to the length of the month.
+    pub day: u8,
+}
+
+/// Day of the week.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl CivilDate {
+    /// The date, or `None` if the month or day doesn't exist.
+    pub fn new(year: i64, month: u8, day: u8) -> Option<Self> {
+        let valid = (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day);
+        valid.then_some(Self { year, month, day })
+    }
+
+    /// The date `days` days after 1970-01-01 (before it if negative).
+    pub fn from_days(days: i64) -> Self {
+        // Hinnant's civil_from_days, on 400-year eras starting in March.
+        let z = days as i128 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (era * 400 + yoe + i128::from(month <= 2)) as i64;
+        Self { year, month, day }
+    }
+
+    /// Days from 1970-01-01 to this date.
+    pub fn to_days(self) -> i64 {
+        let year = i128::from(self.year) - i128::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = i128::from(self.month);
+        let doy = (153 * if month > 2 { month - 3 } else { month + 9 } + 2) / 5
+            + i128::from(self.day)
+            - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        (era * 146_097 + doe - 719_468) as i64
+    }
+
+    /// Day of the week.
+    pub fn weekday(self) -> Weekday {
+        // 1970-01-01 was a Thursday.
+        match (self.to_days() + 3).rem_euclid(7) {
+            0 => Weekday::Monday,
+            1 => Weekday::Tuesday,
+            2 => Weekday::Wednesday,
+            3 => Weekday::Thursday,
+            4 => Weekday::Friday,
+            5 => Weekday::Saturday,
+            _ => Weekday::Sunday,
+        }
+    }
+}
+
+/// Field of a [`CivilDate`] compared by
+/// [`PulseCondition::DateEquals`](crate::PulseCondition::DateEquals).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DateField {
+    Year,
+    /// 1 to 12.
+    Month,
+    /// Day of the month, 1 to 31.
+    Day,
+    /// 1 (Monday) to 7 (Sunday).
+    Weekday,
+}
+
+impl DateField {
+    /// Every field, in the order above.
+    pub const ALL: [DateField; 4] = [
+        DateField::Year,
+        DateField::Month,
+        DateField::Day,
+        DateField::Weekday,
+    ];
+
+    /// Lowercase name, as written in `date(...)`.
+    pub fn name(self) -> &'static str {
+        match self {
+            DateField::Year => "year",
+            DateField::Month => "month",
+            DateField::Day => "day",
+            DateField::Weekday => "weekday",
+        }
+    }
+
+    /// This field of `date`.
+    pub fn of(self, date: CivilDate) -> i64 {
+        match self {
+            DateField::Year => date.year,
+            DateField::Month => i64::from(date.month),
+            DateField::Day => i64::from(date.day),
+            DateField::Weekday => date.weekday() as i64 + 1,
+        }
+    }
+}
+
+impl fmt::Display for DateField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Date of the [`DAY_PARTITION`] in `partitions`, if there is one.
+pub(crate) fn date_of<P>(partitions: &P) -> Option<CivilDate>
+where
+    P: PartitionLookup + ?Sized,
+{
+    let days = i64::try_from(partitions.value_of(DAY_PARTITION)?).ok()?;
+    Some(CivilDate::from_days(days))
+}
+
+/// `YYYY-MM-DD`.
+impl fmt::Display for CivilDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Whether `year` has a February 29th.
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Days in `month` (1 to 12) of `year`, or 0 for any other month.
+pub fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+impl ClockBuilder {
+    /// A builder preset to one tick per second: `sec`, `min` and `hour`
+    /// partitions, least significant first, then a [`DAY_PARTITION`]
+    /// counting days since 1970-01-01. Read dates with
+    /// [`ClockSnapshot::date`] and match them with
+    /// [`PulseCondition::DateEquals`](crate::PulseCondition::DateEquals).
+    ///
+    /// ```
+    /// use beeclock_core::{ClockBuilder, Weekday};
+    ///
+    /// let mut clock = ClockBuilder::calendar()
+    ///     .pulse_when("midnight", "hour == 0 && min == 0 && sec == 0".parse().unwrap())
+    ///     .pulse_when("sunday_noon", "date(weekday) == 7 && hour == 12".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// clock.skip(59 * 86_400 + 12 * 3600 - 1);
+    /// assert_eq!(&*clock.tick().pulses[0].name, "sunday_noon");
+    /// let now = clock.snapshot();
+    /// assert_eq!(now.date().unwrap().to_string(), "1970-03-01");
+    /// assert_eq!(now.date().unwrap().weekday(), Weekday::Sunday);
+    /// assert_eq!(now.get("hour"), 12);
+    /// ```
+    pub fn calendar() -> Self {
+        Self::new()
+            .partition_order(PartitionOrder::LeastSignificantFirst)
+            .partition("sec", 60)
+            .partition("min", 60)
+            .partition("hour", 24)
+            .partition(DAY_PARTITION, u64::MAX)
+    }
+}
+
+impl ClockSnapshot {
+    /// The date of a [calendar](ClockBuilder::calendar) clock, from its
+    /// [`DAY_PARTITION`]; `None` without one or past `i64::MAX` days.
+    pub fn date(&self) -> Option<CivilDate> {
+        date_of(self.partitions.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_years_follow_the_gregorian_rules() {
+        assert!(is_leap_year(2000) && is_leap_year(2024) && is_leap_year(-4));
+        assert!(!is_leap_year(1900) && !is_leap_year(2023) && !is_leap_year(2100));
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+        assert_eq!(days_in_month(2023, 13), 0);
+        assert!(CivilDate::new(2000, 2, 29).is_some());
+        assert!(CivilDate::new(1900, 2, 29).is_none());
+        assert!(CivilDate::new(2024, 4, 31).is_none());
+    }
+
+    #[test]
+    fn day_counts_match_walking_the_calendar() {
+        // Walk 1600-01-01..2401-01-01 one day at a time.
+        let mut date = CivilDate::new(1600, 1, 1).unwrap();
+        let mut days = date.to_days();
+        assert_eq!(CivilDate::from_days(0), CivilDate::new(1970, 1, 1).unwrap());
+        assert_eq!(CivilDate::from_days(-1).to_string(), "1969-12-31");
+        while date.year <= 2400 {
+            assert_eq!(CivilDate::from_days(days), date);
+            assert_eq!(date.to_days(), days);
+            date = match CivilDate::new(date.year, date.month, date.day + 1) {
+                Some(next) => next,
+                None if date.month == 12 => CivilDate::new(date.year + 1, 1, 1).unwrap(),
+                None => CivilDate::new(date.year, date.month + 1, 1).unwrap(),
+            };
+            days += 1;
+        }
+        assert_eq!(
+            CivilDate::new(2000, 1, 1).unwrap().weekday(),
+            Weekday::Saturday
+        );
+        assert_eq!(
+            CivilDate::new(1969, 7, 20).unwrap().weekday(),
+            Weekday::Sunday
+        );
+    }
+
+    #[test]
+    fn calendar_clock_rolls_over_into_a_leap_day() {
+        let mut clock = ClockBuilder::calendar().build().unwrap();
+        let leap_eve = CivilDate::new(2024, 2, 28).unwrap().to_days() as u64;
+        clock.skip(leap_eve * 86_400 + 86_399);
+        assert_eq!(clock.snapshot().date().unwrap().to_string(), "2024-02-28");
+        clock.tick();
+        let midnight = clock.snapshot();
+        assert_eq!(midnight.date().unwrap().to_string(), "2024-02-29");
+        assert_eq!((midnight.get("hour"), midnight.get("sec")), (0, 0));
+        assert!(ClockBuilder::new()
+            .build()
+            .unwrap()
+            .snapshot()
+            .date()
+            .is_none());
+    }
+
+    #[test]
+    fn date_conditions_match_derived_fields() {
+        let leap_day = "date(month) == 2 && date(day) == 29 && hour == 0 && min == 0 && sec == 0";
+        let config = ClockBuilder::calendar()
+            .pulse_when("leap_day", leap_day.parse().unwrap())
+            .pulse_when("friday", "date(weekday) == 5 && hour == 0".parse().unwrap())
+            .build()
+            .unwrap()
+            .config();
+        let mut clock = config.clone().build().unwrap();
+        let mut arena = crate::ClockArena::new(config).unwrap();
+        let agent = arena.push();
+
+        // 2024-02-28 23:59:59, a Wednesday, then midnight into Thursday 29th.
+        let leap_eve = CivilDate::new(2024, 2, 28).unwrap().to_days() as u64;
+        clock.skip(leap_eve * 86_400 + 86_399);
+        for (name, value) in clock
+            .snapshot()
+            .partitions
+            .iter()
+            .map(|p| (&p.name, p.value))
+        {
+            arena.set_partition(agent, name, value).unwrap();
+        }
+        let fired = clock.tick();
+        arena.tick_all();
+        assert_eq!(
+            fired
+                .pulses
+                .iter()
+                .map(|p| &*p.name)
+                .collect::<alloc::vec::Vec<_>>(),
+            ["leap_day"]
+        );
+        assert_eq!(arena.fired(agent).collect::<alloc::vec::Vec<_>>(), [0]);
+        assert_eq!(
+            clock.explain("friday").unwrap().root.children[0].reason,
+            "date is 2024-02-29, weekday 4"
+        );
+
+        clock.skip(86_400);
+        assert_eq!(&*clock.tick().pulses[0].name, "friday");
+        assert!(matches!(
+            ClockBuilder::new()
+                .least_significant_first()
+                .partition("sec", 60)
+                .pulse_when("never", "date(year) == 2024".parse().unwrap())
+                .build(),
+            Err(crate::ClockError::UnknownPartition { partition, .. }) if partition == DAY_PARTITION
+        ));
+    }
+}
@@ -1,30 +1,90 @@
 //! Clock implementation and builder.
 
-use alloc::collections::BTreeSet;
 use alloc::string::{String, ToString};
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::time::Duration;
 
+use crate::ahead::RunAhead;
+use crate::alarm::Alarms;
+use crate::batch::EveryIndex;
+use crate::epoch::EpochHook;
+use crate::explain::explain_condition;
+use crate::gear::ChildClock;
+use crate::partition::PartitionTable;
+use crate::pending::PendingQueue;
+use crate::profile::Profile;
+use crate::pulse::PulseState;
+use crate::quiet::QuietWindows;
+use crate::state_machine::AttachedMachine;
+use crate::validate::{partition_names, validate_layout, validate_stateless_condition};
 use crate::{
-    ClockError, ClockSnapshot, PartitionOrder, PartitionSpec, PartitionState, PulseCondition,
-    PulseFired, PulseSpec, TickOutcome,
+    AlarmTarget, ClockConfig, ClockDuration, ClockError, ClockSnapshot, Explanation, Gear,
+    PartitionInfo, PartitionOrder, PartitionSpec, PulseCondition, PulseFired, PulseList, PulseSpec,
+    QuietWindow, Skipped, StateMachine, TaggedOutcome, TickBuffer, TickOutcome,
 };
 
 #[cfg(feature = "std")]
 use std::sync::mpsc::{self, Receiver};
 
 #[cfg(feature = "std")]
-use crate::Subscriber;
+use crate::subscriber::ChangeSink;
+#[cfg(feature = "std")]
+use crate::{OutcomeSink, PartitionChange, PulseFilter, SinkStatus};
+
+/// Default name of the pulse fired when the tick counter overflows into a
+/// new epoch (see [`ClockBuilder::overflow_pulse`]).
+pub const OVERFLOW_PULSE: &str = "__overflow__";
 
 /// Logical clock with partitioned time and predicate pulses.
 #[derive(Debug)]
 pub struct Clock {
-    tick: u64,
-    epoch: u64,
-    partitions: Vec<PartitionState>,
-    partition_order: PartitionOrder,
-    pulses: Vec<PulseSpec>,
+    name: Option<Arc<str>>,
+    pub(crate) tick: u64,
+    pub(crate) epoch: u64,
+    pub(crate) partitions: PartitionTable,
+    pub(crate) partition_order: PartitionOrder,
+    pub(crate) pulses: Vec<PulseSpec>,
+    pub(crate) pulse_state: Vec<PulseState>,
+    every: EveryIndex,
+    pub(crate) pending: PendingQueue,
+    pub(crate) quiet: QuietWindows,
+    pub(crate) alarms: Alarms,
+    pub(crate) children: Vec<ChildClock>,
+    pub(crate) machines: Vec<AttachedMachine>,
+    /// Tick on which the clock halts, from [`Clock::set_run_for`].
+    run_for: Option<u64>,
+    /// Real time per tick, from [`Clock::set_tick_duration`].
+    tick_duration: Option<Duration>,
+    /// Elapsed time given to [`Clock::tick_elapsed`] not yet making up a
+    /// whole tick.
+    pub(crate) elapsed_carry: Duration,
+    /// Name fired on tick overflow, or `None` to fire nothing.
+    overflow_pulse: Option<String>,
+    pub(crate) halted: bool,
+    /// Fraction of a tick accumulated by [`Clock::advance_scaled`], in
+    /// 32.32 fixed point.
+    pub(crate) fraction: u32,
+    /// Evaluation costs, from [`Clock::set_profiling`].
+    pub(crate) profile: Option<Profile>,
+    /// Outcomes computed ahead, from [`Clock::precompute`].
+    pub(crate) ahead: Option<RunAhead>,
+    /// Partitions read off the epoch, least significant first.
+    pub(crate) epoch_partitions: Vec<PartitionSpec>,
+    /// Hooks from [`Clock::on_epoch_change`].
+    pub(crate) epoch_hooks: Vec<EpochHook>,
     #[cfg(feature = "std")]
-    subscribers: Vec<Subscriber>,
+    subscribers: Vec<Box<dyn OutcomeSink>>,
+}
+
+/// One tick in the fixed-point scale of [`Clock::advance_scaled`].
+const TICK_SCALE: f64 = 4_294_967_296.0;
+
+/// What happened to a pulse during evaluation.
+enum PulseEvent {
+    Fired,
+    Expired,
 }
 
 impl Clock {
@@ -39,36 +99,73 @@ impl Clock {
         partitions: Vec<PartitionSpec>,
         pulses: Vec<PulseSpec>,
     ) -> Result<Self, ClockError> {
-        // Validate partitions
-        let known_partitions: BTreeSet<String> =
-            partitions.iter().map(|p| p.name.clone()).collect();
-
-        let mut states = Vec::with_capacity(partitions.len());
-        for spec in &partitions {
-            if spec.modulus == 0 {
-                return Err(ClockError::ZeroModulus {
-                    name: spec.name.clone(),
-                });
-            }
-            states.push(PartitionState::from_spec(spec));
-        }
+        Self::with_ticks_per_unit(partition_order, partitions, pulses, 1)
+    }
 
-        // Validate pulse conditions
-        for pulse in &pulses {
-            validate_condition(&pulse.condition, &known_partitions, &pulse.name)?;
+    /// [`Clock::new`] with the least significant partition advancing once
+    /// every `ticks_per_unit` ticks.
+    pub(crate) fn with_ticks_per_unit(
+        partition_order: PartitionOrder,
+        partitions: Vec<PartitionSpec>,
+        pulses: Vec<PulseSpec>,
+        ticks_per_unit: u64,
+    ) -> Result<Self, ClockError> {
+        if ticks_per_unit == 0 {
+            return Err(ClockError::ZeroTicksPerUnit);
         }
+        validate_layout(&partitions, &pulses)?;
+
+        let ticks_of = |duration: &ClockDuration| {
+            duration.ticks_in(partition_order, &partitions, ticks_per_unit)
+        };
+        let pulse_state = pulses
+            .iter()
+            .map(|pulse| PulseState::new(pulse, &ticks_of))
+            .collect::<Result<_, _>>()?;
+        let mut table = PartitionTable::from_specs(&partitions);
+        table.set_prescale(ticks_per_unit);
 
         Ok(Self {
+            name: None,
             tick: 0,
             epoch: 0,
-            partitions: states,
+            partitions: table,
             partition_order,
+            every: EveryIndex::new(&pulses),
+            pending: PendingQueue::new(&pulses),
+            quiet: QuietWindows::default(),
+            alarms: Alarms::default(),
+            children: Vec::new(),
+            machines: Vec::new(),
+            run_for: None,
+            tick_duration: None,
+            elapsed_carry: Duration::ZERO,
+            overflow_pulse: Some(String::from(OVERFLOW_PULSE)),
+            halted: false,
+            fraction: 0,
+            profile: None,
+            ahead: None,
+            epoch_partitions: Vec::new(),
+            epoch_hooks: Vec::new(),
+            pulse_state,
             pulses,
             #[cfg(feature = "std")]
             subscribers: Vec::new(),
         })
     }
 
+    /// Name stamped onto this clock's outcomes and fired pulses, if any.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Set the name stamped onto outcomes and fired pulses from the next tick.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(Arc::from(name.into()));
+        self.rerun_ahead();
+    }
+
     /// Get the current tick count.
     #[inline]
     pub fn tick_count(&self) -> u64 {
@@ -81,306 +178,1410 @@ impl Clock {
         self.epoch
     }
 
+    /// Halt the clock on reaching tick `ticks`.
+    ///
+    /// The tick that reaches it evaluates as usual and also fires
+    /// `__halted__`; after that the clock stays put and every `tick()`
+    /// returns a terminal outcome with the final snapshot and no pulses (see
+    /// [`Clock::is_halted`]). If the clock is already past `ticks`, the next
+    /// tick halts it. Has no effect once the clock has halted.
+    pub fn set_run_for(&mut self, ticks: u64) -> Result<(), ClockError> {
+        if ticks == 0 {
+            return Err(ClockError::ZeroRunLength);
+        }
+        self.run_for = Some(ticks);
+        self.rerun_ahead();
+        Ok(())
+    }
+
+    /// Ticks per unit of the least significant partition (see
+    /// [`ClockBuilder::ticks_per_unit`]); 1 unless scaled.
+    #[inline]
+    pub fn ticks_per_unit(&self) -> u64 {
+        self.partitions.prescale()
+    }
+
+    /// Tick on which the clock halts, if it has a run budget.
+    #[inline]
+    pub fn run_for(&self) -> Option<u64> {
+        self.run_for
+    }
+
+    /// Declare the real time one tick stands for, so [`Clock::tick_elapsed`]
+    /// can convert wall time into ticks. Discards any carried remainder.
+    pub fn set_tick_duration(&mut self, duration: Duration) -> Result<(), ClockError> {
+        if duration.is_zero() {
+            return Err(ClockError::ZeroTickDuration);
+        }
+        self.tick_duration = Some(duration);
+        self.elapsed_carry = Duration::ZERO;
+        Ok(())
+    }
+
+    /// Real time one tick stands for, if declared.
+    #[inline]
+    pub fn tick_duration(&self) -> Option<Duration> {
+        self.tick_duration
+    }
+
+    /// Whether the clock has used up its run budget.
+    #[inline]
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Name of the pulse fired when the tick counter overflows, `None` if
+    /// none is.
+    #[inline]
+    pub fn overflow_pulse(&self) -> Option<&str> {
+        self.overflow_pulse.as_deref()
+    }
+
+    /// Rename the overflow pulse ([`OVERFLOW_PULSE`] by default), or stop
+    /// firing it with `None`. [`TickOutcome::overflowed`] still reports the
+    /// overflow either way.
+    pub fn set_overflow_pulse(&mut self, name: Option<String>) {
+        self.overflow_pulse = name;
+        self.rerun_ahead();
+    }
+
+    /// Set a partition's value without advancing time.
+    ///
+    /// The tick counter is unchanged, so tick-based conditions (`Every`,
+    /// `TickRange`) are unaffected; partition conditions see the new value
+    /// from the next tick on.
+    pub fn set_partition(&mut self, name: &str, value: u64) -> Result<(), ClockError> {
+        let idx = self
+            .partitions
+            .position(name)
+            .ok_or_else(|| ClockError::PartitionNotFound {
+                name: name.to_string(),
+            })?;
+        let modulus = self.partitions.modulus(idx);
+        if value >= modulus {
+            return Err(ClockError::ValueOutOfRange {
+                name: name.to_string(),
+                value,
+                modulus,
+            });
+        }
+        self.partitions.set(idx, value);
+        self.rerun_ahead();
+        Ok(())
+    }
+
+    /// Get this clock's configuration (partitions, order, and pulses).
+    pub fn config(&self) -> ClockConfig {
+        ClockConfig {
+            name: self.name.as_deref().map(String::from),
+            order: self.partition_order,
+            partitions: self.partitions.specs(),
+            pulses: self.pulses.clone(),
+            quiet_windows: self.quiet.windows().to_vec(),
+            run_for: self.run_for,
+            tick_duration: self.tick_duration,
+            overflow_pulse: self.overflow_pulse.clone(),
+            ticks_per_unit: (self.ticks_per_unit() > 1).then_some(self.ticks_per_unit()),
+            epoch_partitions: self.epoch_partitions.clone(),
+        }
+    }
+
+    /// Get the number of configured pulses.
+    #[inline]
+    pub fn pulse_count(&self) -> usize {
+        self.pulses.len()
+    }
+
+    /// Name, modulus and labels of each partition, in configured order.
+    pub fn partitions(&self) -> impl ExactSizeIterator<Item = PartitionInfo<'_>> + '_ {
+        self.partitions.infos()
+    }
+
+    /// Name and condition of each pulse, in index order (see
+    /// [`Clock::pulse_name`]). Expired pulses are listed until
+    /// [pruned](Clock::prune_expired).
+    pub fn pulses(&self) -> impl ExactSizeIterator<Item = (&str, &PulseCondition)> + '_ {
+        self.pulses
+            .iter()
+            .map(|pulse| (pulse.name.as_str(), &pulse.condition))
+    }
+
     /// Get a snapshot without advancing time.
     pub fn snapshot(&self) -> ClockSnapshot {
         ClockSnapshot {
             tick: self.tick,
             epoch: self.epoch,
-            partitions: self.partitions.clone(),
+            partitions: self.partitions.states(),
+            order: self.partition_order,
         }
     }
 
-    /// Subscribe with an unbounded channel (backpressure ignored).
-    #[cfg(feature = "std")]
-    pub fn subscribe(&mut self) -> Receiver<TickOutcome> {
-        let (tx, rx) = mpsc::channel();
-        self.subscribers.push(Subscriber::Unbounded(tx));
-        rx
+    /// Check that `snapshot` could have come from this clock: same partition
+    /// names, order, and count, each value below its modulus.
+    pub fn accepts(&self, snapshot: &ClockSnapshot) -> Result<(), ClockError> {
+        snapshot.check_layout(self.partitions.layout())
     }
 
-    /// Subscribe with a bounded channel (drops on full).
-    #[cfg(feature = "std")]
-    pub fn subscribe_bounded(&mut self, capacity: usize) -> Receiver<TickOutcome> {
-        let (tx, rx) = mpsc::sync_channel(capacity);
-        self.subscribers.push(Subscriber::Bounded(tx));
-        rx
+    /// Get the current partition values in configured order.
+    #[inline]
+    pub fn partition_values(&self) -> &[u64] {
+        self.partitions.values()
     }
 
-    /// Advance logical time by one tick and return the outcome.
-    pub fn tick(&mut self) -> TickOutcome {
-        // Advance tick counter
-        let (next_tick, overflowed) = self.tick.overflowing_add(1);
-        self.tick = next_tick;
-        if overflowed {
-            self.epoch = self.epoch.wrapping_add(1);
-        }
-
-        // Advance partitions
-        self.advance_partitions();
-
-        // Build snapshot
-        let snapshot = self.snapshot();
+    /// Whether the named pulse has expired (see [`PulseSpec::expires_at_tick`]
+    /// and [`PulseSpec::max_fires`]).
+    ///
+    /// Returns `None` if no pulse has this name.
+    pub fn is_expired(&self, pulse_name: &str) -> Option<bool> {
+        let index = self.pulses.iter().position(|p| p.name == pulse_name)?;
+        Some(self.pulse_state[index].expired)
+    }
 
-        // Evaluate pulses
-        let mut fired = Vec::new();
-        for pulse in &self.pulses {
-            if pulse.condition.is_met(self.tick, &snapshot) {
-                fired.push(PulseFired {
-                    name: pulse.name.clone(),
-                    tick: self.tick,
-                    epoch: self.epoch,
-                });
-            }
+    /// Remove expired pulses, returning how many were removed.
+    ///
+    /// Expired pulses never fire again but keep their index until pruned, so
+    /// indices reported by [`TickBuffer::fired`] shift after this call.
+    pub fn prune_expired(&mut self) -> usize {
+        let before = self.pulses.len();
+        let remap: Vec<Option<usize>> = self
+            .pulse_state
+            .iter()
+            .scan(0, |next, state| {
+                let index = (!state.expired).then_some(*next);
+                *next += usize::from(!state.expired);
+                Some(index)
+            })
+            .collect();
+        let mut state = self.pulse_state.iter();
+        self.pulses
+            .retain(|_| !state.next().is_some_and(|s| s.expired));
+        self.pulse_state.retain(|state| !state.expired);
+        if let Some(profile) = &mut self.profile {
+            profile.retain(&remap);
         }
-
-        // Add overflow pulse if applicable
-        if overflowed {
-            fired.push(PulseFired {
-                name: "__overflow__".to_string(),
-                tick: self.tick,
-                epoch: self.epoch,
-            });
+        self.every = EveryIndex::new(&self.pulses);
+        self.every.prepare(self.tick);
+        self.pending = self.pending.rebuild(&self.pulses, |idx| remap[idx]);
+        for child in &mut self.children {
+            child.trigger = child.trigger.and_then(|idx| remap[idx]);
+        }
+        for machine in &mut self.machines {
+            machine.remap(&remap);
         }
+        self.rerun_ahead();
+        before - self.pulses.len()
+    }
 
-        let outcome = TickOutcome {
-            snapshot,
-            pulses: fired,
-            overflowed,
-        };
+    /// Number of fires of the named pulse suppressed by
+    /// [`PulseCondition::RateLimit`] wrappers so far.
+    ///
+    /// Returns `None` if no pulse has this name.
+    pub fn suppressed_count(&self, pulse_name: &str) -> Option<u64> {
+        let index = self.pulses.iter().position(|p| p.name == pulse_name)?;
+        Some(self.pulse_state[index].condition.suppressed())
+    }
 
-        // Broadcast to subscribers
-        #[cfg(feature = "std")]
-        self.broadcast(&outcome);
+    /// Number of delayed pulse fires scheduled but not yet due.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
 
-        outcome
+    /// Add a quiet window during which no user pulses fire.
+    ///
+    /// Takes effect from the next tick. The window's condition is evaluated
+    /// without per-pulse state, so a [stateful](PulseCondition::is_stateful)
+    /// one fails with [`ClockError::StatefulCondition`].
+    pub fn add_quiet_window(&mut self, window: QuietWindow) -> Result<(), ClockError> {
+        let known_partitions = partition_names(&self.partitions.specs());
+        validate_stateless_condition(&window.condition, &known_partitions, &window.name)?;
+        self.quiet.push(window);
+        self.rerun_ahead();
+        Ok(())
     }
 
-    fn advance_partitions(&mut self) {
-        let mut carry = true;
-        match self.partition_order {
-            PartitionOrder::LeastSignificantFirst => {
-                for partition in &mut self.partitions {
-                    if !carry {
-                        break;
-                    }
-                    carry = partition.increment();
-                }
+    /// Set a one-shot alarm, replacing any pending alarm with the same name.
+    ///
+    /// When the target is reached the alarm fires as a pulse named `name`
+    /// (after the configured pulses, and not suppressed by quiet windows),
+    /// then clears itself. A tick target jumped over by [`Clock::observe`]
+    /// fires on the tick after the jump.
+    pub fn set_alarm(
+        &mut self,
+        name: impl Into<String>,
+        target: AlarmTarget,
+    ) -> Result<(), ClockError> {
+        let name = name.into();
+        if let AlarmTarget::Tick(tick) = target {
+            if tick <= self.tick {
+                return Err(ClockError::AlarmInPast { name, tick });
             }
-            PartitionOrder::MostSignificantFirst => {
-                for partition in self.partitions.iter_mut().rev() {
-                    if !carry {
-                        break;
-                    }
-                    carry = partition.increment();
+        }
+        let pattern = self.alarm_pattern(&target)?;
+        self.alarms.set(name, self.tick, target, pattern);
+        self.rerun_ahead();
+        Ok(())
+    }
+
+    /// Resolve an alarm target's partition names to `(index, value)` pairs,
+    /// checking each value is in range.
+    pub(crate) fn alarm_pattern(
+        &self,
+        target: &AlarmTarget,
+    ) -> Result<Vec<(usize, u64)>, ClockError> {
+        let AlarmTarget::Partitions(values) = target else {
+            return Ok(Vec::new());
+        };
+        let mut pattern = Vec::with_capacity(values.len());
+        for (partition, value) in values {
+            let idx = self.partitions.position(partition).ok_or_else(|| {
+                ClockError::PartitionNotFound {
+                    name: partition.clone(),
                 }
+            })?;
+            let modulus = self.partitions.modulus(idx);
+            if *value >= modulus {
+                return Err(ClockError::ValueOutOfRange {
+                    name: partition.clone(),
+                    value: *value,
+                    modulus,
+                });
             }
+            pattern.push((idx, *value));
         }
+        Ok(pattern)
     }
 
-    #[cfg(feature = "std")]
-    fn broadcast(&mut self, outcome: &TickOutcome) {
-        self.subscribers.retain(|sub| sub.send(outcome));
+    /// Start a countdown that fires a pulse named `name` after `ticks` ticks,
+    /// replacing any pending alarm or countdown with the same name.
+    pub fn start_countdown(
+        &mut self,
+        name: impl Into<String>,
+        ticks: u64,
+    ) -> Result<(), ClockError> {
+        let name = name.into();
+        if ticks == 0 {
+            return Err(ClockError::ZeroDelay { name });
+        }
+        self.alarms.start_countdown(name, self.tick, ticks);
+        self.rerun_ahead();
+        Ok(())
     }
-}
 
-impl Default for Clock {
-    fn default() -> Self {
-        Clock::builder()
-            .least_significant_first()
-            .partition("sec", 60)
-            .partition("min", 60)
-            .partition("hour", 24)
-            .build()
-            .expect("default clock configuration should be valid")
+    /// Restart a pending countdown from its full length, returning whether
+    /// one was running.
+    pub fn restart_countdown(&mut self, name: &str) -> bool {
+        let restarted = self.alarms.restart_countdown(name, self.tick);
+        self.rerun_ahead();
+        restarted
     }
-}
-
-/// Builder for configuring a clock.
-#[derive(Debug, Default)]
-pub struct ClockBuilder {
-    partitions: Vec<PartitionSpec>,
-    pulses: Vec<PulseSpec>,
-    order: Option<PartitionOrder>,
-}
 
-impl ClockBuilder {
-    /// Start a new builder with no partitions or pulses.
-    pub fn new() -> Self {
-        Self::default()
+    /// Cancel a pending countdown, returning whether one was running.
+    pub fn cancel_countdown(&mut self, name: &str) -> bool {
+        let cancelled = self.alarms.cancel_countdown(name);
+        self.rerun_ahead();
+        cancelled
     }
 
-    /// Set the partition order explicitly.
-    pub fn partition_order(mut self, order: PartitionOrder) -> Self {
-        self.order = Some(order);
-        self
+    /// Ticks left before a pending countdown fires.
+    pub fn remaining(&self, name: &str) -> Option<u64> {
+        self.alarms.remaining(name, self.tick)
     }
 
-    /// Configure least-significant-first order.
-    pub fn least_significant_first(self) -> Self {
-        self.partition_order(PartitionOrder::LeastSignificantFirst)
+    /// Cancel a pending alarm, returning whether one was set.
+    pub fn cancel_alarm(&mut self, name: &str) -> bool {
+        let cancelled = self.alarms.cancel(name);
+        self.rerun_ahead();
+        cancelled
     }
 
-    /// Configure most-significant-first order.
-    pub fn most_significant_first(self) -> Self {
-        self.partition_order(PartitionOrder::MostSignificantFirst)
+    /// Target of a pending alarm.
+    pub fn alarm(&self, name: &str) -> Option<&AlarmTarget> {
+        self.alarms.get(name)
     }
 
-    /// Add a single partition.
-    pub fn partition(mut self, name: impl Into<String>, modulus: u64) -> Self {
-        self.partitions.push(PartitionSpec {
-            name: name.into(),
-            modulus,
-        });
-        self
+    /// Tick on which a pending alarm will fire.
+    ///
+    /// For a partition pattern this is the next tick on which the
+    /// partitions match, counting through their wrap-around, assuming no
+    /// [`Clock::set_partition`] or [`Clock::skip`] in between. `None` if
+    /// no alarm has this name, or if it would fire only after the tick
+    /// counter overflows.
+    pub fn alarm_due(&self, name: &str) -> Option<u64> {
+        let ticks = self.alarms.due_in(name, self.tick, |pattern| {
+            self.partitions.ticks_until(pattern, self.partition_order)
+        })?;
+        self.tick.checked_add(ticks)
     }
 
-    /// Add partitions in an explicit order.
-    pub fn partition_chain<I>(mut self, order: PartitionOrder, specs: I) -> Self
-    where
-        I: IntoIterator<Item = PartitionSpec>,
-    {
-        self.order = Some(order);
-        self.partitions.extend(specs);
-        self
+    /// Pending alarms, in the order they were set.
+    pub fn alarms(&self) -> impl Iterator<Item = (&str, &AlarmTarget)> {
+        self.alarms.iter()
     }
 
-    /// Add a periodic pulse.
-    pub fn pulse_every(mut self, name: impl Into<String>, period: u64) -> Self {
-        self.pulses.push(PulseSpec {
-            name: name.into(),
-            condition: PulseCondition::Every(period),
-        });
-        self
+    /// Attach a child clock that ticks from inside this clock's tick (see
+    /// [`Gear`]). Its outcomes appear in [`TickOutcome::children`].
+    pub fn attach_child(
+        &mut self,
+        name: impl Into<String>,
+        clock: Clock,
+        gear: Gear,
+    ) -> Result<(), ClockError> {
+        let name = name.into();
+        if self.children.iter().any(|c| *c.name == name) {
+            return Err(ClockError::DuplicateClock { name });
+        }
+        let trigger = match &gear {
+            Gear::Every(0) => return Err(ClockError::ZeroDivider { name }),
+            Gear::Every(_) => None,
+            Gear::OnPulse(pulse) => match self.pulses.iter().position(|p| p.name == *pulse) {
+                Some(index) => Some(index),
+                None => {
+                    return Err(ClockError::UnknownTrigger {
+                        pulse: name,
+                        trigger: pulse.clone(),
+                    })
+                }
+            },
+        };
+        self.children
+            .push(ChildClock::new(name, clock, gear, trigger));
+        // Clocks with children aren't precomputed.
+        self.ahead = None;
+        Ok(())
     }
 
-    /// Add a predicate-based pulse.
-    pub fn pulse_when(mut self, name: impl Into<String>, condition: PulseCondition) -> Self {
-        self.pulses.push(PulseSpec {
-            name: name.into(),
-            condition,
-        });
-        self
+    /// Detach and return a child clock by name.
+    pub fn detach_child(&mut self, name: &str) -> Option<Clock> {
+        let index = self.children.iter().position(|c| &*c.name == name)?;
+        Some(self.children.remove(index).clock)
     }
 
-    /// Build the configured clock.
-    pub fn build(self) -> Result<Clock, ClockError> {
-        let order = match self.order {
-            Some(order) => order,
-            None => {
-                if self.partitions.is_empty() {
-                    PartitionOrder::LeastSignificantFirst
-                } else {
-                    return Err(ClockError::MissingPartitionOrder);
-                }
-            }
-        };
-        Clock::new(order, self.partitions, self.pulses)
+    /// Get an attached child clock by name.
+    pub fn child(&self, name: &str) -> Option<&Clock> {
+        self.children
+            .iter()
+            .find(|c| &*c.name == name)
+            .map(|c| &c.clock)
     }
-}
 
-// ─────────────────────────────────────────────────────────────
-// Validation
-// ─────────────────────────────────────────────────────────────
+    /// Get an attached child clock by name, mutably.
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut Clock> {
+        self.children
+            .iter_mut()
+            .find(|c| &*c.name == name)
+            .map(|c| &mut c.clock)
+    }
 
-fn validate_condition(
-    condition: &PulseCondition,
-    partitions: &BTreeSet<String>,
-    pulse_name: &str,
-) -> Result<(), ClockError> {
-    match condition {
-        PulseCondition::Every(period) => {
-            if *period == 0 {
-                Err(ClockError::ZeroPeriod {
-                    name: pulse_name.to_string(),
-                })
-            } else {
-                Ok(())
-            }
+    /// Attach a state machine driven by this clock's pulses. It starts in its
+    /// initial state, and each transition fires a `<name>:<state>` pulse.
+    pub fn attach_machine(
+        &mut self,
+        name: impl Into<String>,
+        machine: StateMachine,
+    ) -> Result<(), ClockError> {
+        let name = name.into();
+        if self.machines.iter().any(|m| *m.name == name) {
+            return Err(ClockError::DuplicateMachine { name });
         }
-
-        PulseCondition::PartitionEquals { name, .. } => {
-            if partitions.contains(name) {
-                Ok(())
-            } else {
-                Err(ClockError::UnknownPartition {
-                    pulse: pulse_name.to_string(),
-                    partition: name.clone(),
-                })
+        let known_partitions = partition_names(&self.partitions.specs());
+        for transition in machine.transitions() {
+            if let Some(guard) = &transition.guard {
+                validate_stateless_condition(guard, &known_partitions, &name)?;
             }
         }
+        let machine = AttachedMachine::new(name, machine, |pulse| {
+            self.pulses.iter().position(|p| p.name == pulse)
+        })?;
+        self.machines.push(machine);
+        self.rerun_ahead();
+        Ok(())
+    }
 
-        PulseCondition::PartitionModulo { name, modulus, .. } => {
-            if *modulus == 0 {
-                return Err(ClockError::ZeroConditionModulus {
-                    pulse: pulse_name.to_string(),
-                    partition: name.clone(),
-                });
-            }
-            if partitions.contains(name) {
-                Ok(())
-            } else {
-                Err(ClockError::UnknownPartition {
-                    pulse: pulse_name.to_string(),
-                    partition: name.clone(),
-                })
-            }
-        }
+    /// Detach and return a state machine by name.
+    pub fn detach_machine(&mut self, name: &str) -> Option<StateMachine> {
+        let index = self.machines.iter().position(|m| &*m.name == name)?;
+        let machine = self.machines.remove(index).machine;
+        self.rerun_ahead();
+        Some(machine)
+    }
+
+    /// Current state of an attached state machine.
+    pub fn machine_state(&self, name: &str) -> Option<&str> {
+        self.machines
+            .iter()
+            .find(|m| &*m.name == name)
+            .map(AttachedMachine::state)
+    }
 
-        PulseCondition::TickRange { start, end } => {
-            if start > end {
-                Err(ClockError::InvalidTickRange {
-                    pulse: pulse_name.to_string(),
-                    start: *start,
-                    end: *end,
+    /// Move an attached state machine to `state` without firing anything.
+    pub fn set_machine_state(&mut self, name: &str, state: &str) -> Result<(), ClockError> {
+        match self.machines.iter_mut().find(|m| &*m.name == name) {
+            Some(machine) => machine.set_state(state)?,
+            None => {
+                return Err(ClockError::UnknownMachine {
+                    name: String::from(name),
                 })
-            } else {
-                Ok(())
             }
         }
+        self.rerun_ahead();
+        Ok(())
+    }
+
+    /// Whether a quiet window was active on the current tick.
+    #[inline]
+    pub fn is_quiet(&self) -> bool {
+        self.quiet.is_quiet()
+    }
 
-        PulseCondition::Not(inner) => validate_condition(inner, partitions, pulse_name),
+    /// Subscribe with an unbounded channel (backpressure ignored).
+    #[cfg(feature = "std")]
+    pub fn subscribe(&mut self) -> Receiver<TickOutcome> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(Box::new(tx));
+        rx
+    }
 
-        PulseCondition::And(conditions) | PulseCondition::Or(conditions) => {
-            for c in conditions {
-                validate_condition(c, partitions, pulse_name)?;
-            }
-            Ok(())
+    /// Subscribe to outcomes carrying pulses whose names match `pattern`,
+    /// where `*` matches any run of characters (e.g. `"ui.*"`). Outcomes
+    /// arrive with only the matching pulses; ticks with none are not sent.
+    /// See [`PulseFilter`] to filter other sinks.
+    #[cfg(feature = "std")]
+    pub fn subscribe_matching(&mut self, pattern: impl Into<String>) -> Receiver<TickOutcome> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers
+            .push(Box::new(PulseFilter::new(pattern, tx)));
+        rx
+    }
+
+    /// Subscribe to the outcomes of ticks on which a pulse other than the
+    /// overflow pulse fired, sparing subscribers to fast clocks with rare
+    /// pulses from a stream of empty outcomes. The overflow pulse is named
+    /// as of subscribing (see [`PulseFilter::ignoring`]).
+    #[cfg(feature = "std")]
+    pub fn subscribe_fired(&mut self) -> Receiver<TickOutcome> {
+        let (tx, rx) = mpsc::channel();
+        let mut filter = PulseFilter::new("*", tx);
+        if let Some(name) = &self.overflow_pulse {
+            filter = filter.ignoring(name.clone());
         }
+        self.subscribers.push(Box::new(filter));
+        rx
     }
-}
 
-// ─────────────────────────────────────────────────────────────
-// Tests
-// ─────────────────────────────────────────────────────────────
+    /// Subscribe to changes of one partition's value, each reported once
+    /// with its old and new value, instead of every tick's snapshot.
+    ///
+    /// Changes are detected between delivered outcomes, so a value moved
+    /// by [`Clock::skip`] or [`Clock::set_partition`] is reported on the
+    /// next tick, from the value last seen.
+    #[cfg(feature = "std")]
+    pub fn subscribe_partition_changes(
+        &mut self,
+        partition: &str,
+    ) -> Result<Receiver<PartitionChange>, ClockError> {
+        let index =
+            self.partitions
+                .position(partition)
+                .ok_or_else(|| ClockError::PartitionNotFound {
+                    name: partition.to_string(),
+                })?;
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(Box::new(ChangeSink {
+            index,
+            last: self.partitions.values()[index],
+            tx,
+        }));
+        Ok(rx)
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Subscribe with a bounded channel (drops on full).
+    #[cfg(feature = "std")]
+    pub fn subscribe_bounded(&mut self, capacity: usize) -> Receiver<TickOutcome> {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        self.subscribers.push(Box::new(tx));
+        rx
+    }
 
-    #[test]
-    fn tick_cascades_partitions() {
-        let mut clock = Clock::builder()
-            .least_significant_first()
-            .partition("sec", 2)
-            .partition("min", 3)
-            .build()
-            .unwrap();
+    /// Deliver every outcome from [`tick`](Clock::tick) to `sink` until it
+    /// reports [`SinkStatus::Closed`](crate::SinkStatus::Closed).
+    #[cfg(feature = "std")]
+    pub fn attach_sink(&mut self, sink: Box<dyn OutcomeSink>) {
+        self.subscribers.push(sink);
+    }
 
-        let tick1 = clock.tick();
-        assert_eq!(tick1.snapshot.partition("sec").unwrap().value, 1);
-        assert_eq!(tick1.snapshot.partition("min").unwrap().value, 0);
+    /// Number of attached sinks, subscriptions included.
+    #[cfg(feature = "std")]
+    pub fn sink_count(&self) -> usize {
+        self.subscribers.len()
+    }
 
-        let tick2 = clock.tick();
-        assert_eq!(tick2.snapshot.partition("sec").unwrap().value, 0);
-        assert_eq!(tick2.snapshot.partition("min").unwrap().value, 1);
+    /// Get the name of the pulse at `index` (as reported by [`TickBuffer::fired`]).
+    pub fn pulse_name(&self, index: usize) -> Option<&str> {
+        self.pulses.get(index).map(|pulse| pulse.name.as_str())
     }
 
-    #[test]
-    fn pulses_fire_on_period() {
-        let mut clock = Clock::builder()
-            .least_significant_first()
-            .partition("sec", 10)
-            .pulse_every("pulse", 3)
+    /// Explain how a pulse's condition evaluates against the current state.
+    ///
+    /// Returns `None` if no pulse has this name.
+    pub fn explain(&self, pulse_name: &str) -> Option<Explanation> {
+        let pulse = self.pulses.iter().find(|p| p.name == pulse_name)?;
+        Some(Explanation {
+            pulse: pulse.name.clone(),
+            tick: self.tick,
+            root: explain_condition(&pulse.condition, self.tick, &self.partitions),
+        })
+    }
+
+    /// Create a [`TickBuffer`] sized for this clock.
+    pub fn tick_buffer(&self) -> TickBuffer {
+        TickBuffer::with_capacity(self.partitions.len(), self.pulses.len())
+    }
+
+    /// Advance logical time by one tick and return the outcome.
+    ///
+    /// Once the clock [has halted](Clock::is_halted), returns a terminal
+    /// outcome instead, without recording metrics or notifying subscribers.
+    pub fn tick(&mut self) -> TickOutcome {
+        if self.halted {
+            return self.halted_outcome();
+        }
+        let outcome = self.step();
+
+        #[cfg(feature = "metrics")]
+        crate::instrument::record_tick(&outcome);
+
+        // Broadcast to subscribers
+        #[cfg(feature = "std")]
+        self.broadcast(&outcome);
+
+        outcome
+    }
+
+    /// Advance one tick and build the outcome, without recording metrics or
+    /// notifying subscribers.
+    pub(crate) fn step(&mut self) -> TickOutcome {
+        if self.halted {
+            return self.halted_outcome();
+        }
+        if let Some(outcome) = self.take_ahead() {
+            return outcome;
+        }
+        let overflowed = self.advance();
+        let mut quiet_reports = Vec::new();
+        self.quiet.prepare(self.tick, &self.partitions, |report| {
+            quiet_reports.push(report)
+        });
+
+        // Build snapshot
+        let snapshot = self.snapshot();
+
+        // Evaluate pulses
+        let (tick, epoch) = (self.tick, self.epoch);
+        let clock = self.name.clone();
+        let mut fired = PulseList::new();
+        let mut fired_indices = Vec::new();
+        let track_indices = !self.children.is_empty() || !self.machines.is_empty();
+        self.evaluate_pulses(|index, pulse, event| match event {
+            PulseEvent::Fired => {
+                if track_indices {
+                    fired_indices.push(index);
+                }
+                fired.push(PulseFired {
+                    name: pulse.name.clone(),
+                    tick,
+                    epoch,
+                    clock: clock.clone(),
+                })
+            }
+            PulseEvent::Expired if pulse.notify_expiry => fired.push(PulseFired {
+                name: pulse.expiry_pulse_name(),
+                tick,
+                epoch,
+                clock: clock.clone(),
+            }),
+            PulseEvent::Expired => {}
+        });
+
+        if !self.alarms.is_empty() {
+            let values = self.partitions.values();
+            self.alarms.fire_due(tick, values, |name| {
+                fired.push(PulseFired {
+                    name: String::from(&**name),
+                    tick,
+                    epoch,
+                    clock: clock.clone(),
+                })
+            });
+        }
+
+        for machine in &mut self.machines {
+            if let Some(name) = machine.step(tick, &fired_indices, &self.partitions) {
+                fired.push(PulseFired {
+                    name: String::from(&**name),
+                    tick,
+                    epoch,
+                    clock: clock.clone(),
+                });
+            }
+        }
+
+        // Add overflow pulse if applicable
+        if let Some(name) = self.overflow_pulse.as_ref().filter(|_| overflowed) {
+            fired.push(PulseFired {
+                name: name.clone(),
+                tick: self.tick,
+                epoch: self.epoch,
+                clock: self.name.clone(),
+            });
+        }
+
+        if self.reached_run_for() {
+            self.halted = true;
+            fired.push(PulseFired {
+                name: "__halted__".to_string(),
+                tick: self.tick,
+                epoch: self.epoch,
+                clock: self.name.clone(),
+            });
+        }
+
+        let children = self
+            .children
+            .iter_mut()
+            .filter(|child| child.is_due(tick, &fired_indices))
+            .map(|child| TaggedOutcome {
+                clock: Arc::clone(&child.name),
+                outcome: child.clock.tick(),
+            })
+            .collect();
+
+        TickOutcome {
+            clock: self.name.clone(),
+            snapshot: Arc::new(snapshot),
+            pulses: fired,
+            overflowed,
+            quiet_reports,
+            children,
+        }
+    }
+
+    /// Merge a remote Lamport timestamp, then tick.
+    ///
+    /// If `remote_tick` is ahead, the clock first jumps to it, carrying the
+    /// skipped ticks through the partitions without evaluating pulses; the
+    /// tick that follows lands on `max(local, remote) + 1` and fires pulses
+    /// as usual. Delayed fires that fell due in the skipped span fire on that
+    /// tick.
+    pub fn observe(&mut self, remote_tick: u64) -> TickOutcome {
+        if remote_tick > self.tick && !self.halted {
+            self.jump(remote_tick - self.tick);
+            self.rerun_ahead();
+        }
+        self.tick()
+    }
+
+    /// Advance `n` ticks without evaluating pulses or notifying subscribers.
+    ///
+    /// For resynchronizing after the host was suspended, when replaying the
+    /// missed pulses would be wrong. Delayed fires that fell due in the
+    /// skipped span are discarded; tick alarms and countdowns that did fire
+    /// on the next tick, since they mark deadlines rather than replays.
+    /// Child clocks are not advanced, and a halted clock does not move.
+    /// Returns the span skipped, which is the only record of it.
+    pub fn skip(&mut self, n: u64) -> Skipped {
+        let from = self.tick;
+        if self.halted {
+            return Skipped { from, to: from };
+        }
+        self.jump(n);
+        if !self.pending.is_empty() {
+            self.pending.discard_through(self.tick);
+        }
+        self.rerun_ahead();
+        Skipped {
+            from,
+            to: self.tick,
+        }
+    }
+
+    /// Accumulate `delta` ticks, which may be fractional, and perform each
+    /// whole tick the total crosses, returning their outcomes.
+    ///
+    /// For playback at a speed other than one tick per frame: calling this
+    /// with `1.5` or `0.25` every frame runs the clock at 1.5x or a quarter
+    /// speed. The leftover fraction carries over to the next call and is
+    /// kept in 32-bit fixed point, so binary fractions like `0.25` add up
+    /// exactly and the result does not drift with the call pattern. Negative
+    /// and NaN deltas count as zero. Stops early if the clock halts.
+    ///
+    /// An infinite or huge delta runs up to `u64::MAX` ticks, collecting
+    /// every outcome; cap deltas from untrusted sources (a stalled frame
+    /// timer, say) before passing them in.
+    ///
+    /// ```rust
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::default();
+    /// assert!(clock.advance_scaled(0.75).is_empty());
+    /// assert_eq!(clock.advance_scaled(0.75).len(), 1);
+    /// assert_eq!(clock.tick_fraction(), 0.5);
+    /// ```
+    pub fn advance_scaled(&mut self, delta: f64) -> Vec<TickOutcome> {
+        // `as` saturates, and maps negatives and NaN to zero.
+        let total = (self.fraction as u128).saturating_add((delta * TICK_SCALE) as u128);
+        self.fraction = total as u32;
+        self.tick_times(u64::try_from(total >> 32).unwrap_or(u64::MAX))
+    }
+
+    /// Tick once per whole [tick duration](Clock::set_tick_duration) in
+    /// `elapsed`, returning the outcomes.
+    ///
+    /// The remainder carries over to the next call, so feeding in the time
+    /// since the last call from a real-time loop never loses or gains
+    /// ticks, however the frames fall. Stops early if the clock halts.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::builder()
+    ///     .tick_duration(Duration::from_millis(10))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(clock.tick_elapsed(Duration::from_millis(25)).unwrap().len(), 2);
+    /// assert_eq!(clock.tick_elapsed(Duration::from_millis(5)).unwrap().len(), 1);
+    /// ```
+    pub fn tick_elapsed(&mut self, elapsed: Duration) -> Result<Vec<TickOutcome>, ClockError> {
+        let duration = self.tick_duration.ok_or(ClockError::NoTickDuration)?;
+        let total = self.elapsed_carry.as_nanos() + elapsed.as_nanos();
+        let per_tick = duration.as_nanos();
+        // The remainder is below `duration`, so it fits.
+        self.elapsed_carry = Duration::from_nanos((total % per_tick) as u64);
+        let whole = u64::try_from(total / per_tick).unwrap_or(u64::MAX);
+        Ok(self.tick_times(whole))
+    }
+
+    /// Time given to [`Clock::tick_elapsed`] and not yet ticked.
+    #[inline]
+    pub fn elapsed_carry(&self) -> Duration {
+        self.elapsed_carry
+    }
+
+    /// Tick up to `n` times, stopping once halted.
+    fn tick_times(&mut self, n: u64) -> Vec<TickOutcome> {
+        let mut outcomes = Vec::new();
+        for _ in 0..n {
+            if self.halted {
+                break;
+            }
+            outcomes.push(self.tick());
+        }
+        outcomes
+    }
+
+    /// Fraction of a tick accumulated by [`Clock::advance_scaled`] and not
+    /// yet performed, in `[0, 1)`.
+    pub fn tick_fraction(&self) -> f64 {
+        self.fraction as f64 / TICK_SCALE
+    }
+
+    /// Iterate over ticks, calling [`Clock::tick`] on each `next()`.
+    ///
+    /// The iterator ends after the tick that halts the clock (see
+    /// [`Clock::set_run_for`]); without a run budget it never ends, so bound
+    /// it with `take`, `find` and the like:
+    ///
+    /// ```rust
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::builder().pulse_every("beat", 4).build().unwrap();
+    /// let first = clock.iter_ticks().find(|o| !o.pulses.is_empty()).unwrap();
+    /// assert_eq!(first.snapshot.tick, 4);
+    /// ```
+    pub fn iter_ticks(&mut self) -> Ticks<'_> {
+        Ticks { clock: self }
+    }
+
+    /// Advance logical time by one tick, writing the outcome into `out`.
+    ///
+    /// Allocation-free once `out` has capacity for this clock (see
+    /// [`Clock::tick_buffer`]). Fired pulses are reported by index and the
+    /// overflow pulse only through [`TickBuffer::overflowed`]. Quiet window
+    /// reports are dropped, subscribers are not notified, and metrics are not
+    /// recorded, since all need an owned [`TickOutcome`]. Child clocks still
+    /// cascade, but their outcomes are only visible through [`Clock::child`].
+    /// A halted clock leaves its final state in `out` with nothing fired. Any
+    /// [run-ahead cache](Clock::precompute) is discarded.
+    pub fn tick_into(&mut self, out: &mut TickBuffer) {
+        self.ahead = None;
+        let overflowed = !self.halted && self.advance();
+        let halted = !self.halted && self.reached_run_for();
+
+        out.tick = self.tick;
+        out.epoch = self.epoch;
+        out.overflowed = overflowed;
+        out.halted = halted;
+        out.values.clear();
+        out.values.extend_from_slice(self.partitions.values());
+        out.fired.clear();
+        out.expired.clear();
+        out.alarms.clear();
+        out.transitions.clear();
+        if self.halted {
+            return;
+        }
+        self.halted = halted;
+        self.quiet.prepare(self.tick, &self.partitions, |_| {});
+        self.evaluate_pulses(|index, _, event| match event {
+            PulseEvent::Fired => out.fired.push(index),
+            PulseEvent::Expired => out.expired.push(index),
+        });
+        if !self.alarms.is_empty() {
+            let values = self.partitions.values();
+            self.alarms
+                .fire_due(self.tick, values, |name| out.alarms.push(Arc::clone(name)));
+        }
+        for machine in &mut self.machines {
+            if let Some(name) = machine.step(self.tick, &out.fired, &self.partitions) {
+                out.transitions.push(Arc::clone(name));
+            }
+        }
+        for child in &mut self.children {
+            if child.is_due(self.tick, &out.fired) {
+                child.clock.tick_into(&mut child.buffer);
+            }
+        }
+    }
+
+    /// Whether the current tick uses up the run budget.
+    #[inline]
+    fn reached_run_for(&self) -> bool {
+        self.run_for.is_some_and(|at| self.tick >= at)
+    }
+
+    /// Terminal outcome returned by a halted clock.
+    fn halted_outcome(&self) -> TickOutcome {
+        TickOutcome {
+            clock: self.name.clone(),
+            snapshot: Arc::new(self.snapshot()),
+            pulses: PulseList::new(),
+            overflowed: false,
+            quiet_reports: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Move `ticks` forward, carrying through the partitions, without
+    /// preparing or evaluating anything.
+    fn jump(&mut self, ticks: u64) {
+        let (tick, overflowed) = self.tick.overflowing_add(ticks);
+        self.tick = tick;
+        if overflowed {
+            self.epoch = self.epoch.wrapping_add(1);
+            self.epoch_changed(self.epoch.wrapping_sub(1));
+        }
+        self.partitions.advance_by(ticks, self.partition_order);
+    }
+
+    /// Advance the tick counter and partitions, returning true on tick overflow.
+    fn advance(&mut self) -> bool {
+        let (next_tick, overflowed) = self.tick.overflowing_add(1);
+        self.tick = next_tick;
+        if overflowed {
+            self.epoch = self.epoch.wrapping_add(1);
+            self.epoch_changed(self.epoch.wrapping_sub(1));
+        }
+        self.partitions.advance(self.partition_order);
+        self.every.prepare(self.tick);
+        if !self.pending.is_empty() {
+            self.pending.prepare(self.tick);
+        }
+        overflowed
+    }
+
+    /// Evaluate every live pulse at the current tick, reporting fires and
+    /// expiries in pulse order.
+    fn evaluate_pulses(&mut self, mut report: impl FnMut(usize, &PulseSpec, PulseEvent)) {
+        for (index, pulse) in self.pulses.iter().enumerate() {
+            let state = &mut self.pulse_state[index];
+            if state.expired {
+                continue;
+            }
+            let started = self.profile.as_ref().map(|_| Profile::start());
+            let mut evaluated = true;
+            let due = if !state.condition.is_pure() {
+                let met = pulse.condition.evaluate_stateful(
+                    &mut state.condition,
+                    self.tick,
+                    &self.partitions,
+                );
+                met && (pulse.after.is_none() || self.pending.is_due(index))
+            } else if pulse.after.is_some() {
+                evaluated = self.pending.is_due(index);
+                evaluated && pulse.condition.evaluate(self.tick, &self.partitions)
+            } else if self.every.is_batched(index) {
+                evaluated = false;
+                self.every.is_due(index)
+            } else {
+                pulse.condition.evaluate(self.tick, &self.partitions)
+            };
+            if let (Some(profile), Some(started), true) = (&mut self.profile, started, evaluated) {
+                profile.record(index, started);
+            }
+            if due && self.quiet.is_quiet() {
+                self.quiet.suppress();
+            } else if due {
+                state.fires += 1;
+                state.condition.fired(self.tick);
+                if !self.pending.is_empty() {
+                    self.pending.fired(index, self.tick);
+                }
+                report(index, pulse, PulseEvent::Fired);
+            }
+            if pulse.can_expire() && pulse.is_spent(self.tick, state.fires) {
+                state.expired = true;
+                report(index, pulse, PulseEvent::Expired);
+            }
+        }
+    }
+
+    /// Copy of this clock's configuration and state, without subscribers.
+    pub(crate) fn detached(&self) -> Clock {
+        Clock {
+            name: self.name.clone(),
+            tick: self.tick,
+            epoch: self.epoch,
+            partitions: self.partitions.clone(),
+            partition_order: self.partition_order,
+            pulses: self.pulses.clone(),
+            pulse_state: self.pulse_state.clone(),
+            every: self.every.clone(),
+            pending: self.pending.clone(),
+            quiet: self.quiet.clone(),
+            alarms: self.alarms.clone(),
+            children: self.children.iter().map(ChildClock::detached).collect(),
+            machines: self.machines.clone(),
+            run_for: self.run_for,
+            tick_duration: self.tick_duration,
+            elapsed_carry: self.elapsed_carry,
+            overflow_pulse: self.overflow_pulse.clone(),
+            halted: self.halted,
+            fraction: self.fraction,
+            profile: None,
+            ahead: None,
+            epoch_partitions: self.epoch_partitions.clone(),
+            epoch_hooks: Vec::new(),
+            #[cfg(feature = "std")]
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Take on the state of `next`, a detached copy stepped ahead, keeping
+    /// what detaching drops and what stepping leaves alone.
+    pub(crate) fn adopt(&mut self, next: Clock) {
+        *self = Clock {
+            tick_duration: self.tick_duration,
+            elapsed_carry: self.elapsed_carry,
+            fraction: self.fraction,
+            profile: self.profile.take(),
+            ahead: self.ahead.take(),
+            epoch_hooks: core::mem::take(&mut self.epoch_hooks),
+            #[cfg(feature = "std")]
+            subscribers: core::mem::take(&mut self.subscribers),
+            ..next
+        };
+    }
+
+    #[cfg(feature = "std")]
+    fn broadcast(&mut self, outcome: &TickOutcome) {
+        self.subscribers
+            .retain_mut(|sink| sink.deliver(outcome) == SinkStatus::Open);
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .partition("hour", 24)
+            .build()
+            .expect("default clock configuration should be valid")
+    }
+}
+
+/// Endless iterator over a clock's ticks, from [`Clock::iter_ticks`].
+#[derive(Debug)]
+pub struct Ticks<'a> {
+    clock: &'a mut Clock,
+}
+
+impl Iterator for Ticks<'_> {
+    type Item = TickOutcome;
+
+    #[inline]
+    fn next(&mut self) -> Option<TickOutcome> {
+        (!self.clock.halted).then(|| self.clock.tick())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.clock.run_for {
+            _ if self.clock.halted => (0, Some(0)),
+            Some(at) => {
+                let left = at.saturating_sub(self.clock.tick).max(1);
+                let left = usize::try_from(left).unwrap_or(usize::MAX);
+                (left, Some(left))
+            }
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl FusedIterator for Ticks<'_> {}
+
+/// Builder for configuring a clock.
+#[derive(Debug)]
+pub struct ClockBuilder {
+    pub(crate) name: Option<String>,
+    pub(crate) partitions: Vec<PartitionSpec>,
+    pub(crate) pulses: Vec<PulseSpec>,
+    pub(crate) order: Option<PartitionOrder>,
+    pub(crate) quiet_windows: Vec<QuietWindow>,
+    pub(crate) children: Vec<(String, Clock, Gear)>,
+    pub(crate) machines: Vec<(String, StateMachine)>,
+    pub(crate) run_for: Option<u64>,
+    pub(crate) tick_duration: Option<Duration>,
+    pub(crate) overflow_pulse: Option<String>,
+    pub(crate) ticks_per_unit: Option<u64>,
+    pub(crate) epoch_partitions: Vec<PartitionSpec>,
+}
+
+impl Default for ClockBuilder {
+    fn default() -> Self {
+        Self {
+            name: None,
+            partitions: Vec::new(),
+            pulses: Vec::new(),
+            order: None,
+            quiet_windows: Vec::new(),
+            children: Vec::new(),
+            machines: Vec::new(),
+            run_for: None,
+            tick_duration: None,
+            overflow_pulse: Some(String::from(OVERFLOW_PULSE)),
+            ticks_per_unit: None,
+            epoch_partitions: Vec::new(),
+        }
+    }
+}
+
+/// Child clocks are copied without their subscribers.
+impl Clone for ClockBuilder {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            partitions: self.partitions.clone(),
+            pulses: self.pulses.clone(),
+            order: self.order,
+            quiet_windows: self.quiet_windows.clone(),
+            children: self
+                .children
+                .iter()
+                .map(|(name, clock, gear)| (name.clone(), clock.detached(), gear.clone()))
+                .collect(),
+            machines: self.machines.clone(),
+            run_for: self.run_for,
+            tick_duration: self.tick_duration,
+            overflow_pulse: self.overflow_pulse.clone(),
+            ticks_per_unit: self.ticks_per_unit,
+            epoch_partitions: self.epoch_partitions.clone(),
+        }
+    }
+}
+
+impl ClockBuilder {
+    /// Start a new builder with no partitions or pulses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name the clock; the name is stamped onto every outcome and fired pulse.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the partition order explicitly.
+    pub fn partition_order(mut self, order: PartitionOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    /// Configure least-significant-first order.
+    pub fn least_significant_first(self) -> Self {
+        self.partition_order(PartitionOrder::LeastSignificantFirst)
+    }
+
+    /// Configure most-significant-first order.
+    pub fn most_significant_first(self) -> Self {
+        self.partition_order(PartitionOrder::MostSignificantFirst)
+    }
+
+    /// Add a single partition.
+    pub fn partition(mut self, name: impl Into<String>, modulus: u64) -> Self {
+        self.partitions.push(PartitionSpec::new(name, modulus));
+        self
+    }
+
+    /// Add a partition with labels for display, e.g.
+    /// `PartitionSpec::new("min", 60).display_name("Minutes").unit("min")`.
+    pub fn partition_spec(mut self, spec: PartitionSpec) -> Self {
+        self.partitions.push(spec);
+        self
+    }
+
+    /// Add partitions in an explicit order.
+    pub fn partition_chain<I>(mut self, order: PartitionOrder, specs: I) -> Self
+    where
+        I: IntoIterator<Item = PartitionSpec>,
+    {
+        self.order = Some(order);
+        self.partitions.extend(specs);
+        self
+    }
+
+    /// Add a periodic pulse.
+    pub fn pulse_every(self, name: impl Into<String>, period: u64) -> Self {
+        self.pulse(PulseSpec::new(name, PulseCondition::Every(period)))
+    }
+
+    /// Add a predicate-based pulse.
+    pub fn pulse_when(self, name: impl Into<String>, condition: PulseCondition) -> Self {
+        self.pulse(PulseSpec::new(name, condition))
+    }
+
+    /// Add a pulse that fires `delay` ticks after each fire of `trigger`.
+    pub fn pulse_after(
+        self,
+        name: impl Into<String>,
+        trigger: impl Into<String>,
+        delay: u64,
+    ) -> Self {
+        self.pulse(PulseSpec::new(name, PulseCondition::always()).after(trigger, delay))
+    }
+
+    /// Add a fully specified pulse (e.g. with expiry limits).
+    pub fn pulse(mut self, spec: PulseSpec) -> Self {
+        self.pulses.push(spec);
+        self
+    }
+
+    /// Add a quiet window during which no user pulses fire.
+    pub fn quiet_window(mut self, window: QuietWindow) -> Self {
+        self.quiet_windows.push(window);
+        self
+    }
+
+    /// Attach a child clock (see [`Clock::attach_child`]).
+    pub fn child(mut self, name: impl Into<String>, clock: Clock, gear: Gear) -> Self {
+        self.children.push((name.into(), clock, gear));
+        self
+    }
+
+    /// Attach a state machine (see [`Clock::attach_machine`]).
+    pub fn machine(mut self, name: impl Into<String>, machine: StateMachine) -> Self {
+        self.machines.push((name.into(), machine));
+        self
+    }
+
+    /// Halt the clock after `ticks` ticks (see [`Clock::set_run_for`]).
+    pub fn run_for(mut self, ticks: u64) -> Self {
+        self.run_for = Some(ticks);
+        self
+    }
+
+    /// Declare the real time one tick stands for (see
+    /// [`Clock::tick_elapsed`]).
+    pub fn tick_duration(mut self, duration: Duration) -> Self {
+        self.tick_duration = Some(duration);
+        self
+    }
+
+    /// Advance the least significant partition only once every `ticks`
+    /// ticks, like a prescaler: with 1000, a `sec` partition counts
+    /// seconds of millisecond ticks, with no `millis` partition to filter
+    /// out of snapshots.
+    ///
+    /// The tick counter and tick-based conditions (`Every`, `TickRange`,
+    /// delays, countdowns) still count every tick, and a partition value
+    /// holds for the whole unit. [`ClockDuration`]s count in partition
+    /// units, so scale with it. Building fails with
+    /// [`ClockError::ZeroTicksPerUnit`] for 0.
+    ///
+    /// ```
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::builder()
+    ///     .least_significant_first()
+    ///     .partition("sec", 60)
+    ///     .partition("min", 60)
+    ///     .ticks_per_unit(1000)
+    ///     .build()
+    ///     .unwrap();
+    /// clock.skip(61_999);
+    /// assert_eq!((clock.snapshot().get("min"), clock.snapshot().get("sec")), (1, 1));
+    /// clock.tick();
+    /// assert_eq!(clock.snapshot().get("sec"), 2);
+    /// ```
+    pub fn ticks_per_unit(mut self, ticks: u64) -> Self {
+        self.ticks_per_unit = Some(ticks);
+        self
+    }
+
+    /// Fire the overflow pulse under `name` instead of [`OVERFLOW_PULSE`].
+    pub fn overflow_pulse(mut self, name: impl Into<String>) -> Self {
+        self.overflow_pulse = Some(name.into());
+        self
+    }
+
+    /// Fire no pulse on tick overflow, so every fired pulse is a configured
+    /// one (see [`Clock::set_overflow_pulse`]).
+    pub fn no_overflow_pulse(mut self) -> Self {
+        self.overflow_pulse = None;
+        self
+    }
+
+    /// Merge `other` into this builder, so a shared template can be cloned
+    /// and specialised.
+    ///
+    /// Partitions, pulses, quiet windows, child clocks, machines and epoch
+    /// partitions from `other` replace those of the same name in place and
+    /// are appended otherwise. Settings `other` changed from their defaults
+    /// (name, order, run budget, tick duration, ticks per unit, overflow
+    /// pulse) override this builder's.
+    ///
+    /// ```
+    /// use beeclock_core::Clock;
+    ///
+    /// let hms = Clock::builder()
+    ///     .least_significant_first()
+    ///     .partition("sec", 60)
+    ///     .partition("min", 60)
+    ///     .partition("hour", 24)
+    ///     .pulse_every("heartbeat", 10);
+    ///
+    /// let fast = hms
+    ///     .clone()
+    ///     .extend(Clock::builder().pulse_every("heartbeat", 2).pulse_every("sync", 60))
+    ///     .build()
+    ///     .unwrap();
+    /// let pulses: Vec<_> = fast.pulses().map(|(name, _)| name).collect();
+    /// assert_eq!(pulses, ["heartbeat", "sync"]);
+    /// assert_eq!(fast.next_fire("heartbeat", 10), Some(2));
+    /// assert_eq!(hms.build().unwrap().next_fire("heartbeat", 20), Some(10));
+    /// ```
+    pub fn extend(mut self, other: ClockBuilder) -> Self {
+        merge_named(&mut self.partitions, other.partitions, |p| &p.name);
+        merge_named(&mut self.pulses, other.pulses, |p| &p.name);
+        merge_named(&mut self.quiet_windows, other.quiet_windows, |w| &w.name);
+        merge_named(&mut self.children, other.children, |c| &c.0);
+        merge_named(&mut self.machines, other.machines, |m| &m.0);
+        merge_named(&mut self.epoch_partitions, other.epoch_partitions, |p| {
+            &p.name
+        });
+        self.name = other.name.or(self.name);
+        self.order = other.order.or(self.order);
+        self.run_for = other.run_for.or(self.run_for);
+        self.tick_duration = other.tick_duration.or(self.tick_duration);
+        self.ticks_per_unit = other.ticks_per_unit.or(self.ticks_per_unit);
+        if other.overflow_pulse.as_deref() != Some(OVERFLOW_PULSE) {
+            self.overflow_pulse = other.overflow_pulse;
+        }
+        self
+    }
+
+    /// Build the configured clock.
+    pub fn build(self) -> Result<Clock, ClockError> {
+        let order = match self.order {
+            Some(order) => order,
+            None => {
+                if self.partitions.is_empty() {
+                    PartitionOrder::LeastSignificantFirst
+                } else {
+                    return Err(ClockError::MissingPartitionOrder);
+                }
+            }
+        };
+        ClockConfig {
+            name: self.name,
+            order,
+            partitions: self.partitions,
+            pulses: self.pulses,
+            quiet_windows: self.quiet_windows,
+            run_for: self.run_for,
+            tick_duration: self.tick_duration,
+            overflow_pulse: self.overflow_pulse,
+            ticks_per_unit: self.ticks_per_unit,
+            epoch_partitions: self.epoch_partitions,
+        }
+        .build()
+        .and_then(|mut clock| {
+            for (name, child, gear) in self.children {
+                clock.attach_child(name, child, gear)?;
+            }
+            for (name, machine) in self.machines {
+                clock.attach_machine(name, machine)?;
+            }
+            Ok(clock)
+        })
+    }
+}
+
+/// Replace the entries of `into` named like those of `from`, appending the
+/// rest.
+fn merge_named<T>(into: &mut Vec<T>, from: Vec<T>, name: impl Fn(&T) -> &String) {
+    for item in from {
+        match into.iter_mut().find(|old| name(old) == name(&item)) {
+            Some(old) => *old = item,
+            None => into.push(item),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuietReport;
+
+    #[test]
+    fn tick_cascades_partitions() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 2)
+            .partition("min", 3)
+            .build()
+            .unwrap();
+
+        let tick1 = clock.tick();
+        assert_eq!(tick1.snapshot.partition("sec").unwrap().value, 1);
+        assert_eq!(tick1.snapshot.partition("min").unwrap().value, 0);
+
+        let tick2 = clock.tick();
+        assert_eq!(tick2.snapshot.partition("sec").unwrap().value, 0);
+        assert_eq!(tick2.snapshot.partition("min").unwrap().value, 1);
+    }
+
+    #[test]
+    fn pulses_fire_on_period() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 10)
+            .pulse_every("pulse", 3)
             .build()
             .unwrap();
 
@@ -401,4 +1602,841 @@ mod tests {
         assert_eq!(outcome.snapshot.get("min"), 0);
         assert_eq!(outcome.snapshot.get("hour"), 0);
     }
+
+    #[test]
+    fn tick_into_matches_tick_and_reuses_buffer() {
+        let build = || {
+            Clock::builder()
+                .least_significant_first()
+                .partition("sec", 3)
+                .partition("min", 2)
+                .pulse_every("two", 2)
+                .pulse_when(
+                    "top",
+                    PulseCondition::PartitionEquals {
+                        name: "sec".into(),
+                        value: 0,
+                    },
+                )
+                .build()
+                .unwrap()
+        };
+        let mut reference = build();
+        let mut clock = build();
+        let mut buffer = clock.tick_buffer();
+        let (values_ptr, fired_ptr) = (buffer.values.as_ptr(), buffer.fired.as_ptr());
+
+        for _ in 0..20 {
+            let outcome = reference.tick();
+            clock.tick_into(&mut buffer);
+
+            assert_eq!(buffer.tick, outcome.snapshot.tick);
+            let values: Vec<u64> = outcome
+                .snapshot
+                .partitions
+                .iter()
+                .map(|p| p.value)
+                .collect();
+            assert_eq!(buffer.values, values);
+            let names: Vec<&str> = buffer
+                .fired
+                .iter()
+                .map(|&idx| clock.pulse_name(idx).unwrap())
+                .collect();
+            let expected: Vec<&str> = outcome.pulses.iter().map(|p| p.name.as_str()).collect();
+            assert_eq!(names, expected);
+        }
+
+        assert_eq!(buffer.values.as_ptr(), values_ptr);
+        assert_eq!(buffer.fired.as_ptr(), fired_ptr);
+    }
+
+    #[test]
+    fn set_partition_validates_and_cascades() {
+        let mut clock = Clock::default();
+        clock.set_partition("sec", 59).unwrap();
+        clock.set_partition("min", 59).unwrap();
+        let outcome = clock.tick();
+        assert_eq!(outcome.snapshot.get("hour"), 1);
+        assert_eq!(outcome.snapshot.get("min"), 0);
+
+        assert!(matches!(
+            clock.set_partition("hour", 24),
+            Err(ClockError::ValueOutOfRange { modulus: 24, .. })
+        ));
+        assert!(matches!(
+            clock.set_partition("day", 0),
+            Err(ClockError::PartitionNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn overflow_pulse_can_be_renamed_or_disabled() {
+        let wrap = |builder: ClockBuilder| {
+            let mut clock = builder.pulse_every("beat", 1).build().unwrap();
+            clock.skip(u64::MAX);
+            let outcome = clock.tick();
+            assert!(outcome.overflowed);
+            let names: Vec<String> = outcome.pulses.iter().map(|p| p.name.clone()).collect();
+            (names, clock.config().overflow_pulse)
+        };
+        assert_eq!(
+            wrap(Clock::builder()),
+            (vec![OVERFLOW_PULSE.into()], Some(OVERFLOW_PULSE.into()))
+        );
+        assert_eq!(
+            wrap(Clock::builder().overflow_pulse("wrapped")),
+            (vec!["wrapped".into()], Some("wrapped".into()))
+        );
+        assert_eq!(wrap(Clock::builder().no_overflow_pulse()), (vec![], None));
+    }
+
+    #[test]
+    fn partition_labels_reach_snapshots() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition_spec(
+                PartitionSpec::new("min", 60)
+                    .display_name("Minutes")
+                    .unit("min")
+                    .description("Minute of the hour"),
+            )
+            .partition("hour", 24)
+            .build()
+            .unwrap();
+        let snapshot = clock.tick().snapshot;
+        let (min, hour) = (&snapshot[0], &snapshot[1]);
+        assert_eq!(
+            (min.display_name(), min.unit(), min.description()),
+            ("Minutes", Some("min"), Some("Minute of the hour"))
+        );
+        assert_eq!(
+            (hour.display_name(), hour.unit(), hour.meta.is_none()),
+            ("hour", None, true)
+        );
+        assert_eq!(
+            clock.config().partitions[0].meta.unit.as_deref(),
+            Some("min")
+        );
+    }
+
+    #[test]
+    fn built_clocks_describe_their_partitions_and_pulses() {
+        let clock = Clock::builder()
+            .most_significant_first()
+            .partition_spec(PartitionSpec::new("hour", 24).display_name("Hours"))
+            .partition("min", 60)
+            .pulse_every("minute", 60)
+            .pulse_when("noon", "hour == 12".parse().unwrap())
+            .build()
+            .unwrap();
+
+        let partitions: Vec<PartitionInfo> = clock.partitions().collect();
+        assert_eq!(partitions.len(), 2);
+        assert_eq!(
+            (partitions[0].display_name(), partitions[0].modulus),
+            ("Hours", 24)
+        );
+        assert_eq!((partitions[1].name, partitions[1].meta), ("min", None));
+        assert_eq!(
+            partitions[1].to_spec().modulus,
+            clock.config().partitions[1].modulus
+        );
+
+        let pulses: Vec<(&str, &PulseCondition)> = clock.pulses().collect();
+        assert!(matches!(pulses[0], ("minute", PulseCondition::Every(60))));
+        assert!(matches!(
+            pulses[1],
+            ("noon", PulseCondition::PartitionEquals { name, value: 12 }) if name == "hour"
+        ));
+    }
+
+    #[test]
+    fn delta_conditions_compare_with_the_previous_tick() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 4)
+            .partition("min", 3)
+            .pulse_when("new_min", "changed(min)".parse().unwrap())
+            .pulse_when("wrapped", "decreased(min)".parse().unwrap())
+            .build()
+            .unwrap();
+        assert!(!clock.explain("new_min").unwrap().fired());
+
+        let fired: Vec<(u64, usize)> = (0..24)
+            .map(|_| clock.tick())
+            .filter(|outcome| !outcome.pulses.is_empty())
+            .map(|outcome| (outcome.snapshot.tick, outcome.pulses.len()))
+            .collect();
+        assert_eq!(fired, [(4, 1), (8, 1), (12, 2), (16, 1), (20, 1), (24, 2)]);
+        assert_eq!(
+            clock.explain("wrapped").unwrap().root.reason,
+            "min went from 2 to 0"
+        );
+    }
+
+    #[test]
+    fn unknown_partitions_suggest_a_close_name() {
+        let err = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_when(
+                "top",
+                PulseCondition::PartitionModulo {
+                    name: "mim".into(),
+                    modulus: 5,
+                    remainder: 0,
+                },
+            )
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "pulse 'top' references unknown partition 'mim' (did you mean 'min'?)"
+        );
+    }
+
+    #[test]
+    fn pulses_expire_by_tick_and_fire_count() {
+        let mut clock = Clock::builder()
+            .pulse(PulseSpec::new("once", PulseCondition::Every(2)).max_fires(1))
+            .pulse(
+                PulseSpec::new("early", PulseCondition::Every(1))
+                    .expires_at(3)
+                    .notify_on_expiry(),
+            )
+            .build()
+            .unwrap();
+
+        let names = |outcome: TickOutcome| -> Vec<String> {
+            outcome.pulses.iter().map(|p| p.name.clone()).collect()
+        };
+        assert_eq!(names(clock.tick()), ["early"]);
+        assert_eq!(names(clock.tick()), ["once", "early"]);
+        assert_eq!(clock.is_expired("once"), Some(true));
+        assert_eq!(names(clock.tick()), ["early", "__expired__:early"]);
+        assert!(clock.tick().pulses.is_empty());
+
+        assert_eq!(clock.prune_expired(), 2);
+        assert_eq!(clock.pulse_count(), 0);
+        assert_eq!(clock.is_expired("once"), None);
+    }
+
+    #[test]
+    fn delayed_pulses_follow_their_trigger() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("threshold", 5)
+            .pulse_after("warning", "threshold", 2)
+            .pulse(
+                PulseSpec::new(
+                    "escalate",
+                    PulseCondition::PartitionModulo {
+                        name: "sec".into(),
+                        modulus: 2,
+                        remainder: 0,
+                    },
+                )
+                .after("warning", 1),
+            )
+            .build()
+            .unwrap();
+
+        let fires = |clock: &mut Clock, name: &str| -> Vec<u64> {
+            (0..20)
+                .filter_map(|_| {
+                    let outcome = clock.tick();
+                    let fired = outcome.pulses.iter().any(|p| p.name == name);
+                    fired.then_some(outcome.snapshot.tick)
+                })
+                .collect()
+        };
+        assert_eq!(fires(&mut clock.detached(), "warning"), [7, 12, 17]);
+        // Gated by an even `sec`: warning at 7 -> 8 fires, 12 -> 13 does not.
+        assert_eq!(fires(&mut clock, "escalate"), [8, 18]);
+
+        assert!(matches!(
+            Clock::builder().pulse_after("late", "missing", 1).build(),
+            Err(ClockError::UnknownTrigger { .. })
+        ));
+        assert!(matches!(
+            Clock::builder()
+                .pulse_every("a", 1)
+                .pulse_after("b", "a", 0)
+                .build(),
+            Err(ClockError::ZeroDelay { .. })
+        ));
+    }
+
+    #[test]
+    fn quiet_windows_suppress_and_report() {
+        let mut clock = Clock::builder()
+            .pulse_every("beat", 1)
+            .pulse_every("even", 2)
+            .quiet_window(QuietWindow::ticks("maintenance", 3, 5).reported())
+            .build()
+            .unwrap();
+
+        let outcomes: Vec<TickOutcome> = (0..7).map(|_| clock.tick()).collect();
+        assert_eq!(outcomes[1].pulses.len(), 2);
+        for quiet in &outcomes[2..5] {
+            assert!(quiet.pulses.is_empty());
+        }
+        assert!(outcomes[4].quiet_reports.is_empty());
+        assert_eq!(
+            outcomes[5].quiet_reports,
+            [QuietReport {
+                window: "maintenance".into(),
+                start_tick: 3,
+                end_tick: 5,
+                suppressed: 4,
+            }]
+        );
+        assert_eq!(outcomes[5].pulses.len(), 2);
+
+        assert!(matches!(
+            clock.add_quiet_window(QuietWindow::new(
+                "night",
+                PulseCondition::PartitionEquals {
+                    name: "hour".into(),
+                    value: 0,
+                },
+            )),
+            Err(ClockError::UnknownPartition { .. })
+        ));
+        assert!(matches!(
+            clock.add_quiet_window(QuietWindow::new(
+                "warmup",
+                "debounce(2, tick in 0..=5)".parse().unwrap()
+            )),
+            Err(ClockError::StatefulCondition { name }) if name == "warmup"
+        ));
+    }
+
+    #[test]
+    fn adding_a_quiet_window_keeps_open_spans() {
+        let mut clock = Clock::builder()
+            .pulse_every("beat", 1)
+            .quiet_window(QuietWindow::ticks("maintenance", 2, 6).reported())
+            .build()
+            .unwrap();
+
+        for _ in 0..4 {
+            clock.tick();
+        }
+        clock
+            .add_quiet_window(QuietWindow::ticks("later", 100, 110))
+            .unwrap();
+        let outcomes: Vec<TickOutcome> = (0..3).map(|_| clock.tick()).collect();
+        assert_eq!(
+            outcomes[2].quiet_reports,
+            [QuietReport {
+                window: "maintenance".into(),
+                start_tick: 2,
+                end_tick: 6,
+                suppressed: 5,
+            }]
+        );
+    }
+
+    #[test]
+    fn jittered_pulses_spread_by_seed() {
+        let jittered = |seed| {
+            let clock = Clock::builder()
+                .pulse_when(
+                    "sync",
+                    PulseCondition::Jitter {
+                        window: 30,
+                        seed,
+                        inner: Box::new(PulseCondition::Every(60)),
+                    },
+                )
+                .build()
+                .unwrap();
+            clock.simulate(600).pulses[0].fire_ticks.clone()
+        };
+        let (a, b) = (jittered(1), jittered(2));
+        assert_eq!(a, jittered(1));
+        assert_ne!(a, b);
+        for (fire, due) in a.iter().zip((60..).step_by(60)) {
+            assert!((due..due + 30).contains(fire));
+        }
+
+        assert!(matches!(
+            Clock::builder()
+                .pulse_when(
+                    "bad",
+                    PulseCondition::Jitter {
+                        window: 0,
+                        seed: 0,
+                        inner: Box::new(PulseCondition::Every(1)),
+                    },
+                )
+                .build(),
+            Err(ClockError::ZeroWindow { .. })
+        ));
+    }
+
+    #[test]
+    fn rate_limit_caps_fires_per_window() {
+        let mut clock = Clock::builder()
+            .pulse_when(
+                "alert",
+                PulseCondition::RateLimit {
+                    max_fires: 2,
+                    per_ticks: 10,
+                    inner: Box::new(PulseCondition::TickRange { start: 1, end: 25 }),
+                },
+            )
+            .build()
+            .unwrap();
+
+        let fires: Vec<u64> = (0..30)
+            .map(|_| clock.tick())
+            .filter(|outcome| !outcome.pulses.is_empty())
+            .map(|outcome| outcome.snapshot.tick)
+            .collect();
+        assert_eq!(fires, [1, 2, 11, 12, 21, 22]);
+        assert_eq!(clock.suppressed_count("alert"), Some(19));
+        assert_eq!(clock.suppressed_count("missing"), None);
+    }
+
+    #[test]
+    fn debounce_fires_once_per_streak_across_restore() {
+        let debounce = |ticks| PulseCondition::Debounce {
+            ticks,
+            inner: Box::new("sec != 0".parse().unwrap()),
+        };
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 10)
+            .pulse_when("steady", debounce(3))
+            .build()
+            .unwrap();
+
+        let fires = |clock: &mut Clock, ticks| -> Vec<u64> {
+            (0..ticks)
+                .map(|_| clock.tick())
+                .filter(|outcome| !outcome.pulses.is_empty())
+                .map(|outcome| outcome.snapshot.tick)
+                .collect()
+        };
+        assert_eq!(fires(&mut clock, 12), [3]);
+        let mut restored = Clock::from_state(clock.save_state()).unwrap();
+        assert_eq!(fires(&mut restored, 12), [13, 23]);
+
+        let err = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 10)
+            .pulse_when("never", debounce(0))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, ClockError::ZeroWindow { .. }));
+    }
+
+    #[test]
+    fn elapsed_durations_count_from_start_or_last_fire() {
+        let build = |warmup: &str, cooldown: &str| {
+            Clock::builder()
+                .least_significant_first()
+                .partition("sec", 60)
+                .partition("min", 60)
+                .partition("hour", 24)
+                .pulse_when("warm", warmup.parse().unwrap())
+                .pulse_when("poll", cooldown.parse().unwrap())
+                .build()
+        };
+        let mut clock = build("elapsed(45 min) && sec == 0", "since_fire(1 min 30 sec)").unwrap();
+        let fires = |clock: &mut Clock, ticks, name: &str| -> Vec<u64> {
+            (0..ticks)
+                .map(|_| clock.tick())
+                .filter(|outcome| outcome.pulses.iter().any(|p| p.name == name))
+                .map(|outcome| outcome.snapshot.tick)
+                .collect()
+        };
+        assert_eq!(
+            fires(&mut clock.detached(), 2820, "warm"),
+            [2700, 2760, 2820]
+        );
+        assert_eq!(fires(&mut clock, 200, "poll"), [90, 180]);
+        let mut restored = Clock::from_state(clock.save_state()).unwrap();
+        assert_eq!(fires(&mut restored, 100, "poll"), [270]);
+
+        let err = build("elapsed(45 mins)", "every(1)").unwrap_err();
+        assert!(matches!(
+            err,
+            ClockError::UnknownPartition { ref partition, .. } if partition == "mins"
+        ));
+        let err = build("elapsed(0 min)", "every(1)").unwrap_err();
+        assert!(matches!(err, ClockError::ZeroWindow { .. }));
+        let err = build("since_fire(18446744073709551615 hour)", "every(1)").unwrap_err();
+        assert!(matches!(err, ClockError::DurationOverflow { .. }));
+    }
+
+    #[test]
+    fn scaled_partitions_match_a_hidden_sub_unit_partition() {
+        // A clock scaled to 7 ticks per unit behaves like one with an extra
+        // least significant partition of modulus 7 that nothing looks at.
+        let build = |scaled: bool| {
+            let builder = Clock::builder().least_significant_first();
+            let builder = match scaled {
+                true => builder.ticks_per_unit(7),
+                false => builder.partition("sub", 7),
+            };
+            builder
+                .partition("sec", 5)
+                .partition("min", 3)
+                .pulse_when("top", "sec == 0".parse().unwrap())
+                .pulse_when("odd", "sec % 2 == 1 && min == 2".parse().unwrap())
+                .pulse_when("turn", "changed(min)".parse().unwrap())
+                .pulse_when("later", "since_fire(2 sec)".parse().unwrap())
+                .build()
+                .unwrap()
+        };
+        let (mut scaled, mut plain) = (build(true), build(false));
+        let visible = |clock: &Clock| {
+            let snapshot = clock.snapshot();
+            (snapshot.tick, snapshot.get("sec"), snapshot.get("min"))
+        };
+        scaled.skip(40);
+        plain.skip(40);
+        scaled
+            .set_alarm("at", AlarmTarget::partitions([("min", 1)]))
+            .unwrap();
+        plain
+            .set_alarm("at", AlarmTarget::partitions([("min", 1)]))
+            .unwrap();
+        for _ in 0..150 {
+            assert_eq!(visible(&scaled), visible(&plain));
+            assert_eq!(
+                scaled.ticks_until_next_event(),
+                plain.ticks_until_next_event()
+            );
+            assert_eq!(scaled.alarm_due("at"), plain.alarm_due("at"));
+            for pulse in ["top", "odd", "turn"] {
+                let (from, to) = (scaled.tick_count(), scaled.tick_count() + 200);
+                assert_eq!(
+                    scaled.count_fires(pulse, from, to).unwrap(),
+                    plain.count_fires(pulse, from, to).unwrap()
+                );
+            }
+            let names = |outcome: TickOutcome| -> Vec<String> {
+                outcome.pulses.iter().map(|p| p.name.clone()).collect()
+            };
+            assert_eq!(names(scaled.tick()), names(plain.tick()));
+        }
+
+        let restored = Clock::from_state(scaled.save_state()).unwrap();
+        assert_eq!(restored.ticks_per_unit(), 7);
+        assert_eq!(
+            restored.ticks_until_next_event(),
+            plain.ticks_until_next_event()
+        );
+        let config = scaled.config();
+        let later = config.add_ticks(&scaled.snapshot(), 33).unwrap();
+        plain.skip(33);
+        assert_eq!(later.get("sec"), plain.snapshot().get("sec"));
+        assert_eq!(ClockDuration::of("sec", 2).to_ticks(&config).unwrap(), 14);
+        assert_ne!(config.config_hash(), build(false).config_hash());
+        assert!(matches!(
+            Clock::builder().ticks_per_unit(0).build(),
+            Err(ClockError::ZeroTicksPerUnit)
+        ));
+    }
+
+    #[test]
+    fn observe_merges_remote_ticks() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_every("minute", 60)
+            .build()
+            .unwrap();
+        clock.tick();
+
+        let outcome = clock.observe(119);
+        assert_eq!(outcome.snapshot.tick, 120);
+        assert_eq!(outcome.snapshot.values().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(outcome.pulses[0].name, "minute");
+
+        let outcome = clock.observe(5);
+        assert_eq!(outcome.snapshot.tick, 121);
+        assert_eq!(outcome.snapshot.values().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn skip_advances_without_firing() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_every("minute", 60)
+            .pulse_after("echo", "minute", 5)
+            .build()
+            .unwrap();
+        #[cfg(feature = "std")]
+        let rx = clock.subscribe();
+        clock.start_countdown("timer", 100).unwrap();
+        clock.iter_ticks().take(60).for_each(drop);
+        #[cfg(feature = "std")]
+        assert_eq!(rx.try_iter().count(), 60);
+
+        let skipped = clock.skip(3600);
+        assert_eq!(skipped, Skipped { from: 60, to: 3660 });
+        assert_eq!(clock.partition_values(), [0, 1]);
+        #[cfg(feature = "std")]
+        assert!(rx.try_recv().is_err());
+
+        let outcome = clock.tick();
+        let names: Vec<&str> = outcome.pulses.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["timer"]);
+        assert_eq!(clock.pending_count(), 0);
+
+        clock.skip(u64::MAX - clock.tick_count());
+        assert_eq!((clock.tick_count(), clock.epoch()), (u64::MAX, 0));
+        assert_eq!(
+            clock.skip(2),
+            Skipped {
+                from: u64::MAX,
+                to: 1
+            }
+        );
+        assert_eq!(clock.epoch(), 1);
+    }
+
+    #[test]
+    fn advance_scaled_accumulates_fractions() {
+        let mut clock = Clock::builder().pulse_every("beat", 3).build().unwrap();
+        let ticks: Vec<usize> = (0..8).map(|_| clock.advance_scaled(1.5).len()).collect();
+        assert_eq!(ticks, [1, 2, 1, 2, 1, 2, 1, 2]);
+        assert_eq!(clock.tick_count(), 12);
+
+        let mut slow = 0;
+        for _ in 0..400 {
+            slow += clock.advance_scaled(0.25).len();
+        }
+        assert_eq!((slow, clock.tick_fraction()), (100, 0.0));
+
+        assert!(clock.advance_scaled(-3.0).is_empty());
+        assert!(clock.advance_scaled(f64::NAN).is_empty());
+        let outcomes = clock.advance_scaled(2.75);
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[1].snapshot.tick, 114);
+        assert_eq!(outcomes[1].pulses[0].name, "beat");
+        assert_eq!(clock.tick_fraction(), 0.75);
+
+        clock.set_run_for(116).unwrap();
+        assert_eq!(clock.advance_scaled(10.0).len(), 2);
+        assert!(clock.is_halted());
+
+        // A carried fraction plus an infinite delta saturates.
+        let mut clock = Clock::builder().run_for(3).build().unwrap();
+        assert!(clock.advance_scaled(0.5).is_empty());
+        assert_eq!(clock.advance_scaled(f64::INFINITY).len(), 3);
+        assert!(clock.is_halted());
+    }
+
+    #[test]
+    fn tick_elapsed_carries_the_remainder() {
+        let mut clock = Clock::default();
+        assert!(matches!(
+            clock.tick_elapsed(Duration::from_secs(1)),
+            Err(ClockError::NoTickDuration)
+        ));
+        assert!(matches!(
+            clock.set_tick_duration(Duration::ZERO),
+            Err(ClockError::ZeroTickDuration)
+        ));
+
+        clock.set_tick_duration(Duration::from_millis(10)).unwrap();
+        let frames = [16, 16, 17, 16, 17, 18];
+        let ticks: Vec<usize> = frames
+            .iter()
+            .map(|&ms| clock.tick_elapsed(Duration::from_millis(ms)).unwrap().len())
+            .collect();
+        assert_eq!(ticks, [1, 2, 1, 2, 2, 2]);
+        assert_eq!(clock.tick_count(), 10);
+        assert_eq!(clock.elapsed_carry(), Duration::ZERO);
+
+        let outcomes = clock.tick_elapsed(Duration::from_secs(2)).unwrap();
+        assert_eq!(outcomes.last().unwrap().snapshot.tick, 210);
+        assert_eq!(
+            clock.config().tick_duration,
+            Some(Duration::from_millis(10))
+        );
+    }
+
+    #[test]
+    fn run_for_halts_the_clock() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("beat", 1)
+            .run_for(3)
+            .build()
+            .unwrap();
+        #[cfg(feature = "std")]
+        let rx = clock.subscribe();
+
+        let mut replica = clock.config().build().unwrap();
+        let mut buffer = replica.tick_buffer();
+        let halted: Vec<bool> = (0..4)
+            .map(|_| {
+                replica.tick_into(&mut buffer);
+                buffer.halted
+            })
+            .collect();
+        assert_eq!(halted, [false, false, true, false]);
+        assert_eq!((buffer.tick, buffer.fired.len()), (3, 0));
+
+        assert_eq!(clock.iter_ticks().size_hint(), (3, Some(3)));
+        let outcomes: Vec<TickOutcome> = clock.iter_ticks().collect();
+        assert_eq!(outcomes.len(), 3);
+        let names: Vec<&str> = outcomes[2].pulses.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["beat", "__halted__"]);
+        assert!(clock.is_halted());
+        assert_eq!(clock.ticks_until_next_event(), None);
+
+        let terminal = clock.tick();
+        assert_eq!(terminal.snapshot.tick, 3);
+        assert!(terminal.pulses.is_empty());
+        assert_eq!(clock.skip(10).to, 3);
+        assert_eq!(clock.observe(100).snapshot.tick, 3);
+        #[cfg(feature = "std")]
+        assert_eq!(rx.try_iter().count(), 3);
+
+        assert!(matches!(
+            Clock::builder().run_for(0).build(),
+            Err(ClockError::ZeroRunLength)
+        ));
+    }
+
+    #[test]
+    fn named_clock_stamps_outcomes() {
+        let mut clock = Clock::builder()
+            .name("frame")
+            .least_significant_first()
+            .partition("n", 2)
+            .pulse_every("beat", 1)
+            .build()
+            .unwrap();
+        let outcome = clock.tick();
+        assert_eq!(outcome.clock.as_deref(), Some("frame"));
+        assert_eq!(outcome.pulses[0].clock.as_deref(), Some("frame"));
+        assert_eq!(clock.config().name.as_deref(), Some("frame"));
+
+        let outcome = Clock::default().tick();
+        assert!(outcome.clock.is_none());
+    }
+
+    #[test]
+    fn snapshots_share_partition_names() {
+        let clock = Clock::default();
+        let a = clock.snapshot();
+        let b = clock.snapshot();
+        assert!(Arc::ptr_eq(&a.partitions[0].name, &b.partitions[0].name));
+    }
+
+    #[test]
+    fn accepts_only_matching_snapshots() {
+        let mut clock = Clock::default();
+        let snapshot = clock.tick().snapshot;
+        assert!(clock.accepts(&snapshot).is_ok());
+
+        let mut swapped = (*snapshot).clone();
+        swapped.partitions.swap(0, 1);
+        assert!(matches!(
+            clock.accepts(&swapped),
+            Err(ClockError::SnapshotMismatch { .. })
+        ));
+
+        let mut out_of_range = (*snapshot).clone();
+        out_of_range.partitions[2].value = 24;
+        assert!(matches!(
+            clock.accepts(&out_of_range),
+            Err(ClockError::ValueOutOfRange { .. })
+        ));
+
+        let mut short = (*snapshot).clone();
+        short.partitions.pop();
+        assert!(clock.accepts(&short).is_err());
+    }
+
+    #[test]
+    fn iter_ticks_drives_the_clock() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("five", 5)
+            .build()
+            .unwrap();
+
+        let fired = clock.iter_ticks().find(|o| !o.pulses.is_empty()).unwrap();
+        assert_eq!(fired.snapshot.tick, 5);
+
+        let ticks: Vec<u64> = clock
+            .iter_ticks()
+            .take(3)
+            .map(|o| o.snapshot.tick)
+            .collect();
+        assert_eq!(ticks, [6, 7, 8]);
+        assert_eq!(clock.tick_count(), 8);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn broadcast_shares_snapshot() {
+        let mut clock = Clock::default();
+        let first = clock.subscribe();
+        let second = clock.subscribe_bounded(1);
+
+        let outcome = clock.tick();
+        let a = first.recv().unwrap();
+        let b = second.recv().unwrap();
+        assert!(Arc::ptr_eq(&outcome.snapshot, &a.snapshot));
+        assert!(Arc::ptr_eq(&a.snapshot, &b.snapshot));
+    }
+
+    #[test]
+    fn extended_templates_override_by_name() {
+        let template = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_every("heartbeat", 10)
+            .child("sub", Clock::default(), Gear::Every(2))
+            .run_for(100);
+        let clock = template
+            .clone()
+            .extend(
+                Clock::builder()
+                    .partition("min", 30)
+                    .partition("hour", 24)
+                    .pulse_every("heartbeat", 3)
+                    .name("special"),
+            )
+            .build()
+            .unwrap();
+        let config = clock.config();
+        let layout: Vec<(&str, u64)> = config
+            .partitions
+            .iter()
+            .map(|p| (p.name.as_str(), p.modulus))
+            .collect();
+        assert_eq!(layout, [("sec", 60), ("min", 30), ("hour", 24)]);
+        assert_eq!(config.pulses.len(), 1);
+        assert_eq!(clock.next_fire("heartbeat", 10), Some(3));
+        assert_eq!((clock.name(), config.run_for), (Some("special"), Some(100)));
+        assert!(clock.child("sub").is_some());
+
+        let plain = template
+            .extend(Clock::builder().no_overflow_pulse())
+            .build()
+            .unwrap();
+        assert_eq!(plain.config().partitions[1].modulus, 60);
+        assert_eq!((plain.name(), plain.config().overflow_pulse), (None, None));
+    }
 }
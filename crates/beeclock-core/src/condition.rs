@@ -4,10 +4,21 @@ use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 
-use crate::ClockSnapshot;
+use crate::calendar::date_of;
+use crate::partition::PartitionLookup;
+use crate::{ClockDuration, ClockSnapshot, DateField};
 
 /// Predicate describing when a pulse should fire.
+///
+/// With the `serde` feature, conditions use the same tagged object form as the
+/// WASM builder, e.g. `{ "type": "every", "period": 5 }` or
+/// `{ "type": "and", "conditions": [...] }`.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "repr::ConditionRepr", from = "repr::ConditionRepr")
+)]
 pub enum PulseCondition {
     /// Fire every N ticks (starting at tick N).
     Every(u64),
@@ -25,6 +36,14 @@ pub enum PulseCondition {
     /// Fire when tick is within an inclusive range.
     TickRange { start: u64, end: u64 },
 
+    /// Fire when a partition differs from its value on the previous tick.
+    /// Never met without a previous tick (see [`EvalContext`]).
+    PartitionChanged { name: String },
+
+    /// Fire when a partition is lower than on the previous tick, i.e. it
+    /// rolled over or was set back.
+    PartitionDecreased { name: String },
+
     /// Logical negation of another condition.
     Not(Box<PulseCondition>),
 
@@ -33,45 +52,377 @@ pub enum PulseCondition {
 
     /// Any condition must be true.
     Or(Vec<PulseCondition>),
+
+    /// Each time `inner` is met, fire once at a deterministic pseudo-random
+    /// offset in `0..window` ticks later (derived from `seed` and the tick).
+    ///
+    /// Stateful: only a running clock applies the offset. Pure evaluation
+    /// ([`PulseCondition::is_met`], explanations) reports `inner`.
+    Jitter {
+        window: u64,
+        seed: u64,
+        inner: Box<PulseCondition>,
+    },
+
+    /// `inner`, but met at most `max_fires` times in any `per_ticks`
+    /// consecutive ticks; further matches are counted as suppressed (see
+    /// [`Clock::suppressed_count`](crate::Clock::suppressed_count)).
+    ///
+    /// Stateful like [`PulseCondition::Jitter`].
+    RateLimit {
+        max_fires: u64,
+        per_ticks: u64,
+        inner: Box<PulseCondition>,
+    },
+
+    /// Met once `inner` has held for `ticks` consecutive ticks, on the tick
+    /// the streak reaches `ticks`; a new streak is needed to fire again.
+    /// With `ticks: 1` it fires on each rising edge of `inner`.
+    ///
+    /// Stateful like [`PulseCondition::Jitter`].
+    Debounce {
+        ticks: u64,
+        inner: Box<PulseCondition>,
+    },
+
+    /// Met once at least `duration` has passed since `since`, e.g. "45
+    /// minutes after the clock started" without working out the tick.
+    ///
+    /// Stateful like [`PulseCondition::Jitter`]: the clock converts
+    /// `duration` to ticks when it is built, so pure evaluation reports it
+    /// unmet.
+    ElapsedAtLeast {
+        duration: ClockDuration,
+        since: ElapsedSince,
+    },
+
+    /// Fire when `field` of the date in the
+    /// [`DAY_PARTITION`](crate::DAY_PARTITION) of a
+    /// [calendar](crate::ClockBuilder::calendar) clock equals `value`.
+    DateEquals { field: DateField, value: u64 },
+}
+
+/// Reference point for [`PulseCondition::ElapsedAtLeast`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ElapsedSince {
+    /// Tick 0.
+    #[default]
+    Start,
+    /// The pulse's last fire, or tick 0 before its first.
+    LastFire,
 }
 
 impl PulseCondition {
+    /// Condition that holds on every tick.
+    pub fn always() -> Self {
+        PulseCondition::TickRange {
+            start: 0,
+            end: u64::MAX,
+        }
+    }
+
     /// Evaluate whether this condition is met at the given tick and snapshot.
+    ///
+    /// There is no previous tick, so [`PulseCondition::PartitionChanged`]
+    /// and [`PulseCondition::PartitionDecreased`] are not met; use
+    /// [`is_met_in`](Self::is_met_in) to supply one.
     pub fn is_met(&self, tick: u64, snapshot: &ClockSnapshot) -> bool {
+        self.evaluate(tick, snapshot.partitions.as_slice())
+    }
+
+    /// Evaluate against the current and previous snapshots in `ctx`.
+    pub fn is_met_in(&self, ctx: &EvalContext<'_>) -> bool {
+        self.evaluate(ctx.tick, ctx)
+    }
+
+    /// Evaluate against any partition lookup (no snapshot required).
+    pub(crate) fn evaluate<P>(&self, tick: u64, partitions: &P) -> bool
+    where
+        P: PartitionLookup + ?Sized,
+    {
         match self {
-            PulseCondition::Every(period) => tick != 0 && tick % period == 0,
+            PulseCondition::Every(period) => tick != 0 && tick.is_multiple_of(*period),
 
-            PulseCondition::PartitionEquals { name, value } => snapshot
-                .partition(name)
-                .map(|part| part.value == *value)
+            PulseCondition::PartitionEquals { name, value } => partitions
+                .value_of(name)
+                .map(|current| current == *value)
                 .unwrap_or(false),
 
             PulseCondition::PartitionModulo {
                 name,
                 modulus,
                 remainder,
-            } => snapshot
-                .partition(name)
-                .map(|part| {
+            } => partitions
+                .value_of(name)
+                .map(|value| {
                     if *modulus == 0 {
                         false
                     } else {
-                        part.value % modulus == *remainder
+                        value % modulus == *remainder
                     }
                 })
                 .unwrap_or(false),
 
             PulseCondition::TickRange { start, end } => tick >= *start && tick <= *end,
 
-            PulseCondition::Not(condition) => !condition.is_met(tick, snapshot),
+            PulseCondition::PartitionChanged { name } => partitions
+                .previous_value_of(name)
+                .zip(partitions.value_of(name))
+                .is_some_and(|(previous, current)| current != previous),
+
+            PulseCondition::PartitionDecreased { name } => partitions
+                .previous_value_of(name)
+                .zip(partitions.value_of(name))
+                .is_some_and(|(previous, current)| current < previous),
+
+            PulseCondition::Not(condition) => !condition.evaluate(tick, partitions),
 
             PulseCondition::And(conditions) => {
                 !conditions.is_empty()
-                    && conditions.iter().all(|c| c.is_met(tick, snapshot))
+                    && conditions.iter().all(|c| c.evaluate(tick, partitions))
             }
 
             PulseCondition::Or(conditions) => {
-                conditions.iter().any(|c| c.is_met(tick, snapshot))
+                conditions.iter().any(|c| c.evaluate(tick, partitions))
+            }
+
+            PulseCondition::Jitter { inner, .. }
+            | PulseCondition::RateLimit { inner, .. }
+            | PulseCondition::Debounce { inner, .. } => inner.evaluate(tick, partitions),
+
+            PulseCondition::ElapsedAtLeast { .. } => false,
+
+            PulseCondition::DateEquals { field, value } => {
+                date_of(partitions).is_some_and(|date| u64::try_from(field.of(date)) == Ok(*value))
+            }
+        }
+    }
+}
+
+/// What [`PulseCondition::is_met_in`] evaluates against: the tick, its
+/// snapshot, and the snapshot of the tick before, for conditions such as
+/// [`PulseCondition::PartitionChanged`] that compare the two.
+///
+/// ```rust
+/// use beeclock_core::{Clock, EvalContext, PulseCondition};
+///
+/// let mut clock = Clock::builder()
+///     .least_significant_first()
+///     .partition("sec", 60)
+///     .partition("min", 60)
+///     .build()
+///     .unwrap();
+/// let new_minute: PulseCondition = "changed(min)".parse().unwrap();
+///
+/// let previous = (0..59).map(|_| clock.tick()).last().unwrap().snapshot;
+/// let current = clock.tick().snapshot;
+/// let ctx = EvalContext {
+///     tick: current.tick,
+///     current: &current,
+///     previous: Some(&previous),
+/// };
+/// assert!(new_minute.is_met_in(&ctx));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct EvalContext<'a> {
+    pub tick: u64,
+    pub current: &'a ClockSnapshot,
+    /// `None` at the first tick, where delta conditions are not met.
+    pub previous: Option<&'a ClockSnapshot>,
+}
+
+impl PartitionLookup for EvalContext<'_> {
+    fn value_of(&self, name: &str) -> Option<u64> {
+        self.current.partitions.as_slice().value_of(name)
+    }
+
+    fn previous_value_of(&self, name: &str) -> Option<u64> {
+        self.previous?.partitions.as_slice().value_of(name)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod repr {
+    use super::*;
+
+    /// Serialized form of [`PulseCondition`].
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    pub enum ConditionRepr {
+        Every {
+            period: u64,
+        },
+        PartitionEquals {
+            name: String,
+            value: u64,
+        },
+        PartitionModulo {
+            name: String,
+            modulus: u64,
+            remainder: u64,
+        },
+        TickRange {
+            start: u64,
+            end: u64,
+        },
+        PartitionChanged {
+            name: String,
+        },
+        PartitionDecreased {
+            name: String,
+        },
+        Not {
+            condition: Box<PulseCondition>,
+        },
+        And {
+            conditions: Vec<PulseCondition>,
+        },
+        Or {
+            conditions: Vec<PulseCondition>,
+        },
+        Jitter {
+            window: u64,
+            #[serde(default)]
+            seed: u64,
+            condition: Box<PulseCondition>,
+        },
+        RateLimit {
+            max_fires: u64,
+            per_ticks: u64,
+            condition: Box<PulseCondition>,
+        },
+        Debounce {
+            ticks: u64,
+            condition: Box<PulseCondition>,
+        },
+        ElapsedAtLeast {
+            duration: ClockDuration,
+            #[serde(default)]
+            since: ElapsedSince,
+        },
+        DateEquals {
+            field: DateField,
+            value: u64,
+        },
+    }
+
+    impl From<PulseCondition> for ConditionRepr {
+        fn from(condition: PulseCondition) -> Self {
+            match condition {
+                PulseCondition::Every(period) => ConditionRepr::Every { period },
+                PulseCondition::PartitionEquals { name, value } => {
+                    ConditionRepr::PartitionEquals { name, value }
+                }
+                PulseCondition::PartitionModulo {
+                    name,
+                    modulus,
+                    remainder,
+                } => ConditionRepr::PartitionModulo {
+                    name,
+                    modulus,
+                    remainder,
+                },
+                PulseCondition::TickRange { start, end } => ConditionRepr::TickRange { start, end },
+                PulseCondition::PartitionChanged { name } => {
+                    ConditionRepr::PartitionChanged { name }
+                }
+                PulseCondition::PartitionDecreased { name } => {
+                    ConditionRepr::PartitionDecreased { name }
+                }
+                PulseCondition::Not(condition) => ConditionRepr::Not { condition },
+                PulseCondition::And(conditions) => ConditionRepr::And { conditions },
+                PulseCondition::Or(conditions) => ConditionRepr::Or { conditions },
+                PulseCondition::Jitter {
+                    window,
+                    seed,
+                    inner,
+                } => ConditionRepr::Jitter {
+                    window,
+                    seed,
+                    condition: inner,
+                },
+                PulseCondition::RateLimit {
+                    max_fires,
+                    per_ticks,
+                    inner,
+                } => ConditionRepr::RateLimit {
+                    max_fires,
+                    per_ticks,
+                    condition: inner,
+                },
+                PulseCondition::Debounce { ticks, inner } => ConditionRepr::Debounce {
+                    ticks,
+                    condition: inner,
+                },
+                PulseCondition::ElapsedAtLeast { duration, since } => {
+                    ConditionRepr::ElapsedAtLeast { duration, since }
+                }
+                PulseCondition::DateEquals { field, value } => {
+                    ConditionRepr::DateEquals { field, value }
+                }
+            }
+        }
+    }
+
+    impl From<ConditionRepr> for PulseCondition {
+        fn from(repr: ConditionRepr) -> Self {
+            match repr {
+                ConditionRepr::Every { period } => PulseCondition::Every(period),
+                ConditionRepr::PartitionEquals { name, value } => {
+                    PulseCondition::PartitionEquals { name, value }
+                }
+                ConditionRepr::PartitionModulo {
+                    name,
+                    modulus,
+                    remainder,
+                } => PulseCondition::PartitionModulo {
+                    name,
+                    modulus,
+                    remainder,
+                },
+                ConditionRepr::TickRange { start, end } => PulseCondition::TickRange { start, end },
+                ConditionRepr::PartitionChanged { name } => {
+                    PulseCondition::PartitionChanged { name }
+                }
+                ConditionRepr::PartitionDecreased { name } => {
+                    PulseCondition::PartitionDecreased { name }
+                }
+                ConditionRepr::Not { condition } => PulseCondition::Not(condition),
+                ConditionRepr::And { conditions } => PulseCondition::And(conditions),
+                ConditionRepr::Or { conditions } => PulseCondition::Or(conditions),
+                ConditionRepr::Jitter {
+                    window,
+                    seed,
+                    condition,
+                } => PulseCondition::Jitter {
+                    window,
+                    seed,
+                    inner: condition,
+                },
+                ConditionRepr::RateLimit {
+                    max_fires,
+                    per_ticks,
+                    condition,
+                } => PulseCondition::RateLimit {
+                    max_fires,
+                    per_ticks,
+                    inner: condition,
+                },
+                ConditionRepr::Debounce { ticks, condition } => PulseCondition::Debounce {
+                    ticks,
+                    inner: condition,
+                },
+                ConditionRepr::ElapsedAtLeast { duration, since } => {
+                    PulseCondition::ElapsedAtLeast { duration, since }
+                }
+                ConditionRepr::DateEquals { field, value } => {
+                    PulseCondition::DateEquals { field, value }
+                }
             }
         }
     }
@@ -0,0 +1,195 @@
+//! Plain clock configuration.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::validate::validate_partitions;
+use crate::{
+    Clock, ClockError, PartitionOrder, PartitionSpec, PulseSpec, QuietWindow, OVERFLOW_PULSE,
+};
+
+/// Complete clock configuration: partition order, partitions, and pulses.
+///
+/// Unlike [`ClockBuilder`](crate::ClockBuilder), the order is always explicit
+/// and the value can be inspected, stored, and reused.
+///
+/// With the `serde` feature this is the on-disk clock definition format:
+///
+/// ```json
+/// {
+///   "order": "lsf",
+///   "partitions": [{ "name": "sec", "modulus": 60 }],
+///   "pulses": [{ "name": "tick", "condition": { "type": "every", "period": 1 } }]
+/// }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClockConfig {
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub name: Option<String>,
+    pub order: PartitionOrder,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub partitions: Vec<PartitionSpec>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pulses: Vec<PulseSpec>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub quiet_windows: Vec<QuietWindow>,
+    /// Tick on which the clock halts (see [`Clock::set_run_for`]).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub run_for: Option<u64>,
+    /// Real time one tick stands for (see [`Clock::set_tick_duration`]).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub tick_duration: Option<Duration>,
+    /// Pulse fired on tick overflow (see [`Clock::set_overflow_pulse`]);
+    /// `null` in JSON fires none.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "default_overflow_pulse",
+            skip_serializing_if = "is_default_overflow_pulse"
+        )
+    )]
+    pub overflow_pulse: Option<String>,
+    /// Ticks per unit of the least significant partition (see
+    /// [`ClockBuilder::ticks_per_unit`](crate::ClockBuilder::ticks_per_unit));
+    /// `None` advances it every tick.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub ticks_per_unit: Option<u64>,
+    /// Partitions over the epoch, least significant first (see
+    /// [`ClockBuilder::epoch_partition`](crate::ClockBuilder::epoch_partition)).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub epoch_partitions: Vec<PartitionSpec>,
+}
+
+impl ClockConfig {
+    /// Create a configuration with the given order and no partitions or pulses.
+    pub fn new(order: PartitionOrder) -> Self {
+        Self {
+            name: None,
+            order,
+            partitions: Vec::new(),
+            pulses: Vec::new(),
+            quiet_windows: Vec::new(),
+            run_for: None,
+            tick_duration: None,
+            overflow_pulse: default_overflow_pulse(),
+            ticks_per_unit: None,
+            epoch_partitions: Vec::new(),
+        }
+    }
+
+    /// Build a clock from this configuration.
+    pub fn build(self) -> Result<Clock, ClockError> {
+        let mut clock = Clock::with_ticks_per_unit(
+            self.order,
+            self.partitions,
+            self.pulses,
+            self.ticks_per_unit.unwrap_or(1),
+        )?;
+        if let Some(name) = self.name {
+            clock.set_name(name);
+        }
+        for window in self.quiet_windows {
+            clock.add_quiet_window(window)?;
+        }
+        if let Some(ticks) = self.run_for {
+            clock.set_run_for(ticks)?;
+        }
+        if let Some(duration) = self.tick_duration {
+            clock.set_tick_duration(duration)?;
+        }
+        clock.set_overflow_pulse(self.overflow_pulse);
+        validate_partitions(&self.epoch_partitions)?;
+        clock.epoch_partitions = self.epoch_partitions;
+        Ok(clock)
+    }
+
+    /// Parse a configuration from JSON (requires the `json` feature).
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Serialize this configuration to JSON (requires the `json` feature).
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("clock configuration is always serializable")
+    }
+}
+
+fn default_overflow_pulse() -> Option<String> {
+    Some(String::from(OVERFLOW_PULSE))
+}
+
+#[cfg(feature = "serde")]
+fn is_default_overflow_pulse(name: &Option<String>) -> bool {
+    name.as_deref() == Some(OVERFLOW_PULSE)
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::PulseCondition;
+
+    #[test]
+    fn json_round_trip() {
+        let config = ClockConfig::from_json(
+            r#"{
+                "name": "seconds",
+                "order": "least_significant_first",
+                "partitions": [{ "name": "sec", "modulus": 60, "unit": "s" }],
+                "pulses": [{
+                    "name": "half",
+                    "condition": { "type": "or", "conditions": [
+                        { "type": "partition_equals", "name": "sec", "value": 30 },
+                        { "type": "every", "period": 60 }
+                    ]}
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.order, PartitionOrder::LeastSignificantFirst);
+        assert!(matches!(&config.pulses[0].condition, PulseCondition::Or(c) if c.len() == 2));
+
+        let json = config.to_json();
+        assert!(json.contains(r#""order":"lsf""#));
+        assert!(json.contains(r#""modulus":60,"unit":"s"}"#));
+        assert_eq!(ClockConfig::from_json(&json).unwrap().to_json(), json);
+        assert_eq!(config.build().unwrap().name(), Some("seconds"));
+    }
+
+    #[test]
+    fn overflow_pulse_round_trips() {
+        let quiet =
+            ClockConfig::from_json(r#"{ "order": "lsf", "overflow_pulse": null }"#).unwrap();
+        assert_eq!(quiet.build().unwrap().overflow_pulse(), None);
+
+        let default = ClockConfig::new(PartitionOrder::LeastSignificantFirst);
+        assert!(!default.to_json().contains("overflow_pulse"));
+        let mut renamed = default.clone();
+        renamed.overflow_pulse = Some("wrapped".into());
+        let json = renamed.to_json();
+        assert!(json.contains(r#""overflow_pulse":"wrapped""#));
+        let parsed = ClockConfig::from_json(&json).unwrap();
+        assert_eq!(parsed.overflow_pulse.as_deref(), Some("wrapped"));
+    }
+}
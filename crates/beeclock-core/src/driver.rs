@@ -0,0 +1,168 @@
+//! Async tick driver for executors such as Embassy.
+//!
+//! [`drive`] ticks a clock each time a [`TickSource`] resolves and hands each
+//! tick to a callback, which can forward fired pulses to a channel or
+//! signal. It is executor-agnostic and ticks through
+//! [`Clock::tick_into`], so a running driver allocates nothing.
+//!
+//! With the `embassy` feature, `embassy_time::Ticker` is a [`TickSource`]
+//! and [`drive_pulses`] sends fired pulses to an `embassy_sync` channel:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "embassy")] {
+//! use beeclock_core::{drive_pulses, Clock};
+//! use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+//! use embassy_sync::channel::Channel;
+//! use embassy_time::{Duration, Ticker};
+//!
+//! /// Indices of fired pulses, for other tasks to receive.
+//! static PULSES: Channel<CriticalSectionRawMutex, usize, 8> = Channel::new();
+//!
+//! // An `#[embassy_executor::task]` on the device.
+//! async fn clock_task(mut clock: Clock) {
+//!     let mut ticker = Ticker::every(Duration::from_millis(10));
+//!     drive_pulses(&mut clock, &mut ticker, &PULSES).await;
+//! }
+//! # }
+//! ```
+
+use core::future::Future;
+
+#[cfg(feature = "embassy")]
+use embassy_sync::{blocking_mutex::raw::RawMutex, channel::Channel};
+
+use crate::{Clock, TickBuffer};
+
+/// When the next tick is due, for [`drive`].
+///
+/// Implement it on a wrapper around your timer, e.g. returning
+/// `embassy_time::Ticker::next()`.
+pub trait TickSource {
+    /// Wait until the next tick is due.
+    fn next(&mut self) -> impl Future<Output = ()>;
+}
+
+/// Tick `clock` each time `source` resolves, calling `on_tick` with the
+/// clock and the tick's [`TickBuffer`], until the clock
+/// [halts](Clock::is_halted).
+///
+/// Pulse indices in [`TickBuffer::fired`] resolve through
+/// [`Clock::pulse_name`]. A clock without a run budget never halts, so the
+/// future runs for as long as it is polled. With std, pet a
+/// [`Watchdog`](crate::Watchdog) from `on_tick` to detect a stalled loop.
+pub async fn drive<S, F>(clock: &mut Clock, source: &mut S, mut on_tick: F)
+where
+    S: TickSource + ?Sized,
+    F: FnMut(&Clock, &TickBuffer),
+{
+    let mut buffer = clock.tick_buffer();
+    while !clock.is_halted() {
+        source.next().await;
+        clock.tick_into(&mut buffer);
+        on_tick(clock, &buffer);
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl TickSource for embassy_time::Ticker {
+    fn next(&mut self) -> impl Future<Output = ()> {
+        embassy_time::Ticker::next(self)
+    }
+}
+
+/// [`drive`] `clock` from `source`, sending the index of each fired pulse
+/// to `pulses` (names via [`Clock::pulse_name`]).
+///
+/// A full channel drops the pulse rather than stall the clock; size it for
+/// the pulses one tick can fire, plus however far receivers may fall behind.
+#[cfg(feature = "embassy")]
+pub async fn drive_pulses<S, M, const N: usize>(
+    clock: &mut Clock,
+    source: &mut S,
+    pulses: &Channel<M, usize, N>,
+) where
+    S: TickSource + ?Sized,
+    M: RawMutex,
+{
+    drive(clock, source, |_, tick| {
+        for &pulse in &tick.fired {
+            let _ = pulses.try_send(pulse);
+        }
+    })
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// Resolves on every other poll, like a timer that is not yet due.
+    struct Alternating(bool);
+
+    impl TickSource for Alternating {
+        fn next(&mut self) -> impl Future<Output = ()> {
+            core::future::poll_fn(|_| {
+                self.0 = !self.0;
+                if self.0 {
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn ticks_once_per_source_until_halted() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("fifth", 5)
+            .run_for(12)
+            .build()
+            .unwrap();
+        let mut source = Alternating(false);
+        let mut fired: Vec<(u64, String)> = Vec::new();
+        let mut polls = 1;
+        {
+            let mut future = pin!(drive(&mut clock, &mut source, |clock, tick| {
+                for &pulse in &tick.fired {
+                    fired.push((tick.tick, clock.pulse_name(pulse).unwrap().into()));
+                }
+            }));
+            let mut cx = Context::from_waker(Waker::noop());
+            while future.as_mut().poll(&mut cx).is_pending() {
+                polls += 1;
+            }
+        }
+        assert_eq!(polls, 13);
+        assert_eq!(fired, [(5, "fifth".into()), (10, "fifth".into())]);
+        assert_eq!(clock.tick_count(), 12);
+    }
+
+    #[cfg(feature = "embassy")]
+    #[test]
+    fn sends_pulses_from_an_embassy_ticker() {
+        use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+        use embassy_time::{Duration, Ticker};
+
+        let mut clock = Clock::builder()
+            .pulse_every("odd", 2)
+            .pulse_every("third", 3)
+            .run_for(6)
+            .build()
+            .unwrap();
+        let pulses: Channel<NoopRawMutex, usize, 4> = Channel::new();
+        let mut ticker = Ticker::every(Duration::from_millis(1));
+        futures::executor::block_on(drive_pulses(&mut clock, &mut ticker, &pulses));
+
+        assert_eq!(clock.tick_count(), 6);
+        let sent: Vec<usize> = core::iter::from_fn(|| pulses.try_receive().ok()).collect();
+        // Ticks 2, 3 and 4 fill the channel; 6 finds it full.
+        assert_eq!(sent, [0, 1, 0, 0]);
+    }
+}
@@ -0,0 +1,447 @@
+//! Text form of pulse conditions.
+//!
+//! ```text
+//! hour == 12 && min == 0          partition equals
+//! sec % 15 == 0                   partition modulo
+//! day != 0                        negated equals
+//! tick in 100..=200               inclusive tick range
+//! every(60)                       every N ticks
+//! changed(hour)                   differs from the previous tick
+//! decreased(sec)                  lower than on the previous tick
+//! jitter(30, 7, min == 0)         window, seed, condition
+//! rate_limit(1, 60, every(5))     max fires, per ticks, condition
+//! debounce(3, sensor == 1)        consecutive ticks, condition
+//! elapsed(1 hour 30 min)          at least this long since tick 0
+//! since_fire(45 min)              at least this long since the last fire
+//! date(weekday) == 7              calendar date field (year, month, day, weekday)
+//! !(a == 1 || b == 2)             not, or, grouping
+//! ```
+//!
+//! `&&` binds tighter than `||`. Partition names are identifiers
+//! (`[A-Za-z_][A-Za-z0-9_]*`); `every`, `changed`, `decreased`, `jitter`,
+//! `rate_limit`, `debounce`, `elapsed`, `since_fire`, `date` and `tick` are only
+//! keywords where used as above, so they remain usable as partition names.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{ClockDuration, DateField, ElapsedSince, PulseCondition};
+
+/// Error from parsing a [`PulseCondition`] from text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConditionParseError {
+    /// Byte offset in the input where parsing failed.
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ConditionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.reason, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConditionParseError {}
+
+impl FromStr for PulseCondition {
+    type Err = ConditionParseError;
+
+    /// Parse the text form described in the [module docs](self).
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { src, pos: 0 };
+        let condition = parser.or()?;
+        parser.skip_ws();
+        if parser.pos < src.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(condition)
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn or(&mut self) -> Result<PulseCondition, ConditionParseError> {
+        let mut terms = vec![self.and()?];
+        while self.eat("||") {
+            terms.push(self.and()?);
+        }
+        Ok(join(terms, PulseCondition::Or))
+    }
+
+    fn and(&mut self) -> Result<PulseCondition, ConditionParseError> {
+        let mut terms = vec![self.unary()?];
+        while self.eat("&&") {
+            terms.push(self.unary()?);
+        }
+        Ok(join(terms, PulseCondition::And))
+    }
+
+    fn unary(&mut self) -> Result<PulseCondition, ConditionParseError> {
+        if self.eat("!") {
+            return Ok(PulseCondition::Not(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<PulseCondition, ConditionParseError> {
+        if self.eat("(") {
+            let condition = self.or()?;
+            self.expect(")")?;
+            return Ok(condition);
+        }
+
+        let Some(name) = self.ident() else {
+            return Err(self.error("expected a condition"));
+        };
+        match name {
+            "every" if self.eat("(") => {
+                let period = self.number()?;
+                self.expect(")")?;
+                return Ok(PulseCondition::Every(period));
+            }
+            "changed" | "decreased" if self.eat("(") => {
+                let Some(partition) = self.ident() else {
+                    return Err(self.error("expected a partition name"));
+                };
+                let partition = partition.to_string();
+                self.expect(")")?;
+                return Ok(if name == "changed" {
+                    PulseCondition::PartitionChanged { name: partition }
+                } else {
+                    PulseCondition::PartitionDecreased { name: partition }
+                });
+            }
+            "jitter" if self.eat("(") => {
+                let (window, seed, inner) = self.wrapper_args()?;
+                return Ok(PulseCondition::Jitter {
+                    window,
+                    seed,
+                    inner,
+                });
+            }
+            "rate_limit" if self.eat("(") => {
+                let (max_fires, per_ticks, inner) = self.wrapper_args()?;
+                return Ok(PulseCondition::RateLimit {
+                    max_fires,
+                    per_ticks,
+                    inner,
+                });
+            }
+            "debounce" if self.eat("(") => {
+                let ticks = self.number()?;
+                self.expect(",")?;
+                let inner = Box::new(self.or()?);
+                self.expect(")")?;
+                return Ok(PulseCondition::Debounce { ticks, inner });
+            }
+            "elapsed" | "since_fire" if self.eat("(") => {
+                let since = match name {
+                    "elapsed" => ElapsedSince::Start,
+                    _ => ElapsedSince::LastFire,
+                };
+                let duration = self.duration()?;
+                return Ok(PulseCondition::ElapsedAtLeast { duration, since });
+            }
+            "date" if self.eat("(") => {
+                let start = self.pos;
+                let field = self.ident();
+                let Some(field) = DateField::ALL.into_iter().find(|f| Some(f.name()) == field)
+                else {
+                    self.pos = start;
+                    return Err(self.error("expected year, month, day or weekday"));
+                };
+                self.expect(")")?;
+                let negated = if self.eat("!=") {
+                    true
+                } else {
+                    self.expect("==")?;
+                    false
+                };
+                let equals = PulseCondition::DateEquals {
+                    field,
+                    value: self.number()?,
+                };
+                return Ok(if negated {
+                    PulseCondition::Not(Box::new(equals))
+                } else {
+                    equals
+                });
+            }
+            "tick" if self.eat_keyword("in") => {
+                let start = self.number()?;
+                self.expect("..=")?;
+                let end = self.number()?;
+                return Ok(PulseCondition::TickRange { start, end });
+            }
+            _ => {}
+        }
+
+        let name = name.to_string();
+        if self.eat("%") {
+            let modulus = self.number()?;
+            self.expect("==")?;
+            let remainder = self.number()?;
+            return Ok(PulseCondition::PartitionModulo {
+                name,
+                modulus,
+                remainder,
+            });
+        }
+        if self.eat("==") {
+            let value = self.number()?;
+            return Ok(PulseCondition::PartitionEquals { name, value });
+        }
+        if self.eat("!=") {
+            let value = self.number()?;
+            let equals = PulseCondition::PartitionEquals { name, value };
+            return Ok(PulseCondition::Not(Box::new(equals)));
+        }
+        Err(self.error("expected '==', '!=' or '%' after partition name"))
+    }
+
+    /// `a, b, condition)` for `jitter` and `rate_limit`.
+    fn wrapper_args(&mut self) -> Result<(u64, u64, Box<PulseCondition>), ConditionParseError> {
+        let first = self.number()?;
+        self.expect(",")?;
+        let second = self.number()?;
+        self.expect(",")?;
+        let inner = self.or()?;
+        self.expect(")")?;
+        Ok((first, second, Box::new(inner)))
+    }
+
+    /// `amount partition ...)`, at least one pair.
+    fn duration(&mut self) -> Result<ClockDuration, ConditionParseError> {
+        let mut duration = ClockDuration::new();
+        loop {
+            let amount = self.number()?;
+            let Some(partition) = self.ident() else {
+                return Err(self.error("expected a partition name"));
+            };
+            duration = duration.and(partition, amount);
+            if self.eat(")") {
+                return Ok(duration);
+            }
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_ws();
+        if self.src[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Eat `word` only as a whole identifier.
+    fn eat_keyword(&mut self, word: &str) -> bool {
+        let start = self.pos;
+        if self.ident() == Some(word) {
+            return true;
+        }
+        self.pos = start;
+        false
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ConditionParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{token}'")))
+        }
+    }
+
+    fn ident(&mut self) -> Option<&'a str> {
+        self.skip_ws();
+        let rest = &self.src[self.pos..];
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return None;
+        }
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        Some(&rest[..len])
+    }
+
+    /// Decimal number, with optional `_` separators.
+    fn number(&mut self) -> Result<u64, ConditionParseError> {
+        self.skip_ws();
+        let rest = &self.src[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '_'))
+            .unwrap_or(rest.len());
+        if !rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error("expected a number"));
+        }
+        let digits: String = rest[..len].chars().filter(|&c| c != '_').collect();
+        let value = digits
+            .parse()
+            .map_err(|_| self.error("number does not fit in u64"))?;
+        self.pos += len;
+        Ok(value)
+    }
+
+    fn error(&self, reason: &str) -> ConditionParseError {
+        ConditionParseError {
+            offset: self.pos,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// The only term, or all of them combined with `combine`.
+fn join(
+    mut terms: Vec<PulseCondition>,
+    combine: fn(Vec<PulseCondition>) -> PulseCondition,
+) -> PulseCondition {
+    if terms.len() == 1 {
+        terms.pop().expect("one term")
+    } else {
+        combine(terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> String {
+        format!("{:?}", src.parse::<PulseCondition>().unwrap())
+    }
+
+    fn equals(name: &str, value: u64) -> PulseCondition {
+        PulseCondition::PartitionEquals {
+            name: name.into(),
+            value,
+        }
+    }
+
+    #[test]
+    fn parses_the_condition_forms() {
+        let noon = PulseCondition::And(vec![equals("hour", 12), equals("min", 0)]);
+        assert_eq!(parse("hour == 12 && min == 0"), format!("{noon:?}"));
+
+        let nested = PulseCondition::Or(vec![
+            PulseCondition::Not(Box::new(PulseCondition::PartitionModulo {
+                name: "sec".into(),
+                modulus: 15,
+                remainder: 0,
+            })),
+            PulseCondition::And(vec![
+                PulseCondition::TickRange {
+                    start: 100,
+                    end: 1_000,
+                },
+                PulseCondition::Not(Box::new(equals("every", 3))),
+            ]),
+        ]);
+        assert_eq!(
+            parse("!(sec % 15 == 0) || tick in 100..=1_000 && every != 3"),
+            format!("{nested:?}")
+        );
+
+        let wrapped = PulseCondition::RateLimit {
+            max_fires: 1,
+            per_ticks: 60,
+            inner: Box::new(PulseCondition::Jitter {
+                window: 30,
+                seed: 7,
+                inner: Box::new(PulseCondition::Every(5)),
+            }),
+        };
+        assert_eq!(
+            parse("rate_limit(1, 60, jitter(30, 7, every(5)))"),
+            format!("{wrapped:?}")
+        );
+
+        let debounced = PulseCondition::Debounce {
+            ticks: 3,
+            inner: Box::new(PulseCondition::Or(vec![
+                equals("door", 1),
+                equals("debounce", 2),
+            ])),
+        };
+        assert_eq!(
+            parse("debounce(3, door == 1 || debounce == 2)"),
+            format!("{debounced:?}")
+        );
+
+        let delta = PulseCondition::And(vec![
+            PulseCondition::PartitionChanged {
+                name: "hour".into(),
+            },
+            PulseCondition::Not(Box::new(PulseCondition::PartitionDecreased {
+                name: "day".into(),
+            })),
+        ]);
+        assert_eq!(
+            parse("changed(hour) && !decreased(day)"),
+            format!("{delta:?}")
+        );
+        assert_eq!(parse("changed == 1"), format!("{:?}", equals("changed", 1)));
+
+        let elapsed = PulseCondition::ElapsedAtLeast {
+            duration: ClockDuration::of("hour", 1).and("min", 30),
+            since: ElapsedSince::Start,
+        };
+        assert_eq!(parse("elapsed(1 hour 30 min)"), format!("{elapsed:?}"));
+        let since_fire = PulseCondition::ElapsedAtLeast {
+            duration: ClockDuration::of("min", 45),
+            since: ElapsedSince::LastFire,
+        };
+        assert_eq!(parse("since_fire(45 min)"), format!("{since_fire:?}"));
+
+        let date = PulseCondition::And(vec![
+            PulseCondition::DateEquals {
+                field: DateField::Month,
+                value: 2,
+            },
+            PulseCondition::Not(Box::new(PulseCondition::DateEquals {
+                field: DateField::Weekday,
+                value: 7,
+            })),
+        ]);
+        assert_eq!(
+            parse("date(month) == 2 && date(weekday) != 7"),
+            format!("{date:?}")
+        );
+        assert_eq!(parse("date == 3"), format!("{:?}", equals("date", 3)));
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let err = |src: &str| src.parse::<PulseCondition>().unwrap_err();
+        assert_eq!(err("hour == ").offset, 8);
+        assert_eq!(err("hour == 12 &&").reason, "expected a condition");
+        assert_eq!(err("(min == 0").reason, "expected ')'");
+        assert_eq!(err("min = 0").offset, 4);
+        assert_eq!(err("min == 0 min").reason, "unexpected input");
+        assert_eq!(
+            err("sec == 99999999999999999999").reason,
+            "number does not fit in u64"
+        );
+        assert_eq!(
+            err("date(hour) == 1").reason,
+            "expected year, month, day or weekday"
+        );
+        assert_eq!(err("date(hour) == 1").offset, 5);
+    }
+}
@@ -0,0 +1,274 @@
+//! Spans of clock time in partition units.
+//!
+//! A [`ClockDuration`] says "2 hours and 5 minutes" instead of a raw tick
+//! count. It only means something against a [`ClockConfig`], whose moduli
+//! give each partition its weight in ticks: with 60 sec per min, one `min`
+//! is 60 ticks and one `hour` is 3600.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{ClockConfig, ClockError, ClockSnapshot, PartitionOrder, PartitionSpec};
+
+/// A span of clock time as amounts of named partitions.
+///
+/// Amounts need not be normalised: `min=90` is the same span as
+/// `hour=1 min=30` under a 60-60-24 clock.
+///
+/// ```
+/// use beeclock_core::{Clock, ClockDuration};
+///
+/// let config = Clock::builder()
+///     .least_significant_first()
+///     .partition("sec", 60)
+///     .partition("min", 60)
+///     .partition("hour", 24)
+///     .build()
+///     .unwrap()
+///     .config();
+///
+/// let span = ClockDuration::of("hour", 2).and("min", 5);
+/// assert_eq!(span.to_ticks(&config).unwrap(), 7500);
+/// assert_eq!(ClockDuration::from_ticks(5400, &config).to_string(), "hour=1 min=30");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ClockDuration {
+    units: Vec<(String, u64)>,
+}
+
+impl ClockDuration {
+    /// The empty duration (zero ticks).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `amount` of the named partition.
+    pub fn of(partition: impl Into<String>, amount: u64) -> Self {
+        Self::new().and(partition, amount)
+    }
+
+    /// Add `amount` of the named partition, saturating at `u64::MAX`.
+    pub fn and(mut self, partition: impl Into<String>, amount: u64) -> Self {
+        let partition = partition.into();
+        match self.units.iter_mut().find(|(name, _)| *name == partition) {
+            Some((_, total)) => *total = total.saturating_add(amount),
+            None => self.units.push((partition, amount)),
+        }
+        self
+    }
+
+    /// Partition names and amounts, in the order they were added.
+    pub fn units(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.units
+            .iter()
+            .map(|(name, amount)| (name.as_str(), *amount))
+    }
+
+    /// Whether the duration is zero ticks long under any configuration.
+    pub fn is_zero(&self) -> bool {
+        self.units.iter().all(|&(_, amount)| amount == 0)
+    }
+
+    /// Length in ticks under `config`.
+    ///
+    /// Fails with [`ClockError::PartitionNotFound`] for a partition the
+    /// configuration lacks and [`ClockError::DurationOverflow`] if the total
+    /// doesn't fit in a `u64`.
+    pub fn to_ticks(&self, config: &ClockConfig) -> Result<u64, ClockError> {
+        self.ticks_in(
+            config.order,
+            &config.partitions,
+            config.ticks_per_unit.unwrap_or(1),
+        )
+    }
+
+    /// [`to_ticks`](Self::to_ticks) for a clock's partitions.
+    pub(crate) fn ticks_in(
+        &self,
+        order: PartitionOrder,
+        partitions: &[PartitionSpec],
+        ticks_per_unit: u64,
+    ) -> Result<u64, ClockError> {
+        let weights = weights(order, partitions, ticks_per_unit);
+        let overflow = || ClockError::DurationOverflow {
+            duration: self.to_string(),
+        };
+        let mut ticks = 0u64;
+        for (name, amount) in self.units() {
+            let weight = weights
+                .iter()
+                .find(|(partition, _)| *partition == name)
+                .ok_or_else(|| ClockError::PartitionNotFound { name: name.into() })?
+                .1;
+            if amount == 0 {
+                continue;
+            }
+            let span = weight.and_then(|w| w.checked_mul(amount));
+            ticks = span
+                .and_then(|s| ticks.checked_add(s))
+                .ok_or_else(overflow)?;
+        }
+        Ok(ticks)
+    }
+
+    /// The normalised duration of `ticks` under `config`, most significant
+    /// partition first and zero amounts left out.
+    ///
+    /// Every partition but the most significant holds less than its modulus;
+    /// the most significant takes whatever remains. Ticks short of a whole
+    /// [unit](crate::ClockBuilder::ticks_per_unit) are dropped. A
+    /// configuration without partitions gives the empty duration.
+    pub fn from_ticks(ticks: u64, config: &ClockConfig) -> Self {
+        let mut rest = ticks / config.ticks_per_unit.unwrap_or(1).max(1);
+        let mut units = Vec::new();
+        let mut partitions = lsf(config.order, &config.partitions).peekable();
+        while let Some(partition) = partitions.next() {
+            let amount = match partitions.peek() {
+                Some(_) => {
+                    let value = rest % partition.modulus;
+                    rest /= partition.modulus;
+                    value
+                }
+                None => rest,
+            };
+            if amount > 0 {
+                units.push((partition.name.clone(), amount));
+            }
+        }
+        units.reverse();
+        Self { units }
+    }
+}
+
+/// Units as `name=amount` separated by spaces, or `0` when empty.
+impl fmt::Display for ClockDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.units.is_empty() {
+            return f.write_str("0");
+        }
+        for (i, (name, amount)) in self.units.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{name}={amount}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ClockConfig {
+    /// The snapshot a clock with this configuration reaches `duration`
+    /// after `snapshot` (see [`add_ticks`](Self::add_ticks)).
+    pub fn add_duration(
+        &self,
+        snapshot: &ClockSnapshot,
+        duration: &ClockDuration,
+    ) -> Result<ClockSnapshot, ClockError> {
+        self.add_ticks(snapshot, duration.to_ticks(self)?)
+    }
+}
+
+fn lsf(
+    order: PartitionOrder,
+    partitions: &[PartitionSpec],
+) -> impl Iterator<Item = &PartitionSpec> {
+    let lsf = order == PartitionOrder::LeastSignificantFirst;
+    let mut partitions = partitions.iter();
+    core::iter::from_fn(move || match lsf {
+        true => partitions.next(),
+        false => partitions.next_back(),
+    })
+}
+
+/// Ticks per unit of each partition, or `None` past `u64::MAX`.
+fn weights(
+    order: PartitionOrder,
+    partitions: &[PartitionSpec],
+    ticks_per_unit: u64,
+) -> Vec<(&str, Option<u64>)> {
+    let mut weight = Some(ticks_per_unit);
+    lsf(order, partitions)
+        .map(|partition| {
+            let this = (partition.name.as_str(), weight);
+            weight = weight.and_then(|w| w.checked_mul(partition.modulus));
+            this
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clock;
+
+    fn config(order: PartitionOrder) -> ClockConfig {
+        let builder = match order {
+            PartitionOrder::LeastSignificantFirst => Clock::builder()
+                .least_significant_first()
+                .partition("sec", 60)
+                .partition("min", 60)
+                .partition("hour", 24),
+            PartitionOrder::MostSignificantFirst => Clock::builder()
+                .most_significant_first()
+                .partition("hour", 24)
+                .partition("min", 60)
+                .partition("sec", 60),
+        };
+        builder.build().unwrap().config()
+    }
+
+    #[test]
+    fn converts_between_units_and_ticks_in_either_order() {
+        for order in [
+            PartitionOrder::LeastSignificantFirst,
+            PartitionOrder::MostSignificantFirst,
+        ] {
+            let config = config(order);
+            let span = ClockDuration::of("min", 90);
+            assert_eq!(span.to_ticks(&config).unwrap(), 5400);
+
+            let normal = ClockDuration::from_ticks(5400, &config);
+            assert_eq!(normal, ClockDuration::of("hour", 1).and("min", 30));
+            assert_eq!(normal.to_ticks(&config).unwrap(), 5400);
+            // The top partition absorbs what would otherwise wrap a day.
+            let days = ClockDuration::from_ticks(3 * 86_400 + 1, &config);
+            assert_eq!(days.to_string(), "hour=72 sec=1");
+
+            let mut clock = config.clone().build().unwrap();
+            clock.skip(59);
+            let start = clock.snapshot();
+            let later = config.add_duration(&start, &span).unwrap();
+            clock.skip(5400);
+            assert_eq!(later.partitions, clock.snapshot().partitions);
+            assert_eq!(later.tick, 5459);
+        }
+    }
+
+    #[test]
+    fn merges_units_and_reports_bad_durations() {
+        let config = config(PartitionOrder::LeastSignificantFirst);
+        let span = ClockDuration::of("sec", 30).and("min", 1).and("sec", 45);
+        assert_eq!(span.to_string(), "sec=75 min=1");
+        assert_eq!(span.to_ticks(&config).unwrap(), 135);
+        assert!(ClockDuration::new().is_zero());
+        assert_eq!(ClockDuration::from_ticks(0, &config).to_string(), "0");
+
+        let err = ClockDuration::of("day", 1).to_ticks(&config).unwrap_err();
+        assert!(matches!(err, ClockError::PartitionNotFound { ref name } if name == "day"));
+
+        let err = ClockDuration::of("hour", u64::MAX)
+            .to_ticks(&config)
+            .unwrap_err();
+        assert_eq!(err.code(), 105);
+        assert_eq!(
+            err.to_string(),
+            "duration 'hour=18446744073709551615' does not fit in u64 ticks"
+        );
+    }
+}
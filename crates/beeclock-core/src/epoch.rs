@@ -0,0 +1,200 @@
+//! Epoch partitions and epoch change hooks.
+//!
+//! The epoch counts tick counter overflows, a bare `u64` on its own. Epoch
+//! partitions ([`ClockBuilder::epoch_partition`]) read it as a chain of
+//! partitions, least significant first, the way the tick's partitions read
+//! the tick: a `cycle` of 1000 epochs and an `era` counting cycles, say.
+//! Their values are derived from the epoch, so they never drift from it.
+//!
+//! [`Clock::on_epoch_change`] runs a hook whenever the epoch moves, whether
+//! by ticking, skipping or observing a remote tick.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Clock, ClockBuilder, PartitionSpec, PartitionState};
+
+/// The epoch moving on, passed to [`Clock::on_epoch_change`] hooks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochChange {
+    /// Name of the clock, if it has one.
+    pub clock: Option<Arc<str>>,
+    pub old: u64,
+    pub new: u64,
+    /// Tick on which the epoch changed.
+    pub tick: u64,
+    /// Epoch partition values for the new epoch.
+    pub partitions: Vec<PartitionState>,
+}
+
+/// A hook from [`Clock::on_epoch_change`].
+pub(crate) struct EpochHook(Box<dyn FnMut(&EpochChange) + Send + Sync>);
+
+impl fmt::Debug for EpochHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EpochHook")
+    }
+}
+
+/// `epoch` split across `specs`, least significant first, each wrapping at
+/// its modulus.
+pub(crate) fn epoch_values(specs: &[PartitionSpec], epoch: u64) -> Vec<PartitionState> {
+    let mut rest = epoch;
+    specs
+        .iter()
+        .map(|spec| {
+            let mut state = PartitionState::from_spec(spec);
+            state.value = rest % spec.modulus;
+            rest /= spec.modulus;
+            state
+        })
+        .collect()
+}
+
+impl ClockBuilder {
+    /// Add a partition over the epoch, after any added before it in
+    /// significance (see [`Clock::epoch_partitions`]).
+    ///
+    /// ```
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::builder()
+    ///     .partition("sec", 60)
+    ///     .least_significant_first()
+    ///     .epoch_partition("cycle", 1000)
+    ///     .epoch_partition("era", u64::MAX)
+    ///     .build()
+    ///     .unwrap();
+    /// clock.skip(u64::MAX);
+    /// clock.tick();
+    /// let epoch: Vec<_> = clock.epoch_partitions().iter().map(|p| p.value).collect();
+    /// assert_eq!((clock.epoch(), epoch), (1, vec![1, 0]));
+    /// ```
+    pub fn epoch_partition(mut self, name: impl Into<String>, modulus: u64) -> Self {
+        self.epoch_partitions
+            .push(PartitionSpec::new(name, modulus));
+        self
+    }
+}
+
+impl Clock {
+    /// The epoch read through the [epoch partitions](ClockBuilder::epoch_partition),
+    /// least significant first; empty without any.
+    pub fn epoch_partitions(&self) -> Vec<PartitionState> {
+        epoch_values(&self.epoch_partitions, self.epoch)
+    }
+
+    /// Run `hook` whenever the epoch changes, on the tick that changes it.
+    ///
+    /// Hooks are not carried by [saved state](Clock::save_state) and are
+    /// not run for ticks computed on a copy, like [`Clock::peek`].
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::default();
+    /// let seen = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&seen);
+    /// clock.on_epoch_change(move |change| sink.lock().unwrap().push(change.new));
+    /// clock.skip(u64::MAX);
+    /// clock.tick();
+    /// assert_eq!(*seen.lock().unwrap(), [1]);
+    /// ```
+    pub fn on_epoch_change(&mut self, hook: impl FnMut(&EpochChange) + Send + Sync + 'static) {
+        self.epoch_hooks.push(EpochHook(Box::new(hook)));
+    }
+
+    /// Run the epoch hooks if the epoch moved on from `old`.
+    pub(crate) fn epoch_changed(&mut self, old: u64) {
+        if self.epoch == old || self.epoch_hooks.is_empty() {
+            return;
+        }
+        let change = EpochChange {
+            clock: self.name().map(Arc::from),
+            old,
+            new: self.epoch,
+            tick: self.tick,
+            partitions: self.epoch_partitions(),
+        };
+        for hook in &mut self.epoch_hooks {
+            (hook.0)(&change);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClockError;
+
+    fn clock() -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .epoch_partition("cycle", 3)
+            .epoch_partition("era", 2)
+            .name("long")
+            .build()
+            .unwrap()
+    }
+
+    fn values(partitions: &[PartitionState]) -> Vec<u64> {
+        partitions.iter().map(|p| p.value).collect()
+    }
+
+    #[test]
+    fn epoch_partitions_split_the_epoch() {
+        let specs = clock().config().epoch_partitions;
+        assert_eq!(values(&epoch_values(&specs, 0)), [0, 0]);
+        assert_eq!(values(&epoch_values(&specs, 5)), [2, 1]);
+        assert_eq!(values(&epoch_values(&specs, 6)), [0, 0]);
+        assert!(epoch_values(&[], 7).is_empty());
+
+        let layout = |specs: &[PartitionSpec]| -> Vec<(String, u64)> {
+            specs.iter().map(|s| (s.name.clone(), s.modulus)).collect()
+        };
+        let restored = Clock::from_state(clock().save_state()).unwrap();
+        assert_eq!(layout(&restored.config().epoch_partitions), layout(&specs));
+        assert!(matches!(
+            Clock::builder().epoch_partition("era", 0).build(),
+            Err(ClockError::ZeroModulus { .. })
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hooks_see_every_epoch_change() {
+        // Ticks served from the run-ahead cache run the hooks too.
+        for precompute in [false, true] {
+            let mut clock = clock();
+            let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = Arc::clone(&changes);
+            clock.on_epoch_change(move |change| sink.lock().unwrap().push(change.clone()));
+
+            clock.skip(u64::MAX);
+            if precompute {
+                clock.precompute(3);
+            }
+            clock.tick();
+            clock.observe(u64::MAX);
+            for _ in 0..4 {
+                clock.skip(u64::MAX);
+            }
+            assert_eq!(clock.epoch(), 5);
+            assert_eq!(values(&clock.epoch_partitions()), [2, 1]);
+
+            let changes = changes.lock().unwrap();
+            let epochs: Vec<(u64, u64)> = changes.iter().map(|c| (c.old, c.new)).collect();
+            assert_eq!(epochs, [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+            assert_eq!(
+                (changes[0].clock.as_deref(), changes[0].tick),
+                (Some("long"), 0)
+            );
+            assert_eq!(values(&changes[4].partitions), [2, 1]);
+        }
+    }
+}
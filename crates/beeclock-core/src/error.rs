@@ -1,10 +1,16 @@
 //! Clock error types.
 
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt;
 
 /// Errors that can occur when building or running a clock.
+///
+/// Non-exhaustive so variants can be added; bindings should map errors
+/// through [`ClockError::code`] and [`ClockError::category`] rather than by
+/// matching on variants or messages.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum ClockError {
     /// Partition modulus must be greater than 0.
     ZeroModulus { name: String },
@@ -16,13 +22,89 @@ pub enum ClockError {
     MissingPartitionOrder,
 
     /// Pulse references an unknown partition.
-    UnknownPartition { pulse: String, partition: String },
+    UnknownPartition {
+        pulse: String,
+        partition: String,
+        /// Closest existing partition name, when one is a likely typo.
+        suggestion: Option<String>,
+    },
 
     /// Pulse condition has zero modulus.
     ZeroConditionModulus { pulse: String, partition: String },
 
     /// Tick range is invalid (start > end).
     InvalidTickRange { pulse: String, start: u64, end: u64 },
+
+    /// Condition wrapper has a zero tick window.
+    ZeroWindow { pulse: String },
+
+    /// Delayed pulse has a zero delay.
+    ZeroDelay { name: String },
+
+    /// Delayed pulse references an unknown trigger pulse.
+    UnknownTrigger { pulse: String, trigger: String },
+
+    /// Snapshot partitions don't match the clock configuration.
+    SnapshotMismatch { reason: String },
+
+    /// Saved clock state is inconsistent with its configuration.
+    InvalidState { reason: String },
+
+    /// Alarm target tick has already passed.
+    AlarmInPast { name: String, tick: u64 },
+
+    /// Run budget must be greater than 0.
+    ZeroRunLength,
+
+    /// Clock set divider must be greater than 0.
+    ZeroDivider { name: String },
+
+    /// Clock set already holds a clock with this name.
+    DuplicateClock { name: String },
+
+    /// State machine transition references an undeclared state.
+    UnknownState { machine: String, state: String },
+
+    /// Tick duration must be greater than 0.
+    ZeroTickDuration,
+
+    /// Elapsed time was given to a clock with no tick duration.
+    NoTickDuration,
+
+    /// Configuration needs per-pulse state, which a clock arena lacks.
+    UnsupportedInArena { reason: String },
+
+    /// Configuration needs per-pulse state, which an atomic clock lacks.
+    UnsupportedInAtomic { reason: String },
+
+    /// No partition with this name exists on the clock.
+    PartitionNotFound { name: String },
+
+    /// Partition value is not below the partition's modulus.
+    ValueOutOfRange {
+        name: String,
+        value: u64,
+        modulus: u64,
+    },
+
+    /// Duration is more ticks than fit in a `u64`.
+    DurationOverflow { duration: String },
+
+    /// Ticks per partition unit must be greater than 0.
+    ZeroTicksPerUnit,
+
+    /// Clock already has a state machine with this name.
+    DuplicateMachine { name: String },
+
+    /// No state machine with this name is attached to the clock.
+    UnknownMachine { name: String },
+
+    /// State machine transition is triggered by an unknown pulse.
+    UnknownMachineTrigger { machine: String, trigger: String },
+
+    /// Stateful condition where only stateless ones are evaluated (quiet
+    /// windows, state machine guards and condition equivalence checks).
+    StatefulCondition { name: String },
 }
 
 impl fmt::Display for ClockError {
@@ -37,8 +119,16 @@ impl fmt::Display for ClockError {
             ClockError::MissingPartitionOrder => {
                 write!(f, "partition order must be specified explicitly")
             }
-            ClockError::UnknownPartition { pulse, partition } => {
-                write!(f, "pulse '{pulse}' references unknown partition '{partition}'")
+            ClockError::UnknownPartition {
+                pulse,
+                partition,
+                suggestion,
+            } => {
+                write!(f, "pulse '{pulse}' references unknown partition '{partition}'")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, " (did you mean '{suggestion}'?)"),
+                    None => Ok(()),
+                }
             }
             ClockError::ZeroConditionModulus { pulse, partition } => {
                 write!(
@@ -49,9 +139,288 @@ impl fmt::Display for ClockError {
             ClockError::InvalidTickRange { pulse, start, end } => {
                 write!(f, "pulse '{pulse}' has invalid tick range ({start}..={end})")
             }
+            ClockError::ZeroWindow { pulse } => {
+                write!(f, "pulse '{pulse}' has a condition window of 0 ticks")
+            }
+            ClockError::ZeroDelay { name } => {
+                write!(f, "pulse delay must be > 0 for '{name}'")
+            }
+            ClockError::UnknownTrigger { pulse, trigger } => {
+                write!(f, "pulse '{pulse}' is delayed after unknown pulse '{trigger}'")
+            }
+            ClockError::SnapshotMismatch { reason } => {
+                write!(f, "snapshot does not match clock: {reason}")
+            }
+            ClockError::InvalidState { reason } => {
+                write!(f, "invalid clock state: {reason}")
+            }
+            ClockError::AlarmInPast { name, tick } => {
+                write!(f, "alarm '{name}' targets tick {tick}, which has already passed")
+            }
+            ClockError::ZeroRunLength => {
+                write!(f, "run length must be > 0")
+            }
+            ClockError::ZeroDivider { name } => {
+                write!(f, "clock divider must be > 0 for '{name}'")
+            }
+            ClockError::DuplicateClock { name } => {
+                write!(f, "clock set already contains '{name}'")
+            }
+            ClockError::UnknownState { machine, state } => {
+                write!(f, "state machine '{machine}' has no state '{state}'")
+            }
+            ClockError::ZeroTickDuration => {
+                write!(f, "tick duration must be > 0")
+            }
+            ClockError::NoTickDuration => {
+                write!(f, "clock has no tick duration to convert elapsed time")
+            }
+            ClockError::UnsupportedInArena { reason } => {
+                write!(f, "clock arenas do not support {reason}")
+            }
+            ClockError::UnsupportedInAtomic { reason } => {
+                write!(f, "atomic clocks do not support {reason}")
+            }
+            ClockError::PartitionNotFound { name } => {
+                write!(f, "no partition named '{name}'")
+            }
+            ClockError::ValueOutOfRange {
+                name,
+                value,
+                modulus,
+            } => {
+                write!(
+                    f,
+                    "value {value} out of range for partition '{name}' (modulus {modulus})"
+                )
+            }
+            ClockError::DurationOverflow { duration } => {
+                write!(f, "duration '{duration}' does not fit in u64 ticks")
+            }
+            ClockError::ZeroTicksPerUnit => {
+                write!(f, "ticks per partition unit must be > 0")
+            }
+            ClockError::DuplicateMachine { name } => {
+                write!(f, "clock already has a state machine named '{name}'")
+            }
+            ClockError::UnknownMachine { name } => {
+                write!(f, "no state machine named '{name}'")
+            }
+            ClockError::UnknownMachineTrigger { machine, trigger } => {
+                write!(
+                    f,
+                    "state machine '{machine}' is triggered by unknown pulse '{trigger}'"
+                )
+            }
+            ClockError::StatefulCondition { name } => {
+                write!(
+                    f,
+                    "'{name}' has a stateful condition, which only pulses evaluate"
+                )
+            }
+        }
+    }
+}
+
+impl ClockError {
+    /// Stable numeric code for this error, for FFI and WASM callers.
+    ///
+    /// The hundreds digit is the [`ErrorCategory`]. A variant keeps its code
+    /// across releases and new variants get new codes.
+    pub fn code(&self) -> u32 {
+        match self {
+            ClockError::ZeroModulus { .. } => 101,
+            ClockError::MissingPartitionOrder => 102,
+            ClockError::PartitionNotFound { .. } => 103,
+            ClockError::ValueOutOfRange { .. } => 104,
+            ClockError::DurationOverflow { .. } => 105,
+            ClockError::ZeroTicksPerUnit => 106,
+
+            ClockError::ZeroPeriod { .. } => 201,
+            ClockError::UnknownPartition { .. } => 202,
+            ClockError::ZeroConditionModulus { .. } => 203,
+            ClockError::InvalidTickRange { .. } => 204,
+            ClockError::ZeroWindow { .. } => 205,
+            ClockError::ZeroDelay { .. } => 206,
+            ClockError::UnknownTrigger { .. } => 207,
+            ClockError::StatefulCondition { .. } => 208,
+
+            ClockError::SnapshotMismatch { .. } => 301,
+            ClockError::InvalidState { .. } => 302,
+
+            ClockError::AlarmInPast { .. } => 401,
+            ClockError::ZeroRunLength => 402,
+            ClockError::ZeroDivider { .. } => 403,
+            ClockError::DuplicateClock { .. } => 404,
+            ClockError::UnknownState { .. } => 405,
+            ClockError::ZeroTickDuration => 406,
+            ClockError::NoTickDuration => 407,
+            ClockError::UnsupportedInArena { .. } => 408,
+            ClockError::UnsupportedInAtomic { .. } => 409,
+            ClockError::DuplicateMachine { .. } => 410,
+            ClockError::UnknownMachine { .. } => 411,
+            ClockError::UnknownMachineTrigger { .. } => 412,
+        }
+    }
+
+    /// What kind of input the error is about.
+    pub fn category(&self) -> ErrorCategory {
+        match self.code() / 100 {
+            1 => ErrorCategory::Partition,
+            2 => ErrorCategory::Pulse,
+            3 => ErrorCategory::State,
+            _ => ErrorCategory::Clock,
         }
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for ClockError {}
+
+/// The candidate closest to `name` by edit distance, if close enough to be a
+/// likely typo: at most 2 edits, and fewer than the length of `name`.
+pub(crate) fn closest_name<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let limit = 2.min(name.chars().count().saturating_sub(1));
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| String::from(candidate))
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Broad group of a [`ClockError`], the hundreds digit of its code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// Partition layout or values (1xx).
+    Partition,
+    /// Pulse specs and conditions (2xx).
+    Pulse,
+    /// Snapshots and saved state that don't fit the clock (3xx).
+    State,
+    /// Alarms, run budgets, tick durations, clock sets, arenas, atomic
+    /// clocks or children, and state machines (4xx).
+    Clock,
+}
+
+impl ErrorCategory {
+    /// Lowercase name, e.g. `"partition"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Partition => "partition",
+            ErrorCategory::Pulse => "pulse",
+            ErrorCategory::State => "state",
+            ErrorCategory::Clock => "clock",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeSet;
+    use alloc::vec;
+
+    #[test]
+    fn codes_are_unique_and_categorized() {
+        let name = String::new;
+        let errors = vec![
+            ClockError::ZeroModulus { name: name() },
+            ClockError::MissingPartitionOrder,
+            ClockError::PartitionNotFound { name: name() },
+            ClockError::ValueOutOfRange {
+                name: name(),
+                value: 0,
+                modulus: 0,
+            },
+            ClockError::DurationOverflow { duration: name() },
+            ClockError::ZeroTicksPerUnit,
+            ClockError::ZeroPeriod { name: name() },
+            ClockError::UnknownPartition {
+                pulse: name(),
+                partition: name(),
+                suggestion: None,
+            },
+            ClockError::ZeroConditionModulus {
+                pulse: name(),
+                partition: name(),
+            },
+            ClockError::InvalidTickRange {
+                pulse: name(),
+                start: 0,
+                end: 0,
+            },
+            ClockError::ZeroWindow { pulse: name() },
+            ClockError::ZeroDelay { name: name() },
+            ClockError::UnknownTrigger {
+                pulse: name(),
+                trigger: name(),
+            },
+            ClockError::SnapshotMismatch { reason: name() },
+            ClockError::InvalidState { reason: name() },
+            ClockError::AlarmInPast {
+                name: name(),
+                tick: 0,
+            },
+            ClockError::ZeroRunLength,
+            ClockError::ZeroDivider { name: name() },
+            ClockError::DuplicateClock { name: name() },
+            ClockError::UnknownState {
+                machine: name(),
+                state: name(),
+            },
+            ClockError::ZeroTickDuration,
+            ClockError::NoTickDuration,
+            ClockError::UnsupportedInArena { reason: name() },
+            ClockError::UnsupportedInAtomic { reason: name() },
+            ClockError::DuplicateMachine { name: name() },
+            ClockError::UnknownMachine { name: name() },
+            ClockError::UnknownMachineTrigger {
+                machine: name(),
+                trigger: name(),
+            },
+            ClockError::StatefulCondition { name: name() },
+        ];
+        let codes: BTreeSet<u32> = errors.iter().map(ClockError::code).collect();
+        assert_eq!(codes.len(), errors.len());
+
+        let unknown = &errors[7];
+        assert_eq!(unknown.code(), 202);
+        assert_eq!(unknown.category(), ErrorCategory::Pulse);
+        assert_eq!(errors[15].category().as_str(), "clock");
+        assert_eq!(errors[26].code(), 412);
+        assert_eq!(errors[26].category(), ErrorCategory::Clock);
+        assert_eq!(errors[27].category(), ErrorCategory::Pulse);
+    }
+
+    #[test]
+    fn suggests_close_partition_names() {
+        let names = ["sec", "min", "hour", "minute"];
+        assert_eq!(closest_name("mim", names).as_deref(), Some("min"));
+        assert_eq!(closest_name("huor", names).as_deref(), Some("hour"));
+        assert_eq!(closest_name("minutes", names).as_deref(), Some("minute"));
+        assert_eq!(closest_name("day", names), None);
+        assert_eq!(closest_name("m", names), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
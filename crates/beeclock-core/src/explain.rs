@@ -0,0 +1,310 @@
+//! Condition evaluation explainer.
+//!
+//! Produces a tree mirroring a pulse condition, recording whether each node
+//! was met at the current tick and the partition values that decided it.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::calendar::date_of;
+use crate::partition::PartitionLookup;
+use crate::{ElapsedSince, PulseCondition, DAY_PARTITION};
+
+/// Evaluation trace of one pulse against the clock's current state.
+#[derive(Clone, Debug)]
+pub struct Explanation {
+    pub pulse: String,
+    pub tick: u64,
+    pub root: ExplainNode,
+}
+
+impl Explanation {
+    /// Whether the pulse's condition was met.
+    pub fn fired(&self) -> bool {
+        self.root.met
+    }
+}
+
+/// One node of an [`Explanation`] tree.
+#[derive(Clone, Debug)]
+pub struct ExplainNode {
+    /// What the node tests, e.g. `hour == 12`.
+    pub summary: String,
+    /// Whether the node was met.
+    pub met: bool,
+    /// Why, e.g. `hour is 11`.
+    pub reason: String,
+    pub children: Vec<ExplainNode>,
+}
+
+impl ExplainNode {
+    fn leaf(summary: String, met: bool, reason: String) -> Self {
+        Self {
+            summary,
+            met,
+            reason,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Build the explanation tree for `condition` at `tick`.
+pub(crate) fn explain_condition<P>(
+    condition: &PulseCondition,
+    tick: u64,
+    partitions: &P,
+) -> ExplainNode
+where
+    P: PartitionLookup + ?Sized,
+{
+    match condition {
+        PulseCondition::Every(period) => {
+            let reason = if tick == 0 {
+                String::from("tick 0 never fires")
+            } else if *period == 0 {
+                String::from("period is zero")
+            } else {
+                format!("tick {tick} % {period} = {}", tick % period)
+            };
+            ExplainNode::leaf(
+                format!("every {period} ticks"),
+                condition.evaluate(tick, partitions),
+                reason,
+            )
+        }
+
+        PulseCondition::PartitionEquals { name, value } => ExplainNode::leaf(
+            format!("{name} == {value}"),
+            condition.evaluate(tick, partitions),
+            describe_partition(partitions, name),
+        ),
+
+        PulseCondition::PartitionModulo {
+            name,
+            modulus,
+            remainder,
+        } => {
+            let reason = match partitions.value_of(name) {
+                Some(value) if *modulus != 0 => {
+                    format!(
+                        "{name} is {value} ({value} % {modulus} = {})",
+                        value % modulus
+                    )
+                }
+                Some(_) => String::from("modulus is zero"),
+                None => describe_partition(partitions, name),
+            };
+            ExplainNode::leaf(
+                format!("{name} % {modulus} == {remainder}"),
+                condition.evaluate(tick, partitions),
+                reason,
+            )
+        }
+
+        PulseCondition::TickRange { start, end } => ExplainNode::leaf(
+            format!("tick in {start}..={end}"),
+            condition.evaluate(tick, partitions),
+            format!("tick is {tick}"),
+        ),
+
+        PulseCondition::PartitionChanged { name } | PulseCondition::PartitionDecreased { name } => {
+            let summary = match condition {
+                PulseCondition::PartitionChanged { .. } => format!("{name} changed"),
+                _ => format!("{name} decreased"),
+            };
+            let reason = match (
+                partitions.previous_value_of(name),
+                partitions.value_of(name),
+            ) {
+                (Some(previous), Some(current)) => {
+                    format!("{name} went from {previous} to {current}")
+                }
+                (None, Some(current)) => format!("{name} is {current}, no previous tick"),
+                _ => describe_partition(partitions, name),
+            };
+            ExplainNode::leaf(summary, condition.evaluate(tick, partitions), reason)
+        }
+
+        PulseCondition::Not(inner) => {
+            let child = explain_condition(inner, tick, partitions);
+            ExplainNode {
+                summary: String::from("not"),
+                met: !child.met,
+                reason: format!("inner condition is {}", child.met),
+                children: alloc::vec![child],
+            }
+        }
+
+        PulseCondition::Jitter {
+            window,
+            seed,
+            inner,
+        } => {
+            let child = explain_condition(inner, tick, partitions);
+            ExplainNode {
+                summary: format!("jitter within {window} ticks (seed {seed})"),
+                met: child.met,
+                reason: String::from("fires at a pseudo-random offset after the inner condition"),
+                children: alloc::vec![child],
+            }
+        }
+
+        PulseCondition::RateLimit {
+            max_fires,
+            per_ticks,
+            inner,
+        } => {
+            let child = explain_condition(inner, tick, partitions);
+            ExplainNode {
+                summary: format!("at most {max_fires} per {per_ticks} ticks"),
+                met: child.met,
+                reason: String::from("limit applies to recent fires on a running clock"),
+                children: alloc::vec![child],
+            }
+        }
+
+        PulseCondition::Debounce { ticks, inner } => {
+            let child = explain_condition(inner, tick, partitions);
+            ExplainNode {
+                summary: format!("after {ticks} consecutive ticks"),
+                met: child.met,
+                reason: String::from("fires once per streak on a running clock"),
+                children: alloc::vec![child],
+            }
+        }
+
+        PulseCondition::ElapsedAtLeast { duration, since } => {
+            let since = match since {
+                ElapsedSince::Start => "start",
+                ElapsedSince::LastFire => "last fire",
+            };
+            ExplainNode::leaf(
+                format!("{duration} since {since}"),
+                condition.evaluate(tick, partitions),
+                String::from("measured in ticks on a running clock"),
+            )
+        }
+
+        PulseCondition::DateEquals { field, value } => {
+            let reason = match date_of(partitions) {
+                Some(date) => format!("date is {date}, {field} {}", field.of(date)),
+                None => describe_partition(partitions, DAY_PARTITION),
+            };
+            ExplainNode::leaf(
+                format!("date({field}) == {value}"),
+                condition.evaluate(tick, partitions),
+                reason,
+            )
+        }
+
+        PulseCondition::And(conditions) | PulseCondition::Or(conditions) => {
+            let children: Vec<ExplainNode> = conditions
+                .iter()
+                .map(|c| explain_condition(c, tick, partitions))
+                .collect();
+            let total = children.len();
+            let met_count = children.iter().filter(|c| c.met).count();
+            let (summary, met) = match condition {
+                PulseCondition::And(_) => {
+                    (format!("all of {total}"), total != 0 && met_count == total)
+                }
+                _ => (format!("any of {total}"), met_count != 0),
+            };
+            let reason = if total == 0 {
+                String::from("no sub-conditions")
+            } else {
+                format!("{met_count} of {total} met")
+            };
+            ExplainNode {
+                summary,
+                met,
+                reason,
+                children,
+            }
+        }
+    }
+}
+
+fn describe_partition<P>(partitions: &P, name: &str) -> String
+where
+    P: PartitionLookup + ?Sized,
+{
+    match partitions.value_of(name) {
+        Some(value) => format!("{name} is {value}"),
+        None => format!("partition '{name}' not found"),
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = if self.fired() {
+            "fires"
+        } else {
+            "does not fire"
+        };
+        writeln!(f, "pulse '{}' {verdict} at tick {}", self.pulse, self.tick)?;
+        write_node(f, &self.root, 1)
+    }
+}
+
+fn write_node(f: &mut fmt::Formatter<'_>, node: &ExplainNode, depth: usize) -> fmt::Result {
+    let mark = if node.met { '✓' } else { '✗' };
+    writeln!(
+        f,
+        "{:indent$}{mark} {} ({})",
+        "",
+        node.summary,
+        node.reason,
+        indent = depth * 2
+    )?;
+    for child in &node.children {
+        write_node(f, child, depth + 1)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Clock, PulseCondition};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn explains_failing_branch() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_when(
+                "top_of_minute",
+                PulseCondition::And(vec![
+                    PulseCondition::PartitionEquals {
+                        name: "sec".into(),
+                        value: 0,
+                    },
+                    PulseCondition::PartitionModulo {
+                        name: "min".into(),
+                        modulus: 2,
+                        remainder: 0,
+                    },
+                ]),
+            )
+            .build()
+            .unwrap();
+        for _ in 0..60 {
+            clock.tick();
+        }
+
+        let explanation = clock.explain("top_of_minute").unwrap();
+        assert!(!explanation.fired());
+        assert_eq!(explanation.root.reason, "1 of 2 met");
+        let failing = &explanation.root.children[1];
+        assert!(!failing.met);
+        assert_eq!(failing.reason, "min is 1 (1 % 2 = 1)");
+        assert!(explanation.to_string().contains("✗ min % 2 == 0"));
+
+        assert!(clock.explain("missing").is_none());
+    }
+}
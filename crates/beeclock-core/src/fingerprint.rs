@@ -0,0 +1,247 @@
+//! Stable configuration fingerprints.
+//!
+//! [`ClockConfig::config_hash`] and [`Clock::config_hash`] run a fixed
+//! binary encoding of the clock's shape (order, partition names and moduli,
+//! and pulses) through 64-bit FNV-1a. Unlike [`core::hash::Hash`], the
+//! result does not depend on the platform, process, or compiler version, so
+//! it can be persisted and compared between peers.
+//!
+//! The clock name, partition labels, quiet windows, run budget, tick
+//! duration, and overflow pulse name are not part of the shape and do not
+//! affect the hash.
+
+use crate::{Clock, ClockConfig, PartitionOrder, PulseCondition, PulseSpec};
+
+impl ClockConfig {
+    /// Stable fingerprint of the partitions, order, and pulses.
+    pub fn config_hash(&self) -> u64 {
+        let layout = self.partitions.iter().map(|p| (p.name.as_str(), p.modulus));
+        let scale = self.ticks_per_unit.unwrap_or(1);
+        fingerprint(self.order, layout, scale, &self.pulses)
+    }
+}
+
+impl Clock {
+    /// Stable fingerprint of the partitions, order, and pulses; equal to
+    /// `self.config().config_hash()`.
+    pub fn config_hash(&self) -> u64 {
+        fingerprint(
+            self.partition_order,
+            self.partitions.layout(),
+            self.ticks_per_unit(),
+            &self.pulses,
+        )
+    }
+}
+
+fn fingerprint<'a>(
+    order: PartitionOrder,
+    layout: impl ExactSizeIterator<Item = (&'a str, u64)>,
+    ticks_per_unit: u64,
+    pulses: &[PulseSpec],
+) -> u64 {
+    let mut fnv = Fnv::new();
+    fnv.u64(match order {
+        PartitionOrder::LeastSignificantFirst => 0,
+        PartitionOrder::MostSignificantFirst => 1,
+    });
+    fnv.u64(layout.len() as u64);
+    for (name, modulus) in layout {
+        fnv.str(name);
+        fnv.u64(modulus);
+    }
+    // Unscaled clocks keep the fingerprints they had before scaling existed.
+    if ticks_per_unit > 1 {
+        fnv.u64(ticks_per_unit);
+    }
+    fnv.u64(pulses.len() as u64);
+    for pulse in pulses {
+        fnv.str(&pulse.name);
+        fnv.condition(&pulse.condition);
+        fnv.option(pulse.expires_at_tick);
+        fnv.option(pulse.max_fires);
+        fnv.u64(pulse.notify_expiry as u64);
+        match &pulse.after {
+            Some(after) => {
+                fnv.u64(1);
+                fnv.str(&after.pulse);
+                fnv.u64(after.delay);
+            }
+            None => fnv.u64(0),
+        }
+    }
+    fnv.finish()
+}
+
+/// 64-bit FNV-1a over length-prefixed little-endian fields.
+pub(crate) struct Fnv(u64);
+
+impl Fnv {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes(value.as_bytes());
+    }
+
+    fn option(&mut self, value: Option<u64>) {
+        match value {
+            Some(value) => {
+                self.u64(1);
+                self.u64(value);
+            }
+            None => self.u64(0),
+        }
+    }
+
+    /// Variant tag, then fields in declaration order.
+    fn condition(&mut self, condition: &PulseCondition) {
+        match condition {
+            PulseCondition::Every(period) => {
+                self.u64(0);
+                self.u64(*period);
+            }
+            PulseCondition::PartitionEquals { name, value } => {
+                self.u64(1);
+                self.str(name);
+                self.u64(*value);
+            }
+            PulseCondition::PartitionModulo {
+                name,
+                modulus,
+                remainder,
+            } => {
+                self.u64(2);
+                self.str(name);
+                self.u64(*modulus);
+                self.u64(*remainder);
+            }
+            PulseCondition::TickRange { start, end } => {
+                self.u64(3);
+                self.u64(*start);
+                self.u64(*end);
+            }
+            PulseCondition::Not(inner) => {
+                self.u64(4);
+                self.condition(inner);
+            }
+            PulseCondition::And(conditions) => self.conditions(5, conditions),
+            PulseCondition::Or(conditions) => self.conditions(6, conditions),
+            PulseCondition::Jitter {
+                window,
+                seed,
+                inner,
+            } => {
+                self.u64(7);
+                self.u64(*window);
+                self.u64(*seed);
+                self.condition(inner);
+            }
+            PulseCondition::RateLimit {
+                max_fires,
+                per_ticks,
+                inner,
+            } => {
+                self.u64(8);
+                self.u64(*max_fires);
+                self.u64(*per_ticks);
+                self.condition(inner);
+            }
+            PulseCondition::Debounce { ticks, inner } => {
+                self.u64(9);
+                self.u64(*ticks);
+                self.condition(inner);
+            }
+            PulseCondition::PartitionChanged { name } => {
+                self.u64(10);
+                self.str(name);
+            }
+            PulseCondition::PartitionDecreased { name } => {
+                self.u64(11);
+                self.str(name);
+            }
+            PulseCondition::ElapsedAtLeast { duration, since } => {
+                self.u64(12);
+                self.u64(duration.units().count() as u64);
+                for (name, amount) in duration.units() {
+                    self.str(name);
+                    self.u64(amount);
+                }
+                self.u64(*since as u64);
+            }
+            PulseCondition::DateEquals { field, value } => {
+                self.u64(13);
+                self.u64(*field as u64);
+                self.u64(*value);
+            }
+        }
+    }
+
+    fn conditions(&mut self, tag: u64, conditions: &[PulseCondition]) {
+        self.u64(tag);
+        self.u64(conditions.len() as u64);
+        for condition in conditions {
+            self.condition(condition);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Clock, PartitionOrder, PartitionSpec};
+
+    fn clock() -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_every("minute", 60)
+            .pulse_when("half", "min == 30 && !changed(sec)".parse().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn hash_is_stable_and_follows_the_shape() {
+        let clock = clock();
+        // Changing this value breaks persisted fingerprints.
+        assert_eq!(clock.config_hash(), 0x67e2_b016_98d6_888e);
+        assert_eq!(clock.config().config_hash(), clock.config_hash());
+
+        let mut config = clock.config();
+        config.name = Some("renamed".into());
+        config.partitions[0] = PartitionSpec::new("sec", 60).unit("s");
+        config.run_for = Some(10);
+        assert_eq!(config.config_hash(), clock.config_hash());
+
+        let mut config = clock.config();
+        config.partitions[1].modulus = 61;
+        assert_ne!(config.config_hash(), clock.config_hash());
+        let mut config = clock.config();
+        config.order = PartitionOrder::MostSignificantFirst;
+        assert_ne!(config.config_hash(), clock.config_hash());
+        let mut config = clock.config();
+        config.pulses.swap(0, 1);
+        assert_ne!(config.config_hash(), clock.config_hash());
+        let mut config = clock.config();
+        config.pulses[1].condition = "min == 30 || !changed(sec)".parse().unwrap();
+        assert_ne!(config.config_hash(), clock.config_hash());
+    }
+}
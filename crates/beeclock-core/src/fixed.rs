@@ -0,0 +1,587 @@
+//! Fixed-size clock for targets with no heap at all.
+//!
+//! [`ClockFixed`] holds its partitions and pulses in arrays sized at compile
+//! time, names them with `&'static str`, and never allocates, so it is
+//! available without the `alloc` feature. It supports the pure conditions in
+//! [`FixedCondition`]; jitter, rate limits, delayed pulses, alarms, quiet
+//! windows and child clocks need [`Clock`](crate::Clock).
+//!
+//! ```rust
+//! use beeclock_core::{ClockFixed, FixedCondition, FixedPartition, FixedPulse, PartitionOrder};
+//!
+//! static TOP_OF_MINUTE: [FixedCondition; 2] = [
+//!     FixedCondition::PartitionEquals { name: "sec", value: 0 },
+//!     FixedCondition::TickRange { start: 1, end: u64::MAX },
+//! ];
+//!
+//! let mut clock = ClockFixed::new(
+//!     PartitionOrder::LeastSignificantFirst,
+//!     [FixedPartition::new("sec", 60), FixedPartition::new("min", 60)],
+//!     [
+//!         FixedPulse::new("second", FixedCondition::Every(1)),
+//!         FixedPulse::new("minute", FixedCondition::And(&TOP_OF_MINUTE)),
+//!     ],
+//! )
+//! .unwrap();
+//!
+//! let outcome = (0..60).map(|_| clock.tick()).last().unwrap();
+//! assert_eq!(outcome.values, [0, 1]);
+//! assert_eq!(outcome.fired, [true, true]);
+//! ```
+
+use core::fmt;
+
+use crate::{PartitionOrder, TickWidth};
+
+/// A partition of a [`ClockFixed`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedPartition {
+    pub name: &'static str,
+    pub modulus: u64,
+}
+
+impl FixedPartition {
+    pub const fn new(name: &'static str, modulus: u64) -> Self {
+        Self { name, modulus }
+    }
+}
+
+/// Pulse predicate for a [`ClockFixed`], the pure subset of
+/// [`PulseCondition`](crate::PulseCondition) with borrowed children.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FixedCondition {
+    /// Fire every N ticks (starting at tick N).
+    Every(u64),
+
+    /// Fire when a partition equals a specific value.
+    PartitionEquals { name: &'static str, value: u64 },
+
+    /// Fire when a partition value modulo `modulus` equals `remainder`.
+    PartitionModulo {
+        name: &'static str,
+        modulus: u64,
+        remainder: u64,
+    },
+
+    /// Fire when tick is within an inclusive range.
+    TickRange { start: u64, end: u64 },
+
+    /// Logical negation of another condition.
+    Not(&'static FixedCondition),
+
+    /// All conditions must be true.
+    And(&'static [FixedCondition]),
+
+    /// Any condition must be true.
+    Or(&'static [FixedCondition]),
+}
+
+/// A named pulse of a [`ClockFixed`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedPulse {
+    pub name: &'static str,
+    pub condition: FixedCondition,
+}
+
+impl FixedPulse {
+    pub const fn new(name: &'static str, condition: FixedCondition) -> Self {
+        Self { name, condition }
+    }
+}
+
+/// Errors from [`ClockFixed::new`], the subset of
+/// [`ClockError`](crate::ClockError) that applies, with borrowed names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FixedError {
+    /// Partition modulus must be greater than 0.
+    ZeroModulus { name: &'static str },
+
+    /// Pulse period must be greater than 0.
+    ZeroPeriod { name: &'static str },
+
+    /// Pulse references an unknown partition.
+    UnknownPartition {
+        pulse: &'static str,
+        partition: &'static str,
+    },
+
+    /// Pulse condition has zero modulus.
+    ZeroConditionModulus {
+        pulse: &'static str,
+        partition: &'static str,
+    },
+
+    /// Tick range is invalid (start > end).
+    InvalidTickRange {
+        pulse: &'static str,
+        start: u64,
+        end: u64,
+    },
+}
+
+impl fmt::Display for FixedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixedError::ZeroModulus { name } => {
+                write!(f, "partition modulus must be > 0 for '{name}'")
+            }
+            FixedError::ZeroPeriod { name } => {
+                write!(f, "pulse period must be > 0 for '{name}'")
+            }
+            FixedError::UnknownPartition { pulse, partition } => {
+                write!(
+                    f,
+                    "pulse '{pulse}' references unknown partition '{partition}'"
+                )
+            }
+            FixedError::ZeroConditionModulus { pulse, partition } => {
+                write!(
+                    f,
+                    "pulse '{pulse}' has zero modulus for partition '{partition}'"
+                )
+            }
+            FixedError::InvalidTickRange { pulse, start, end } => {
+                write!(f, "pulse '{pulse}' has invalid tick range {start}..={end}")
+            }
+        }
+    }
+}
+
+impl FixedError {
+    /// What went wrong, without the names; for `panic!` in const contexts,
+    /// which cannot format them.
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            FixedError::ZeroModulus { .. } => "partition modulus must be > 0",
+            FixedError::ZeroPeriod { .. } => "pulse period must be > 0",
+            FixedError::UnknownPartition { .. } => "pulse references an unknown partition",
+            FixedError::ZeroConditionModulus { .. } => "pulse condition has a zero modulus",
+            FixedError::InvalidTickRange { .. } => "pulse tick range has start > end",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FixedError {}
+
+/// Result of one [`ClockFixed::tick`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedOutcome<const P: usize, const Q: usize, T = u64> {
+    pub tick: T,
+    pub epoch: u64,
+    /// Partition values in configured order.
+    pub values: [u64; P],
+    /// Whether the tick counter wrapped into a new epoch.
+    pub overflowed: bool,
+    /// Whether each pulse fired, in configured order.
+    pub fired: [bool; Q],
+}
+
+impl<const P: usize, const Q: usize, T> FixedOutcome<P, Q, T> {
+    /// Indices of the pulses that fired.
+    pub fn fired_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.fired
+            .iter()
+            .enumerate()
+            .filter(|(_, &fired)| fired)
+            .map(|(idx, _)| idx)
+    }
+}
+
+/// A clock with `P` partitions and `Q` pulses fixed at compile time,
+/// counting ticks in `T` (see [`TickWidth`]).
+///
+/// Ticks like [`Clock`](crate::Clock) with the same conditions: the same
+/// partition carries, epochs, and fires at each tick.
+#[derive(Clone, Debug)]
+pub struct ClockFixed<const P: usize, const Q: usize, T = u64> {
+    order: PartitionOrder,
+    partitions: [FixedPartition; P],
+    pulses: [FixedPulse; Q],
+    values: [u64; P],
+    tick: T,
+    epoch: u64,
+}
+
+impl<const P: usize, const Q: usize> ClockFixed<P, Q> {
+    /// Validate the layout and create a clock at tick 0, counting in `u64`.
+    ///
+    /// A `const fn`, so a layout checked in a `const` item fails the build
+    /// rather than the first run.
+    pub const fn new(
+        order: PartitionOrder,
+        partitions: [FixedPartition; P],
+        pulses: [FixedPulse; Q],
+    ) -> Result<Self, FixedError> {
+        Self::with_width(order, partitions, pulses)
+    }
+}
+
+impl<const P: usize, const Q: usize, T: TickWidth> ClockFixed<P, Q, T> {
+    /// Like [`ClockFixed::new`], counting ticks in `T`.
+    pub const fn with_width(
+        order: PartitionOrder,
+        partitions: [FixedPartition; P],
+        pulses: [FixedPulse; Q],
+    ) -> Result<Self, FixedError> {
+        let mut idx = 0;
+        while idx < P {
+            if partitions[idx].modulus == 0 {
+                return Err(FixedError::ZeroModulus {
+                    name: partitions[idx].name,
+                });
+            }
+            idx += 1;
+        }
+        let mut idx = 0;
+        while idx < Q {
+            if let Err(err) =
+                validate_condition(&pulses[idx].condition, &partitions, pulses[idx].name)
+            {
+                return Err(err);
+            }
+            idx += 1;
+        }
+        Ok(Self {
+            order,
+            partitions,
+            pulses,
+            values: [0; P],
+            tick: T::ZERO,
+            epoch: 0,
+        })
+    }
+
+    /// Advance logical time by one tick.
+    pub fn tick(&mut self) -> FixedOutcome<P, Q, T> {
+        let (tick, overflowed) = self.tick.increment();
+        self.tick = tick;
+        if overflowed {
+            self.epoch = self.epoch.wrapping_add(1);
+        }
+        self.advance();
+
+        let mut fired = [false; Q];
+        for (fired, pulse) in fired.iter_mut().zip(&self.pulses) {
+            *fired = self.evaluate(&pulse.condition);
+        }
+        FixedOutcome {
+            tick: self.tick,
+            epoch: self.epoch,
+            values: self.values,
+            overflowed,
+            fired,
+        }
+    }
+
+    /// Get the current tick count.
+    pub fn tick_count(&self) -> T {
+        self.tick
+    }
+
+    /// Get the current epoch (increments on tick overflow).
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Partition values in configured order.
+    pub fn values(&self) -> &[u64; P] {
+        &self.values
+    }
+
+    /// Current value of the named partition.
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.position(name).map(|idx| self.values[idx])
+    }
+
+    pub fn order(&self) -> PartitionOrder {
+        self.order
+    }
+
+    pub fn partitions(&self) -> &[FixedPartition; P] {
+        &self.partitions
+    }
+
+    pub fn pulses(&self) -> &[FixedPulse; Q] {
+        &self.pulses
+    }
+
+    /// Index of the named pulse in [`FixedOutcome::fired`].
+    pub fn pulse_index(&self, name: &str) -> Option<usize> {
+        self.pulses.iter().position(|pulse| pulse.name == name)
+    }
+
+    /// Whether `condition` holds at the current tick.
+    pub fn evaluate(&self, condition: &FixedCondition) -> bool {
+        match *condition {
+            FixedCondition::Every(period) => {
+                self.tick != T::ZERO && self.tick.is_multiple_of(period)
+            }
+            FixedCondition::PartitionEquals { name, value } => self.get(name) == Some(value),
+            FixedCondition::PartitionModulo {
+                name,
+                modulus,
+                remainder,
+            } => self
+                .get(name)
+                .is_some_and(|value| modulus != 0 && value % modulus == remainder),
+            FixedCondition::TickRange { start, end } => self.tick.in_range(start, end),
+            FixedCondition::Not(inner) => !self.evaluate(inner),
+            FixedCondition::And(conditions) => {
+                !conditions.is_empty() && conditions.iter().all(|c| self.evaluate(c))
+            }
+            FixedCondition::Or(conditions) => conditions.iter().any(|c| self.evaluate(c)),
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.partitions.iter().position(|part| part.name == name)
+    }
+
+    /// Increment with carry in significance order.
+    fn advance(&mut self) {
+        let mut increment = |idx: usize| {
+            self.values[idx] += 1;
+            let carry = self.values[idx] >= self.partitions[idx].modulus;
+            if carry {
+                self.values[idx] = 0;
+            }
+            carry
+        };
+        match self.order {
+            PartitionOrder::LeastSignificantFirst => {
+                for idx in 0..P {
+                    if !increment(idx) {
+                        break;
+                    }
+                }
+            }
+            PartitionOrder::MostSignificantFirst => {
+                for idx in (0..P).rev() {
+                    if !increment(idx) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+const fn validate_condition(
+    condition: &FixedCondition,
+    partitions: &[FixedPartition],
+    pulse: &'static str,
+) -> Result<(), FixedError> {
+    match *condition {
+        FixedCondition::Every(0) => Err(FixedError::ZeroPeriod { name: pulse }),
+        FixedCondition::Every(_) => Ok(()),
+        FixedCondition::PartitionEquals { name, .. } => known_partition(name, partitions, pulse),
+        FixedCondition::PartitionModulo { name, modulus, .. } => {
+            if modulus == 0 {
+                return Err(FixedError::ZeroConditionModulus {
+                    pulse,
+                    partition: name,
+                });
+            }
+            known_partition(name, partitions, pulse)
+        }
+        FixedCondition::TickRange { start, end } => {
+            if start > end {
+                Err(FixedError::InvalidTickRange { pulse, start, end })
+            } else {
+                Ok(())
+            }
+        }
+        FixedCondition::Not(inner) => validate_condition(inner, partitions, pulse),
+        FixedCondition::And(conditions) | FixedCondition::Or(conditions) => {
+            let mut idx = 0;
+            while idx < conditions.len() {
+                if let Err(err) = validate_condition(&conditions[idx], partitions, pulse) {
+                    return Err(err);
+                }
+                idx += 1;
+            }
+            Ok(())
+        }
+    }
+}
+
+const fn known_partition(
+    name: &'static str,
+    partitions: &[FixedPartition],
+    pulse: &'static str,
+) -> Result<(), FixedError> {
+    let mut idx = 0;
+    while idx < partitions.len() {
+        if str_eq(partitions[idx].name, name) {
+            return Ok(());
+        }
+        idx += 1;
+    }
+    Err(FixedError::UnknownPartition {
+        pulse,
+        partition: name,
+    })
+}
+
+/// `a == b`, usable in `const fn`.
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut idx = 0;
+    while idx < a.len() {
+        if a[idx] != b[idx] {
+            return false;
+        }
+        idx += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, PulseCondition};
+    use alloc::boxed::Box;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    static ODD_MINUTE: FixedCondition = FixedCondition::PartitionModulo {
+        name: "min",
+        modulus: 2,
+        remainder: 1,
+    };
+    static QUARTER: [FixedCondition; 2] = [
+        FixedCondition::PartitionEquals {
+            name: "sec",
+            value: 15,
+        },
+        FixedCondition::Not(&ODD_MINUTE),
+    ];
+
+    #[test]
+    fn ticks_like_clock() {
+        let mut fixed = ClockFixed::new(
+            PartitionOrder::MostSignificantFirst,
+            [
+                FixedPartition::new("min", 3),
+                FixedPartition::new("sec", 60),
+            ],
+            [
+                FixedPulse::new("five", FixedCondition::Every(5)),
+                FixedPulse::new("quarter", FixedCondition::And(&QUARTER)),
+                FixedPulse::new(
+                    "window",
+                    FixedCondition::TickRange {
+                        start: 100,
+                        end: 120,
+                    },
+                ),
+            ],
+        )
+        .unwrap();
+        let mut clock = Clock::builder()
+            .most_significant_first()
+            .partition("min", 3)
+            .partition("sec", 60)
+            .pulse_every("five", 5)
+            .pulse_when(
+                "quarter",
+                PulseCondition::And(vec![
+                    PulseCondition::PartitionEquals {
+                        name: "sec".into(),
+                        value: 15,
+                    },
+                    PulseCondition::Not(Box::new(PulseCondition::PartitionModulo {
+                        name: "min".into(),
+                        modulus: 2,
+                        remainder: 1,
+                    })),
+                ]),
+            )
+            .pulse_when(
+                "window",
+                PulseCondition::TickRange {
+                    start: 100,
+                    end: 120,
+                },
+            )
+            .build()
+            .unwrap();
+
+        for _ in 0..400 {
+            let outcome = fixed.tick();
+            let expected = clock.tick();
+            assert_eq!(outcome.tick, expected.snapshot.tick);
+            assert!(outcome.values.iter().copied().eq(clock.snapshot().values()));
+            let names: Vec<&str> = outcome
+                .fired_indices()
+                .map(|idx| fixed.pulses()[idx].name)
+                .collect();
+            let expected: Vec<&str> = expected.pulses.iter().map(|p| p.name.as_str()).collect();
+            assert_eq!(names, expected);
+        }
+        assert_eq!(fixed.get("min"), Some(0));
+        assert_eq!(fixed.pulse_index("window"), Some(2));
+    }
+
+    #[test]
+    fn tick_width_sets_the_counter_type() {
+        const PULSES: [FixedPulse; 2] = [
+            FixedPulse::new("big", FixedCondition::Every(1 << 33)),
+            FixedPulse::new(
+                "late",
+                FixedCondition::TickRange {
+                    start: u32::MAX as u64,
+                    end: u64::MAX,
+                },
+            ),
+        ];
+        const SEC: [FixedPartition; 1] = [FixedPartition::new("sec", 60)];
+        let order = PartitionOrder::LeastSignificantFirst;
+
+        let mut narrow: ClockFixed<1, 2, u32> = ClockFixed::with_width(order, SEC, PULSES).unwrap();
+        narrow.tick = u32::MAX - 1;
+        let outcome = narrow.tick();
+        assert_eq!((outcome.tick, outcome.fired), (u32::MAX, [false, true]));
+        let outcome = narrow.tick();
+        assert_eq!((outcome.tick, outcome.epoch), (0, 1));
+        assert!(outcome.overflowed);
+        assert_eq!(outcome.fired, [false, false]);
+
+        let mut wide: ClockFixed<1, 2, u128> = ClockFixed::with_width(order, SEC, PULSES).unwrap();
+        wide.tick = u64::MAX as u128;
+        let outcome = wide.tick();
+        assert_eq!((outcome.tick, outcome.epoch), (1 << 64, 0));
+        assert_eq!(outcome.fired, [true, false]);
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        const SEC: [FixedPartition; 1] = [FixedPartition::new("sec", 60)];
+        let new = |pulse| ClockFixed::new(PartitionOrder::LeastSignificantFirst, SEC, [pulse]);
+        assert_eq!(
+            new(FixedPulse::new("p", FixedCondition::Every(0))).unwrap_err(),
+            FixedError::ZeroPeriod { name: "p" }
+        );
+        assert_eq!(
+            new(FixedPulse::new("p", FixedCondition::Not(&ODD_MINUTE))).unwrap_err(),
+            FixedError::UnknownPartition {
+                pulse: "p",
+                partition: "min"
+            }
+        );
+        assert!(ClockFixed::<1, 0>::new(
+            PartitionOrder::LeastSignificantFirst,
+            [FixedPartition::new("sec", 0)],
+            []
+        )
+        .is_err());
+
+        const CHECKED: Result<ClockFixed<1, 0>, FixedError> =
+            ClockFixed::new(PartitionOrder::LeastSignificantFirst, SEC, []);
+        assert!(CHECKED.is_ok());
+    }
+}
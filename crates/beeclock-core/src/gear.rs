@@ -0,0 +1,156 @@
+//! Child clocks geared to a parent clock.
+//!
+//! A child is ticked from inside its parent's tick, either once every `n`
+//! parent ticks or whenever a named parent pulse fires. Children are runtime
+//! attachments and are not part of [`ClockConfig`](crate::ClockConfig).
+
+use alloc::string::String;
+use alloc::sync::Arc;
+
+use crate::{Clock, TickBuffer};
+
+/// When an attached child clock ticks, relative to its parent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Gear {
+    /// Tick once every `n` parent ticks.
+    Every(u64),
+    /// Tick whenever the named parent pulse fires.
+    OnPulse(String),
+}
+
+/// A child clock attached to a parent.
+#[derive(Debug)]
+pub(crate) struct ChildClock {
+    pub name: Arc<str>,
+    pub clock: Clock,
+    pub gear: Gear,
+    /// Parent index of the [`Gear::OnPulse`] trigger (`None` once pruned).
+    pub trigger: Option<usize>,
+    /// Reused by [`Clock::tick_into`] so cascading stays allocation-free.
+    pub buffer: TickBuffer,
+}
+
+impl ChildClock {
+    pub fn new(name: String, clock: Clock, gear: Gear, trigger: Option<usize>) -> Self {
+        let buffer = clock.tick_buffer();
+        Self {
+            name: Arc::from(name),
+            clock,
+            gear,
+            trigger,
+            buffer,
+        }
+    }
+
+    /// Whether the child ticks on this parent tick, given the parent pulses
+    /// (by index) that just fired.
+    #[inline]
+    pub fn is_due(&self, tick: u64, fired: &[usize]) -> bool {
+        match self.gear {
+            Gear::Every(n) => tick.is_multiple_of(n),
+            Gear::OnPulse(_) => self.trigger.is_some_and(|index| fired.contains(&index)),
+        }
+    }
+
+    /// Copy for a detached parent.
+    pub fn detached(&self) -> Self {
+        Self {
+            name: Arc::clone(&self.name),
+            clock: self.clock.detached(),
+            gear: self.gear.clone(),
+            trigger: self.trigger,
+            buffer: self.buffer.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClockError;
+    use alloc::vec::Vec;
+
+    fn world() -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("min", 60)
+            .partition("hour", 24)
+            .build()
+            .unwrap()
+    }
+
+    fn real() -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("frame", 30)
+            .partition("sec", 60)
+            .pulse_every("second", 30)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn child_cascades_on_every_n_parent_ticks() {
+        let mut clock = real();
+        clock
+            .attach_child("world", world(), Gear::Every(30))
+            .unwrap();
+
+        let mut child_ticks = Vec::new();
+        for _ in 0..90 {
+            let outcome = clock.tick();
+            for child in &outcome.children {
+                assert_eq!(&*child.clock, "world");
+                child_ticks.push(outcome.snapshot.tick);
+            }
+        }
+        assert_eq!(child_ticks, [30, 60, 90]);
+        assert_eq!(clock.child("world").unwrap().snapshot().get("min"), 3);
+    }
+
+    #[test]
+    fn child_follows_parent_pulse() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("frame", 30)
+            .pulse_every("second", 30)
+            .child("world", world(), Gear::OnPulse("second".into()))
+            .build()
+            .unwrap();
+
+        let mut buffer = clock.tick_buffer();
+        for _ in 0..60 {
+            clock.tick_into(&mut buffer);
+        }
+        assert_eq!(clock.child("world").unwrap().tick_count(), 2);
+
+        let sim = clock.simulate(30);
+        assert_eq!(sim.pulse("second").unwrap().first(), Some(90));
+        assert_eq!(clock.child("world").unwrap().tick_count(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_gears() {
+        let mut clock = real();
+        assert!(matches!(
+            clock.attach_child("w", world(), Gear::Every(0)),
+            Err(ClockError::ZeroDivider { .. })
+        ));
+        assert!(matches!(
+            clock.attach_child("w", world(), Gear::OnPulse("minute".into())),
+            Err(ClockError::UnknownTrigger { .. })
+        ));
+        clock.attach_child("w", world(), Gear::Every(1)).unwrap();
+        assert!(matches!(
+            clock.attach_child("w", world(), Gear::Every(1)),
+            Err(ClockError::DuplicateClock { .. })
+        ));
+        assert!(clock.detach_child("w").is_some());
+        assert!(clock.child("w").is_none());
+    }
+}
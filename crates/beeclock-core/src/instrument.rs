@@ -0,0 +1,84 @@
+//! `metrics` facade integration (requires the `metrics` feature).
+//!
+//! Emitted on every [`Clock::tick`](crate::Clock::tick):
+//!
+//! - `beeclock_ticks_total` (counter)
+//! - `beeclock_pulses_fired_total{pulse}` (counter, overflow pulse included)
+//! - `beeclock_partition_value{partition}` (gauge)
+//! - `beeclock_pulses_per_tick` (histogram)
+
+use metrics::{counter, gauge, histogram};
+
+use crate::TickOutcome;
+
+/// Record one tick outcome with the installed metrics recorder.
+pub(crate) fn record_tick(outcome: &TickOutcome) {
+    counter!("beeclock_ticks_total").increment(1);
+
+    for pulse in &outcome.pulses {
+        counter!("beeclock_pulses_fired_total", "pulse" => pulse.name.clone()).increment(1);
+    }
+
+    for part in &outcome.snapshot.partitions {
+        gauge!("beeclock_partition_value", "partition" => part.name.clone()).set(part.value as f64);
+    }
+
+    histogram!("beeclock_pulses_per_tick").record(outcome.pulses.len() as f64);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    use crate::Clock;
+
+    #[test]
+    fn records_ticks_pulses_partitions_and_histogram() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("beat", 2)
+            .build()
+            .unwrap();
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            clock.tick();
+            clock.tick();
+        });
+
+        let mut metrics: Vec<(String, Vec<String>, DebugValue)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                let labels = key
+                    .labels()
+                    .map(|label| format!("{}={}", label.key(), label.value()))
+                    .collect();
+                (key.name().to_string(), labels, value)
+            })
+            .collect();
+        metrics.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        let [partition, fired, per_tick, ticks] = metrics.as_slice() else {
+            panic!("expected four metrics, got {metrics:?}");
+        };
+        assert_eq!(partition.0, "beeclock_partition_value");
+        assert_eq!(partition.1, ["partition=sec"]);
+        assert!(matches!(&partition.2, DebugValue::Gauge(value) if value.0 == 2.0));
+        assert_eq!(fired.0, "beeclock_pulses_fired_total");
+        assert_eq!(fired.1, ["pulse=beat"]);
+        assert_eq!(fired.2, DebugValue::Counter(1));
+        assert_eq!(per_tick.0, "beeclock_pulses_per_tick");
+        assert!(per_tick.1.is_empty());
+        assert!(matches!(
+            &per_tick.2,
+            DebugValue::Histogram(values) if values.iter().map(|v| v.0).eq([0.0, 1.0])
+        ));
+        assert_eq!(ticks.0, "beeclock_ticks_total");
+        assert!(ticks.1.is_empty());
+        assert_eq!(ticks.2, DebugValue::Counter(2));
+    }
+}
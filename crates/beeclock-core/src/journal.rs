@@ -0,0 +1,344 @@
+//! Write-ahead tick journal for crash recovery (std only).
+//!
+//! A [`TickJournal`] appends one short record per tick and syncs it to
+//! stable storage every few records. After a crash, rebuild the clock the
+//! journal was started from and call [`Clock::recover`]: it steps the clock
+//! through every record that reached the disk, without firing anything, so
+//! the service resumes at the last durable tick instead of tick 0.
+//!
+//! Append each outcome before acting on its pulses. A tick whose record is
+//! durable is then never fired again; with [`TickJournal::sync_every`] above
+//! 1, ticks since the last sync may be lost and fired a second time.
+//!
+//! ## Format
+//!
+//! Integers are LEB128 varints, as in the [`TickRecorder`](crate::TickRecorder)
+//! format.
+//!
+//! ```text
+//! header: "BEEW" version config_hash tick epoch
+//! record: tick epoch fired_count check
+//! ```
+//!
+//! `config_hash` is [`Clock::config_hash`] and `tick epoch` is where the
+//! clock stood when the journal was started. `check` is one byte of FNV-1a
+//! over the rest of the record; recovery stops at the first record that is
+//! cut short or fails its check, since only the tail of a journal can be
+//! torn by a crash.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+
+use crate::fingerprint::Fnv;
+use crate::replay::{invalid_data, invalid_input, read_varint, read_varint_or_eof, write_varint};
+use crate::{Clock, TickOutcome};
+
+const MAGIC: &[u8; 4] = b"BEEW";
+const VERSION: u64 = 1;
+
+/// A writer whose data can be forced to stable storage.
+pub trait SyncWrite: Write {
+    /// Flush buffered data and wait until it is durable.
+    fn sync(&mut self) -> io::Result<()>;
+}
+
+impl SyncWrite for File {
+    fn sync(&mut self) -> io::Result<()> {
+        self.sync_data()
+    }
+}
+
+impl<W: SyncWrite> SyncWrite for BufWriter<W> {
+    fn sync(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.get_mut().sync()
+    }
+}
+
+impl<W: SyncWrite + ?Sized> SyncWrite for &mut W {
+    fn sync(&mut self) -> io::Result<()> {
+        (**self).sync()
+    }
+}
+
+/// In-memory journals are durable as soon as they are written.
+impl SyncWrite for Vec<u8> {
+    fn sync(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Appends a record per tick, syncing on a configurable interval.
+///
+/// ```
+/// use beeclock_core::{Clock, TickJournal};
+///
+/// let config = Clock::builder().pulse_every("flush", 3).build().unwrap().config();
+/// let mut clock = config.clone().build().unwrap();
+/// let mut journal = TickJournal::new(Vec::new(), &clock).unwrap();
+/// for _ in 0..10 {
+///     let outcome = clock.tick();
+///     journal.append(&outcome).unwrap();
+///     // ...act on outcome.pulses...
+/// }
+///
+/// // After a restart, pick up at tick 10 rather than firing "flush" again.
+/// let mut restarted = config.build().unwrap();
+/// let recovered = restarted.recover(journal.into_inner().as_slice()).unwrap();
+/// assert_eq!((recovered.ticks, restarted.tick_count()), (10, 10));
+/// let next = restarted.iter_ticks().find(|o| !o.pulses.is_empty()).unwrap();
+/// assert_eq!(next.snapshot.tick, 12);
+/// ```
+#[derive(Debug)]
+pub struct TickJournal<W: SyncWrite> {
+    writer: W,
+    sync_every: u64,
+    unsynced: u64,
+    record: Vec<u8>,
+}
+
+impl<W: SyncWrite> TickJournal<W> {
+    /// Start a journal for `clock` at its current position, writing and
+    /// syncing the header immediately. Every record is synced until
+    /// [`sync_every`](Self::sync_every) says otherwise.
+    pub fn new(mut writer: W, clock: &Clock) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        write_varint(&mut writer, VERSION)?;
+        write_varint(&mut writer, clock.config_hash())?;
+        write_varint(&mut writer, clock.tick_count())?;
+        write_varint(&mut writer, clock.epoch())?;
+        writer.sync()?;
+        Ok(Self::resume(writer))
+    }
+
+    /// Continue appending to a journal whose header is already written,
+    /// with `writer` positioned at the end of its last valid record (see
+    /// [`Recovered::valid_len`]).
+    pub fn resume(writer: W) -> Self {
+        Self {
+            writer,
+            sync_every: 1,
+            unsynced: 0,
+            record: Vec::new(),
+        }
+    }
+
+    /// Sync after every `records` appends instead of after each one.
+    ///
+    /// Fewer syncs are faster, but up to `records - 1` ticks may be lost in
+    /// a crash and fired again after recovery.
+    pub fn sync_every(mut self, records: u64) -> Self {
+        self.sync_every = records.max(1);
+        self
+    }
+
+    /// Append the record for `outcome`, syncing if the interval is reached.
+    pub fn append(&mut self, outcome: &TickOutcome) -> io::Result<()> {
+        let snapshot = &outcome.snapshot;
+        self.record.clear();
+        write_varint(&mut self.record, snapshot.tick)?;
+        write_varint(&mut self.record, snapshot.epoch)?;
+        write_varint(&mut self.record, outcome.pulses.len() as u64)?;
+        self.record.push(check(&self.record));
+        self.writer.write_all(&self.record)?;
+
+        self.unsynced += 1;
+        if self.unsynced >= self.sync_every {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    /// Make every appended record durable now.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.writer.sync()?;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    /// Records appended since the last sync.
+    pub fn unsynced(&self) -> u64 {
+        self.unsynced
+    }
+
+    /// Unwrap the underlying writer, without syncing it.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// What [`Clock::recover`] replayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Recovered {
+    /// Ticks stepped through, one per intact record.
+    pub ticks: u64,
+    /// Length in bytes of the header and intact records. Truncate the
+    /// journal to this length before [resuming](TickJournal::resume) it.
+    pub valid_len: u64,
+}
+
+impl Clock {
+    /// Replay a journal written by [`TickJournal`] onto this clock, which
+    /// must stand where the journal was started.
+    ///
+    /// Each intact record advances the clock one tick, as [`Clock::tick`]
+    /// would but without notifying subscribers or recording metrics, so
+    /// stateful pulses, alarms, and child clocks all end up where they were.
+    /// Fails with [`io::ErrorKind::InvalidInput`] if the journal belongs to a
+    /// differently shaped clock or position, and with
+    /// [`io::ErrorKind::InvalidData`] if the clock diverges from a record.
+    pub fn recover<R: Read>(&mut self, mut journal: R) -> io::Result<Recovered> {
+        let mut bytes = Vec::new();
+        journal.read_to_end(&mut bytes)?;
+        let mut rest = bytes.as_slice();
+
+        let mut magic = [0u8; 4];
+        rest.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a beeclock write-ahead journal"));
+        }
+        if read_varint(&mut rest)? != VERSION {
+            return Err(invalid_data("unsupported journal version"));
+        }
+        if read_varint(&mut rest)? != self.config_hash() {
+            return Err(invalid_input("journal was written by a different clock"));
+        }
+        let start = (read_varint(&mut rest)?, read_varint(&mut rest)?);
+        if start != (self.tick, self.epoch) {
+            return Err(invalid_input("clock is not where the journal started"));
+        }
+
+        let mut recovered = Recovered {
+            ticks: 0,
+            valid_len: (bytes.len() - rest.len()) as u64,
+        };
+        while let Some((tick, epoch, fired)) = next_record(&mut rest) {
+            let outcome = self.step();
+            if (outcome.snapshot.tick, outcome.snapshot.epoch) != (tick, epoch)
+                || outcome.pulses.len() as u64 != fired
+            {
+                return Err(invalid_data("clock diverged from the journal"));
+            }
+            recovered.ticks += 1;
+            recovered.valid_len = (bytes.len() - rest.len()) as u64;
+        }
+        Ok(recovered)
+    }
+}
+
+/// Read one record, or `None` at the end of the intact records.
+fn next_record(rest: &mut &[u8]) -> Option<(u64, u64, u64)> {
+    let start = *rest;
+    let tick = read_varint_or_eof(rest).ok()??;
+    let epoch = read_varint(rest).ok()?;
+    let fired = read_varint(rest).ok()?;
+    let body = &start[..start.len() - rest.len()];
+    let (&stored, tail) = rest.split_first()?;
+    if stored != check(body) {
+        return None;
+    }
+    *rest = tail;
+    Some((tick, epoch, fired))
+}
+
+fn check(record: &[u8]) -> u8 {
+    let mut fnv = Fnv::new();
+    fnv.bytes(record);
+    fnv.finish() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClockConfig, PulseCondition};
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom};
+
+    fn config() -> ClockConfig {
+        Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("even", 2)
+            .pulse_when(
+                "steady",
+                PulseCondition::Debounce {
+                    ticks: 3,
+                    inner: Box::new(PulseCondition::Every(1)),
+                },
+            )
+            .build()
+            .unwrap()
+            .config()
+    }
+
+    fn names(outcome: &TickOutcome) -> Vec<String> {
+        outcome.pulses.iter().map(|p| p.name.clone()).collect()
+    }
+
+    #[test]
+    fn recovers_to_the_last_intact_record_and_resumes() {
+        let path = std::env::temp_dir().join(format!("beeclock-wal-{}", std::process::id()));
+        let mut clock = config().build().unwrap();
+        let mut journal = TickJournal::new(File::create(&path).unwrap(), &clock)
+            .unwrap()
+            .sync_every(4);
+        for _ in 0..7 {
+            journal.append(&clock.tick()).unwrap();
+        }
+        assert_eq!(journal.unsynced(), 3);
+        drop(journal);
+
+        // A crash tears the last record in half.
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - 2).unwrap();
+
+        let mut restarted = config().build().unwrap();
+        let recovered = restarted.recover(File::open(&path).unwrap()).unwrap();
+        assert_eq!(recovered.ticks, 6);
+        assert_eq!(restarted.tick_count(), 6);
+
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(recovered.valid_len).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        let mut journal = TickJournal::resume(file);
+        let mut reference = config().build().unwrap();
+        for _ in 0..6 {
+            reference.tick();
+        }
+        for _ in 0..5 {
+            let outcome = restarted.tick();
+            assert_eq!(names(&outcome), names(&reference.tick()));
+            journal.append(&outcome).unwrap();
+        }
+
+        let mut again = config().build().unwrap();
+        let recovered = again.recover(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recovered.ticks, 11);
+        assert_eq!(again.save_state().tick(), 11);
+        assert_eq!(names(&again.tick()), names(&restarted.tick()));
+    }
+
+    #[test]
+    fn rejects_a_journal_for_another_clock() {
+        let mut clock = config().build().unwrap();
+        let mut journal = TickJournal::new(Vec::new(), &clock).unwrap();
+        for _ in 0..3 {
+            journal.append(&clock.tick()).unwrap();
+        }
+        let bytes = journal.into_inner();
+
+        let mut other = Clock::default();
+        let err = other.recover(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = clock.recover(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let mut corrupt = bytes.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xff;
+        let mut fresh = config().build().unwrap();
+        assert_eq!(fresh.recover(corrupt.as_slice()).unwrap().ticks, 2);
+    }
+}
@@ -12,7 +12,7 @@
 //!
 //! - **Partitioned Time**: Mixed-radix counter (e.g., 60-60-24 for time)
 //! - **Predicate Pulses**: Fire events based on tick conditions
-//! - **No Allocation After Init**: All memory allocated upfront
+//! - **No Allocation After Init**: `tick_into` reuses caller-owned buffers
 //! - **no_std Compatible**: Works in embedded/WASM environments
 //!
 //! ## Example
@@ -40,24 +40,194 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod fixed;
+mod macros;
+mod order;
+mod shared;
+mod width;
+#[cfg(feature = "postcard")]
+pub mod wire;
+
+pub use fixed::{ClockFixed, FixedCondition, FixedError, FixedOutcome, FixedPartition, FixedPulse};
+pub use order::PartitionOrder;
+#[cfg(feature = "critical-section")]
+pub use shared::GlobalSection;
+pub use shared::{CriticalSection, SharedClock};
+pub use width::TickWidth;
+
+#[cfg(feature = "alloc")]
+mod ahead;
+#[cfg(feature = "alloc")]
+mod alarm;
+#[cfg(feature = "alloc")]
+mod arena;
+#[cfg(feature = "alloc")]
+mod arith;
+#[cfg(feature = "alloc")]
+mod batch;
+#[cfg(feature = "alloc")]
+mod calendar;
+#[cfg(feature = "alloc")]
 mod clock;
+#[cfg(feature = "alloc")]
 mod condition;
+#[cfg(feature = "alloc")]
+mod config;
+#[cfg(feature = "alloc")]
+mod dsl;
+#[cfg(feature = "alloc")]
+mod driver;
+#[cfg(feature = "alloc")]
+mod duration;
+#[cfg(feature = "alloc")]
+mod epoch;
+#[cfg(feature = "alloc")]
 mod error;
+#[cfg(feature = "alloc")]
+mod explain;
+#[cfg(feature = "alloc")]
+mod fingerprint;
+#[cfg(feature = "alloc")]
+mod gear;
+#[cfg(feature = "alloc")]
+mod lookahead;
+#[cfg(feature = "alloc")]
 mod partition;
+#[cfg(feature = "alloc")]
+mod pending;
+#[cfg(feature = "alloc")]
+mod profile;
+#[cfg(feature = "prost")]
+pub mod proto;
+#[cfg(feature = "alloc")]
 mod pulse;
+#[cfg(feature = "alloc")]
+mod quiet;
+#[cfg(feature = "alloc")]
+pub mod raw;
+#[cfg(feature = "alloc")]
+mod set;
+#[cfg(feature = "alloc")]
+mod sim;
+#[cfg(feature = "alloc")]
 mod snapshot;
+#[cfg(feature = "alloc")]
+mod state;
+#[cfg(feature = "alloc")]
+mod state_machine;
+#[cfg(feature = "alloc")]
+mod stateful;
+#[cfg(feature = "alloc")]
+mod timeline;
+#[cfg(feature = "alloc")]
+mod validate;
+#[cfg(feature = "alloc")]
+mod wheel;
+
+#[cfg(feature = "alloc")]
+pub use alarm::AlarmTarget;
+#[cfg(feature = "alloc")]
+pub use arena::ClockArena;
+#[cfg(feature = "alloc")]
+pub use calendar::{days_in_month, is_leap_year, CivilDate, DateField, Weekday, DAY_PARTITION};
+#[cfg(feature = "alloc")]
+pub use clock::{Clock, ClockBuilder, Ticks, OVERFLOW_PULSE};
+#[cfg(feature = "alloc")]
+pub use condition::{ElapsedSince, EvalContext, PulseCondition};
+#[cfg(feature = "alloc")]
+pub use config::ClockConfig;
+#[cfg(feature = "alloc")]
+pub use dsl::ConditionParseError;
+#[cfg(feature = "alloc")]
+pub use driver::{drive, TickSource};
+#[cfg(feature = "embassy")]
+pub use driver::drive_pulses;
+#[cfg(feature = "alloc")]
+pub use duration::ClockDuration;
+#[cfg(feature = "alloc")]
+pub use epoch::EpochChange;
+#[cfg(feature = "alloc")]
+pub use error::{ClockError, ErrorCategory};
+#[cfg(feature = "alloc")]
+pub use explain::{ExplainNode, Explanation};
+#[cfg(feature = "alloc")]
+pub use gear::Gear;
+#[cfg(feature = "alloc")]
+pub use partition::{PartitionInfo, PartitionMeta, PartitionSpec, PartitionState};
+#[cfg(feature = "alloc")]
+pub use profile::EvalCost;
+#[cfg(feature = "alloc")]
+pub use pulse::{PulseDelay, PulseFired, PulseList, PulseSpec};
+#[cfg(feature = "alloc")]
+pub use quiet::{QuietReport, QuietWindow};
+#[cfg(feature = "alloc")]
+pub use set::{ClockSet, TaggedOutcome};
+#[cfg(feature = "alloc")]
+pub use sim::{PulseSchedule, ScheduleFormat, ScheduleReport, COUNT_FIRES_MAX_STEPS};
+#[cfg(feature = "alloc")]
+pub use snapshot::{ClockSnapshot, Skipped, TickBuffer, TickOutcome};
+#[cfg(feature = "alloc")]
+pub use state::ClockState;
+#[cfg(feature = "alloc")]
+pub use state_machine::{StateMachine, Transition};
 
-pub use clock::{Clock, ClockBuilder};
-pub use condition::PulseCondition;
-pub use error::ClockError;
-pub use partition::{PartitionOrder, PartitionSpec, PartitionState};
-pub use pulse::{PulseFired, PulseSpec};
-pub use snapshot::{ClockSnapshot, TickOutcome};
+#[cfg(feature = "rkyv")]
+pub use order::ArchivedPartitionOrder;
+#[cfg(feature = "rkyv")]
+pub use partition::{ArchivedPartitionMeta, ArchivedPartitionState};
+#[cfg(feature = "rkyv")]
+pub use pulse::ArchivedPulseFired;
+#[cfg(feature = "rkyv")]
+pub use quiet::ArchivedQuietReport;
+#[cfg(feature = "rkyv")]
+pub use set::ArchivedTaggedOutcome;
+#[cfg(feature = "rkyv")]
+pub use snapshot::{ArchivedClockSnapshot, ArchivedTickOutcome};
 
+#[cfg(feature = "std")]
+mod atomic;
+#[cfg(feature = "std")]
+mod journal;
+#[cfg(feature = "std")]
+mod replay;
+#[cfg(feature = "std")]
+mod router;
+#[cfg(feature = "std")]
+mod sink;
 #[cfg(feature = "std")]
 mod subscriber;
+#[cfg(feature = "std")]
+mod watchdog;
 
 #[cfg(feature = "std")]
-pub use subscriber::Subscriber;
+pub use atomic::AtomicClock;
+#[cfg(feature = "std")]
+pub use journal::{Recovered, SyncWrite, TickJournal};
+#[cfg(feature = "std")]
+pub use replay::{Divergence, TickReader, TickRecorder};
+#[cfg(feature = "std")]
+pub use router::{PulseRouter, RouteId, DEFAULT_ROUTE_CAPACITY};
+#[cfg(feature = "std")]
+pub use sink::JsonLinesSink;
+#[cfg(feature = "std")]
+pub use subscriber::{OutcomeSink, PartitionChange, PulseFilter, SinkStatus, Subscriber};
+#[cfg(feature = "std")]
+pub use watchdog::{Watchdog, WatchdogHealth, STALLED_PULSE};
+
+#[cfg(feature = "metrics")]
+mod instrument;
+
+#[cfg(feature = "stream")]
+mod stream;
+
+#[cfg(feature = "stream")]
+pub use stream::TickStream;
+
+#[cfg(any(test, feature = "proptest"))]
+mod arbitrary;
+
+#[cfg(feature = "proptest")]
+pub use arbitrary::ConditionParams;
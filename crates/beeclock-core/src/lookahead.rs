@@ -0,0 +1,262 @@
+//! Sleep-until computation: how far ahead the next event could be.
+
+use crate::gear::Gear;
+use crate::{Clock, PulseCondition};
+
+impl Clock {
+    /// Ticks until the next tick on which anything could fire: a pulse, an
+    /// alarm, an expiry notice, `__overflow__`, `__halted__`, or a geared
+    /// child's event. `Some(1)` means the very next tick; `None` means
+    /// nothing ever will (always the case once the clock has halted).
+    ///
+    /// This is a lower bound: exact for tick, period, and partition
+    /// conditions and `Or`, while `And` reports the latest of its parts'
+    /// next matches, and `Not` and `Jitter` always report 1. Nothing fires
+    /// sooner, so a driver can sleep through the silent ticks with
+    /// `clock.observe(clock.tick_count() + n - 1)` and then ask again.
+    pub fn ticks_until_next_event(&self) -> Option<u64> {
+        if self.is_halted() {
+            return None;
+        }
+        let overflow = Some((u64::MAX - self.tick_count()).saturating_add(1));
+        let halt = self
+            .run_for()
+            .map(|at| at.saturating_sub(self.tick_count()).max(1));
+        let pulses = (0..self.pulses.len()).filter_map(|idx| self.pulse_lookahead(idx, 0));
+        let alarms = self.alarms.earliest(self.tick_count(), |pattern| {
+            self.partitions.ticks_until(pattern, self.partition_order)
+        });
+        let children = self.children.iter().filter_map(|child| match child.gear {
+            Gear::Every(n) => {
+                let first = n - self.tick_count() % n;
+                let ticks = child.clock.ticks_until_next_event()?;
+                first.checked_add((ticks - 1).checked_mul(n)?)
+            }
+            // Covered by the trigger pulse itself.
+            Gear::OnPulse(_) => None,
+        });
+        pulses
+            .chain(alarms)
+            .chain(children)
+            .chain(overflow)
+            .chain(halt)
+            .min()
+    }
+
+    /// Earliest fire or expiry notice of the pulse at `idx`. `depth` guards
+    /// against cycles of delayed pulses.
+    fn pulse_lookahead(&self, idx: usize, depth: usize) -> Option<u64> {
+        let (pulse, state) = (&self.pulses[idx], &self.pulse_state[idx]);
+        if state.expired || depth > self.pulses.len() {
+            return None;
+        }
+        let fire = match self.pending.trigger_of(idx) {
+            Some((trigger, delay)) => {
+                let scheduled = self
+                    .pending
+                    .next_due(idx)
+                    .map(|at| at.saturating_sub(self.tick_count()).max(1));
+                let triggered = self
+                    .pulse_lookahead(trigger, depth + 1)
+                    .and_then(|ticks| ticks.checked_add(delay));
+                let trigger_bound = scheduled.into_iter().chain(triggered).min();
+                let own = self.condition_lookahead(&pulse.condition);
+                Some(trigger_bound?.max(own?))
+            }
+            None => self.condition_lookahead(&pulse.condition),
+        };
+        let notice = pulse
+            .expires_at_tick
+            .filter(|_| pulse.notify_expiry)
+            .map(|last| last.saturating_sub(self.tick_count()).max(1));
+        fire.into_iter().chain(notice).min()
+    }
+
+    /// Earliest tick (relative, at least 1) the condition could hold.
+    fn condition_lookahead(&self, condition: &PulseCondition) -> Option<u64> {
+        let now = self.tick_count();
+        match condition {
+            PulseCondition::Every(period) => Some(period - now % period),
+            PulseCondition::TickRange { start, end } => {
+                let next = now.checked_add(1)?;
+                (next <= *end).then(|| start.saturating_sub(now).max(1))
+            }
+            PulseCondition::PartitionEquals { name, value } => {
+                let idx = self.partitions.position(name)?;
+                self.digit_lookahead(idx, |d, modulus| {
+                    (*value < modulus).then(|| (value + modulus - d) % modulus)
+                })
+            }
+            PulseCondition::PartitionModulo {
+                name,
+                modulus: m,
+                remainder: r,
+            } => {
+                let idx = self.partitions.position(name)?;
+                self.digit_lookahead(idx, |d, modulus| {
+                    // Smallest matching digit at or after d, else wrap around.
+                    let at_or_after = d + (r + m - d % m) % m;
+                    if r >= m {
+                        None
+                    } else if at_or_after < modulus {
+                        Some(at_or_after - d)
+                    } else {
+                        (*r < modulus).then(|| modulus - d + r)
+                    }
+                })
+            }
+            PulseCondition::PartitionChanged { name } => {
+                let idx = self.partitions.position(name)?;
+                self.digit_lookahead(idx, |_, modulus| (modulus > 1).then_some(1))
+            }
+            PulseCondition::PartitionDecreased { name } => {
+                let idx = self.partitions.position(name)?;
+                // Decreases only by rolling over to 0.
+                self.digit_lookahead(idx, |d, modulus| (modulus > 1).then(|| modulus - d))
+            }
+            PulseCondition::And(conditions) => {
+                if conditions.is_empty() {
+                    return None;
+                }
+                conditions
+                    .iter()
+                    .try_fold(1, |acc, c| Some(acc.max(self.condition_lookahead(c)?)))
+            }
+            PulseCondition::Or(conditions) => conditions
+                .iter()
+                .filter_map(|c| self.condition_lookahead(c))
+                .min(),
+            PulseCondition::RateLimit { inner, .. } | PulseCondition::Debounce { inner, .. } => {
+                self.condition_lookahead(inner)
+            }
+            PulseCondition::Not(_)
+            | PulseCondition::Jitter { .. }
+            | PulseCondition::ElapsedAtLeast { .. }
+            | PulseCondition::DateEquals { .. } => Some(1),
+        }
+    }
+
+    /// Ticks until partition `idx` next holds a matching value, where
+    /// `digits_ahead(value, modulus)` gives how many values past the current
+    /// one the next match is (0 if the current value matches).
+    fn digit_lookahead(
+        &self,
+        idx: usize,
+        digits_ahead: impl Fn(u64, u64) -> Option<u64>,
+    ) -> Option<u64> {
+        let (value, modulus, offset, weight) =
+            match self.partitions.digit(idx, self.partition_order) {
+                Some(digit) => digit,
+                // Weight beyond u128: the value never changes again.
+                None => {
+                    let (value, modulus) =
+                        (self.partitions.values()[idx], self.partitions.modulus(idx));
+                    return (digits_ahead(value, modulus)? == 0).then_some(1);
+                }
+            };
+        let ahead = match digits_ahead(value, modulus)? {
+            0 if offset + 1 < weight => return Some(1),
+            // Current value matches but is about to roll over: next cycle.
+            0 => modulus as u128,
+            ahead => ahead as u128,
+        };
+        u64::try_from(ahead * weight - offset).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AlarmTarget, Clock, PulseCondition};
+
+    /// Brute-force reference: first tick with any fired pulse.
+    fn actual(clock: &Clock, horizon: u64) -> Option<u64> {
+        let mut sim = clock.detached();
+        (1..=horizon).find(|_| !sim.tick().pulses.is_empty())
+    }
+
+    #[test]
+    fn matches_brute_force_for_pure_conditions() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .partition("hour", 24)
+            .pulse_when(
+                "half",
+                PulseCondition::And(vec![
+                    PulseCondition::PartitionEquals {
+                        name: "min".into(),
+                        value: 30,
+                    },
+                    PulseCondition::PartitionEquals {
+                        name: "sec".into(),
+                        value: 0,
+                    },
+                ]),
+            )
+            .pulse_when(
+                "evening",
+                PulseCondition::PartitionModulo {
+                    name: "hour".into(),
+                    modulus: 6,
+                    remainder: 5,
+                },
+            )
+            .pulse_every("quarter", 900)
+            .pulse_when(
+                "new_hour",
+                PulseCondition::PartitionChanged {
+                    name: "hour".into(),
+                },
+            )
+            .pulse_when(
+                "midnight",
+                PulseCondition::PartitionDecreased {
+                    name: "hour".into(),
+                },
+            )
+            .build()
+            .unwrap();
+        let mut fired = 0;
+        while fired < 12 {
+            let n = clock.ticks_until_next_event().unwrap();
+            let actual = actual(&clock, 100_000).unwrap();
+            assert!(n <= actual, "predicted {n}, fired after {actual}");
+            let outcome = clock.observe(clock.tick_count() + n - 1);
+            if !outcome.pulses.is_empty() {
+                assert_eq!(n, actual);
+                fired += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn accounts_for_alarms_and_delays() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("minute", 60)
+            .pulse_after("late", "minute", 5)
+            .build()
+            .unwrap();
+        assert_eq!(clock.ticks_until_next_event(), Some(60));
+        clock.set_alarm("wake", AlarmTarget::Tick(42)).unwrap();
+        assert_eq!(clock.ticks_until_next_event(), Some(42));
+        clock.cancel_alarm("wake");
+
+        clock.observe(59);
+        assert_eq!(clock.ticks_until_next_event(), Some(5));
+
+        let silent = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_when("never", PulseCondition::TickRange { start: 0, end: 0 })
+            .build()
+            .unwrap();
+        assert_eq!(
+            silent.ticks_until_next_event(),
+            Some(u64::MAX),
+            "only the tick overflow remains"
+        );
+    }
+}
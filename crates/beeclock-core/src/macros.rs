@@ -0,0 +1,191 @@
+//! The [`beeclock!`](crate::beeclock) macro.
+
+/// Declare a [`ClockFixed`](crate::ClockFixed) as a `const` item, checked
+/// at compile time.
+///
+/// The layout is validated by [`ClockFixed::new`](crate::ClockFixed::new)
+/// during const evaluation, so a zero modulus, a zero period or a pulse
+/// naming an unknown partition fails the build instead of a runtime
+/// `build()`.
+///
+/// ```rust
+/// beeclock_core::beeclock! {
+///     /// Wall clock with a few pulses.
+///     pub const WALL = lsf {
+///         partitions { sec: 60, min: 60, hour: 24 }
+///         pulses {
+///             second: every(1),
+///             quarter: sec % 15 == 0,
+///             noon: all((hour == 12), (min == 0), (sec == 0)),
+///             awake: not(any((hour == 2), (hour == 3))),
+///             warmup: ticks(1, 100),
+///         }
+///     }
+/// }
+///
+/// let mut clock = WALL;
+/// let outcome = clock.tick();
+/// assert_eq!(outcome.fired, [true, false, false, true, true]);
+/// ```
+///
+/// An optional tick width follows the name, as in `const TINY: u32 = lsf`
+/// (see [`TickWidth`](crate::TickWidth)); it defaults to `u64`.
+///
+/// The order is `lsf`/`least_significant_first` or
+/// `msf`/`most_significant_first`. Pulse conditions are:
+///
+/// | Form | Condition |
+/// |------|-----------|
+/// | `every(n)` | [`FixedCondition::Every`](crate::FixedCondition::Every) |
+/// | `part == v` / `part != v` | partition equals / not equals |
+/// | `part % m == r` | [`FixedCondition::PartitionModulo`](crate::FixedCondition::PartitionModulo) |
+/// | `ticks(start, end)` | inclusive [`FixedCondition::TickRange`](crate::FixedCondition::TickRange) |
+/// | `not(c)` | negation |
+/// | `all((c), (c), ...)` / `any((c), ...)` | and / or, each term in parentheses |
+///
+/// A layout that fails validation does not compile:
+///
+/// ```compile_fail
+/// beeclock_core::beeclock! {
+///     const BROKEN = lsf {
+///         partitions { sec: 60 }
+///         pulses { noon: hour == 12 }
+///     }
+/// }
+/// let _ = BROKEN;
+/// ```
+#[macro_export]
+macro_rules! beeclock {
+    (
+        $(#[$meta:meta])*
+        $vis:vis const $name:ident $(: $width:ty)? = $order:ident {
+            partitions { $($part:ident : $modulus:expr),* $(,)? }
+            pulses { $($pulses:tt)* }
+        }
+    ) => {
+        $crate::beeclock!(@pulses
+            [$(#[$meta])* $vis $name [$($width)?] $order [$($part $modulus)*]]
+            [] [] $($pulses)*
+        );
+    };
+
+    // Split the pulse list on top-level commas, one token at a time.
+    (@pulses $header:tt [$($done:tt)*] []) => {
+        $crate::beeclock!(@emit $header [$($done)*]);
+    };
+    (@pulses $header:tt [$($done:tt)*] [$($pulse:tt)+] $(,)?) => {
+        $crate::beeclock!(@emit $header [$($done)* [$($pulse)+]]);
+    };
+    (@pulses $header:tt [$($done:tt)*] [$($pulse:tt)+] , $($rest:tt)+) => {
+        $crate::beeclock!(@pulses $header [$($done)* [$($pulse)+]] [] $($rest)+);
+    };
+    (@pulses $header:tt $done:tt [$($pulse:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::beeclock!(@pulses $header $done [$($pulse)* $next] $($rest)*);
+    };
+
+    (@emit
+        [
+            $(#[$meta:meta])* $vis:vis $name:ident [$($width:ty)?] $order:ident
+            [$($part:ident $modulus:expr)*]
+        ]
+        [$([$pulse:ident : $($cond:tt)+])*]
+    ) => {
+        $(#[$meta])*
+        $vis const $name: $crate::ClockFixed<
+            { [$(stringify!($part)),*].len() },
+            { [$(stringify!($pulse)),*].len() },
+            $($width)?
+        > = match $crate::ClockFixed::with_width(
+            $crate::beeclock!(@order $order),
+            [$($crate::FixedPartition::new(stringify!($part), $modulus)),*],
+            [$($crate::FixedPulse::new(
+                stringify!($pulse),
+                $crate::beeclock!(@cond $($cond)+),
+            )),*],
+        ) {
+            Ok(clock) => clock,
+            Err(err) => panic!("{}", err.reason()),
+        };
+    };
+
+    (@order lsf) => { $crate::PartitionOrder::LeastSignificantFirst };
+    (@order least_significant_first) => { $crate::PartitionOrder::LeastSignificantFirst };
+    (@order msf) => { $crate::PartitionOrder::MostSignificantFirst };
+    (@order most_significant_first) => { $crate::PartitionOrder::MostSignificantFirst };
+
+    (@cond every($period:expr)) => {
+        $crate::FixedCondition::Every($period)
+    };
+    (@cond ticks($start:expr, $end:expr)) => {
+        $crate::FixedCondition::TickRange { start: $start, end: $end }
+    };
+    (@cond not($($inner:tt)+)) => {
+        $crate::FixedCondition::Not(&$crate::beeclock!(@cond $($inner)+))
+    };
+    (@cond all($(($($term:tt)+)),+ $(,)?)) => {
+        $crate::FixedCondition::And(&[$($crate::beeclock!(@cond $($term)+)),+])
+    };
+    (@cond any($(($($term:tt)+)),+ $(,)?)) => {
+        $crate::FixedCondition::Or(&[$($crate::beeclock!(@cond $($term)+)),+])
+    };
+    (@cond $part:ident % $modulus:tt == $remainder:expr) => {
+        $crate::FixedCondition::PartitionModulo {
+            name: stringify!($part),
+            modulus: $modulus,
+            remainder: $remainder,
+        }
+    };
+    (@cond $part:ident == $value:expr) => {
+        $crate::FixedCondition::PartitionEquals { name: stringify!($part), value: $value }
+    };
+    (@cond $part:ident != $value:expr) => {
+        $crate::FixedCondition::Not(&$crate::FixedCondition::PartitionEquals {
+            name: stringify!($part),
+            value: $value,
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FixedCondition, PartitionOrder};
+
+    beeclock! {
+        const CLOCK: u32 = msf {
+            partitions { hour: 24, min: 60 }
+            pulses {
+                hourly: min == 0,
+                odd: min % 2 == 1,
+                late: any((hour == 23), (hour != 12)),
+            }
+        }
+    }
+
+    #[test]
+    fn expands_to_a_validated_clock() {
+        let mut clock = CLOCK;
+        let _: u32 = clock.tick_count();
+        assert_eq!(clock.partitions()[0].name, "hour");
+        assert_eq!(
+            clock.pulses()[2].condition,
+            FixedCondition::Or(&[
+                FixedCondition::PartitionEquals {
+                    name: "hour",
+                    value: 23
+                },
+                FixedCondition::Not(&FixedCondition::PartitionEquals {
+                    name: "hour",
+                    value: 12
+                }),
+            ])
+        );
+
+        let outcome = (0..60).map(|_| clock.tick()).last().unwrap();
+        assert_eq!(outcome.values, [1, 0]);
+        assert_eq!(outcome.fired, [true, false, true]);
+        assert_eq!(
+            beeclock!(@order least_significant_first),
+            PartitionOrder::LeastSignificantFirst
+        );
+    }
+}
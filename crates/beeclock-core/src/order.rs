@@ -0,0 +1,27 @@
+//! Partition significance order.
+
+/// Defines how partitions are ordered by significance.
+///
+/// Serialized as `"lsf"`/`"msf"` (also accepting `"least"`/`"most"` and the
+/// full snake_case names), matching the WASM builder.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
+pub enum PartitionOrder {
+    /// Least-significant partition first (sec, min, hour).
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "lsf", alias = "least", alias = "least_significant_first")
+    )]
+    LeastSignificantFirst,
+    /// Most-significant partition first (hour, min, sec).
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "msf", alias = "most", alias = "most_significant_first")
+    )]
+    MostSignificantFirst,
+}
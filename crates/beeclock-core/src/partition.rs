@@ -1,41 +1,161 @@
 //! Partition types and logic.
 
 use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::PartitionOrder;
 
 /// Specification for a partition (a mixed-radix digit).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartitionSpec {
     pub name: String,
     pub modulus: u64,
+    /// Labels for front-ends; serialized inline, e.g. `"unit": "min"`.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub meta: PartitionMeta,
 }
 
-/// Defines how partitions are ordered by significance.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum PartitionOrder {
-    /// Least-significant partition first (sec, min, hour).
-    LeastSignificantFirst,
-    /// Most-significant partition first (hour, min, sec).
-    MostSignificantFirst,
+impl PartitionSpec {
+    /// Create a partition counting `0..modulus`, without labels.
+    pub fn new(name: impl Into<String>, modulus: u64) -> Self {
+        Self {
+            name: name.into(),
+            modulus,
+            meta: PartitionMeta::default(),
+        }
+    }
+
+    /// Set the human-friendly name, e.g. `Minutes`.
+    pub fn display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.meta.display_name = Some(display_name.into());
+        self
+    }
+
+    /// Set the unit, e.g. `min`.
+    pub fn unit(mut self, unit: impl Into<String>) -> Self {
+        self.meta.unit = Some(unit.into());
+        self
+    }
+
+    /// Set a longer description, e.g. for tooltips.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.meta.description = Some(description.into());
+        self
+    }
+}
+
+/// Optional human-facing labels for a partition, carried from its
+/// [`PartitionSpec`] into every [`PartitionState`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
+pub struct PartitionMeta {
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub display_name: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub unit: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub description: Option<String>,
+}
+
+impl PartitionMeta {
+    /// Whether no label is set.
+    pub fn is_empty(&self) -> bool {
+        self.display_name.is_none() && self.unit.is_none() && self.description.is_none()
+    }
+}
+
+/// A built clock's view of one partition, from
+/// [`Clock::partitions`](crate::Clock::partitions).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartitionInfo<'a> {
+    pub name: &'a str,
+    pub modulus: u64,
+    /// `None` when the spec set no labels.
+    pub meta: Option<&'a PartitionMeta>,
+}
+
+impl PartitionInfo<'_> {
+    /// The display name if set, else the name.
+    pub fn display_name(&self) -> &str {
+        self.meta
+            .and_then(|meta| meta.display_name.as_deref())
+            .unwrap_or(self.name)
+    }
+
+    /// An owned spec that builds this partition again.
+    pub fn to_spec(&self) -> PartitionSpec {
+        PartitionSpec {
+            name: String::from(self.name),
+            modulus: self.modulus,
+            meta: self.meta.cloned().unwrap_or_default(),
+        }
+    }
 }
 
 /// Runtime state for a partition.
-#[derive(Clone, Debug)]
+///
+/// The name and labels are shared with the owning clock, so cloning a state
+/// (and taking a snapshot) bumps refcounts rather than copying strings.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
 pub struct PartitionState {
-    pub name: String,
+    pub name: Arc<str>,
     pub value: u64,
     pub modulus: u64,
+    /// `None` when the spec set no labels.
+    pub meta: Option<Arc<PartitionMeta>>,
 }
 
 impl PartitionState {
     /// Create a new partition state from a spec.
     pub fn from_spec(spec: &PartitionSpec) -> Self {
         Self {
-            name: spec.name.clone(),
+            name: Arc::from(spec.name.as_str()),
             value: 0,
             modulus: spec.modulus,
+            meta: shared_meta(&spec.meta),
         }
     }
 
+    /// The display name if set, else the name.
+    pub fn display_name(&self) -> &str {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.display_name.as_deref())
+            .unwrap_or(&self.name)
+    }
+
+    /// The unit, if set.
+    pub fn unit(&self) -> Option<&str> {
+        self.meta.as_ref()?.unit.as_deref()
+    }
+
+    /// The description, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.meta.as_ref()?.description.as_deref()
+    }
+
     /// Increment the partition, returning true if it overflowed (carry).
     #[inline]
     pub fn increment(&mut self) -> bool {
@@ -48,3 +168,357 @@ impl PartitionState {
         }
     }
 }
+
+/// Structure-of-arrays partition storage used inside [`Clock`](crate::Clock).
+///
+/// Values and moduli are hot on every tick and live in their own contiguous
+/// arrays; names are only touched for lookups and snapshots.
+#[derive(Clone, Debug)]
+pub(crate) struct PartitionTable {
+    names: Vec<Arc<str>>,
+    values: Vec<u64>,
+    moduli: Vec<u64>,
+    metas: Vec<Option<Arc<PartitionMeta>>>,
+    /// Values one tick ago, as of the last [`advance`](Self::advance); empty
+    /// until then and after [`advance_by`](Self::advance_by), where they are
+    /// unknown.
+    previous: Vec<u64>,
+    /// Ticks per unit of the least significant partition, from
+    /// [`ClockBuilder::ticks_per_unit`](crate::ClockBuilder::ticks_per_unit).
+    prescale: u64,
+    /// Ticks into the current unit, below `prescale`.
+    phase: u64,
+}
+
+impl PartitionTable {
+    /// Build a zeroed table from specs (moduli are assumed validated).
+    pub fn from_specs(specs: &[PartitionSpec]) -> Self {
+        Self {
+            names: specs
+                .iter()
+                .map(|spec| Arc::from(spec.name.as_str()))
+                .collect(),
+            values: vec![0; specs.len()],
+            moduli: specs.iter().map(|spec| spec.modulus).collect(),
+            metas: specs.iter().map(|spec| shared_meta(&spec.meta)).collect(),
+            previous: Vec::new(),
+            prescale: 1,
+            phase: 0,
+        }
+    }
+
+    /// Ticks per unit of the least significant partition.
+    #[inline]
+    pub fn prescale(&self) -> u64 {
+        self.prescale
+    }
+
+    /// Advance the partitions once every `ticks` ticks (at least 1), from
+    /// the start of a unit.
+    pub fn set_prescale(&mut self, ticks: u64) {
+        self.prescale = ticks.max(1);
+        self.phase = 0;
+    }
+
+    /// Line the position within the current unit up with `(epoch, tick)`,
+    /// counted from tick 0 of epoch 0 at the start of a unit.
+    pub fn sync_phase(&mut self, epoch: u64, tick: u64) {
+        let position = (epoch as u128) << 64 | tick as u128;
+        self.phase = (position % self.prescale as u128) as u64;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+
+    /// Increment with carry in the given significance order.
+    pub fn advance(&mut self, order: PartitionOrder) {
+        self.previous.clone_from(&self.values);
+        if self.prescale > 1 {
+            self.phase += 1;
+            if self.phase < self.prescale {
+                return;
+            }
+            self.phase = 0;
+        }
+        match order {
+            PartitionOrder::LeastSignificantFirst => {
+                for idx in 0..self.len() {
+                    if !self.increment(idx) {
+                        break;
+                    }
+                }
+            }
+            PartitionOrder::MostSignificantFirst => {
+                for idx in (0..self.len()).rev() {
+                    if !self.increment(idx) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add `ticks` with carry in the given significance order, as if
+    /// [`advance`](Self::advance) ran `ticks` times.
+    pub fn advance_by(&mut self, ticks: u64, order: PartitionOrder) {
+        self.previous.clear();
+        let len = self.len();
+        let total = self.phase as u128 + ticks as u128;
+        self.phase = (total % self.prescale as u128) as u64;
+        let mut carry = (total / self.prescale as u128) as u64;
+        let mut add = |idx: usize| {
+            let total = self.values[idx] as u128 + carry as u128;
+            let modulus = self.moduli[idx] as u128;
+            self.values[idx] = (total % modulus) as u64;
+            carry = (total / modulus) as u64;
+        };
+        match order {
+            PartitionOrder::LeastSignificantFirst => (0..len).for_each(&mut add),
+            PartitionOrder::MostSignificantFirst => (0..len).rev().for_each(&mut add),
+        }
+    }
+
+    /// Subtract `ticks` with borrow in the given significance order, undoing
+    /// [`advance_by`](Self::advance_by).
+    pub fn rewind_by(&mut self, ticks: u64, order: PartitionOrder) {
+        self.previous.clear();
+        let len = self.len();
+        let (whole, part) = (ticks / self.prescale, ticks % self.prescale);
+        let mut borrow = whole as u128;
+        if let Some(phase) = self.phase.checked_sub(part) {
+            self.phase = phase;
+        } else {
+            self.phase = self.prescale - (part - self.phase);
+            borrow += 1;
+        }
+        let mut sub = |idx: usize| {
+            let modulus = self.moduli[idx] as u128;
+            let (whole, part) = (borrow / modulus, borrow % modulus);
+            let value = self.values[idx] as u128;
+            let (value, wrapped) = match value.checked_sub(part) {
+                Some(value) => (value, 0),
+                None => (value + modulus - part, 1),
+            };
+            self.values[idx] = value as u64;
+            borrow = whole + wrapped;
+        };
+        match order {
+            PartitionOrder::LeastSignificantFirst => (0..len).for_each(&mut sub),
+            PartitionOrder::MostSignificantFirst => (0..len).rev().for_each(&mut sub),
+        }
+    }
+
+    /// Where partition `idx` stands in its cycle: `(value, modulus, offset,
+    /// weight)`, where it advances once every `weight` ticks and `offset`
+    /// ticks have passed since it last did. `None` if the weight overflows.
+    pub fn digit(&self, idx: usize, order: PartitionOrder) -> Option<(u64, u64, u128, u128)> {
+        let (mut offset, mut weight) = (self.phase as u128, self.prescale as u128);
+        let mut add = |j: usize| {
+            offset += self.values[j] as u128 * weight;
+            weight = weight.checked_mul(self.moduli[j] as u128)?;
+            Some(())
+        };
+        match order {
+            PartitionOrder::LeastSignificantFirst => (0..idx).try_for_each(&mut add)?,
+            PartitionOrder::MostSignificantFirst => {
+                (idx + 1..self.len()).rev().try_for_each(&mut add)?
+            }
+        }
+        Some((self.values[idx], self.moduli[idx], offset, weight))
+    }
+
+    /// Ticks until every `(index, value)` in `pattern` holds at once, at
+    /// least 1: a pattern that holds now and at no other point of the cycle
+    /// is a full cycle away. `None` if it never holds (conflicting values)
+    /// or is more than `u64::MAX` ticks away.
+    pub fn ticks_until(&self, pattern: &[(usize, u64)], order: PartitionOrder) -> Option<u64> {
+        let mut wanted = vec![None; self.len()];
+        for &(idx, value) in pattern {
+            if *wanted[idx].get_or_insert(value) != value {
+                return None;
+            }
+        }
+        // Indices from least to most significant.
+        let mut digits: Vec<usize> = match order {
+            PartitionOrder::LeastSignificantFirst => (0..self.len()).collect(),
+            PartitionOrder::MostSignificantFirst => (0..self.len()).rev().collect(),
+        };
+        // The position within a unit counts as one more digit, below the
+        // rest and free in any pattern.
+        let (mut values, mut moduli) = (self.values.clone(), self.moduli.clone());
+        if self.prescale > 1 {
+            digits.insert(0, self.len());
+            values.push(self.phase);
+            moduli.push(self.prescale);
+            wanted.push(None);
+        }
+
+        // Start from the next tick's values.
+        let mut target = values.clone();
+        for &idx in &digits {
+            target[idx] += 1;
+            if target[idx] < moduli[idx] {
+                break;
+            }
+            target[idx] = 0;
+        }
+        // Find the first match at or after them: keep the most significant
+        // values that fit the pattern, raise the least significant value
+        // that can be raised among those, and reset everything below it to
+        // the smallest allowed. If nothing can be raised, wrap around to the
+        // first match of the next cycle.
+        let mut raise = None;
+        for (pos, &idx) in digits.iter().enumerate().rev() {
+            let value = target[idx];
+            let raised = match wanted[idx] {
+                Some(want) => (want > value).then_some(want),
+                None => (value + 1 < moduli[idx]).then_some(value + 1),
+            };
+            if let Some(raised) = raised {
+                raise = Some((pos, raised));
+            }
+            if wanted[idx].is_some_and(|want| want != value) {
+                let below = match raise {
+                    Some((pos, raised)) => {
+                        target[digits[pos]] = raised;
+                        &digits[..pos]
+                    }
+                    None => &digits[..],
+                };
+                for &idx in below {
+                    target[idx] = wanted[idx].unwrap_or(0);
+                }
+                break;
+            }
+        }
+
+        // Subtract the current values with borrow, modulo the cycle.
+        let (mut ticks, mut weight, mut borrow) = (0u64, Some(1u64), 0u64);
+        for &idx in &digits {
+            let modulus = moduli[idx];
+            let current = values[idx] as u128 + borrow as u128;
+            let mut diff = target[idx] as u128;
+            borrow = u64::from(diff < current);
+            if diff < current {
+                diff += modulus as u128;
+            }
+            let diff = (diff - current) as u64;
+            if diff > 0 {
+                ticks = ticks.checked_add(diff.checked_mul(weight?)?)?;
+            }
+            weight = weight.and_then(|weight| weight.checked_mul(modulus));
+        }
+        match ticks {
+            0 => weight,
+            ticks => Some(ticks),
+        }
+    }
+
+    /// Increment one partition, returning true if it overflowed (carry).
+    #[inline]
+    fn increment(&mut self, idx: usize) -> bool {
+        self.values[idx] += 1;
+        if self.values[idx] >= self.moduli[idx] {
+            self.values[idx] = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Index of the partition with this name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| &**n == name)
+    }
+
+    /// Overwrite one partition value (caller checks `value < modulus`).
+    pub fn set(&mut self, idx: usize, value: u64) {
+        self.values[idx] = value;
+    }
+
+    #[inline]
+    pub fn modulus(&self, idx: usize) -> u64 {
+        self.moduli[idx]
+    }
+
+    /// Reconstruct the specs this table was built from.
+    pub fn specs(&self) -> Vec<PartitionSpec> {
+        self.infos().map(|info| info.to_spec()).collect()
+    }
+
+    /// Name, modulus and labels of each partition, in configured order.
+    pub fn infos(&self) -> impl ExactSizeIterator<Item = PartitionInfo<'_>> + '_ {
+        self.names
+            .iter()
+            .zip(&self.moduli)
+            .zip(&self.metas)
+            .map(|((name, &modulus), meta)| PartitionInfo {
+                name,
+                modulus,
+                meta: meta.as_deref(),
+            })
+    }
+
+    /// Name and modulus of each partition, in configured order.
+    pub fn layout(&self) -> impl ExactSizeIterator<Item = (&str, u64)> + '_ {
+        self.names
+            .iter()
+            .zip(&self.moduli)
+            .map(|(name, &modulus)| (&**name, modulus))
+    }
+
+    /// Materialize per-partition state for a snapshot.
+    pub fn states(&self) -> Vec<PartitionState> {
+        self.names
+            .iter()
+            .zip(&self.values)
+            .zip(&self.moduli)
+            .zip(&self.metas)
+            .map(|(((name, &value), &modulus), meta)| PartitionState {
+                name: name.clone(),
+                value,
+                modulus,
+                meta: meta.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Labels to share from snapshots, or `None` if there are none.
+fn shared_meta(meta: &PartitionMeta) -> Option<Arc<PartitionMeta>> {
+    (!meta.is_empty()).then(|| Arc::new(meta.clone()))
+}
+
+/// Name-based partition value lookup used by condition evaluation.
+pub(crate) trait PartitionLookup {
+    fn value_of(&self, name: &str) -> Option<u64>;
+
+    /// Value on the previous tick, if known.
+    fn previous_value_of(&self, _name: &str) -> Option<u64> {
+        None
+    }
+}
+
+impl PartitionLookup for [PartitionState] {
+    fn value_of(&self, name: &str) -> Option<u64> {
+        self.iter().find(|p| &*p.name == name).map(|p| p.value)
+    }
+}
+
+impl PartitionLookup for PartitionTable {
+    fn value_of(&self, name: &str) -> Option<u64> {
+        self.position(name).map(|idx| self.values[idx])
+    }
+
+    fn previous_value_of(&self, name: &str) -> Option<u64> {
+        self.position(name)
+            .and_then(|idx| self.previous.get(idx).copied())
+    }
+}
@@ -0,0 +1,166 @@
+//! Pending queue for delayed (chained) pulses.
+//!
+//! When a pulse fires, every pulse declared `after` it is scheduled for
+//! `tick + delay`. Each tick the due entries are drained into a bitmask that
+//! the pulse loop consults instead of evaluating those pulses' triggers.
+
+use alloc::collections::BinaryHeap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::PulseSpec;
+
+/// Delayed pulse waiting on a trigger.
+#[derive(Clone, Debug)]
+struct Dependent {
+    trigger: usize,
+    pulse: usize,
+    delay: u64,
+}
+
+/// Scheduled fires for delayed pulses, ordered by due tick.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PendingQueue {
+    dependents: Vec<Dependent>,
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+    /// Bit per pulse: set when a delayed fire is due at the prepared tick.
+    due: Vec<u64>,
+}
+
+impl PendingQueue {
+    /// Resolve `after` references to pulse indices.
+    ///
+    /// Pulses must already be validated, so unknown triggers are skipped.
+    pub fn new(pulses: &[PulseSpec]) -> Self {
+        let dependents = pulses
+            .iter()
+            .enumerate()
+            .filter_map(|(pulse, spec)| {
+                let after = spec.after.as_ref()?;
+                let trigger = pulses.iter().position(|p| p.name == after.pulse)?;
+                Some(Dependent {
+                    trigger,
+                    pulse,
+                    delay: after.delay,
+                })
+            })
+            .collect();
+        Self {
+            dependents,
+            heap: BinaryHeap::new(),
+            due: vec![0; pulses.len().div_ceil(64)],
+        }
+    }
+
+    /// Whether any pulse is delayed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.dependents.is_empty()
+    }
+
+    /// Drain the entries due at `tick` into the due mask.
+    pub fn prepare(&mut self, tick: u64) {
+        self.due.fill(0);
+        while let Some(&Reverse((at, pulse))) = self.heap.peek() {
+            if at > tick {
+                break;
+            }
+            self.heap.pop();
+            self.due[pulse / 64] |= 1 << (pulse % 64);
+        }
+    }
+
+    /// Whether the delayed pulse at `idx` is due at the prepared tick.
+    #[inline]
+    pub fn is_due(&self, idx: usize) -> bool {
+        self.due[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// Schedule the pulses waiting on `trigger`, which fired at `tick`.
+    pub fn fired(&mut self, trigger: usize, tick: u64) {
+        for dep in &self.dependents {
+            if dep.trigger == trigger {
+                self.heap
+                    .push(Reverse((tick.saturating_add(dep.delay), dep.pulse)));
+            }
+        }
+    }
+
+    /// Drop every scheduled fire due at or before `tick`.
+    pub fn discard_through(&mut self, tick: u64) {
+        self.heap.retain(|Reverse((at, _))| *at > tick);
+    }
+
+    /// Earliest scheduled fire of the delayed pulse at `pulse`.
+    pub fn next_due(&self, pulse: usize) -> Option<u64> {
+        self.heap
+            .iter()
+            .filter(|Reverse((_, p))| *p == pulse)
+            .map(|Reverse((at, _))| *at)
+            .min()
+    }
+
+    /// Trigger index and delay of the delayed pulse at `pulse`.
+    pub fn trigger_of(&self, pulse: usize) -> Option<(usize, u64)> {
+        self.dependents
+            .iter()
+            .find(|dep| dep.pulse == pulse)
+            .map(|dep| (dep.trigger, dep.delay))
+    }
+
+    /// Scheduled fires as `(due tick, pulse index)`, in no particular order.
+    pub fn scheduled(&self) -> impl Iterator<Item = (u64, usize)> + '_ {
+        self.heap.iter().map(|&Reverse(entry)| entry)
+    }
+
+    /// Schedule a fire of the delayed pulse at `pulse` for tick `at`.
+    pub fn schedule(&mut self, at: u64, pulse: usize) {
+        self.heap.push(Reverse((at, pulse)));
+    }
+
+    /// Number of scheduled fires not yet due.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Rebuild for a new pulse list, carrying scheduled fires over through
+    /// `remap` (old index to new index, `None` if the pulse was removed).
+    pub fn rebuild(&self, pulses: &[PulseSpec], remap: impl Fn(usize) -> Option<usize>) -> Self {
+        let mut queue = Self::new(pulses);
+        queue.heap = self
+            .heap
+            .iter()
+            .filter_map(|&Reverse((at, pulse))| Some(Reverse((at, remap(pulse)?))))
+            .collect();
+        queue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PulseCondition;
+
+    #[test]
+    fn schedules_dependents_after_trigger() {
+        let pulses = [
+            PulseSpec::new("threshold", PulseCondition::Every(5)),
+            PulseSpec::new("warning", PulseCondition::always()).after("threshold", 3),
+        ];
+        let mut queue = PendingQueue::new(&pulses);
+
+        queue.fired(0, 5);
+        queue.fired(0, 6);
+        assert_eq!(queue.len(), 2);
+
+        queue.prepare(7);
+        assert!(!queue.is_due(1));
+        queue.prepare(8);
+        assert!(queue.is_due(1));
+        assert_eq!(queue.len(), 1);
+
+        let queue = queue.rebuild(&pulses[1..], |idx| idx.checked_sub(1));
+        assert_eq!(queue.len(), 1);
+    }
+}
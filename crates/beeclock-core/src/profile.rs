@@ -0,0 +1,204 @@
+//! Per-pulse condition evaluation costs.
+//!
+//! With profiling on ([`Clock::set_profiling`]), every tick adds to each
+//! evaluated pulse's count of condition nodes walked and, under std, the
+//! time spent evaluating it. [`Clock::eval_costs`] ranks the pulses, so the
+//! large generated condition trees behind slow ticks stand out.
+//!
+//! Node counts are deterministic and cheap; stateful conditions walk their
+//! whole tree on every tick, pure ones are charged their full size even
+//! where `And`/`Or` short-circuit. Batched `Every` pulses and delayed pulses
+//! that aren't due evaluate nothing and are charged nothing.
+
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use crate::{Clock, PulseCondition, PulseSpec};
+
+/// Accumulated evaluation cost of one pulse, from [`Clock::eval_costs`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvalCost<'a> {
+    pub pulse: &'a str,
+    /// Ticks on which the condition was evaluated.
+    pub evaluations: u64,
+    /// Condition nodes evaluated in total.
+    pub nodes: u64,
+    /// Time spent evaluating the condition (std only).
+    #[cfg(feature = "std")]
+    pub elapsed: Duration,
+}
+
+/// Running totals, indexed like [`Clock::pulses`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Profile {
+    costs: Vec<Cost>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Cost {
+    size: u64,
+    evaluations: u64,
+    nodes: u64,
+    #[cfg(feature = "std")]
+    elapsed: Duration,
+}
+
+/// When an evaluation started, if timing is available.
+#[cfg(feature = "std")]
+pub(crate) type Started = std::time::Instant;
+#[cfg(not(feature = "std"))]
+pub(crate) type Started = ();
+
+impl Profile {
+    pub fn new(pulses: &[PulseSpec]) -> Self {
+        let costs = pulses
+            .iter()
+            .map(|pulse| Cost {
+                size: pulse.condition.node_count(),
+                ..Cost::default()
+            })
+            .collect();
+        Self { costs }
+    }
+
+    pub fn start() -> Started {
+        #[cfg(feature = "std")]
+        let started = std::time::Instant::now();
+        #[cfg(not(feature = "std"))]
+        let started = ();
+        started
+    }
+
+    /// Charge pulse `index` for an evaluation begun at `started`.
+    pub fn record(&mut self, index: usize, started: Started) {
+        let cost = &mut self.costs[index];
+        cost.evaluations += 1;
+        cost.nodes += cost.size;
+        #[cfg(feature = "std")]
+        {
+            cost.elapsed += started.elapsed();
+        }
+        #[cfg(not(feature = "std"))]
+        let () = started;
+    }
+
+    /// Keep the totals of pulses that survive a prune, where `remap` maps
+    /// old indices to new ones.
+    pub fn retain(&mut self, remap: &[Option<usize>]) {
+        let mut index = remap.iter();
+        self.costs
+            .retain(|_| index.next().is_some_and(Option::is_some));
+    }
+}
+
+impl PulseCondition {
+    /// Nodes in the condition tree.
+    pub(crate) fn node_count(&self) -> u64 {
+        1 + match self {
+            PulseCondition::Not(inner)
+            | PulseCondition::Jitter { inner, .. }
+            | PulseCondition::RateLimit { inner, .. }
+            | PulseCondition::Debounce { inner, .. } => inner.node_count(),
+            PulseCondition::And(conditions) | PulseCondition::Or(conditions) => {
+                conditions.iter().map(PulseCondition::node_count).sum()
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl Clock {
+    /// Turn per-pulse evaluation profiling on or off. Turning it on starts
+    /// from zero; turning it off discards the totals.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profile = enabled.then(|| Profile::new(&self.pulses));
+    }
+
+    /// Whether evaluation profiling is on.
+    pub fn is_profiling(&self) -> bool {
+        self.profile.is_some()
+    }
+
+    /// Evaluation costs per pulse since profiling was turned on, most
+    /// expensive first: by time under std, by nodes otherwise. Empty while
+    /// profiling is off.
+    ///
+    /// ```
+    /// use beeclock_core::Clock;
+    ///
+    /// let mut clock = Clock::builder()
+    ///     .least_significant_first()
+    ///     .partition("sec", 60)
+    ///     .pulse_when("cheap", "sec == 0".parse().unwrap())
+    ///     .pulse_when("generated", "sec == 1 || sec == 2 || sec == 3".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// clock.set_profiling(true);
+    /// for _ in 0..100 {
+    ///     clock.tick();
+    /// }
+    ///
+    /// let costs = clock.eval_costs();
+    /// let generated = costs.iter().find(|c| c.pulse == "generated").unwrap();
+    /// assert_eq!((generated.evaluations, generated.nodes), (100, 400));
+    /// ```
+    pub fn eval_costs(&self) -> Vec<EvalCost<'_>> {
+        let Some(profile) = &self.profile else {
+            return Vec::new();
+        };
+        let mut costs: Vec<EvalCost<'_>> = self
+            .pulses
+            .iter()
+            .zip(&profile.costs)
+            .map(|(pulse, cost)| EvalCost {
+                pulse: &pulse.name,
+                evaluations: cost.evaluations,
+                nodes: cost.nodes,
+                #[cfg(feature = "std")]
+                elapsed: cost.elapsed,
+            })
+            .collect();
+        #[cfg(feature = "std")]
+        costs.sort_by_key(|cost| Reverse((cost.elapsed, cost.nodes)));
+        #[cfg(not(feature = "std"))]
+        costs.sort_by_key(|cost| Reverse(cost.nodes));
+        costs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_pulses_for_the_conditions_they_evaluate() {
+        let wide = (0..20)
+            .map(|value| alloc::format!("sec == {value}"))
+            .collect::<Vec<_>>()
+            .join(" || ");
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("batched", 5)
+            .pulse_when("wide", wide.parse().unwrap())
+            .build()
+            .unwrap();
+        clock.tick();
+        assert!(clock.eval_costs().is_empty());
+
+        clock.set_profiling(true);
+        for _ in 0..30 {
+            clock.tick();
+        }
+        let costs = clock.eval_costs();
+        assert_eq!(costs[0].pulse, "wide");
+        assert_eq!((costs[0].evaluations, costs[0].nodes), (30, 30 * 21));
+        let batched = costs.iter().find(|c| c.pulse == "batched").unwrap();
+        assert_eq!(batched.evaluations, 0);
+
+        clock.set_profiling(false);
+        assert!(!clock.is_profiling() && clock.eval_costs().is_empty());
+    }
+}
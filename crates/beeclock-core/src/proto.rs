@@ -0,0 +1,350 @@
+//! Protobuf messages for streaming outcomes (`prost` feature).
+//!
+//! The types here are the Rust side of `proto/beeclock.proto` (package
+//! `beeclock.v1`), derived by hand so building needs no `protoc`. Convert
+//! from the clock's types with `From<&T>`, and back with `TryFrom`, which
+//! rejects messages no clock could have produced with
+//! [`ClockError::InvalidState`].
+//!
+//! ```
+//! use beeclock_core::{proto, Clock};
+//! use prost::Message;
+//!
+//! let mut clock = Clock::default();
+//! let outcome = clock.observe(59);
+//! let bytes = proto::TickOutcome::from(&outcome).encode_to_vec();
+//!
+//! let received = proto::TickOutcome::decode(bytes.as_slice()).unwrap();
+//! assert_eq!(received.pulses.len(), outcome.pulses.len());
+//! let outcome = beeclock_core::TickOutcome::try_from(received).unwrap();
+//! assert_eq!(outcome.snapshot.get("min"), 1);
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{ClockError, PartitionMeta, PartitionState};
+
+/// Significance order of a snapshot's partitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum PartitionOrder {
+    LeastSignificantFirst = 0,
+    MostSignificantFirst = 1,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Partition {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(uint64, tag = "2")]
+    pub value: u64,
+    #[prost(uint64, tag = "3")]
+    pub modulus: u64,
+    #[prost(string, optional, tag = "4")]
+    pub display_name: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub unit: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Snapshot {
+    #[prost(uint64, tag = "1")]
+    pub tick: u64,
+    #[prost(uint64, tag = "2")]
+    pub epoch: u64,
+    #[prost(enumeration = "PartitionOrder", tag = "3")]
+    pub order: i32,
+    /// In configured order.
+    #[prost(message, repeated, tag = "4")]
+    pub partitions: Vec<Partition>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PulseFired {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(uint64, tag = "2")]
+    pub tick: u64,
+    #[prost(uint64, tag = "3")]
+    pub epoch: u64,
+    #[prost(string, optional, tag = "4")]
+    pub clock: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuietReport {
+    #[prost(string, tag = "1")]
+    pub window: String,
+    #[prost(uint64, tag = "2")]
+    pub start_tick: u64,
+    #[prost(uint64, tag = "3")]
+    pub end_tick: u64,
+    #[prost(uint64, tag = "4")]
+    pub suppressed: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TaggedOutcome {
+    #[prost(string, tag = "1")]
+    pub clock: String,
+    #[prost(message, optional, tag = "2")]
+    pub outcome: Option<TickOutcome>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TickOutcome {
+    #[prost(string, optional, tag = "1")]
+    pub clock: Option<String>,
+    #[prost(message, optional, tag = "2")]
+    pub snapshot: Option<Snapshot>,
+    #[prost(message, repeated, tag = "3")]
+    pub pulses: Vec<PulseFired>,
+    #[prost(bool, tag = "4")]
+    pub overflowed: bool,
+    #[prost(message, repeated, tag = "5")]
+    pub quiet_reports: Vec<QuietReport>,
+    /// Child clocks that ticked on this tick.
+    #[prost(message, repeated, tag = "6")]
+    pub children: Vec<TaggedOutcome>,
+}
+
+impl From<crate::PartitionOrder> for PartitionOrder {
+    fn from(order: crate::PartitionOrder) -> Self {
+        match order {
+            crate::PartitionOrder::LeastSignificantFirst => PartitionOrder::LeastSignificantFirst,
+            crate::PartitionOrder::MostSignificantFirst => PartitionOrder::MostSignificantFirst,
+        }
+    }
+}
+
+impl From<PartitionOrder> for crate::PartitionOrder {
+    fn from(order: PartitionOrder) -> Self {
+        match order {
+            PartitionOrder::LeastSignificantFirst => crate::PartitionOrder::LeastSignificantFirst,
+            PartitionOrder::MostSignificantFirst => crate::PartitionOrder::MostSignificantFirst,
+        }
+    }
+}
+
+impl From<&PartitionState> for Partition {
+    fn from(state: &PartitionState) -> Self {
+        let meta = state.meta.as_deref();
+        Self {
+            name: String::from(&*state.name),
+            value: state.value,
+            modulus: state.modulus,
+            display_name: meta.and_then(|m| m.display_name.clone()),
+            unit: meta.and_then(|m| m.unit.clone()),
+            description: meta.and_then(|m| m.description.clone()),
+        }
+    }
+}
+
+impl TryFrom<Partition> for PartitionState {
+    type Error = ClockError;
+
+    fn try_from(partition: Partition) -> Result<Self, ClockError> {
+        if partition.value >= partition.modulus {
+            return Err(ClockError::InvalidState {
+                reason: format!(
+                    "partition '{}' has value {} for modulus {}",
+                    partition.name, partition.value, partition.modulus
+                ),
+            });
+        }
+        let meta = PartitionMeta {
+            display_name: partition.display_name,
+            unit: partition.unit,
+            description: partition.description,
+        };
+        Ok(Self {
+            name: Arc::from(partition.name),
+            value: partition.value,
+            modulus: partition.modulus,
+            meta: (!meta.is_empty()).then(|| Arc::new(meta)),
+        })
+    }
+}
+
+impl From<&crate::ClockSnapshot> for Snapshot {
+    fn from(snapshot: &crate::ClockSnapshot) -> Self {
+        Self {
+            tick: snapshot.tick,
+            epoch: snapshot.epoch,
+            order: PartitionOrder::from(snapshot.order) as i32,
+            partitions: snapshot.partitions.iter().map(Partition::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<Snapshot> for crate::ClockSnapshot {
+    type Error = ClockError;
+
+    fn try_from(snapshot: Snapshot) -> Result<Self, ClockError> {
+        let order =
+            PartitionOrder::try_from(snapshot.order).map_err(|_| ClockError::InvalidState {
+                reason: format!("unknown partition order {}", snapshot.order),
+            })?;
+        Ok(Self {
+            tick: snapshot.tick,
+            epoch: snapshot.epoch,
+            partitions: snapshot
+                .partitions
+                .into_iter()
+                .map(PartitionState::try_from)
+                .collect::<Result<_, _>>()?,
+            order: order.into(),
+        })
+    }
+}
+
+impl From<&crate::PulseFired> for PulseFired {
+    fn from(pulse: &crate::PulseFired) -> Self {
+        Self {
+            name: pulse.name.clone(),
+            tick: pulse.tick,
+            epoch: pulse.epoch,
+            clock: pulse.clock.as_deref().map(String::from),
+        }
+    }
+}
+
+impl From<PulseFired> for crate::PulseFired {
+    fn from(pulse: PulseFired) -> Self {
+        Self {
+            name: pulse.name,
+            tick: pulse.tick,
+            epoch: pulse.epoch,
+            clock: pulse.clock.map(Arc::from),
+        }
+    }
+}
+
+impl From<&crate::QuietReport> for QuietReport {
+    fn from(report: &crate::QuietReport) -> Self {
+        Self {
+            window: report.window.clone(),
+            start_tick: report.start_tick,
+            end_tick: report.end_tick,
+            suppressed: report.suppressed,
+        }
+    }
+}
+
+impl From<QuietReport> for crate::QuietReport {
+    fn from(report: QuietReport) -> Self {
+        Self {
+            window: report.window,
+            start_tick: report.start_tick,
+            end_tick: report.end_tick,
+            suppressed: report.suppressed,
+        }
+    }
+}
+
+impl From<&crate::TickOutcome> for TickOutcome {
+    fn from(outcome: &crate::TickOutcome) -> Self {
+        Self {
+            clock: outcome.clock.as_deref().map(String::from),
+            snapshot: Some(Snapshot::from(&*outcome.snapshot)),
+            pulses: outcome.pulses.iter().map(PulseFired::from).collect(),
+            overflowed: outcome.overflowed,
+            quiet_reports: outcome
+                .quiet_reports
+                .iter()
+                .map(QuietReport::from)
+                .collect(),
+            children: outcome
+                .children
+                .iter()
+                .map(|child| TaggedOutcome {
+                    clock: String::from(&*child.clock),
+                    outcome: Some(TickOutcome::from(&child.outcome)),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<TickOutcome> for crate::TickOutcome {
+    type Error = ClockError;
+
+    fn try_from(outcome: TickOutcome) -> Result<Self, ClockError> {
+        let missing = |what: &str| ClockError::InvalidState {
+            reason: format!("outcome has no {what}"),
+        };
+        let snapshot = outcome.snapshot.ok_or_else(|| missing("snapshot"))?;
+        Ok(Self {
+            clock: outcome.clock.map(Arc::from),
+            snapshot: Arc::new(snapshot.try_into()?),
+            pulses: outcome.pulses.into_iter().map(Into::into).collect(),
+            overflowed: outcome.overflowed,
+            quiet_reports: outcome.quiet_reports.into_iter().map(Into::into).collect(),
+            children: outcome
+                .children
+                .into_iter()
+                .map(|child| {
+                    let outcome = child.outcome.ok_or_else(|| missing("child outcome"))?;
+                    Ok(crate::TaggedOutcome {
+                        clock: Arc::from(child.clock),
+                        outcome: outcome.try_into()?,
+                    })
+                })
+                .collect::<Result<_, ClockError>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, Gear, PartitionSpec};
+    use prost::Message;
+
+    #[test]
+    fn outcomes_round_trip_through_protobuf() {
+        let minutes = Clock::builder()
+            .least_significant_first()
+            .partition("min", 60)
+            .build()
+            .unwrap();
+        let mut clock = Clock::builder()
+            .name("kitchen")
+            .most_significant_first()
+            .partition("min", 60)
+            .partition_spec(PartitionSpec::new("sec", 60).unit("s"))
+            .pulse_every("minute", 60)
+            .child("minutes", minutes, Gear::OnPulse("minute".into()))
+            .build()
+            .unwrap();
+        let outcome = clock.observe(59);
+
+        let bytes = TickOutcome::from(&outcome).encode_to_vec();
+        let message = TickOutcome::decode(bytes.as_slice()).unwrap();
+        let snapshot = message.snapshot.as_ref().unwrap();
+        assert_eq!(snapshot.order, PartitionOrder::MostSignificantFirst as i32);
+        assert_eq!(snapshot.partitions[1].unit.as_deref(), Some("s"));
+        assert_eq!(message.pulses[0].clock.as_deref(), Some("kitchen"));
+
+        let restored = crate::TickOutcome::try_from(message).unwrap();
+        assert_eq!(restored.snapshot, outcome.snapshot);
+        assert_eq!(restored.to_string(), outcome.to_string());
+        assert_eq!(restored.children[0].outcome.snapshot.get("min"), 1);
+    }
+
+    #[test]
+    fn rejects_impossible_messages() {
+        let mut snapshot = Snapshot::from(&Clock::default().snapshot());
+        snapshot.partitions[0].value = 60;
+        assert!(matches!(
+            crate::ClockSnapshot::try_from(snapshot),
+            Err(ClockError::InvalidState { .. })
+        ));
+        assert!(crate::TickOutcome::try_from(TickOutcome::default()).is_err());
+    }
+}
@@ -1,20 +1,320 @@
 //! Pulse types.
 
+use alloc::format;
 use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Deref;
+use core::{mem, slice};
 
-use crate::PulseCondition;
+use crate::stateful::ConditionState;
+use crate::{ClockDuration, ClockError, PulseCondition};
 
 /// Specification for a pulse.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PulseSpec {
     pub name: String,
     pub condition: PulseCondition,
+    /// Last tick on which the pulse may fire; it expires after that tick.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub expires_at_tick: Option<u64>,
+    /// Number of fires after which the pulse expires.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub max_fires: Option<u64>,
+    /// Emit an `__expired__:<name>` pulse on the tick the pulse expires.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "core::ops::Not::not")
+    )]
+    pub notify_expiry: bool,
+    /// Fire `delay` ticks after another pulse fires, instead of on a
+    /// schedule. `condition` still gates the delayed fire.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub after: Option<PulseDelay>,
+}
+
+/// Trigger for a delayed pulse: fire `delay` ticks after `pulse` fires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PulseDelay {
+    pub pulse: String,
+    pub delay: u64,
+}
+
+impl PulseSpec {
+    /// Create a pulse that never expires.
+    pub fn new(name: impl Into<String>, condition: PulseCondition) -> Self {
+        Self {
+            name: name.into(),
+            condition,
+            expires_at_tick: None,
+            max_fires: None,
+            notify_expiry: false,
+            after: None,
+        }
+    }
+
+    /// Fire `delay` ticks after each fire of the `trigger` pulse.
+    pub fn after(mut self, trigger: impl Into<String>, delay: u64) -> Self {
+        self.after = Some(PulseDelay {
+            pulse: trigger.into(),
+            delay,
+        });
+        self
+    }
+
+    /// Expire the pulse after `tick`.
+    pub fn expires_at(mut self, tick: u64) -> Self {
+        self.expires_at_tick = Some(tick);
+        self
+    }
+
+    /// Expire the pulse after it has fired `count` times.
+    pub fn max_fires(mut self, count: u64) -> Self {
+        self.max_fires = Some(count);
+        self
+    }
+
+    /// Emit an `__expired__:<name>` pulse when the pulse expires.
+    pub fn notify_on_expiry(mut self) -> Self {
+        self.notify_expiry = true;
+        self
+    }
+
+    /// Whether the pulse has any expiry limit.
+    #[inline]
+    pub(crate) fn can_expire(&self) -> bool {
+        self.expires_at_tick.is_some() || self.max_fires.is_some()
+    }
+
+    /// Whether the pulse is spent after being evaluated at `tick` with
+    /// `fires` total fires.
+    #[inline]
+    pub(crate) fn is_spent(&self, tick: u64, fires: u64) -> bool {
+        self.expires_at_tick.is_some_and(|last| tick >= last)
+            || self.max_fires.is_some_and(|max| fires >= max)
+    }
+
+    /// Name of the notification pulse emitted when this pulse expires.
+    pub fn expiry_pulse_name(&self) -> String {
+        format!("__expired__:{}", self.name)
+    }
+}
+
+/// Per-pulse runtime state: expiry bookkeeping and condition state.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PulseState {
+    pub(crate) fires: u64,
+    pub(crate) expired: bool,
+    pub(crate) condition: ConditionState,
+}
+
+impl PulseState {
+    pub(crate) fn new(
+        spec: &PulseSpec,
+        ticks_of: &dyn Fn(&ClockDuration) -> Result<u64, ClockError>,
+    ) -> Result<Self, ClockError> {
+        Ok(Self {
+            condition: ConditionState::new(&spec.condition, ticks_of)?,
+            ..Self::default()
+        })
+    }
+
+    /// Check that this state fits `spec`, e.g. after being restored.
+    pub(crate) fn check(&self, spec: &PulseSpec) -> Result<(), &'static str> {
+        if spec.max_fires.is_some_and(|max| self.fires > max) {
+            return Err("has fired more often than its max_fires");
+        }
+        if self.expired && !spec.can_expire() {
+            return Err("is expired but can never expire");
+        }
+        self.condition.check(&spec.condition)
+    }
 }
 
 /// Emitted when a pulse fires.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
 pub struct PulseFired {
     pub name: String,
     pub tick: u64,
     pub epoch: u64,
+    /// Name of the clock that fired it (see [`Clock::name`](crate::Clock::name)).
+    pub clock: Option<Arc<str>>,
+}
+
+/// Pulses fired on a single tick.
+///
+/// Most ticks fire zero or one pulse, so those cases are stored inline and
+/// only two or more pulses spill to the heap. Derefs to `[PulseFired]`.
+#[derive(Clone, Debug, Default)]
+pub struct PulseList {
+    repr: Repr,
+}
+
+#[derive(Clone, Debug, Default)]
+enum Repr {
+    #[default]
+    Empty,
+    One(PulseFired),
+    Many(Vec<PulseFired>),
+}
+
+impl PulseList {
+    /// Create an empty list (no allocation).
+    pub const fn new() -> Self {
+        Self { repr: Repr::Empty }
+    }
+
+    /// Append a fired pulse.
+    pub fn push(&mut self, pulse: PulseFired) {
+        self.repr = match mem::take(&mut self.repr) {
+            Repr::Empty => Repr::One(pulse),
+            Repr::One(first) => Repr::Many(vec![first, pulse]),
+            Repr::Many(mut list) => {
+                list.push(pulse);
+                Repr::Many(list)
+            }
+        };
+    }
+
+    /// Whether the pulses are stored inline (no heap allocation).
+    pub fn is_inline(&self) -> bool {
+        !matches!(self.repr, Repr::Many(_))
+    }
+
+    /// Convert into a `Vec`.
+    pub fn into_vec(self) -> Vec<PulseFired> {
+        match self.repr {
+            Repr::Empty => Vec::new(),
+            Repr::One(pulse) => vec![pulse],
+            Repr::Many(list) => list,
+        }
+    }
+}
+
+impl Deref for PulseList {
+    type Target = [PulseFired];
+
+    fn deref(&self) -> &[PulseFired] {
+        match &self.repr {
+            Repr::Empty => &[],
+            Repr::One(pulse) => slice::from_ref(pulse),
+            Repr::Many(list) => list,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a PulseList {
+    type Item = &'a PulseFired;
+    type IntoIter = slice::Iter<'a, PulseFired>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<PulseFired> for PulseList {
+    fn from_iter<I: IntoIterator<Item = PulseFired>>(iter: I) -> Self {
+        let mut list = PulseList::new();
+        for pulse in iter {
+            list.push(pulse);
+        }
+        list
+    }
+}
+
+impl From<Vec<PulseFired>> for PulseList {
+    fn from(list: Vec<PulseFired>) -> Self {
+        match list.len() {
+            0 => PulseList::new(),
+            _ => Self {
+                repr: Repr::Many(list),
+            },
+        }
+    }
+}
+
+/// Archived as a plain vector of pulses, whatever the inline state.
+#[cfg(feature = "rkyv")]
+impl rkyv::Archive for PulseList {
+    type Archived = rkyv::vec::ArchivedVec<ArchivedPulseFired>;
+    type Resolver = rkyv::vec::VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: rkyv::Place<Self::Archived>) {
+        rkyv::vec::ArchivedVec::resolve_from_len(self.len(), resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<S> rkyv::Serialize<S> for PulseList
+where
+    S: rkyv::rancor::Fallible + rkyv::ser::Allocator + rkyv::ser::Writer + ?Sized,
+    PulseFired: rkyv::Serialize<S>,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        rkyv::vec::ArchivedVec::serialize_from_slice(self, serializer)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<D> rkyv::Deserialize<PulseList, D> for rkyv::vec::ArchivedVec<ArchivedPulseFired>
+where
+    D: rkyv::rancor::Fallible + ?Sized,
+    ArchivedPulseFired: rkyv::Deserialize<PulseFired, D>,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<PulseList, D::Error> {
+        let mut list = PulseList::new();
+        for pulse in self.iter() {
+            list.push(pulse.deserialize(deserializer)?);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn fired(name: &str) -> PulseFired {
+        PulseFired {
+            name: name.to_string(),
+            tick: 1,
+            epoch: 0,
+            clock: None,
+        }
+    }
+
+    #[test]
+    fn spills_to_heap_after_one() {
+        let mut list = PulseList::new();
+        assert!(list.is_empty() && list.is_inline());
+
+        list.push(fired("a"));
+        assert!(list.is_inline());
+        assert_eq!(list[0].name, "a");
+
+        list.push(fired("b"));
+        list.push(fired("c"));
+        assert!(!list.is_inline());
+        let names: Vec<&str> = list.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
 }
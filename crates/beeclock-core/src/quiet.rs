@@ -0,0 +1,153 @@
+//! Clock-level quiet windows.
+//!
+//! While any quiet window's condition holds, user pulses are suppressed
+//! (the overflow pulse and expiry notices still fire). Windows with
+//! `report` set count the fires they swallowed and report them in a
+//! [`QuietReport`] on the first tick after the window closes.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::partition::PartitionLookup;
+use crate::PulseCondition;
+
+/// A window during which no user pulses fire.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuietWindow {
+    pub name: String,
+    pub condition: PulseCondition,
+    /// Report suppressed fires in a [`QuietReport`] when the window ends.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "core::ops::Not::not")
+    )]
+    pub report: bool,
+}
+
+impl QuietWindow {
+    /// Quiet while `condition` holds.
+    pub fn new(name: impl Into<String>, condition: PulseCondition) -> Self {
+        Self {
+            name: name.into(),
+            condition,
+            report: false,
+        }
+    }
+
+    /// Quiet for ticks `start..=end`.
+    pub fn ticks(name: impl Into<String>, start: u64, end: u64) -> Self {
+        Self::new(name, PulseCondition::TickRange { start, end })
+    }
+
+    /// Count suppressed fires and report them when the window ends.
+    pub fn reported(mut self) -> Self {
+        self.report = true;
+        self
+    }
+}
+
+/// Suppressed fires of a reporting quiet window, emitted when it ends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
+pub struct QuietReport {
+    pub window: String,
+    /// First quiet tick.
+    pub start_tick: u64,
+    /// Last quiet tick.
+    pub end_tick: u64,
+    /// Pulse fires suppressed while the window was active.
+    pub suppressed: u64,
+}
+
+/// Active span of a window.
+#[derive(Clone, Copy, Debug)]
+struct Span {
+    start: u64,
+    suppressed: u64,
+}
+
+/// Runtime state of a clock's quiet windows.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct QuietWindows {
+    windows: Vec<QuietWindow>,
+    active: Vec<Option<Span>>,
+    quiet: bool,
+}
+
+impl QuietWindows {
+    /// Add a window, leaving the spans of existing windows open.
+    pub fn push(&mut self, window: QuietWindow) {
+        self.windows.push(window);
+        self.active.push(None);
+    }
+
+    pub fn windows(&self) -> &[QuietWindow] {
+        &self.windows
+    }
+
+    /// Active spans per window as `(start tick, suppressed fires)`.
+    pub fn spans(&self) -> impl Iterator<Item = Option<(u64, u64)>> + '_ {
+        self.active
+            .iter()
+            .map(|span| span.map(|span| (span.start, span.suppressed)))
+    }
+
+    /// Restore spans saved with [`QuietWindows::spans`], one per window.
+    pub fn restore_spans(&mut self, spans: impl IntoIterator<Item = Option<(u64, u64)>>) {
+        for (active, span) in self.active.iter_mut().zip(spans) {
+            *active = span.map(|(start, suppressed)| Span { start, suppressed });
+        }
+        self.quiet = self.active.iter().any(Option::is_some);
+    }
+
+    /// Whether any window is active at the prepared tick.
+    #[inline]
+    pub fn is_quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Update window activity for `tick`, reporting reporting windows that
+    /// were active on the previous tick and have now closed.
+    pub fn prepare<P>(&mut self, tick: u64, partitions: &P, mut ended: impl FnMut(QuietReport))
+    where
+        P: PartitionLookup + ?Sized,
+    {
+        self.quiet = false;
+        for (window, span) in self.windows.iter().zip(&mut self.active) {
+            let active = window.condition.evaluate(tick, partitions);
+            match (active, *span) {
+                (true, None) => {
+                    *span = Some(Span {
+                        start: tick,
+                        suppressed: 0,
+                    })
+                }
+                (false, Some(closed)) => {
+                    *span = None;
+                    if window.report {
+                        ended(QuietReport {
+                            window: window.name.clone(),
+                            start_tick: closed.start,
+                            end_tick: tick.wrapping_sub(1),
+                            suppressed: closed.suppressed,
+                        });
+                    }
+                }
+                _ => {}
+            }
+            self.quiet |= active;
+        }
+    }
+
+    /// Record a suppressed fire against every active window.
+    pub fn suppress(&mut self) {
+        for span in self.active.iter_mut().flatten() {
+            span.suppressed += 1;
+        }
+    }
+}
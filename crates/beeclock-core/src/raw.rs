@@ -0,0 +1,141 @@
+//! Raw `u32` buffer layout shared by the WASM and C bindings.
+//!
+//! Snapshot buffer (`u32` words, 64-bit values as little-endian lo/hi pairs):
+//!
+//! ```text
+//! [0..2)  tick        [2..4)  epoch
+//! [4]     overflowed  [5]     partition count
+//! [6..)   partition values, two words each, in configured order
+//! ```
+//!
+//! Wide snapshot buffer (`u64` words, e.g. a JS `BigUint64Array`):
+//!
+//! ```text
+//! [0]     tick        [1]     epoch
+//! [2]     overflowed  [3]     partition count
+//! [4..)   partition values, one word each, in configured order
+//! ```
+//!
+//! Pulse bit buffer: bit `i` is set when pulse `i` fired; bit `pulse_count`
+//! is the overflow pulse, left clear when the clock fires none (see
+//! [`Clock::set_overflow_pulse`](crate::Clock::set_overflow_pulse)).
+
+use crate::TickBuffer;
+
+pub const HEADER_WORDS: usize = 6;
+pub const TICK_LO: usize = 0;
+pub const EPOCH_LO: usize = 2;
+pub const OVERFLOWED: usize = 4;
+pub const PARTITION_COUNT: usize = 5;
+
+pub const WIDE_HEADER_WORDS: usize = 4;
+pub const WIDE_TICK: usize = 0;
+pub const WIDE_EPOCH: usize = 1;
+pub const WIDE_OVERFLOWED: usize = 2;
+pub const WIDE_PARTITION_COUNT: usize = 3;
+
+/// Snapshot buffer length (in words) for a clock with `partitions` partitions.
+pub const fn snapshot_len(partitions: usize) -> usize {
+    HEADER_WORDS + partitions * 2
+}
+
+/// Wide snapshot buffer length (in `u64` words) for `partitions` partitions.
+pub const fn wide_snapshot_len(partitions: usize) -> usize {
+    WIDE_HEADER_WORDS + partitions
+}
+
+/// Pulse bit buffer length (in words) for a clock with `pulses` pulses.
+pub const fn pulse_words(pulses: usize) -> usize {
+    (pulses + 1).div_ceil(32)
+}
+
+/// Write a snapshot into `out` (must be at least `snapshot_len(values.len())`).
+pub fn write_snapshot(out: &mut [u32], tick: u64, epoch: u64, overflowed: bool, values: &[u64]) {
+    write_u64(out, TICK_LO, tick);
+    write_u64(out, EPOCH_LO, epoch);
+    out[OVERFLOWED] = overflowed as u32;
+    out[PARTITION_COUNT] = values.len() as u32;
+    for (idx, &value) in values.iter().enumerate() {
+        write_u64(out, HEADER_WORDS + idx * 2, value);
+    }
+}
+
+/// Write a wide snapshot into `out` (must be at least
+/// `wide_snapshot_len(values.len())`).
+pub fn write_wide_snapshot(
+    out: &mut [u64],
+    tick: u64,
+    epoch: u64,
+    overflowed: bool,
+    values: &[u64],
+) {
+    out[WIDE_TICK] = tick;
+    out[WIDE_EPOCH] = epoch;
+    out[WIDE_OVERFLOWED] = overflowed as u64;
+    out[WIDE_PARTITION_COUNT] = values.len() as u64;
+    out[WIDE_HEADER_WORDS..WIDE_HEADER_WORDS + values.len()].copy_from_slice(values);
+}
+
+/// Clear `out` and set the bits for a tick's fired pulses and, if the clock
+/// has an `overflow_pulse`, its overflow.
+pub fn write_pulse_bits(
+    out: &mut [u32],
+    buffer: &TickBuffer,
+    pulse_count: usize,
+    overflow_pulse: bool,
+) {
+    out.fill(0);
+    for &idx in &buffer.fired {
+        set_bit(out, idx);
+    }
+    if buffer.overflowed && overflow_pulse {
+        set_bit(out, pulse_count);
+    }
+}
+
+#[inline]
+fn write_u64(out: &mut [u32], index: usize, value: u64) {
+    out[index] = value as u32;
+    out[index + 1] = (value >> 32) as u32;
+}
+
+#[inline]
+fn set_bit(out: &mut [u32], bit: usize) {
+    out[bit / 32] |= 1 << (bit % 32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, ClockBuilder, OVERFLOW_PULSE};
+
+    #[test]
+    fn overflow_bit_follows_the_overflow_pulse() {
+        let wrap = |builder: ClockBuilder| {
+            let mut clock = builder.pulse_every("beat", 2).build().unwrap();
+            clock.skip(u64::MAX);
+            let mut peeked = clock.detached();
+            let fired = peeked
+                .tick()
+                .pulses
+                .iter()
+                .any(|p| p.name == OVERFLOW_PULSE);
+
+            let mut buffer = clock.tick_buffer();
+            clock.tick_into(&mut buffer);
+            let mut snapshot = [0; 6];
+            let mut bits = [u32::MAX; 1];
+            write_snapshot(
+                &mut snapshot,
+                buffer.tick,
+                buffer.epoch,
+                buffer.overflowed,
+                &buffer.values,
+            );
+            write_pulse_bits(&mut bits, &buffer, 1, clock.overflow_pulse().is_some());
+            (fired, snapshot[OVERFLOWED], bits[0])
+        };
+        assert_eq!(wrap(Clock::builder()), (true, 1, 0b10));
+        assert_eq!(wrap(Clock::builder().no_overflow_pulse()), (false, 1, 0));
+    }
+}
@@ -0,0 +1,507 @@
+//! Record-and-replay tick journal (std only).
+//!
+//! [`TickRecorder`] writes tick outcomes to any [`Write`] in a compact binary
+//! log; [`TickReader`] reads them back as [`TickOutcome`]s for replay into
+//! subscribers or comparison against a live clock.
+//!
+//! ## Format
+//!
+//! All integers are LEB128 varints; strings are length-prefixed UTF-8.
+//!
+//! ```text
+//! header: "BEEJ" version order partition_count (name modulus)* pulse_count name*
+//! record: tick epoch overflowed value* fired_count pulse*
+//! pulse:  pulse_index | pulse_count name
+//! ```
+//!
+//! `order` is 0 for least-significant-first, 1 for most-significant-first.
+//!
+//! Quiet window reports, child clock outcomes, and the clock name are not
+//! recorded.
+//!
+//! The pulse table is the clock's pulses, then the `__expired__:<name>`
+//! notices of pulses with `notify_expiry`, then the overflow pulse unless it
+//! is disabled (see [`Clock::overflow_pulse`]). Pulses outside
+//! the table, such as alarms, are written inline: the escape index
+//! `pulse_count` followed by the name.
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+use crate::{
+    Clock, ClockSnapshot, OutcomeSink, PartitionOrder, PartitionState, PulseFired, PulseList,
+    SinkStatus, TickOutcome,
+};
+
+const MAGIC: &[u8; 4] = b"BEEJ";
+const VERSION: u64 = 1;
+
+/// Writes tick outcomes to a compact binary journal.
+#[derive(Debug)]
+pub struct TickRecorder<W: Write> {
+    writer: W,
+    pulse_names: Vec<String>,
+    partition_count: usize,
+}
+
+impl<W: Write> TickRecorder<W> {
+    /// Start a journal for `clock`, writing the header immediately.
+    pub fn new(mut writer: W, clock: &Clock) -> io::Result<Self> {
+        let snapshot = clock.snapshot();
+        let mut pulse_names: Vec<String> = (0..clock.pulse_count())
+            .filter_map(|idx| clock.pulse_name(idx).map(String::from))
+            .collect();
+        pulse_names.extend(
+            clock
+                .pulses
+                .iter()
+                .filter(|pulse| pulse.notify_expiry)
+                .map(|pulse| pulse.expiry_pulse_name()),
+        );
+        pulse_names.extend(clock.overflow_pulse().map(String::from));
+
+        writer.write_all(MAGIC)?;
+        write_varint(&mut writer, VERSION)?;
+        let order = match snapshot.order {
+            PartitionOrder::LeastSignificantFirst => 0,
+            PartitionOrder::MostSignificantFirst => 1,
+        };
+        write_varint(&mut writer, order)?;
+        write_varint(&mut writer, snapshot.partitions.len() as u64)?;
+        for part in &snapshot.partitions {
+            write_str(&mut writer, &part.name)?;
+            write_varint(&mut writer, part.modulus)?;
+        }
+        write_varint(&mut writer, pulse_names.len() as u64)?;
+        for name in &pulse_names {
+            write_str(&mut writer, name)?;
+        }
+
+        Ok(Self {
+            writer,
+            pulse_names,
+            partition_count: snapshot.partitions.len(),
+        })
+    }
+
+    /// Append one outcome.
+    pub fn record(&mut self, outcome: &TickOutcome) -> io::Result<()> {
+        let snapshot = &outcome.snapshot;
+        if snapshot.partitions.len() != self.partition_count {
+            return Err(invalid_input(
+                "partition count does not match journal header",
+            ));
+        }
+
+        write_varint(&mut self.writer, snapshot.tick)?;
+        write_varint(&mut self.writer, snapshot.epoch)?;
+        write_varint(&mut self.writer, outcome.overflowed as u64)?;
+        for part in &snapshot.partitions {
+            write_varint(&mut self.writer, part.value)?;
+        }
+        write_varint(&mut self.writer, outcome.pulses.len() as u64)?;
+        for pulse in &outcome.pulses {
+            match self.pulse_names.iter().position(|name| *name == pulse.name) {
+                Some(index) => write_varint(&mut self.writer, index as u64)?,
+                None => {
+                    write_varint(&mut self.writer, self.pulse_names.len() as u64)?;
+                    write_str(&mut self.writer, &pulse.name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads tick outcomes back from a journal written by [`TickRecorder`].
+#[derive(Debug)]
+pub struct TickReader<R: Read> {
+    reader: R,
+    partitions: Vec<(Arc<str>, u64)>,
+    order: PartitionOrder,
+    pulse_names: Vec<String>,
+}
+
+impl<R: Read> TickReader<R> {
+    /// Open a journal, reading and validating its header.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a beeclock journal"));
+        }
+        if read_varint(&mut reader)? != VERSION {
+            return Err(invalid_data("unsupported journal version"));
+        }
+        let order = match read_varint(&mut reader)? {
+            0 => PartitionOrder::LeastSignificantFirst,
+            1 => PartitionOrder::MostSignificantFirst,
+            _ => return Err(invalid_data("unknown partition order")),
+        };
+
+        let partition_count = read_len(&mut reader)?;
+        let mut partitions = Vec::with_capacity(partition_count);
+        for _ in 0..partition_count {
+            let name = read_str(&mut reader)?;
+            partitions.push((Arc::from(name), read_varint(&mut reader)?));
+        }
+        let pulse_count = read_len(&mut reader)?;
+        let mut pulse_names = Vec::with_capacity(pulse_count);
+        for _ in 0..pulse_count {
+            pulse_names.push(read_str(&mut reader)?);
+        }
+
+        Ok(Self {
+            reader,
+            partitions,
+            order,
+            pulse_names,
+        })
+    }
+
+    /// Read the next outcome, or `None` at a clean end of journal.
+    pub fn next_outcome(&mut self) -> io::Result<Option<TickOutcome>> {
+        let tick = match read_varint_or_eof(&mut self.reader)? {
+            Some(tick) => tick,
+            None => return Ok(None),
+        };
+        let epoch = read_varint(&mut self.reader)?;
+        let overflowed = read_varint(&mut self.reader)? != 0;
+
+        let mut states = Vec::with_capacity(self.partitions.len());
+        for (name, modulus) in &self.partitions {
+            states.push(PartitionState {
+                name: name.clone(),
+                value: read_varint(&mut self.reader)?,
+                modulus: *modulus,
+                meta: None,
+            });
+        }
+
+        let fired_count = read_len(&mut self.reader)?;
+        let mut pulses = PulseList::new();
+        for _ in 0..fired_count {
+            let index = read_len(&mut self.reader)?;
+            let name = match self.pulse_names.get(index) {
+                Some(name) => name.clone(),
+                None if index == self.pulse_names.len() => read_str(&mut self.reader)?,
+                None => return Err(invalid_data("pulse index out of range")),
+            };
+            pulses.push(PulseFired {
+                name,
+                tick,
+                epoch,
+                clock: None,
+            });
+        }
+
+        Ok(Some(TickOutcome {
+            clock: None,
+            snapshot: Arc::new(ClockSnapshot {
+                tick,
+                epoch,
+                partitions: states,
+                order: self.order,
+            }),
+            pulses,
+            overflowed,
+            quiet_reports: Vec::new(),
+            children: Vec::new(),
+        }))
+    }
+
+    /// Deliver every remaining outcome to `sinks`, returning the count.
+    ///
+    /// Closed sinks are skipped, as in live broadcast.
+    pub fn replay_into<S: OutcomeSink>(&mut self, sinks: &mut [S]) -> io::Result<u64> {
+        let mut open = vec![true; sinks.len()];
+        let mut count = 0;
+        while let Some(outcome) = self.next_outcome()? {
+            for (sink, open) in sinks.iter_mut().zip(&mut open) {
+                if *open {
+                    *open = sink.deliver(&outcome) == SinkStatus::Open;
+                }
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Tick `clock` once per remaining record and report the first mismatch.
+    ///
+    /// Outcomes match when tick, epoch, overflow flag, partition values, and
+    /// fired pulse names (in order) are equal.
+    pub fn compare(&mut self, clock: &mut Clock) -> io::Result<Option<Divergence>> {
+        let mut index = 0;
+        while let Some(expected) = self.next_outcome()? {
+            let actual = clock.tick();
+            if !outcomes_match(&expected, &actual) {
+                return Ok(Some(Divergence {
+                    index,
+                    expected,
+                    actual,
+                }));
+            }
+            index += 1;
+        }
+        Ok(None)
+    }
+}
+
+impl<R: Read> Iterator for TickReader<R> {
+    type Item = io::Result<TickOutcome>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_outcome().transpose()
+    }
+}
+
+/// First point where a live run disagreed with a journal.
+#[derive(Clone, Debug)]
+pub struct Divergence {
+    /// Zero-based record index.
+    pub index: u64,
+    pub expected: TickOutcome,
+    pub actual: TickOutcome,
+}
+
+fn outcomes_match(a: &TickOutcome, b: &TickOutcome) -> bool {
+    a.snapshot.tick == b.snapshot.tick
+        && a.snapshot.epoch == b.snapshot.epoch
+        && a.overflowed == b.overflowed
+        && a.snapshot.partitions.len() == b.snapshot.partitions.len()
+        && a.snapshot
+            .partitions
+            .iter()
+            .zip(&b.snapshot.partitions)
+            .all(|(x, y)| x.value == y.value)
+        && a.pulses.len() == b.pulses.len()
+        && a.pulses
+            .iter()
+            .zip(&b.pulses)
+            .all(|(x, y)| x.name == y.name)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Encoding
+// ─────────────────────────────────────────────────────────────
+
+pub(crate) fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> io::Result<()> {
+    let mut buf = [0u8; 10];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    writer.write_all(&buf[..len])
+}
+
+fn write_str<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write_varint(writer, value.len() as u64)?;
+    writer.write_all(value.as_bytes())
+}
+
+pub(crate) fn read_varint_or_eof<R: Read>(reader: &mut R) -> io::Result<Option<u64>> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        if reader.read(&mut byte)? == 0 {
+            return if shift == 0 {
+                Ok(None)
+            } else {
+                Err(io::ErrorKind::UnexpectedEof.into())
+            };
+        }
+        if shift >= 64 {
+            return Err(invalid_data("varint too long"));
+        }
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+        shift += 7;
+    }
+}
+
+pub(crate) fn read_varint<R: Read>(reader: &mut R) -> io::Result<u64> {
+    read_varint_or_eof(reader)?.ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+}
+
+fn read_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+    usize::try_from(read_varint(reader)?).map_err(|_| invalid_data("length out of range"))
+}
+
+fn read_str<R: Read>(reader: &mut R) -> io::Result<String> {
+    let len = read_len(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8 name"))
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub(crate) fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Subscriber;
+    use std::sync::mpsc;
+
+    fn clock(period: u64) -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("sec", 4)
+            .partition("min", 3)
+            .pulse_every("beat", period)
+            .build()
+            .unwrap()
+    }
+
+    fn journal(ticks: usize) -> Vec<u8> {
+        let mut source = clock(2);
+        let mut recorder = TickRecorder::new(Vec::new(), &source).unwrap();
+        for _ in 0..ticks {
+            recorder.record(&source.tick()).unwrap();
+        }
+        recorder.into_inner()
+    }
+
+    #[test]
+    fn round_trips_outcomes() {
+        let bytes = journal(30);
+        let outcomes: Vec<TickOutcome> = TickReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        let mut reference = clock(2);
+        assert_eq!(outcomes.len(), 30);
+        for outcome in &outcomes {
+            assert!(outcomes_match(outcome, &reference.tick()));
+        }
+        assert_eq!(outcomes[5].snapshot.get("min"), 1);
+        assert_eq!(outcomes[5].pulses[0].name, "beat");
+        assert_eq!(outcomes[5].snapshot.to_string(), "min=1 sec=2 @tick 6");
+    }
+
+    #[test]
+    fn records_partition_order_and_rejects_other_versions() {
+        let mut source = Clock::builder()
+            .most_significant_first()
+            .partition("min", 3)
+            .partition("sec", 4)
+            .build()
+            .unwrap();
+        let mut recorder = TickRecorder::new(Vec::new(), &source).unwrap();
+        recorder.record(&source.tick()).unwrap();
+        let mut bytes = recorder.into_inner();
+        let outcome = TickReader::new(bytes.as_slice())
+            .unwrap()
+            .next_outcome()
+            .unwrap()
+            .unwrap();
+        assert_eq!(outcome.snapshot.order, PartitionOrder::MostSignificantFirst);
+
+        bytes[MAGIC.len()] = 2;
+        let err = TickReader::new(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn compares_against_live_run() {
+        let bytes = journal(10);
+        assert!(TickReader::new(bytes.as_slice())
+            .unwrap()
+            .compare(&mut clock(2))
+            .unwrap()
+            .is_none());
+
+        let divergence = TickReader::new(bytes.as_slice())
+            .unwrap()
+            .compare(&mut clock(3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(divergence.index, 1);
+    }
+
+    #[test]
+    fn replays_into_subscribers() {
+        let bytes = journal(8);
+        let (tx, rx) = mpsc::channel();
+        let count = TickReader::new(bytes.as_slice())
+            .unwrap()
+            .replay_into(&mut [Subscriber::Unbounded(tx)])
+            .unwrap();
+        assert_eq!(count, 8);
+        assert_eq!(rx.try_iter().count(), 8);
+    }
+
+    #[test]
+    fn records_expiry_notices() {
+        let mut source = Clock::builder()
+            .pulse(
+                crate::PulseSpec::new("once", crate::PulseCondition::Every(2))
+                    .max_fires(1)
+                    .notify_on_expiry(),
+            )
+            .build()
+            .unwrap();
+        let mut recorder = TickRecorder::new(Vec::new(), &source).unwrap();
+        for _ in 0..3 {
+            recorder.record(&source.tick()).unwrap();
+        }
+        let bytes = recorder.into_inner();
+        let second = TickReader::new(bytes.as_slice())
+            .unwrap()
+            .nth(1)
+            .unwrap()
+            .unwrap();
+        let names: Vec<&str> = second.pulses.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["once", "__expired__:once"]);
+    }
+
+    #[test]
+    fn records_alarms_inline() {
+        let mut source = clock(2);
+        source
+            .set_alarm("wake", crate::AlarmTarget::Tick(2))
+            .unwrap();
+        let mut recorder = TickRecorder::new(Vec::new(), &source).unwrap();
+        for _ in 0..3 {
+            recorder.record(&source.tick()).unwrap();
+        }
+        let bytes = recorder.into_inner();
+        let outcomes: Vec<TickOutcome> = TickReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert!(outcomes[1].pulses.iter().any(|p| p.name == "wake"));
+        assert!(outcomes[2].pulses.iter().all(|p| p.name != "wake"));
+    }
+}
@@ -0,0 +1,253 @@
+//! Pulse dispatch by name pattern (std only).
+//!
+//! [`PulseRouter`] subscribes to any number of clocks, buffers matching
+//! pulses per route in bounded queues, and hands them to route handlers on
+//! [`PulseRouter::dispatch`].
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crate::{Clock, PulseFired, TickOutcome};
+
+/// Default per-route queue capacity for [`PulseRouter::route`].
+pub const DEFAULT_ROUTE_CAPACITY: usize = 1024;
+
+/// Identifies a route registered on a [`PulseRouter`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RouteId(usize);
+
+struct Route {
+    pattern: String,
+    capacity: usize,
+    queue: VecDeque<PulseFired>,
+    dropped: u64,
+    handler: Box<dyn FnMut(&PulseFired)>,
+}
+
+/// Dispatches pulses from subscribed clocks to handlers by name pattern.
+///
+/// Patterns match pulse names, with `*` matching any run of characters
+/// (`"*"` matches every pulse, `"alarm.*"` every pulse starting `alarm.`).
+/// A pulse is queued on every route it matches. Full queues drop new
+/// pulses, like bounded subscribers, and count them in
+/// [`PulseRouter::dropped`].
+#[derive(Default)]
+pub struct PulseRouter {
+    sources: Vec<Receiver<TickOutcome>>,
+    routes: Vec<Route>,
+}
+
+impl PulseRouter {
+    /// Create a router with no sources or routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to a clock's outcomes.
+    pub fn attach(&mut self, clock: &mut Clock) {
+        self.sources.push(clock.subscribe());
+    }
+
+    /// Read outcomes from an existing subscription.
+    pub fn attach_receiver(&mut self, receiver: Receiver<TickOutcome>) {
+        self.sources.push(receiver);
+    }
+
+    /// Register a handler for pulses matching `pattern`, with the default
+    /// queue capacity.
+    pub fn route(
+        &mut self,
+        pattern: impl Into<String>,
+        handler: impl FnMut(&PulseFired) + 'static,
+    ) -> RouteId {
+        self.route_bounded(pattern, DEFAULT_ROUTE_CAPACITY, handler)
+    }
+
+    /// Register a handler for pulses matching `pattern`, queueing at most
+    /// `capacity` undispatched pulses.
+    pub fn route_bounded(
+        &mut self,
+        pattern: impl Into<String>,
+        capacity: usize,
+        handler: impl FnMut(&PulseFired) + 'static,
+    ) -> RouteId {
+        self.routes.push(Route {
+            pattern: pattern.into(),
+            capacity,
+            queue: VecDeque::new(),
+            dropped: 0,
+            handler: Box::new(handler),
+        });
+        RouteId(self.routes.len() - 1)
+    }
+
+    /// Move every pending outcome from the sources into route queues,
+    /// returning how many pulses were queued. Disconnected sources are
+    /// dropped.
+    pub fn collect(&mut self) -> usize {
+        let mut queued = 0;
+        let routes = &mut self.routes;
+        self.sources.retain(|source| loop {
+            match source.try_recv() {
+                Ok(outcome) => {
+                    for pulse in outcome.pulses.iter() {
+                        queued += enqueue(routes, pulse);
+                    }
+                }
+                Err(TryRecvError::Empty) => break true,
+                Err(TryRecvError::Disconnected) => break false,
+            }
+        });
+        queued
+    }
+
+    /// Run handlers over every queued pulse, returning how many ran.
+    pub fn dispatch(&mut self) -> usize {
+        let mut handled = 0;
+        for route in &mut self.routes {
+            while let Some(pulse) = route.queue.pop_front() {
+                (route.handler)(&pulse);
+                handled += 1;
+            }
+        }
+        handled
+    }
+
+    /// [`collect`](Self::collect) then [`dispatch`](Self::dispatch).
+    pub fn pump(&mut self) -> usize {
+        self.collect();
+        self.dispatch()
+    }
+
+    /// Pulses waiting in a route's queue.
+    pub fn queued(&self, route: RouteId) -> usize {
+        self.routes[route.0].queue.len()
+    }
+
+    /// Pulses dropped because a route's queue was full.
+    pub fn dropped(&self, route: RouteId) -> u64 {
+        self.routes[route.0].dropped
+    }
+
+    /// Number of live sources.
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+}
+
+impl core::fmt::Debug for PulseRouter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let patterns: Vec<&str> = self.routes.iter().map(|r| r.pattern.as_str()).collect();
+        f.debug_struct("PulseRouter")
+            .field("sources", &self.sources.len())
+            .field("routes", &patterns)
+            .finish()
+    }
+}
+
+/// Queue `pulse` on every matching route, returning how many took it.
+fn enqueue(routes: &mut [Route], pulse: &PulseFired) -> usize {
+    let mut queued = 0;
+    for route in routes.iter_mut() {
+        if !matches_pattern(&route.pattern, &pulse.name) {
+            continue;
+        }
+        if route.queue.len() < route.capacity {
+            route.queue.push_back(pulse.clone());
+            queued += 1;
+        } else {
+            route.dropped += 1;
+        }
+    }
+    queued
+}
+
+/// Glob match where `*` matches any (possibly empty) run of characters.
+pub(crate) fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn glob_patterns() {
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("beat", "beat"));
+        assert!(!matches_pattern("beat", "beats"));
+        assert!(matches_pattern("alarm.*", "alarm.fire"));
+        assert!(!matches_pattern("alarm.*", "alarm"));
+        assert!(matches_pattern("*.fire", "alarm.fire"));
+        assert!(matches_pattern("a*b*c", "aXbYc"));
+        assert!(!matches_pattern("a*b*c", "aXcYb"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn routes_pulses_from_several_clocks() {
+        let build = |name: &str| {
+            Clock::builder()
+                .name(name)
+                .least_significant_first()
+                .partition("n", 10)
+                .pulse_every("beat", 1)
+                .pulse_every("alarm.even", 2)
+                .build()
+                .unwrap()
+        };
+        let (mut a, mut b) = (build("a"), build("b"));
+
+        let mut router = PulseRouter::new();
+        router.attach(&mut a);
+        router.attach(&mut b);
+
+        let alarms = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&alarms);
+        router.route("alarm.*", move |pulse| {
+            sink.borrow_mut()
+                .push((pulse.clock.as_deref().map(String::from), pulse.tick))
+        });
+        let beats = router.route_bounded("beat", 3, |_| {});
+
+        for _ in 0..4 {
+            a.tick();
+        }
+        b.tick();
+        b.tick();
+
+        assert_eq!(router.collect(), 3 + 3);
+        assert_eq!(router.queued(beats), 3);
+        assert_eq!(router.dropped(beats), 3);
+        assert_eq!(router.dispatch(), 6);
+        assert_eq!(
+            *alarms.borrow(),
+            [
+                (Some("a".into()), 2),
+                (Some("a".into()), 4),
+                (Some("b".into()), 2),
+            ]
+        );
+
+        drop(a);
+        assert_eq!(router.pump(), 0);
+        assert_eq!(router.source_count(), 1);
+    }
+}
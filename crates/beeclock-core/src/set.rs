@@ -0,0 +1,178 @@
+//! Named groups of clocks ticked together.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{Clock, ClockError, TickOutcome};
+
+/// A tick outcome tagged with the name of the clock that produced it.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
+pub struct TaggedOutcome {
+    pub clock: Arc<str>,
+    pub outcome: TickOutcome,
+}
+
+#[derive(Debug)]
+struct Member {
+    name: Arc<str>,
+    clock: Clock,
+    divider: u64,
+}
+
+/// Several named clocks driven from one tick source.
+///
+/// Each [`ClockSet::tick`] advances the set by one step. A clock inserted
+/// with divider `n` ticks on every `n`th step, so a fast "frame" clock and a
+/// slow "calendar" clock stay in lockstep. Outcomes come back in insertion
+/// order, tagged with the clock name.
+#[derive(Debug, Default)]
+pub struct ClockSet {
+    members: Vec<Member>,
+    steps: u64,
+}
+
+impl ClockSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a clock that ticks on every step.
+    pub fn insert(&mut self, name: impl Into<String>, clock: Clock) -> Result<(), ClockError> {
+        self.insert_with_divider(name, clock, 1)
+    }
+
+    /// Add a clock that ticks once every `divider` steps.
+    pub fn insert_with_divider(
+        &mut self,
+        name: impl Into<String>,
+        clock: Clock,
+        divider: u64,
+    ) -> Result<(), ClockError> {
+        let name = name.into();
+        if divider == 0 {
+            return Err(ClockError::ZeroDivider { name });
+        }
+        if self.position(&name).is_some() {
+            return Err(ClockError::DuplicateClock { name });
+        }
+        self.members.push(Member {
+            name: Arc::from(name),
+            clock,
+            divider,
+        });
+        Ok(())
+    }
+
+    /// Remove a clock by name.
+    pub fn remove(&mut self, name: &str) -> Option<Clock> {
+        self.position(name)
+            .map(|idx| self.members.remove(idx).clock)
+    }
+
+    /// Get a clock by name.
+    pub fn get(&self, name: &str) -> Option<&Clock> {
+        self.position(name).map(|idx| &self.members[idx].clock)
+    }
+
+    /// Get a clock by name, mutably.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Clock> {
+        self.position(name)
+            .map(move |idx| &mut self.members[idx].clock)
+    }
+
+    /// Clock names in insertion order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|m| &*m.name)
+    }
+
+    /// Number of clocks in the set.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Whether the set holds no clocks.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Number of steps taken so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Advance one step, ticking every clock whose divider is due.
+    pub fn tick(&mut self) -> Vec<TaggedOutcome> {
+        self.steps += 1;
+        let steps = self.steps;
+        self.members
+            .iter_mut()
+            .filter(|m| steps.is_multiple_of(m.divider))
+            .map(|m| TaggedOutcome {
+                clock: Arc::clone(&m.name),
+                outcome: m.clock.tick(),
+            })
+            .collect()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.members.iter().position(|m| &*m.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn clock(modulus: u64) -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("n", modulus)
+            .pulse_every("each", 1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn dividers_keep_clocks_coordinated() {
+        let mut set = ClockSet::new();
+        set.insert("frame", clock(60)).unwrap();
+        set.insert_with_divider("calendar", clock(24), 3).unwrap();
+
+        let mut merged = Vec::new();
+        for _ in 0..6 {
+            merged.extend(set.tick());
+        }
+        let tags: Vec<&str> = merged.iter().map(|t| &*t.clock).collect();
+        assert_eq!(
+            tags,
+            vec!["frame", "frame", "frame", "calendar", "frame", "frame", "frame", "calendar"]
+        );
+        assert_eq!(set.get("frame").unwrap().tick_count(), 6);
+        assert_eq!(set.get("calendar").unwrap().tick_count(), 2);
+        assert_eq!(set.steps(), 6);
+    }
+
+    #[test]
+    fn rejects_duplicates_and_zero_dividers() {
+        let mut set = ClockSet::new();
+        set.insert("a", clock(10)).unwrap();
+        assert!(matches!(
+            set.insert("a", clock(10)),
+            Err(ClockError::DuplicateClock { .. })
+        ));
+        assert!(matches!(
+            set.insert_with_divider("b", clock(10), 0),
+            Err(ClockError::ZeroDivider { .. })
+        ));
+        assert!(set.remove("a").is_some());
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Clock shared between interrupt handlers and the main loop.
+//!
+//! [`SharedClock`] can live in a `static`: an ISR calls
+//! [`tick`](SharedClock::tick) while the main loop reads the clock, each
+//! inside a critical section supplied through [`CriticalSection`]. With the
+//! `critical-section` feature, [`GlobalSection`] uses the
+//! [`critical-section`](https://docs.rs/critical-section) crate's, which the
+//! HAL or RTOS provides; without it, implement the trait directly.
+//!
+//! ```rust
+//! # #[cfg(feature = "critical-section")] {
+//! use beeclock_core::{beeclock, ClockFixed, GlobalSection, SharedClock};
+//!
+//! beeclock! {
+//!     const WALL = lsf {
+//!         partitions { sec: 60, min: 60 }
+//!         pulses { minute: sec == 0 }
+//!     }
+//! }
+//!
+//! static CLOCK: SharedClock<ClockFixed<2, 1>, GlobalSection> = SharedClock::new(WALL);
+//!
+//! // In the timer interrupt:
+//! let outcome = CLOCK.tick();
+//! // In the main loop:
+//! assert_eq!(CLOCK.values(), outcome.values);
+//! # }
+//! ```
+//!
+//! # Latency
+//!
+//! Interrupts stay masked for exactly one closure passed to
+//! [`SharedClock::with`]. For a [`ClockFixed`] that is one tick: at most `P`
+//! partition increments and one evaluation of each of the `Q` pulse
+//! conditions, where each partition lookup scans at most `P` names. Nothing
+//! allocates and nothing loops on external state, so the worst case is fixed
+//! by the layout and can be measured once. The read helpers copy at most `P`
+//! values. A [`Clock`](crate::Clock) can be shared too, but its `tick`
+//! allocates; call [`tick_into`](crate::Clock::tick_into) with a buffer
+//! kept alongside it instead.
+
+use core::cell::{Cell, UnsafeCell};
+use core::marker::PhantomData;
+
+use crate::{ClockFixed, FixedOutcome, TickWidth};
+
+/// Runs a closure with every other [`SharedClock`] access excluded,
+/// typically by masking interrupts.
+///
+/// # Safety
+///
+/// While `f` runs, no other call to `with` on the same implementation may
+/// run, on any core or at any interrupt priority.
+pub unsafe trait CriticalSection {
+    /// Run `f` with all other accesses excluded.
+    fn with<R>(f: impl FnOnce() -> R) -> R;
+}
+
+/// The global critical section of the `critical-section` crate.
+#[cfg(feature = "critical-section")]
+#[derive(Clone, Copy, Debug)]
+pub struct GlobalSection;
+
+// SAFETY: `critical_section::with` excludes every other critical section.
+#[cfg(feature = "critical-section")]
+unsafe impl CriticalSection for GlobalSection {
+    fn with<R>(f: impl FnOnce() -> R) -> R {
+        critical_section::with(|_| f())
+    }
+}
+
+/// A clock behind a critical section, usable from a `static`.
+pub struct SharedClock<K, C> {
+    clock: UnsafeCell<K>,
+    /// Set while a `with` closure runs, to catch re-entrant access.
+    busy: Cell<bool>,
+    _section: PhantomData<fn() -> C>,
+}
+
+// SAFETY: every access to `clock` and `busy` happens inside `C::with`, which
+// excludes all other accesses, and `busy` rules out re-entry from within.
+unsafe impl<K: Send, C: CriticalSection> Sync for SharedClock<K, C> {}
+
+impl<K, C: CriticalSection> SharedClock<K, C> {
+    /// Wrap `clock`; `const`, so it can initialise a `static`.
+    pub const fn new(clock: K) -> Self {
+        Self {
+            clock: UnsafeCell::new(clock),
+            busy: Cell::new(false),
+            _section: PhantomData,
+        }
+    }
+
+    /// Run `f` on the clock inside a critical section.
+    ///
+    /// # Panics
+    ///
+    /// If called from inside another `with` on the same clock.
+    pub fn with<R>(&self, f: impl FnOnce(&mut K) -> R) -> R {
+        C::with(|| {
+            assert!(
+                !self.busy.replace(true),
+                "SharedClock accessed re-entrantly"
+            );
+            // Cleared even if `f` panics, so the clock stays usable.
+            let _busy = Busy(&self.busy);
+            // SAFETY: the critical section excludes other accesses and
+            // `busy` excludes nested ones, so this is the only reference.
+            f(unsafe { &mut *self.clock.get() })
+        })
+    }
+
+    /// Unwrap the clock.
+    pub fn into_inner(self) -> K {
+        self.clock.into_inner()
+    }
+}
+
+impl<const P: usize, const Q: usize, T: TickWidth, C: CriticalSection>
+    SharedClock<ClockFixed<P, Q, T>, C>
+{
+    /// Advance the clock by one tick.
+    pub fn tick(&self) -> FixedOutcome<P, Q, T> {
+        self.with(ClockFixed::tick)
+    }
+
+    /// Copy of the partition values.
+    pub fn values(&self) -> [u64; P] {
+        self.with(|clock| *clock.values())
+    }
+
+    /// Current tick count.
+    pub fn tick_count(&self) -> T {
+        self.with(|clock| clock.tick_count())
+    }
+}
+
+/// Clears the busy flag when dropped.
+struct Busy<'a>(&'a Cell<bool>);
+
+impl Drop for Busy<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clock;
+
+    struct Unmasked;
+
+    unsafe impl CriticalSection for Unmasked {
+        fn with<R>(f: impl FnOnce() -> R) -> R {
+            f()
+        }
+    }
+
+    crate::beeclock! {
+        const TINY: u32 = lsf {
+            partitions { sec: 60 }
+            pulses { ten: every(10) }
+        }
+    }
+
+    static SHARED: SharedClock<ClockFixed<1, 1, u32>, Unmasked> = SharedClock::new(TINY);
+
+    #[test]
+    fn ticks_and_reads_through_the_section() {
+        let fired = (0..20).filter(|_| SHARED.tick().fired[0]).count();
+        assert_eq!(fired, 2);
+        assert_eq!((SHARED.tick_count(), SHARED.values()), (20, [20]));
+
+        let shared: SharedClock<Clock, Unmasked> = SharedClock::new(Clock::default());
+        let mut buffer = shared.with(|clock| clock.tick_buffer());
+        shared.with(|clock| clock.tick_into(&mut buffer));
+        assert_eq!(shared.into_inner().tick_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "re-entrantly")]
+    fn rejects_nested_access() {
+        let shared: SharedClock<_, Unmasked> = SharedClock::new(TINY);
+        shared.with(|_| shared.tick());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn stays_usable_after_a_panic() {
+        let shared: SharedClock<_, Unmasked> = SharedClock::new(TINY);
+        let caught = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            shared.with(|_| panic!("handler failed"));
+        }));
+        assert!(caught.is_err());
+        shared.tick();
+        assert_eq!(shared.tick_count(), 1);
+    }
+
+    #[cfg(feature = "critical-section")]
+    #[test]
+    fn ticks_inside_the_global_section() {
+        static GLOBAL: SharedClock<ClockFixed<1, 1, u32>, GlobalSection> = SharedClock::new(TINY);
+        let fired = (0..10).filter(|_| GLOBAL.tick().fired[0]).count();
+        assert_eq!((fired, GLOBAL.values()), (1, [10]));
+    }
+}
@@ -0,0 +1,818 @@
+//! Schedule simulation.
+//!
+//! [`Clock::simulate`] runs a detached copy of a clock forward and reports
+//! when each pulse fired, without touching the original clock or notifying
+//! its subscribers. [`Clock::export_schedule`] renders the same run as a
+//! CSV or JSON table of `(tick, pulse)` rows, and [`Clock::peek`] previews a
+//! single upcoming tick's full outcome.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::partition::PartitionTable;
+use crate::{Clock, ClockConfig, ClockError, PulseCondition, PulseSpec, TickOutcome};
+
+/// Most ticks [`Clock::count_fires`] visits one by one before giving up.
+pub const COUNT_FIRES_MAX_STEPS: u64 = 1 << 20;
+
+/// Firing schedule of every pulse over a simulated horizon.
+#[derive(Clone, Debug)]
+pub struct ScheduleReport {
+    /// Tick the simulation started from (exclusive).
+    pub start_tick: u64,
+    /// Number of ticks simulated.
+    pub horizon: u64,
+    /// One entry per pulse, in configuration order.
+    pub pulses: Vec<PulseSchedule>,
+}
+
+impl ScheduleReport {
+    /// Get the schedule for a pulse by name.
+    pub fn pulse(&self, name: &str) -> Option<&PulseSchedule> {
+        self.pulses.iter().find(|p| p.name == name)
+    }
+
+    /// Total number of fires across all pulses.
+    pub fn total_fires(&self) -> usize {
+        self.pulses.iter().map(|p| p.fire_ticks.len()).sum()
+    }
+}
+
+/// Ticks at which a single pulse fired during a simulation.
+#[derive(Clone, Debug)]
+pub struct PulseSchedule {
+    pub name: String,
+    pub fire_ticks: Vec<u64>,
+}
+
+impl PulseSchedule {
+    /// Number of fires.
+    pub fn count(&self) -> usize {
+        self.fire_ticks.len()
+    }
+
+    /// First tick the pulse fired at.
+    pub fn first(&self) -> Option<u64> {
+        self.fire_ticks.first().copied()
+    }
+
+    /// Last tick the pulse fired at.
+    pub fn last(&self) -> Option<u64> {
+        self.fire_ticks.last().copied()
+    }
+
+    /// Ticks between consecutive fires.
+    pub fn gaps(&self) -> impl Iterator<Item = u64> + '_ {
+        self.fire_ticks.windows(2).map(|w| w[1].wrapping_sub(w[0]))
+    }
+
+    /// Smallest gap between consecutive fires.
+    pub fn min_gap(&self) -> Option<u64> {
+        self.gaps().min()
+    }
+
+    /// Largest gap between consecutive fires.
+    pub fn max_gap(&self) -> Option<u64> {
+        self.gaps().max()
+    }
+}
+
+/// Output format for [`Clock::export_schedule`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScheduleFormat {
+    /// `tick,pulse` header followed by one row per fire.
+    Csv,
+    /// Array of `{"tick": n, "pulse": "name"}` objects.
+    Json,
+}
+
+impl Clock {
+    /// Simulate the next `horizon` ticks on a detached copy of this clock.
+    ///
+    /// The clock itself is not advanced and subscribers are not notified.
+    pub fn simulate(&self, horizon: u64) -> ScheduleReport {
+        let mut sim = self.detached();
+        let mut buffer = sim.tick_buffer();
+        let mut pulses: Vec<PulseSchedule> = (0..sim.pulse_count())
+            .map(|idx| PulseSchedule {
+                name: String::from(sim.pulse_name(idx).unwrap_or_default()),
+                fire_ticks: Vec::new(),
+            })
+            .collect();
+
+        for _ in 0..horizon {
+            sim.tick_into(&mut buffer);
+            for &idx in &buffer.fired {
+                pulses[idx].fire_ticks.push(buffer.tick);
+            }
+        }
+
+        ScheduleReport {
+            start_tick: self.tick_count(),
+            horizon,
+            pulses,
+        }
+    }
+}
+
+impl Clock {
+    /// Outcome the next tick would produce, without advancing the clock.
+    ///
+    /// Equivalent to `peek_at(1)`.
+    pub fn peek(&self) -> TickOutcome {
+        self.detached().step()
+    }
+
+    /// Outcome of the tick `n` ticks ahead, without advancing the clock.
+    ///
+    /// The ticks in between run on a detached copy, so pulse state such as
+    /// rate limits and delays carries over exactly as it would for real.
+    /// Returns `None` for `n == 0`. Subscribers are not notified and metrics
+    /// are not recorded.
+    pub fn peek_at(&self, n: u64) -> Option<TickOutcome> {
+        let ahead = n.checked_sub(1)?;
+        let mut sim = self.detached();
+        let mut buffer = sim.tick_buffer();
+        for _ in 0..ahead {
+            sim.tick_into(&mut buffer);
+        }
+        Some(sim.step())
+    }
+
+    /// Tick at which `pulse_name` next fires, searching at most `horizon`
+    /// ticks ahead. Returns `None` if it does not fire in that window or no
+    /// pulse has this name.
+    pub fn next_fire(&self, pulse_name: &str, horizon: u64) -> Option<u64> {
+        self.fire_schedule(pulse_name, 1, horizon).first().copied()
+    }
+
+    /// Ticks of the next `count` fires of `pulse_name`, searching at most
+    /// `horizon` ticks ahead. Stops early once `count` fires are found;
+    /// empty if no pulse has this name.
+    pub fn fire_schedule(&self, pulse_name: &str, count: usize, horizon: u64) -> Vec<u64> {
+        let mut fires = Vec::new();
+        let Some(index) =
+            (0..self.pulse_count()).find(|&idx| self.pulse_name(idx) == Some(pulse_name))
+        else {
+            return fires;
+        };
+        let mut sim = self.detached();
+        let mut buffer = sim.tick_buffer();
+        for _ in 0..horizon {
+            if fires.len() >= count {
+                break;
+            }
+            sim.tick_into(&mut buffer);
+            if buffer.fired.contains(&index) {
+                fires.push(buffer.tick);
+            }
+        }
+        fires
+    }
+
+    /// Whether `pulse_name` would fire on `tick` of the current epoch, past
+    /// or future, answered without simulating the ticks in between.
+    ///
+    /// The partition values on `tick` (and the tick before, for delta
+    /// conditions) are computed from where the clock stands now, assuming
+    /// nothing is set in between. Expiry by tick, an already expired pulse
+    /// and the run budget count; quiet windows do not. Returns `None` if no
+    /// pulse has this name, or if the answer depends on runtime history
+    /// ([stateful](PulseCondition::is_stateful) conditions and delayed
+    /// pulses); use [`Clock::fire_schedule`] for those.
+    pub fn will_fire_at(&self, pulse_name: &str, tick: u64) -> Option<bool> {
+        let index = self.pulses.iter().position(|p| p.name == pulse_name)?;
+        let pulse = &self.pulses[index];
+        if self.fires_depend_on_history(pulse) {
+            return None;
+        }
+        if tick > self.last_possible_fire(index) {
+            return Some(false);
+        }
+        Some(pulse.condition.evaluate(tick, &self.partitions_at(tick)))
+    }
+
+    /// Number of ticks in `start_tick..=end_tick` of the current epoch on
+    /// which `pulse_name` fires.
+    ///
+    /// Where [`Clock::will_fire_at`] can answer, this counts the ticks it
+    /// would say yes to: in closed form for [`Every`](PulseCondition::Every),
+    /// [`TickRange`](PulseCondition::TickRange), partition equality and
+    /// modulo conditions and their negations, so ranges of any length are
+    /// cheap, and by stepping through the range otherwise. Pulses whose fires
+    /// depend on runtime history are simulated on a detached copy up to
+    /// `end_tick`, and only their future fires are counted.
+    ///
+    /// `None` if no pulse has this name, if stepping or simulating would
+    /// take more than [`COUNT_FIRES_MAX_STEPS`] ticks, or if the count
+    /// doesn't fit in a `u64` (every tick of a whole epoch).
+    ///
+    /// ```
+    /// use beeclock_core::Clock;
+    ///
+    /// let clock = Clock::builder()
+    ///     .least_significant_first()
+    ///     .partition("sec", 60)
+    ///     .partition("min", 60)
+    ///     .partition("hour", 24)
+    ///     .pulse_when("quarter", "min % 15 == 0".parse().unwrap())
+    ///     .pulse_when("sync", "sec == 0 && min % 15 == 0".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let day = 24 * 60 * 60;
+    /// assert_eq!(clock.count_fires("sync", 1, day), Some(96));
+    /// assert_eq!(clock.count_fires("quarter", 1, 365 * day), Some(365 * 96 * 60));
+    /// ```
+    pub fn count_fires(&self, pulse_name: &str, start_tick: u64, end_tick: u64) -> Option<u64> {
+        let index = self.pulses.iter().position(|p| p.name == pulse_name)?;
+        let pulse = &self.pulses[index];
+        if self.fires_depend_on_history(pulse) {
+            return self.simulated_fires(index, start_tick, end_tick);
+        }
+        let end = end_tick.min(self.last_possible_fire(index));
+        if start_tick > end {
+            return Some(0);
+        }
+        if let Some(count) = self.closed_form_fires(&pulse.condition, start_tick, end) {
+            return Some(count);
+        }
+        if end - start_tick >= COUNT_FIRES_MAX_STEPS {
+            return None;
+        }
+        let mut partitions = self.partitions_at(start_tick);
+        let mut count = u64::from(pulse.condition.evaluate(start_tick, &partitions));
+        for tick in start_tick..end {
+            partitions.advance(self.partition_order);
+            count += u64::from(pulse.condition.evaluate(tick + 1, &partitions));
+        }
+        Some(count)
+    }
+
+    /// Whether a pulse's fires can only be known by running the clock.
+    fn fires_depend_on_history(&self, pulse: &PulseSpec) -> bool {
+        pulse.condition.is_stateful() || pulse.after.is_some()
+    }
+
+    /// Last tick the pulse at `index` can fire on, given its expiry and the
+    /// run budget.
+    fn last_possible_fire(&self, index: usize) -> u64 {
+        let expired = self.pulse_state[index].expired.then_some(self.tick);
+        [expired, self.pulses[index].expires_at_tick, self.run_for()]
+            .into_iter()
+            .flatten()
+            .fold(u64::MAX, u64::min)
+    }
+
+    /// Partition values on `tick`, with the tick before as the previous
+    /// values for delta conditions.
+    fn partitions_at(&self, tick: u64) -> PartitionTable {
+        let mut partitions = self.partitions.clone();
+        let before = tick.saturating_sub(1);
+        match before.checked_sub(self.tick) {
+            Some(ahead) => partitions.advance_by(ahead, self.partition_order),
+            None => partitions.rewind_by(self.tick - before, self.partition_order),
+        }
+        if tick > 0 {
+            partitions.advance(self.partition_order);
+        }
+        partitions
+    }
+
+    /// Ticks in `start..=end` on which `condition` holds, if countable
+    /// without visiting each one.
+    fn closed_form_fires(&self, condition: &PulseCondition, start: u64, end: u64) -> Option<u64> {
+        match condition {
+            PulseCondition::Every(period) => {
+                let first = start.max(1);
+                Some(if first > end {
+                    0
+                } else {
+                    end / period - (first - 1) / period
+                })
+            }
+            PulseCondition::TickRange {
+                start: from,
+                end: to,
+            } => {
+                let (first, last) = (start.max(*from), end.min(*to));
+                if first > last {
+                    Some(0)
+                } else {
+                    (last - first).checked_add(1)
+                }
+            }
+            PulseCondition::PartitionEquals { name, value } => {
+                self.digit_fires(name, start, end, |below| u64::from(*value < below))
+            }
+            PulseCondition::PartitionModulo {
+                name,
+                modulus,
+                remainder,
+            } => self.digit_fires(name, start, end, |below| match *modulus {
+                0 => 0,
+                m if below > *remainder => (below - 1 - remainder) / m + 1,
+                _ => 0,
+            }),
+            PulseCondition::Not(inner) => {
+                let held = self.closed_form_fires(inner, start, end)?;
+                let ticks = (end - start) as u128 + 1;
+                u64::try_from(ticks - held as u128).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Ticks in `start..=end` on which partition `name` holds an allowed
+    /// value, where `allowed_below(k)` counts the allowed values under `k`.
+    ///
+    /// The partition moves through a fixed cycle of `modulus * weight`
+    /// ticks, so whole cycles count alike and only the ends need care.
+    /// `None` if the cycle is too long to count in.
+    fn digit_fires(
+        &self,
+        name: &str,
+        start: u64,
+        end: u64,
+        allowed_below: impl Fn(u64) -> u64,
+    ) -> Option<u64> {
+        let Some(idx) = self.partitions.position(name) else {
+            return Some(0);
+        };
+        let (value, modulus, offset, weight) = self.partitions.digit(idx, self.partition_order)?;
+        let cycle = weight.checked_mul(modulus as u128)?;
+        // Position of `start` within the cycle.
+        let now = value as u128 * weight + offset;
+        let first = match start.checked_sub(self.tick) {
+            Some(ahead) => now.checked_add(ahead as u128 % cycle)? % cycle,
+            None => now.checked_add(cycle - (self.tick - start) as u128 % cycle)? % cycle,
+        };
+        // Fires at cycle positions `0..position`, counted from 0.
+        let fires_before = |position: u128| -> Option<u128> {
+            let (cycles, rest) = (position / cycle, position % cycle);
+            let (digit, into) = ((rest / weight) as u64, rest % weight);
+            let allowed = allowed_below(digit + 1) > allowed_below(digit);
+            let partial = allowed_below(digit) as u128 * weight + if allowed { into } else { 0 };
+            cycles
+                .checked_mul(allowed_below(modulus) as u128 * weight)?
+                .checked_add(partial)
+        };
+        let last = first.checked_add((end - start) as u128 + 1)?;
+        u64::try_from(fires_before(last)? - fires_before(first)?).ok()
+    }
+
+    /// Fires of the pulse at `index` on ticks in `start..=end`, found by
+    /// running a detached copy of the clock up to `end`.
+    fn simulated_fires(&self, index: usize, start: u64, end: u64) -> Option<u64> {
+        let end = end.min(self.last_possible_fire(index));
+        if end.saturating_sub(self.tick) > COUNT_FIRES_MAX_STEPS {
+            return None;
+        }
+        let mut sim = self.detached();
+        let mut buffer = sim.tick_buffer();
+        let mut count = 0;
+        while sim.tick < end && !sim.is_halted() {
+            sim.tick_into(&mut buffer);
+            count += u64::from(buffer.tick >= start && buffer.fired.contains(&index));
+        }
+        Some(count)
+    }
+
+    /// Export the `(tick, pulse)` fires over the next `horizon` ticks.
+    ///
+    /// Rows are ordered by tick, then pulse configuration order. Like
+    /// [`Clock::simulate`], this runs on a detached copy of the clock.
+    pub fn export_schedule(&self, horizon: u64, format: ScheduleFormat) -> String {
+        let mut sim = self.detached();
+        let mut buffer = sim.tick_buffer();
+        let mut out = String::new();
+        let mut first = true;
+
+        match format {
+            ScheduleFormat::Csv => out.push_str("tick,pulse\n"),
+            ScheduleFormat::Json => out.push('['),
+        }
+        for _ in 0..horizon {
+            sim.tick_into(&mut buffer);
+            for &idx in &buffer.fired {
+                let name = sim.pulse_name(idx).unwrap_or_default();
+                match format {
+                    ScheduleFormat::Csv => {
+                        let _ = write!(out, "{},", buffer.tick);
+                        write_csv_field(&mut out, name);
+                        out.push('\n');
+                    }
+                    ScheduleFormat::Json => {
+                        if !first {
+                            out.push(',');
+                        }
+                        let _ = write!(out, "{{\"tick\":{},\"pulse\":", buffer.tick);
+                        write_json_string(&mut out, name);
+                        out.push('}');
+                    }
+                }
+                first = false;
+            }
+        }
+        if format == ScheduleFormat::Json {
+            out.push(']');
+        }
+        out
+    }
+}
+
+impl PulseCondition {
+    /// Whether `self` and `other` fire on exactly the same ticks over the
+    /// first `horizon` ticks of a fresh clock with `config`'s layout
+    /// (partitions, epoch partitions and ticks per unit).
+    ///
+    /// Fails if either condition is invalid for the configuration, or with
+    /// [`ClockError::StatefulCondition`] if either is
+    /// [stateful](PulseCondition::is_stateful), since their fires depend on
+    /// history rather than on the tick alone.
+    pub fn equivalent_over(
+        &self,
+        other: &PulseCondition,
+        config: &ClockConfig,
+        horizon: u64,
+    ) -> Result<bool, ClockError> {
+        Ok(self
+            .first_difference_over(other, config, horizon)?
+            .is_none())
+    }
+
+    /// First tick within `horizon` where `self` and `other` disagree, if any.
+    pub fn first_difference_over(
+        &self,
+        other: &PulseCondition,
+        config: &ClockConfig,
+        horizon: u64,
+    ) -> Result<Option<u64>, ClockError> {
+        for (name, condition) in [("lhs", self), ("rhs", other)] {
+            if condition.is_stateful() {
+                return Err(ClockError::StatefulCondition { name: name.into() });
+            }
+        }
+        let mut config = config.clone();
+        config.pulses = alloc::vec![
+            PulseSpec::new("lhs", self.clone()),
+            PulseSpec::new("rhs", other.clone()),
+        ];
+        config.quiet_windows.clear();
+        config.run_for = None;
+        config.overflow_pulse = None;
+        let mut clock = config.build()?;
+        let mut buffer = clock.tick_buffer();
+        for _ in 0..horizon {
+            clock.tick_into(&mut buffer);
+            let lhs = buffer.fired.contains(&0);
+            let rhs = buffer.fired.contains(&1);
+            if lhs != rhs {
+                return Ok(Some(buffer.tick));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn write_csv_field(out: &mut String, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&value.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(value);
+    }
+}
+
+pub(crate) fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::COUNT_FIRES_MAX_STEPS;
+    use crate::{Clock, ClockError, PulseCondition, ScheduleFormat};
+
+    #[test]
+    fn reports_fires_and_gaps_without_advancing() {
+        let clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 5)
+            .pulse_every("three", 3)
+            .pulse_when(
+                "wrap",
+                PulseCondition::PartitionEquals {
+                    name: "sec".into(),
+                    value: 0,
+                },
+            )
+            .build()
+            .unwrap();
+
+        let report = clock.simulate(12);
+        assert_eq!(clock.tick_count(), 0);
+        assert_eq!(report.horizon, 12);
+
+        assert_eq!(clock.next_fire("wrap", 100), Some(5));
+        assert_eq!(clock.next_fire("wrap", 4), None);
+        assert_eq!(clock.next_fire("missing", 100), None);
+        assert_eq!(clock.fire_schedule("wrap", 3, 100), [5, 10, 15]);
+        assert_eq!(clock.fire_schedule("three", 10, 7), [3, 6]);
+        assert!(clock.fire_schedule("missing", 3, 100).is_empty());
+
+        let three = report.pulse("three").unwrap();
+        assert_eq!(three.fire_ticks, [3, 6, 9, 12]);
+        assert_eq!(three.min_gap(), Some(3));
+
+        let wrap = report.pulse("wrap").unwrap();
+        assert_eq!((wrap.first(), wrap.last()), (Some(5), Some(10)));
+        assert_eq!(report.total_fires(), 6);
+    }
+
+    #[test]
+    fn answers_fire_queries_for_past_and_future_ticks() {
+        let build = || {
+            let mut clock = Clock::builder()
+                .most_significant_first()
+                .partition("hour", 3)
+                .partition("min", 4)
+                .partition("sec", 5)
+                .pulse_every("seven", 7)
+                .pulse_when("hourly", "changed(hour)".parse().unwrap())
+                .pulse_when("wrap", "decreased(hour)".parse().unwrap())
+                .pulse_when("odd", "sec % 2 == 1 && !(min == 2)".parse().unwrap())
+                .pulse(crate::PulseSpec::new("early", PulseCondition::Every(2)).expires_at(50))
+                .build()
+                .unwrap();
+            clock.set_partition("min", 3).unwrap();
+            clock
+        };
+        let names = ["seven", "hourly", "wrap", "odd", "early"];
+        let mut reference = build();
+        let fired: Vec<Vec<String>> = (0..200)
+            .map(|_| {
+                reference
+                    .tick()
+                    .pulses
+                    .iter()
+                    .map(|p| p.name.clone())
+                    .collect()
+            })
+            .collect();
+
+        let mut clock = build();
+        clock.skip(100);
+        for tick in 1..=200u64 {
+            for name in names {
+                let expected = fired[tick as usize - 1].iter().any(|p| p == name);
+                assert_eq!(
+                    clock.will_fire_at(name, tick),
+                    Some(expected),
+                    "{name} at {tick}"
+                );
+            }
+        }
+        assert_eq!(clock.tick_count(), 100);
+        assert_eq!(clock.will_fire_at("seven", 0), Some(false));
+        assert_eq!(clock.will_fire_at("missing", 7), None);
+
+        let limited = Clock::builder()
+            .pulse_when("calm", "debounce(3, every(2))".parse().unwrap())
+            .pulse_after("echo", "calm", 1)
+            .build()
+            .unwrap();
+        assert_eq!(limited.will_fire_at("calm", 6), None);
+        assert_eq!(limited.will_fire_at("echo", 7), None);
+    }
+
+    #[test]
+    fn counts_fires_in_closed_form_and_by_stepping() {
+        let mut clock = Clock::builder()
+            .most_significant_first()
+            .partition("day", 7)
+            .partition("hour", 6)
+            .partition("min", 5)
+            .pulse_every("seven", 7)
+            .pulse_when("window", "tick in 20..=90".parse().unwrap())
+            .pulse_when("noon", "hour == 3".parse().unwrap())
+            .pulse_when("even", "min % 2 == 0".parse().unwrap())
+            .pulse_when("odd_hour", "!(hour % 2 == 0)".parse().unwrap())
+            .pulse_when(
+                "busy",
+                "changed(hour) || day == 2 && min == 1".parse().unwrap(),
+            )
+            .pulse(crate::PulseSpec::new("early", PulseCondition::Every(3)).expires_at(150))
+            .build()
+            .unwrap();
+        clock.set_partition("hour", 4).unwrap();
+        clock.skip(123);
+
+        let names = [
+            "seven", "window", "noon", "even", "odd_hour", "busy", "early",
+        ];
+        for (start, end) in [
+            (0, 0),
+            (1, 40),
+            (17, 123),
+            (100, 400),
+            (123, 124),
+            (250, 1000),
+        ] {
+            for name in names {
+                let stepped = (start..=end)
+                    .filter(|&tick| clock.will_fire_at(name, tick).unwrap())
+                    .count() as u64;
+                assert_eq!(
+                    clock.count_fires(name, start, end),
+                    Some(stepped),
+                    "{name} in {start}..={end}"
+                );
+            }
+        }
+        assert_eq!(clock.count_fires("even", 10, 9), Some(0));
+        assert_eq!(clock.count_fires("missing", 1, 10), None);
+        // 3 of every 5 minutes, over a whole epoch, in closed form.
+        let epoch = clock.count_fires("even", 0, u64::MAX);
+        assert_eq!(epoch, Some((u64::MAX / 5) * 3 + 1));
+        // Past the step limit, stateless and stateful pulses alike give up.
+        assert_eq!(clock.count_fires("busy", 0, u64::MAX), None);
+        assert_eq!(
+            clock.count_fires("busy", 200, 200 + COUNT_FIRES_MAX_STEPS),
+            None
+        );
+        assert!(clock
+            .count_fires("busy", 200, 199 + COUNT_FIRES_MAX_STEPS)
+            .is_some());
+
+        let limited = Clock::builder()
+            .pulse_when("capped", "rate_limit(2, 10, every(2))".parse().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(limited.count_fires("capped", 1, 100), Some(20));
+        assert_eq!(limited.count_fires("capped", 15, 30), Some(2));
+        assert_eq!(
+            limited.fire_schedule("capped", 100, 30).len() as u64,
+            limited.count_fires("capped", 0, 30).unwrap()
+        );
+        assert_eq!(limited.count_fires("capped", 0, u64::MAX), None);
+    }
+
+    #[test]
+    fn counts_fires_over_the_whole_epoch() {
+        let clock = Clock::builder()
+            .least_significant_first()
+            .partition("min", 5)
+            .pulse_when("odd", "!(min % 2 == 0)".parse().unwrap())
+            .pulse_when("but_one", "!(tick in 7..=7)".parse().unwrap())
+            .pulse_when(
+                "always",
+                "tick in 0..=18446744073709551615".parse().unwrap(),
+            )
+            .build()
+            .unwrap();
+        let odd = u64::MAX - (u64::MAX / 5) * 3;
+        assert_eq!(clock.count_fires("odd", 0, u64::MAX), Some(odd));
+        assert_eq!(clock.count_fires("but_one", 0, u64::MAX), Some(u64::MAX));
+        assert_eq!(
+            clock.count_fires("but_one", 1, u64::MAX),
+            Some(u64::MAX - 1)
+        );
+        // 2^64 ticks don't fit in the count.
+        assert_eq!(clock.count_fires("always", 0, u64::MAX), None);
+        assert_eq!(clock.count_fires("always", 1, u64::MAX), Some(u64::MAX));
+    }
+
+    #[test]
+    fn peeks_without_advancing() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_every("three", 3)
+            .build()
+            .unwrap();
+        #[cfg(feature = "std")]
+        let rx = clock.subscribe();
+        clock
+            .set_alarm("wake", crate::AlarmTarget::Tick(2))
+            .unwrap();
+
+        let next = clock.peek();
+        assert_eq!(next.snapshot.tick, 1);
+        assert!(next.pulses.is_empty());
+
+        let third = clock.peek_at(3).unwrap();
+        assert_eq!(third.snapshot.get("sec"), 3);
+        assert_eq!(third.pulses.len(), 1);
+        assert_eq!(third.pulses[0].name, "three");
+        assert!(clock.peek_at(0).is_none());
+
+        assert_eq!(clock.tick_count(), 0);
+        assert!(clock.alarm("wake").is_some());
+        #[cfg(feature = "std")]
+        assert!(rx.try_recv().is_err());
+
+        assert_eq!(clock.tick().snapshot, next.snapshot);
+        assert_eq!(clock.peek().pulses[0].name, "wake");
+    }
+
+    #[test]
+    fn exports_csv_and_json() {
+        let clock = Clock::builder()
+            .pulse_every("a,b", 2)
+            .pulse_every("c", 3)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            clock.export_schedule(6, ScheduleFormat::Csv),
+            "tick,pulse\n2,\"a,b\"\n3,c\n4,\"a,b\"\n6,\"a,b\"\n6,c\n"
+        );
+        assert_eq!(
+            clock.export_schedule(3, ScheduleFormat::Json),
+            r#"[{"tick":2,"pulse":"a,b"},{"tick":3,"pulse":"c"}]"#
+        );
+        assert_eq!(clock.export_schedule(1, ScheduleFormat::Json), "[]");
+    }
+
+    #[test]
+    fn checks_condition_equivalence() {
+        let config = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .build()
+            .unwrap()
+            .config();
+        let sec = |value| PulseCondition::PartitionEquals {
+            name: "sec".into(),
+            value,
+        };
+
+        // sec == 0 || sec == 30  <=>  sec % 30 == 0
+        let tree = PulseCondition::Or(alloc::vec![sec(0), sec(30)]);
+        let modulo = PulseCondition::PartitionModulo {
+            name: "sec".into(),
+            modulus: 30,
+            remainder: 0,
+        };
+        assert!(tree.equivalent_over(&modulo, &config, 7200).unwrap());
+        assert!(modulo
+            .equivalent_over(&PulseCondition::Every(30), &config, 7200)
+            .unwrap());
+
+        let first = tree.first_difference_over(&PulseCondition::Every(60), &config, 7200);
+        assert_eq!(first.unwrap(), Some(30));
+
+        let unknown = PulseCondition::PartitionEquals {
+            name: "hour".into(),
+            value: 0,
+        };
+        assert!(tree.equivalent_over(&unknown, &config, 10).is_err());
+
+        let debounced: PulseCondition = "debounce(1, sec == 0)".parse().unwrap();
+        assert!(matches!(
+            tree.equivalent_over(&debounced, &config, 10),
+            Err(ClockError::StatefulCondition { name }) if name == "rhs"
+        ));
+    }
+
+    #[test]
+    fn condition_equivalence_respects_ticks_per_unit() {
+        let config = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .ticks_per_unit(1000)
+            .build()
+            .unwrap()
+            .config();
+        let sec = PulseCondition::PartitionEquals {
+            name: "sec".into(),
+            value: 1,
+        };
+        let range = PulseCondition::TickRange {
+            start: 1000,
+            end: 1999,
+        };
+        assert!(sec.equivalent_over(&range, &config, 5000).unwrap());
+        assert_eq!(
+            sec.first_difference_over(&PulseCondition::Every(1000), &config, 5000)
+                .unwrap(),
+            Some(1001)
+        );
+    }
+}
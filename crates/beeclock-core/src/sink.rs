@@ -0,0 +1,168 @@
+//! Streaming outcome sinks (std only).
+//!
+//! [`JsonLinesSink`] writes one JSON object per tick outcome, one per line,
+//! to any [`Write`]: a file for long simulations, a socket for a live feed.
+//!
+//! ```text
+//! {"clock":"kitchen","tick":60,"epoch":0,"overflowed":false,"partitions":{"min":1,"sec":0},"pulses":["quarter","minute"]}
+//! ```
+//!
+//! `clock` is present only for named clocks. Partitions appear in configured
+//! order. Quiet window reports and child clock outcomes are not written.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::sync::mpsc::Receiver;
+
+use crate::sim::write_json_string;
+use crate::TickOutcome;
+
+/// Writes tick outcomes as JSON Lines.
+///
+/// Feed it outcomes directly with [`write`](Self::write), attach it to a
+/// clock with [`Clock::attach_sink`](crate::Clock::attach_sink), or hand it a
+/// [`Clock::subscribe`](crate::Clock::subscribe) receiver and let
+/// [`drain`](Self::drain) stream on another thread until the clock is
+/// dropped:
+///
+/// ```
+/// use beeclock_core::{Clock, JsonLinesSink};
+///
+/// let mut clock = Clock::default();
+/// let rx = clock.subscribe();
+/// let writer = std::thread::spawn(move || {
+///     let mut sink = JsonLinesSink::new(Vec::new());
+///     sink.drain(&rx).unwrap();
+///     sink.into_inner()
+/// });
+/// clock.tick();
+/// clock.tick();
+/// drop(clock);
+///
+/// let text = String::from_utf8(writer.join().unwrap()).unwrap();
+/// assert_eq!(text.lines().count(), 2);
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+    /// Reused line buffer, so steady-state writes don't allocate.
+    line: String,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            line: String::new(),
+        }
+    }
+
+    /// Write one outcome as a single line.
+    pub fn write(&mut self, outcome: &TickOutcome) -> io::Result<()> {
+        self.line.clear();
+        write_outcome(&mut self.line, outcome);
+        self.line.push('\n');
+        self.writer.write_all(self.line.as_bytes())
+    }
+
+    /// Write every outcome from `receiver` until its clock drops the
+    /// subscription, then flush. Returns the number of lines written.
+    pub fn drain(&mut self, receiver: &Receiver<TickOutcome>) -> io::Result<u64> {
+        let mut count = 0;
+        for outcome in receiver {
+            self.write(&outcome)?;
+            count += 1;
+        }
+        self.flush()?;
+        Ok(count)
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn write_outcome(out: &mut String, outcome: &TickOutcome) {
+    let snapshot = &outcome.snapshot;
+    out.push('{');
+    if let Some(clock) = &outcome.clock {
+        out.push_str("\"clock\":");
+        write_json_string(out, clock);
+        out.push(',');
+    }
+    let _ = write!(
+        out,
+        "\"tick\":{},\"epoch\":{},\"overflowed\":{},\"partitions\":{{",
+        snapshot.tick, snapshot.epoch, outcome.overflowed
+    );
+    for (idx, part) in snapshot.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        write_json_string(out, &part.name);
+        let _ = write!(out, ":{}", part.value);
+    }
+    out.push_str("},\"pulses\":[");
+    for (idx, pulse) in outcome.pulses.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        write_json_string(out, &pulse.name);
+    }
+    out.push_str("]}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clock;
+
+    #[test]
+    fn writes_one_object_per_line() {
+        let mut clock = Clock::builder()
+            .name("kit\"chen")
+            .most_significant_first()
+            .partition("min", 60)
+            .partition("sec", 60)
+            .pulse_every("quarter", 15)
+            .pulse_every("minute", 60)
+            .build()
+            .unwrap();
+        let mut sink = JsonLinesSink::new(Vec::new());
+        sink.write(&clock.observe(59)).unwrap();
+        sink.write(&clock.tick()).unwrap();
+
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                r#"{"clock":"kit\"chen","tick":60,"epoch":0,"overflowed":false,"partitions":{"min":1,"sec":0},"pulses":["quarter","minute"]}"#,
+                r#"{"clock":"kit\"chen","tick":61,"epoch":0,"overflowed":false,"partitions":{"min":1,"sec":1},"pulses":[]}"#,
+            ]
+        );
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn drains_a_subscription() {
+        let mut clock = Clock::default();
+        let rx = clock.subscribe();
+        for _ in 0..3 {
+            clock.tick();
+        }
+        drop(clock);
+
+        let mut sink = JsonLinesSink::new(Vec::new());
+        assert_eq!(sink.drain(&rx).unwrap(), 3);
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        assert!(text.lines().all(|line| !line.contains("\"clock\"")));
+        assert!(text.lines().last().unwrap().starts_with(r#"{"tick":3,"#));
+    }
+}
@@ -1,33 +1,394 @@
 //! Clock snapshot and tick outcome types.
 
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::Index;
+use core::slice;
 
-use crate::{PartitionState, PulseFired};
+use crate::{ClockError, PartitionOrder, PartitionState, PulseList, QuietReport, TaggedOutcome};
 
 /// Immutable snapshot of the clock state at a tick.
-#[derive(Clone, Debug)]
+///
+/// Snapshots order by epoch, then tick, so stored snapshots of one clock can
+/// be sorted and bisected. Ties (snapshots of different clocks at the same
+/// tick) fall back to the partitions, keeping `Ord` consistent with `Eq`.
+///
+/// Displays as `name=value` pairs, most significant first, followed by the
+/// tick: `hour=0 min=3 sec=15 @tick 195`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
 pub struct ClockSnapshot {
     pub tick: u64,
     pub epoch: u64,
     pub partitions: Vec<PartitionState>,
+    /// Significance order of `partitions`, as configured on the clock.
+    pub order: PartitionOrder,
 }
 
 impl ClockSnapshot {
     /// Get a partition by name.
     pub fn partition(&self, name: &str) -> Option<&PartitionState> {
-        self.partitions.iter().find(|p| p.name == name)
+        self.partitions.iter().find(|p| &*p.name == name)
     }
 
     /// Get a partition value by name, returning 0 if not found.
     pub fn get(&self, name: &str) -> u64 {
         self.partition(name).map(|p| p.value).unwrap_or(0)
     }
+
+    /// Iterate partitions in the clock's configured significance order.
+    pub fn iter(&self) -> slice::Iter<'_, PartitionState> {
+        self.partitions.iter()
+    }
+
+    /// Partition values in the clock's configured significance order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = u64> + ExactSizeIterator + '_ {
+        self.partitions.iter().map(|p| p.value)
+    }
+
+    /// Compare partition values as mixed-radix numbers, most significant
+    /// digit first, where `order` is the clock's partition order.
+    ///
+    /// Partitions are matched by position; snapshots with different
+    /// partition counts order by count first. Ticks and epochs are ignored.
+    pub fn cmp_partitions(&self, other: &ClockSnapshot, order: PartitionOrder) -> Ordering {
+        let by_count = self.partitions.len().cmp(&other.partitions.len());
+        if by_count != Ordering::Equal {
+            return by_count;
+        }
+        let pairs = self.values().zip(other.values());
+        match order {
+            PartitionOrder::MostSignificantFirst => pairs
+                .map(|(a, b)| a.cmp(&b))
+                .find(|ord| ord.is_ne())
+                .unwrap_or(Ordering::Equal),
+            PartitionOrder::LeastSignificantFirst => pairs
+                .rev()
+                .map(|(a, b)| a.cmp(&b))
+                .find(|ord| ord.is_ne())
+                .unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+impl ClockSnapshot {
+    /// Verify that the partitions match `layout` (name and modulus per
+    /// partition, in configured order) and hold in-range values.
+    pub(crate) fn check_layout<'a>(
+        &self,
+        layout: impl ExactSizeIterator<Item = (&'a str, u64)>,
+    ) -> Result<(), ClockError> {
+        if self.partitions.len() != layout.len() {
+            return Err(ClockError::SnapshotMismatch {
+                reason: format!(
+                    "expected {} partitions, found {}",
+                    layout.len(),
+                    self.partitions.len()
+                ),
+            });
+        }
+        for ((name, modulus), state) in layout.zip(&self.partitions) {
+            if name != &*state.name || modulus != state.modulus {
+                return Err(ClockError::SnapshotMismatch {
+                    reason: format!(
+                        "expected partition '{name}' (modulus {modulus}), found '{}' (modulus {})",
+                        state.name, state.modulus
+                    ),
+                });
+            }
+            if state.value >= state.modulus {
+                return Err(ClockError::ValueOutOfRange {
+                    name: String::from(&*state.name),
+                    value: state.value,
+                    modulus: state.modulus,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PartialOrd for ClockSnapshot {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ClockSnapshot {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.epoch, self.tick, &self.partitions, self.order).cmp(&(
+            other.epoch,
+            other.tick,
+            &other.partitions,
+            other.order,
+        ))
+    }
+}
+
+impl fmt::Display for ClockSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut write_part = |part: &PartitionState| write!(f, "{}={} ", part.name, part.value);
+        match self.order {
+            PartitionOrder::MostSignificantFirst => self.iter().try_for_each(&mut write_part)?,
+            PartitionOrder::LeastSignificantFirst => {
+                self.iter().rev().try_for_each(&mut write_part)?
+            }
+        }
+        write!(f, "@tick {}", self.tick)?;
+        if self.epoch != 0 {
+            write!(f, " epoch {}", self.epoch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Positional access in configured order; panics if out of bounds.
+impl Index<usize> for ClockSnapshot {
+    type Output = PartitionState;
+
+    fn index(&self, index: usize) -> &PartitionState {
+        &self.partitions[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a ClockSnapshot {
+    type Item = &'a PartitionState;
+    type IntoIter = slice::Iter<'a, PartitionState>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 /// Result of a single tick: snapshot + fired pulses.
+///
+/// The snapshot is shared, so cloning an outcome (e.g. once per subscriber)
+/// bumps a reference count instead of copying every partition.
+///
+/// Displays as the snapshot, then the fired pulses and the clock name if
+/// any: `hour=0 min=3 sec=15 @tick 195 [quarter, minute] (kitchen)`.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(
+        derive(Debug),
+        serialize_bounds(
+            __S: rkyv::ser::Writer + rkyv::ser::Allocator + rkyv::ser::Sharing,
+            <__S as rkyv::rancor::Fallible>::Error: rkyv::rancor::Source,
+        ),
+        deserialize_bounds(
+            __D: rkyv::de::Pooling,
+            <__D as rkyv::rancor::Fallible>::Error: rkyv::rancor::Source,
+        ),
+        bytecheck(bounds(
+            __C: rkyv::validation::ArchiveContext + rkyv::validation::SharedContext,
+            <__C as rkyv::rancor::Fallible>::Error: rkyv::rancor::Source,
+        ))
+    )
+)]
 pub struct TickOutcome {
-    pub snapshot: ClockSnapshot,
-    pub pulses: Vec<PulseFired>,
+    /// Name of the clock that produced it (see [`Clock::name`](crate::Clock::name)).
+    pub clock: Option<Arc<str>>,
+    pub snapshot: Arc<ClockSnapshot>,
+    pub pulses: PulseList,
     pub overflowed: bool,
+    /// Reporting quiet windows that closed on this tick.
+    pub quiet_reports: Vec<QuietReport>,
+    /// Outcomes of attached child clocks that ticked on this tick.
+    #[cfg_attr(feature = "rkyv", rkyv(omit_bounds))]
+    pub children: Vec<TaggedOutcome>,
+}
+
+impl fmt::Display for TickOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.snapshot)?;
+        if !self.pulses.is_empty() {
+            f.write_str(" [")?;
+            for (idx, pulse) in self.pulses.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_str(&pulse.name)?;
+            }
+            f.write_str("]")?;
+        }
+        if let Some(clock) = &self.clock {
+            write!(f, " ({clock})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Span of ticks passed over by [`Clock::skip`](crate::Clock::skip).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Skipped {
+    /// Tick before the skip.
+    pub from: u64,
+    /// Tick after the skip (wraps past `u64::MAX` like the tick itself).
+    pub to: u64,
+}
+
+/// Reusable output buffer for [`Clock::tick_into`](crate::Clock::tick_into).
+///
+/// Holds partition values and fired pulse indices instead of owned names, so
+/// once its vectors have grown to fit the clock, ticking allocates nothing.
+#[derive(Clone, Debug, Default)]
+pub struct TickBuffer {
+    pub tick: u64,
+    pub epoch: u64,
+    pub overflowed: bool,
+    /// Whether this tick used up the run budget (the `__halted__` pulse).
+    pub halted: bool,
+    /// Partition values in configured order.
+    pub values: Vec<u64>,
+    /// Indices of fired pulses, resolved with [`Clock::pulse_name`](crate::Clock::pulse_name).
+    pub fired: Vec<usize>,
+    /// Indices of pulses that expired on this tick.
+    pub expired: Vec<usize>,
+    /// Names of alarms that fired on this tick.
+    pub alarms: Vec<Arc<str>>,
+    /// `<machine>:<state>` pulses of state machine transitions on this tick.
+    pub transitions: Vec<Arc<str>>,
+}
+
+impl TickBuffer {
+    /// Create a buffer pre-sized for the given partition and pulse counts.
+    pub fn with_capacity(partitions: usize, pulses: usize) -> Self {
+        Self {
+            tick: 0,
+            epoch: 0,
+            overflowed: false,
+            halted: false,
+            values: Vec::with_capacity(partitions),
+            fired: Vec::with_capacity(pulses),
+            expired: Vec::new(),
+            alarms: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clock;
+
+    #[test]
+    fn snapshot_is_indexable_and_iterable() {
+        let mut clock = Clock::default();
+        clock.set_partition("min", 7).unwrap();
+        let snapshot = clock.tick().snapshot;
+
+        assert_eq!(&*snapshot[1].name, "min");
+        assert_eq!(snapshot[1].value, 7);
+        assert_eq!(snapshot.values().collect::<Vec<_>>(), [1, 7, 0]);
+        let names: Vec<&str> = snapshot.iter().map(|p| &*p.name).collect();
+        assert_eq!(names, ["sec", "min", "hour"]);
+        assert_eq!((&*snapshot).into_iter().count(), 3);
+    }
+
+    #[test]
+    fn snapshots_order_by_epoch_then_tick() {
+        let mut clock = Clock::default();
+        let mut history: Vec<ClockSnapshot> =
+            (0..5).map(|_| (*clock.tick().snapshot).clone()).collect();
+        history.reverse();
+        history.sort();
+        assert!(history.windows(2).all(|w| w[0].tick < w[1].tick));
+        assert_eq!(history.binary_search(&clock.snapshot()), Ok(4));
+
+        let mut later = clock.snapshot();
+        later.epoch += 1;
+        later.tick = 0;
+        assert!(later > clock.snapshot());
+    }
+
+    #[test]
+    fn cmp_partitions_honors_significance() {
+        let mut clock = Clock::default();
+        clock.set_partition("sec", 59).unwrap();
+        let early = clock.snapshot();
+        clock.set_partition("sec", 0).unwrap();
+        clock.set_partition("min", 1).unwrap();
+        let late = clock.snapshot();
+
+        let lsf = PartitionOrder::LeastSignificantFirst;
+        assert_eq!(early.cmp_partitions(&late, lsf), Ordering::Less);
+        let msf = PartitionOrder::MostSignificantFirst;
+        assert_eq!(early.cmp_partitions(&late, msf), Ordering::Greater);
+        assert_eq!(late.cmp_partitions(&late, lsf), Ordering::Equal);
+    }
+
+    #[test]
+    fn displays_most_significant_first() {
+        let mut clock = Clock::default();
+        let outcome = clock.observe(194);
+        assert_eq!(
+            outcome.snapshot.to_string(),
+            "hour=0 min=3 sec=15 @tick 195"
+        );
+
+        let mut clock = Clock::builder()
+            .name("kitchen")
+            .most_significant_first()
+            .partition("min", 60)
+            .partition("sec", 60)
+            .pulse_every("quarter", 15)
+            .pulse_every("minute", 60)
+            .build()
+            .unwrap();
+        let outcome = clock.observe(59);
+        assert_eq!(
+            outcome.to_string(),
+            "min=1 sec=0 @tick 60 [quarter, minute] (kitchen)"
+        );
+
+        let mut snapshot = (*outcome.snapshot).clone();
+        snapshot.epoch = 2;
+        assert_eq!(snapshot.to_string(), "min=1 sec=0 @tick 60 epoch 2");
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn outcomes_archive_and_read_in_place() {
+        use rkyv::rancor::Error;
+
+        let minutes = Clock::builder()
+            .least_significant_first()
+            .partition("min", 60)
+            .build()
+            .unwrap();
+        let mut clock = Clock::builder()
+            .name("kitchen")
+            .least_significant_first()
+            .partition_spec(crate::PartitionSpec::new("sec", 60).unit("s"))
+            .pulse_every("quarter", 15)
+            .pulse_every("minute", 60)
+            .child("minutes", minutes, crate::Gear::OnPulse("minute".into()))
+            .build()
+            .unwrap();
+        let outcome = clock.observe(59);
+        let bytes = rkyv::to_bytes::<Error>(&outcome).unwrap();
+
+        let archived = rkyv::access::<ArchivedTickOutcome, Error>(&bytes).unwrap();
+        assert_eq!(archived.snapshot.tick, 60);
+        assert_eq!(archived.snapshot.partitions[0].value, 0);
+        let names: Vec<&str> = archived.pulses.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["quarter", "minute"]);
+        assert_eq!(&*archived.children[0].clock, "minutes");
+
+        let restored = rkyv::deserialize::<TickOutcome, Error>(archived).unwrap();
+        assert_eq!(restored.to_string(), outcome.to_string());
+        assert_eq!(restored.snapshot, outcome.snapshot);
+        assert_eq!(restored.children[0].outcome.snapshot.get("min"), 1);
+    }
 }
@@ -0,0 +1,495 @@
+//! Saving and resuming a clock's full runtime state.
+//!
+//! A [`ClockSnapshot`](crate::ClockSnapshot) records where the partitions
+//! stand. A [`ClockState`] also carries the configuration and everything
+//! ticking accumulates (rate limit, jitter and debounce history, expiry
+//! counts, delayed fires, open quiet windows, alarms, child clocks, state
+//! machines), so [`Clock::from_state`] resumes exactly where
+//! [`Clock::save_state`] left off. Subscribers are not saved.
+//!
+//! Restoring only a snapshot would start every stateful pulse condition
+//! afresh: a half-finished debounce streak or a full rate limit window would
+//! be forgotten. [`Clock::from_state`] instead checks each pulse's saved state
+//! against its condition and rejects state that does not fit.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::pulse::PulseState;
+use crate::{AlarmTarget, Clock, ClockConfig, ClockError, Gear, StateMachine};
+
+/// Complete saved state of a clock, from [`Clock::save_state`].
+///
+/// With the `serde` feature this serializes alongside the configuration,
+/// so it can be persisted and restored in another process.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClockState {
+    config: ClockConfig,
+    tick: u64,
+    epoch: u64,
+    /// Partition values in configured order.
+    values: Vec<u64>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "core::ops::Not::not")
+    )]
+    halted: bool,
+    /// Tick fraction from [`Clock::advance_scaled`], in 32.32 fixed point.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    fraction: u32,
+    /// Time carried by [`Clock::tick_elapsed`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Duration::is_zero")
+    )]
+    elapsed_carry: Duration,
+    /// Runtime state per pulse, in configuration order.
+    pulses: Vec<PulseState>,
+    /// Scheduled delayed fires as `(due tick, pulse index)`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pending: Vec<(u64, usize)>,
+    /// Open span per quiet window as `(start tick, suppressed fires)`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    quiet: Vec<Option<(u64, u64)>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    alarms: Vec<SavedAlarm>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    children: Vec<SavedChild>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    machines: Vec<SavedMachine>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct SavedAlarm {
+    name: String,
+    target: AlarmTarget,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    countdown: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct SavedChild {
+    name: String,
+    gear: Gear,
+    state: ClockState,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct SavedMachine {
+    name: String,
+    machine: StateMachine,
+    state: String,
+}
+
+impl ClockState {
+    /// Configuration of the saved clock.
+    pub fn config(&self) -> &ClockConfig {
+        &self.config
+    }
+
+    /// Tick the clock was saved at.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Epoch the clock was saved at.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Parse a saved state from JSON (requires the `json` feature).
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Serialize this state to JSON (requires the `json` feature).
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("clock state is always serializable")
+    }
+}
+
+impl Clock {
+    /// Capture the configuration and full runtime state of this clock.
+    pub fn save_state(&self) -> ClockState {
+        let mut pending: Vec<(u64, usize)> = self.pending.scheduled().collect();
+        pending.sort_unstable();
+        ClockState {
+            config: self.config(),
+            tick: self.tick,
+            epoch: self.epoch,
+            values: self.partitions.values().to_vec(),
+            halted: self.halted,
+            fraction: self.fraction,
+            elapsed_carry: self.elapsed_carry,
+            pulses: self.pulse_state.clone(),
+            pending,
+            quiet: self.quiet.spans().collect(),
+            alarms: self
+                .alarms
+                .entries()
+                .map(|(name, target, countdown)| SavedAlarm {
+                    name: String::from(name),
+                    target: target.clone(),
+                    countdown,
+                })
+                .collect(),
+            children: self
+                .children
+                .iter()
+                .map(|child| SavedChild {
+                    name: String::from(&*child.name),
+                    gear: child.gear.clone(),
+                    state: child.clock.save_state(),
+                })
+                .collect(),
+            machines: self
+                .machines
+                .iter()
+                .map(|machine| SavedMachine {
+                    name: String::from(&*machine.name),
+                    machine: machine.machine.clone(),
+                    state: String::from(machine.state()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuild a clock from a saved state.
+    ///
+    /// The configuration is validated as by [`ClockConfig::build`], and the
+    /// runtime state must fit it, down to the state of each pulse's
+    /// condition wrappers.
+    pub fn from_state(state: ClockState) -> Result<Clock, ClockError> {
+        let mut clock = state.config.build()?;
+        let invalid = |reason| ClockError::InvalidState { reason };
+
+        if state.values.len() != clock.partitions.len() {
+            return Err(invalid(format!(
+                "{} partition values for {} partitions",
+                state.values.len(),
+                clock.partitions.len()
+            )));
+        }
+        for ((name, modulus), &value) in clock.partitions.layout().zip(&state.values) {
+            if value >= modulus {
+                return Err(ClockError::ValueOutOfRange {
+                    name: String::from(name),
+                    value,
+                    modulus,
+                });
+            }
+        }
+        for (idx, &value) in state.values.iter().enumerate() {
+            clock.partitions.set(idx, value);
+        }
+        clock.tick = state.tick;
+        clock.partitions.sync_phase(state.epoch, state.tick);
+        clock.epoch = state.epoch;
+        clock.halted = state.halted;
+        clock.fraction = state.fraction;
+        let carry = state.elapsed_carry;
+        if !carry.is_zero() && carry >= clock.tick_duration().unwrap_or_default() {
+            return Err(invalid(format!(
+                "carried time {carry:?} is not below the tick duration"
+            )));
+        }
+        clock.elapsed_carry = carry;
+
+        if state.pulses.len() != clock.pulses.len() {
+            return Err(invalid(format!(
+                "state for {} pulses, configuration has {}",
+                state.pulses.len(),
+                clock.pulses.len()
+            )));
+        }
+        for (pulse, saved) in clock.pulses.iter().zip(&state.pulses) {
+            saved
+                .check(pulse)
+                .map_err(|why| invalid(format!("state for pulse '{}' {why}", pulse.name)))?;
+        }
+        clock.pulse_state = state.pulses;
+
+        for (at, pulse) in state.pending {
+            if clock.pending.trigger_of(pulse).is_none() {
+                return Err(invalid(format!(
+                    "delayed fire scheduled for pulse {pulse}, which is not delayed"
+                )));
+            }
+            clock.pending.schedule(at, pulse);
+        }
+
+        if state.quiet.len() != clock.quiet.windows().len() {
+            return Err(invalid(format!(
+                "{} quiet window spans for {} windows",
+                state.quiet.len(),
+                clock.quiet.windows().len()
+            )));
+        }
+        clock.quiet.restore_spans(state.quiet);
+
+        for alarm in state.alarms {
+            let pattern = clock.alarm_pattern(&alarm.target)?;
+            clock.alarms.insert(
+                alarm.name,
+                clock.tick,
+                alarm.target,
+                pattern,
+                alarm.countdown,
+            );
+        }
+
+        for child in state.children {
+            let child_clock = Clock::from_state(child.state)?;
+            clock.attach_child(child.name, child_clock, child.gear)?;
+        }
+        for machine in state.machines {
+            clock.attach_machine(machine.name.clone(), machine.machine)?;
+            clock.set_machine_state(&machine.name, &machine.state)?;
+        }
+        Ok(clock)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PulseCondition, PulseSpec, QuietWindow};
+    use alloc::boxed::Box;
+    use alloc::vec;
+
+    fn busy_clock() -> Clock {
+        let world = Clock::builder()
+            .least_significant_first()
+            .partition("min", 60)
+            .pulse_every("minute", 1)
+            .build()
+            .unwrap();
+        let mut clock = Clock::builder()
+            .name("busy")
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_when(
+                "limited",
+                PulseCondition::RateLimit {
+                    max_fires: 2,
+                    per_ticks: 10,
+                    inner: Box::new(PulseCondition::Every(2)),
+                },
+            )
+            .pulse_when(
+                "jittered",
+                PulseCondition::Jitter {
+                    window: 7,
+                    seed: 3,
+                    inner: Box::new(PulseCondition::Every(5)),
+                },
+            )
+            .pulse(PulseSpec::new("capped", PulseCondition::Every(10)).max_fires(4))
+            .pulse_after("echo", "capped", 20)
+            .quiet_window(QuietWindow::ticks("hush", 30, 50).reported())
+            .child("world", world, Gear::Every(15))
+            .machine(
+                "phase",
+                StateMachine::new("even")
+                    .state("odd")
+                    .transition("even", "capped", "odd")
+                    .transition("odd", "capped", "even"),
+            )
+            .run_for(500)
+            .tick_duration(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        clock.set_alarm("noon", AlarmTarget::Tick(90)).unwrap();
+        clock
+            .set_alarm("top", AlarmTarget::partitions([("sec", 0), ("min", 2)]))
+            .unwrap();
+        clock.start_countdown("egg", 70).unwrap();
+        clock
+    }
+
+    fn run(clock: &mut Clock, ticks: usize) -> Vec<(u64, String)> {
+        clock
+            .iter_ticks()
+            .take(ticks)
+            .flat_map(|outcome| {
+                let tick = outcome.snapshot.tick;
+                let children = outcome.children.iter().flat_map(|child| {
+                    child
+                        .outcome
+                        .pulses
+                        .iter()
+                        .map(|p| (child.outcome.snapshot.tick, p.name.clone()))
+                        .collect::<Vec<_>>()
+                });
+                outcome
+                    .pulses
+                    .iter()
+                    .map(|p| (tick, p.name.clone()))
+                    .chain(children)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resumes_exactly_where_it_left_off() {
+        let mut original = busy_clock();
+        run(&mut original, 40);
+        assert!(original.advance_scaled(0.5).is_empty());
+        assert!(original
+            .tick_elapsed(Duration::from_millis(4))
+            .unwrap()
+            .is_empty());
+
+        let state = original.save_state();
+        assert_eq!(
+            (state.tick(), state.config().name.as_deref()),
+            (40, Some("busy"))
+        );
+        let mut resumed = Clock::from_state(state).unwrap();
+        assert_eq!(resumed.snapshot(), original.snapshot());
+        assert_eq!(resumed.tick_fraction(), 0.5);
+        assert_eq!(resumed.elapsed_carry(), Duration::from_millis(4));
+
+        let expected = run(&mut original, 600);
+        assert!(expected.iter().any(|(_, name)| name == "egg"));
+        assert!(expected.iter().any(|(_, name)| name == "echo"));
+        assert!(expected.iter().any(|(_, name)| name == "phase:odd"));
+        assert_eq!(run(&mut resumed, 600), expected);
+        assert!(resumed.is_halted());
+    }
+
+    #[test]
+    fn pulse_condition_state_survives_a_restore() {
+        let mut original = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .pulse_when(
+                "steady",
+                PulseCondition::Debounce {
+                    ticks: 4,
+                    inner: Box::new(PulseCondition::TickRange { start: 3, end: 9 }),
+                },
+            )
+            .pulse_when(
+                "burst",
+                PulseCondition::And(vec![
+                    PulseCondition::Every(2),
+                    PulseCondition::RateLimit {
+                        max_fires: 2,
+                        per_ticks: 20,
+                        inner: Box::new(PulseCondition::Every(1)),
+                    },
+                ]),
+            )
+            .pulse_when(
+                "late",
+                PulseCondition::Not(Box::new(PulseCondition::Jitter {
+                    window: 9,
+                    seed: 11,
+                    inner: Box::new(PulseCondition::Every(4)),
+                })),
+            )
+            .build()
+            .unwrap();
+        run(&mut original, 5);
+        let state = original.save_state();
+
+        // Rebuilding from the configuration and snapshot alone forgets the
+        // debounce streak and the rate limit window.
+        let mut fresh = state.config().clone().build().unwrap();
+        fresh.tick = state.tick();
+        fresh.partitions.set(0, state.values[0]);
+        let mut resumed = Clock::from_state(state).unwrap();
+        let expected = run(&mut original, 40);
+        assert!(expected.contains(&(6, String::from("steady"))));
+        assert_ne!(run(&mut fresh, 40), expected);
+        assert_eq!(run(&mut resumed, 40), expected);
+        assert_eq!(
+            resumed.suppressed_count("burst"),
+            original.suppressed_count("burst")
+        );
+
+        let mut state = original.save_state();
+        state.pulses.swap(0, 1);
+        assert!(matches!(
+            Clock::from_state(state),
+            Err(ClockError::InvalidState { reason }) if reason.contains("'steady'")
+        ));
+
+        let mut state = original.save_state();
+        state.pulses[2] = PulseState::default();
+        assert!(Clock::from_state(state).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn state_round_trips_through_json() {
+        let mut original = busy_clock();
+        run(&mut original, 45);
+
+        let json = original.save_state().to_json();
+        let state = ClockState::from_json(&json).unwrap();
+        assert_eq!(state.to_json(), json);
+        let mut resumed = Clock::from_state(state).unwrap();
+        assert_eq!(run(&mut resumed, 200), run(&mut original, 200));
+    }
+
+    #[test]
+    fn rejects_state_that_does_not_fit() {
+        let clock = busy_clock();
+
+        let mut state = clock.save_state();
+        state.values.pop();
+        assert!(matches!(
+            Clock::from_state(state),
+            Err(ClockError::InvalidState { .. })
+        ));
+
+        let mut state = clock.save_state();
+        state.values[0] = 60;
+        assert!(matches!(
+            Clock::from_state(state),
+            Err(ClockError::ValueOutOfRange { .. })
+        ));
+
+        let mut state = clock.save_state();
+        state.pending = vec![(10, 0)];
+        assert!(matches!(
+            Clock::from_state(state),
+            Err(ClockError::InvalidState { .. })
+        ));
+    }
+}
@@ -0,0 +1,365 @@
+//! Pulse-driven state machines.
+//!
+//! A [`StateMachine`] moves between named states when the clock it is
+//! attached to fires a trigger pulse, optionally only while a guard condition
+//! holds. Transitions happen inside the clock's tick and fire a
+//! `<machine>:<state>` pulse for the state entered, after the clock's own
+//! pulses and alarms. Like child clocks, machines are runtime attachments and
+//! are not part of [`ClockConfig`](crate::ClockConfig);
+//! [`ClockState`](crate::ClockState) saves them with their current state.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::partition::PartitionTable;
+use crate::{ClockError, PulseCondition};
+
+/// States and the pulse-triggered transitions between them.
+///
+/// ```
+/// use beeclock_core::{Clock, PulseCondition, StateMachine};
+///
+/// let door = StateMachine::new("closed")
+///     .state("open")
+///     .transition("closed", "push", "open")
+///     .transition("open", "push", "closed");
+/// let mut clock = Clock::builder()
+///     .pulse_every("push", 10)
+///     .machine("door", door)
+///     .build()
+///     .unwrap();
+///
+/// let outcome = clock.observe(9);
+/// assert_eq!(outcome.pulses[1].name, "door:open");
+/// assert_eq!(clock.machine_state("door"), Some("open"));
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateMachine {
+    initial: String,
+    states: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    transitions: Vec<Transition>,
+}
+
+/// Move from one state to another when a pulse fires.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transition {
+    pub from: String,
+    /// Trigger pulse name.
+    pub on: String,
+    pub to: String,
+    /// Only transition while this holds. Evaluated without per-pulse state,
+    /// so a [stateful](PulseCondition::is_stateful) guard is rejected with
+    /// [`ClockError::StatefulCondition`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub guard: Option<PulseCondition>,
+}
+
+impl StateMachine {
+    /// Start a machine in `initial`, its first state.
+    pub fn new(initial: impl Into<String>) -> Self {
+        let initial = initial.into();
+        Self {
+            states: alloc::vec![initial.clone()],
+            initial,
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Declare another state.
+    pub fn state(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.states.contains(&name) {
+            self.states.push(name);
+        }
+        self
+    }
+
+    /// Move `from` → `to` whenever pulse `on` fires.
+    pub fn transition(
+        self,
+        from: impl Into<String>,
+        on: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        self.push(from.into(), on.into(), to.into(), None)
+    }
+
+    /// Move `from` → `to` when pulse `on` fires while `guard` holds.
+    pub fn transition_when(
+        self,
+        from: impl Into<String>,
+        on: impl Into<String>,
+        to: impl Into<String>,
+        guard: PulseCondition,
+    ) -> Self {
+        self.push(from.into(), on.into(), to.into(), Some(guard))
+    }
+
+    fn push(mut self, from: String, on: String, to: String, guard: Option<PulseCondition>) -> Self {
+        self.transitions.push(Transition {
+            from,
+            on,
+            to,
+            guard,
+        });
+        self
+    }
+
+    pub fn initial(&self) -> &str {
+        &self.initial
+    }
+
+    /// Declared states, initial first.
+    pub fn states(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.states.iter().map(String::as_str)
+    }
+
+    /// Transitions in declaration order, which is also priority order.
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    fn position(&self, state: &str) -> Option<usize> {
+        self.states.iter().position(|s| s == state)
+    }
+}
+
+/// A state machine attached to a clock.
+#[derive(Clone, Debug)]
+pub(crate) struct AttachedMachine {
+    pub name: Arc<str>,
+    pub machine: StateMachine,
+    pub current: usize,
+    /// Resolved `(from, trigger pulse index, to)` per transition (trigger
+    /// `None` once pruned).
+    routes: Vec<(usize, Option<usize>, usize)>,
+    /// `<machine>:<state>` pulse name per state.
+    entered: Vec<Arc<str>>,
+}
+
+impl AttachedMachine {
+    /// Resolve states and triggers, where `pulse_index` finds a clock pulse
+    /// by name.
+    pub fn new(
+        name: String,
+        machine: StateMachine,
+        pulse_index: impl Fn(&str) -> Option<usize>,
+    ) -> Result<Self, ClockError> {
+        let state = |state: &str| {
+            machine
+                .position(state)
+                .ok_or_else(|| ClockError::UnknownState {
+                    machine: name.clone(),
+                    state: String::from(state),
+                })
+        };
+        let routes = machine
+            .transitions
+            .iter()
+            .map(|t| {
+                let trigger =
+                    pulse_index(&t.on).ok_or_else(|| ClockError::UnknownMachineTrigger {
+                        machine: name.clone(),
+                        trigger: t.on.clone(),
+                    })?;
+                Ok((state(&t.from)?, Some(trigger), state(&t.to)?))
+            })
+            .collect::<Result<_, ClockError>>()?;
+        let entered = machine
+            .states
+            .iter()
+            .map(|state| Arc::from(format!("{name}:{state}")))
+            .collect();
+        Ok(Self {
+            name: Arc::from(name),
+            machine,
+            current: 0,
+            routes,
+            entered,
+        })
+    }
+
+    pub fn state(&self) -> &str {
+        &self.machine.states[self.current]
+    }
+
+    /// Enter the named state without firing anything.
+    pub fn set_state(&mut self, state: &str) -> Result<(), ClockError> {
+        self.current = self
+            .machine
+            .position(state)
+            .ok_or_else(|| ClockError::UnknownState {
+                machine: String::from(&*self.name),
+                state: String::from(state),
+            })?;
+        Ok(())
+    }
+
+    /// Take the first transition out of the current state triggered by a
+    /// fired pulse (by index, in firing order) whose guard holds, returning
+    /// the pulse name for the state entered.
+    pub fn step(
+        &mut self,
+        tick: u64,
+        fired: &[usize],
+        partitions: &PartitionTable,
+    ) -> Option<&Arc<str>> {
+        let current = self.current;
+        let to = fired.iter().find_map(|&index| {
+            self.routes
+                .iter()
+                .zip(&self.machine.transitions)
+                .find(|((from, trigger, _), t)| {
+                    *from == current
+                        && *trigger == Some(index)
+                        && t.guard
+                            .as_ref()
+                            .is_none_or(|guard| guard.evaluate(tick, partitions))
+                })
+                .map(|((_, _, to), _)| *to)
+        })?;
+        self.current = to;
+        Some(&self.entered[to])
+    }
+
+    /// Follow pulses to their new indices after pruning.
+    pub fn remap(&mut self, remap: &[Option<usize>]) {
+        for (_, trigger, _) in &mut self.routes {
+            *trigger = trigger.and_then(|idx| remap[idx]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, TickOutcome};
+    use alloc::vec;
+
+    fn names(outcome: &TickOutcome) -> Vec<&str> {
+        outcome.pulses.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    fn lamp() -> StateMachine {
+        StateMachine::new("off")
+            .state("on")
+            .state("dim")
+            .transition_when(
+                "off",
+                "switch",
+                "dim",
+                PulseCondition::PartitionModulo {
+                    name: "hour".into(),
+                    modulus: 2,
+                    remainder: 1,
+                },
+            )
+            .transition("off", "switch", "on")
+            .transition("on", "switch", "off")
+            .transition("dim", "switch", "off")
+    }
+
+    fn clock() -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("min", 2)
+            .partition("hour", 24)
+            .pulse_every("switch", 2)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn transitions_on_pulses_in_priority_order() {
+        let mut clock = clock();
+        clock.attach_machine("lamp", lamp()).unwrap();
+        assert_eq!(clock.machine_state("lamp"), Some("off"));
+
+        clock.tick();
+        // hour 1: the guarded transition wins.
+        assert_eq!(names(&clock.tick()), ["switch", "lamp:dim"]);
+        assert_eq!(names(&clock.tick()), Vec::<&str>::new());
+        // hour 2: back off.
+        assert_eq!(names(&clock.tick()), ["switch", "lamp:off"]);
+        clock.tick();
+        // hour 3: dim again; the unguarded fallback would need an even hour.
+        assert_eq!(names(&clock.tick()), ["switch", "lamp:dim"]);
+
+        let mut buffer = clock.tick_buffer();
+        clock.tick_into(&mut buffer);
+        clock.tick_into(&mut buffer);
+        assert_eq!(buffer.transitions, vec![Arc::from("lamp:off")]);
+        assert_eq!(clock.machine_state("lamp"), Some("off"));
+
+        let sim = clock.simulate(2);
+        assert_eq!(sim.pulse("switch").unwrap().count(), 1);
+        assert_eq!(clock.machine_state("lamp"), Some("off"));
+    }
+
+    #[test]
+    fn rejects_unknown_states_and_triggers() {
+        let mut clock = clock();
+        let missing = StateMachine::new("off").transition("off", "switch", "on");
+        assert!(matches!(
+            clock.attach_machine("lamp", missing),
+            Err(ClockError::UnknownState { state, .. }) if state == "on"
+        ));
+        let stray = StateMachine::new("off").transition("off", "knock", "off");
+        assert!(matches!(
+            clock.attach_machine("lamp", stray),
+            Err(ClockError::UnknownMachineTrigger { trigger, .. }) if trigger == "knock"
+        ));
+        let unguarded = StateMachine::new("off").transition_when(
+            "off",
+            "switch",
+            "off",
+            PulseCondition::PartitionEquals {
+                name: "day".into(),
+                value: 0,
+            },
+        );
+        assert!(matches!(
+            clock.attach_machine("lamp", unguarded),
+            Err(ClockError::UnknownPartition { .. })
+        ));
+        let debounced = StateMachine::new("off").transition_when(
+            "off",
+            "switch",
+            "off",
+            "debounce(2, hour == 0)".parse().unwrap(),
+        );
+        assert!(matches!(
+            clock.attach_machine("lamp", debounced),
+            Err(ClockError::StatefulCondition { name }) if name == "lamp"
+        ));
+
+        clock.attach_machine("lamp", lamp()).unwrap();
+        assert!(matches!(
+            clock.attach_machine("lamp", lamp()),
+            Err(ClockError::DuplicateMachine { .. })
+        ));
+        assert!(matches!(
+            clock.set_machine_state("lamp", "blinking"),
+            Err(ClockError::UnknownState { state, .. }) if state == "blinking"
+        ));
+        assert!(matches!(
+            clock.set_machine_state("lantern", "on"),
+            Err(ClockError::UnknownMachine { name }) if name == "lantern"
+        ));
+        clock.set_machine_state("lamp", "on").unwrap();
+        assert_eq!(clock.machine_state("lamp"), Some("on"));
+        assert_eq!(clock.detach_machine("lamp").unwrap().initial(), "off");
+        assert_eq!(clock.machine_state("lamp"), None);
+    }
+}
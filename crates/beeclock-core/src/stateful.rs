@@ -0,0 +1,407 @@
+//! Runtime state for stateful condition wrappers.
+//!
+//! Most conditions are pure functions of the tick and partition values.
+//! Wrappers such as [`PulseCondition::Jitter`],
+//! [`PulseCondition::RateLimit`] and [`PulseCondition::Debounce`] remember
+//! earlier ticks, and [`PulseCondition::ElapsedAtLeast`] needs its duration
+//! in ticks and the pulse's last fire, so
+//! each pulse keeps a [`ConditionState`] tree mirroring its condition.
+//! Subtrees without wrappers are `Pure` and evaluated directly.
+//!
+//! Stateful subtrees are evaluated on every tick the pulse is live, without
+//! `And`/`Or` short-circuiting, so their state never skips a tick.
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::partition::PartitionLookup;
+use crate::{ClockDuration, ClockError, ElapsedSince, PulseCondition};
+
+/// State tree for one pulse condition.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub(crate) enum ConditionState {
+    /// No wrappers below this node.
+    #[default]
+    Pure,
+    /// Scheduled fire ticks, ascending.
+    Jitter {
+        pending: VecDeque<u64>,
+        inner: Box<ConditionState>,
+    },
+    /// Recent fire ticks, ascending, and matches suppressed so far.
+    RateLimit {
+        fires: VecDeque<u64>,
+        suppressed: u64,
+        inner: Box<ConditionState>,
+    },
+    /// Consecutive ticks the inner condition has held.
+    Debounce {
+        streak: u64,
+        inner: Box<ConditionState>,
+    },
+    /// Duration in ticks and the tick the pulse last fired (0 before then).
+    Elapsed {
+        ticks: u64,
+        last_fire: u64,
+    },
+    Not(Box<ConditionState>),
+    /// Children of `And`/`Or`.
+    Children(Vec<ConditionState>),
+}
+
+impl ConditionState {
+    /// Build the initial state for `condition`, converting durations to
+    /// ticks with `ticks_of`.
+    pub fn new(
+        condition: &PulseCondition,
+        ticks_of: &dyn Fn(&ClockDuration) -> Result<u64, ClockError>,
+    ) -> Result<Self, ClockError> {
+        if !condition.is_stateful() {
+            return Ok(ConditionState::Pure);
+        }
+        let boxed = |inner| Self::new(inner, ticks_of).map(Box::new);
+        Ok(match condition {
+            PulseCondition::Jitter { inner, .. } => ConditionState::Jitter {
+                pending: VecDeque::new(),
+                inner: boxed(inner)?,
+            },
+            PulseCondition::RateLimit { inner, .. } => ConditionState::RateLimit {
+                fires: VecDeque::new(),
+                suppressed: 0,
+                inner: boxed(inner)?,
+            },
+            PulseCondition::Debounce { inner, .. } => ConditionState::Debounce {
+                streak: 0,
+                inner: boxed(inner)?,
+            },
+            PulseCondition::ElapsedAtLeast { duration, .. } => ConditionState::Elapsed {
+                ticks: ticks_of(duration)?,
+                last_fire: 0,
+            },
+            PulseCondition::Not(inner) => ConditionState::Not(boxed(inner)?),
+            PulseCondition::And(conditions) | PulseCondition::Or(conditions) => {
+                ConditionState::Children(
+                    conditions
+                        .iter()
+                        .map(|c| Self::new(c, ticks_of))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            _ => ConditionState::Pure,
+        })
+    }
+
+    /// Record that the pulse fired at `tick`.
+    pub fn fired(&mut self, tick: u64) {
+        match self {
+            ConditionState::Pure => {}
+            ConditionState::Elapsed { last_fire, .. } => *last_fire = tick,
+            ConditionState::Jitter { inner, .. }
+            | ConditionState::RateLimit { inner, .. }
+            | ConditionState::Debounce { inner, .. }
+            | ConditionState::Not(inner) => inner.fired(tick),
+            ConditionState::Children(children) => {
+                children.iter_mut().for_each(|child| child.fired(tick))
+            }
+        }
+    }
+
+    /// Total matches suppressed by rate limits in this tree.
+    pub fn suppressed(&self) -> u64 {
+        match self {
+            ConditionState::Pure | ConditionState::Elapsed { .. } => 0,
+            ConditionState::RateLimit {
+                suppressed, inner, ..
+            } => suppressed + inner.suppressed(),
+            ConditionState::Jitter { inner, .. }
+            | ConditionState::Debounce { inner, .. }
+            | ConditionState::Not(inner) => inner.suppressed(),
+            ConditionState::Children(children) => {
+                children.iter().map(ConditionState::suppressed).sum()
+            }
+        }
+    }
+
+    /// Whether this tree holds no state.
+    #[inline]
+    pub fn is_pure(&self) -> bool {
+        matches!(self, ConditionState::Pure)
+    }
+
+    /// Check that this tree could have been built and advanced for
+    /// `condition`, so evaluation picks up where it left off instead of
+    /// falling back to the stateless path.
+    pub(crate) fn check(&self, condition: &PulseCondition) -> Result<(), &'static str> {
+        match (condition, self) {
+            (condition, ConditionState::Pure) if condition.is_stateful() => {
+                Err("has no state for its stateful condition")
+            }
+            (_, ConditionState::Pure) => Ok(()),
+            (condition, _) if !condition.is_stateful() => {
+                Err("has state its condition does not use")
+            }
+
+            (
+                PulseCondition::Jitter { inner, .. },
+                ConditionState::Jitter {
+                    pending,
+                    inner: inner_state,
+                },
+            ) => {
+                if !is_ascending(pending) {
+                    return Err("has jitter fires out of order");
+                }
+                inner_state.check(inner)
+            }
+
+            (
+                PulseCondition::RateLimit {
+                    max_fires, inner, ..
+                },
+                ConditionState::RateLimit {
+                    fires,
+                    inner: inner_state,
+                    ..
+                },
+            ) => {
+                if fires.len() as u64 > *max_fires {
+                    return Err("has more rate limited fires than the limit allows");
+                }
+                if !is_ascending(fires) {
+                    return Err("has rate limited fires out of order");
+                }
+                inner_state.check(inner)
+            }
+
+            (
+                PulseCondition::Debounce { inner, .. },
+                ConditionState::Debounce {
+                    inner: inner_state, ..
+                },
+            ) => inner_state.check(inner),
+
+            (PulseCondition::ElapsedAtLeast { .. }, ConditionState::Elapsed { .. }) => Ok(()),
+
+            (PulseCondition::Not(inner), ConditionState::Not(state)) => state.check(inner),
+
+            (
+                PulseCondition::And(conditions) | PulseCondition::Or(conditions),
+                ConditionState::Children(states),
+            ) => {
+                if states.len() != conditions.len() {
+                    return Err("has state for a different number of subconditions");
+                }
+                conditions
+                    .iter()
+                    .zip(states)
+                    .try_for_each(|(condition, state)| state.check(condition))
+            }
+
+            _ => Err("has state shaped for a different condition"),
+        }
+    }
+}
+
+fn is_ascending(ticks: &VecDeque<u64>) -> bool {
+    ticks.iter().zip(ticks.iter().skip(1)).all(|(a, b)| a <= b)
+}
+
+impl PulseCondition {
+    /// Whether this condition contains a stateful wrapper.
+    pub fn is_stateful(&self) -> bool {
+        match self {
+            PulseCondition::Jitter { .. }
+            | PulseCondition::RateLimit { .. }
+            | PulseCondition::Debounce { .. }
+            | PulseCondition::ElapsedAtLeast { .. } => true,
+            PulseCondition::Not(inner) => inner.is_stateful(),
+            PulseCondition::And(conditions) | PulseCondition::Or(conditions) => {
+                conditions.iter().any(PulseCondition::is_stateful)
+            }
+            _ => false,
+        }
+    }
+
+    /// Evaluate at `tick`, advancing `state` (built for this condition).
+    pub(crate) fn evaluate_stateful<P>(
+        &self,
+        state: &mut ConditionState,
+        tick: u64,
+        partitions: &P,
+    ) -> bool
+    where
+        P: PartitionLookup + ?Sized,
+    {
+        match (self, state) {
+            (_, ConditionState::Pure) => self.evaluate(tick, partitions),
+
+            (
+                PulseCondition::Jitter {
+                    window,
+                    seed,
+                    inner,
+                },
+                ConditionState::Jitter {
+                    pending,
+                    inner: inner_state,
+                },
+            ) => {
+                if inner.evaluate_stateful(inner_state, tick, partitions) {
+                    let at = tick.saturating_add(jitter_offset(*seed, tick, *window));
+                    let pos = pending.partition_point(|&scheduled| scheduled <= at);
+                    pending.insert(pos, at);
+                }
+                let mut due = false;
+                while let Some(&scheduled) = pending.front() {
+                    if scheduled > tick {
+                        break;
+                    }
+                    due |= scheduled == tick;
+                    pending.pop_front();
+                }
+                due
+            }
+
+            (
+                PulseCondition::RateLimit {
+                    max_fires,
+                    per_ticks,
+                    inner,
+                },
+                ConditionState::RateLimit {
+                    fires,
+                    suppressed,
+                    inner: inner_state,
+                },
+            ) => {
+                if !inner.evaluate_stateful(inner_state, tick, partitions) {
+                    return false;
+                }
+                let window_start = tick.saturating_sub(*per_ticks - 1);
+                while fires.front().is_some_and(|&fired| fired < window_start) {
+                    fires.pop_front();
+                }
+                if (fires.len() as u64) < *max_fires {
+                    fires.push_back(tick);
+                    true
+                } else {
+                    *suppressed += 1;
+                    false
+                }
+            }
+
+            (
+                PulseCondition::Debounce { ticks, inner },
+                ConditionState::Debounce {
+                    streak,
+                    inner: inner_state,
+                },
+            ) => {
+                if inner.evaluate_stateful(inner_state, tick, partitions) {
+                    *streak = streak.saturating_add(1);
+                    *streak == *ticks
+                } else {
+                    *streak = 0;
+                    false
+                }
+            }
+
+            (
+                PulseCondition::ElapsedAtLeast { since, .. },
+                ConditionState::Elapsed { ticks, last_fire },
+            ) => {
+                let from = match since {
+                    ElapsedSince::Start => 0,
+                    ElapsedSince::LastFire => *last_fire,
+                };
+                tick.saturating_sub(from) >= *ticks
+            }
+
+            (PulseCondition::Not(inner), ConditionState::Not(state)) => {
+                !inner.evaluate_stateful(state, tick, partitions)
+            }
+
+            (PulseCondition::And(conditions), ConditionState::Children(states)) => {
+                let mut all = !conditions.is_empty();
+                for (condition, state) in conditions.iter().zip(states) {
+                    all &= condition.evaluate_stateful(state, tick, partitions);
+                }
+                all
+            }
+
+            (PulseCondition::Or(conditions), ConditionState::Children(states)) => {
+                let mut any = false;
+                for (condition, state) in conditions.iter().zip(states) {
+                    any |= condition.evaluate_stateful(state, tick, partitions);
+                }
+                any
+            }
+
+            _ => self.evaluate(tick, partitions),
+        }
+    }
+}
+
+/// Deterministic offset in `0..window` for a jittered fire due at `tick`.
+pub(crate) fn jitter_offset(seed: u64, tick: u64, window: u64) -> u64 {
+    if window <= 1 {
+        return 0;
+    }
+    // splitmix64 finalizer
+    let mut z = seed ^ tick.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) % window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partition::PartitionState;
+
+    #[test]
+    fn jitter_fires_once_per_due_tick_within_window() {
+        let condition = PulseCondition::Jitter {
+            window: 8,
+            seed: 42,
+            inner: Box::new(PulseCondition::Every(10)),
+        };
+        let mut state = ConditionState::new(&condition, &|_| unreachable!()).unwrap();
+        let partitions: [PartitionState; 0] = [];
+
+        let fires: Vec<u64> = (1..=110)
+            .filter(|&tick| condition.evaluate_stateful(&mut state, tick, &partitions[..]))
+            .collect();
+        assert_eq!(fires.len(), 10);
+        for (due, fired) in (10..=100).step_by(10).zip(&fires) {
+            assert_eq!(*fired, due + jitter_offset(42, due, 8));
+            assert!(*fired - due < 8);
+        }
+        assert!(fires.iter().any(|&tick| tick % 10 != 0));
+    }
+
+    #[test]
+    fn debounce_fires_once_per_long_enough_streak() {
+        let condition = PulseCondition::Debounce {
+            ticks: 3,
+            inner: Box::new(PulseCondition::Or(alloc::vec![
+                PulseCondition::TickRange { start: 2, end: 6 },
+                PulseCondition::TickRange { start: 9, end: 10 },
+                PulseCondition::TickRange { start: 12, end: 14 },
+            ])),
+        };
+        let mut state = ConditionState::new(&condition, &|_| unreachable!()).unwrap();
+        let partitions: [PartitionState; 0] = [];
+
+        let fires: Vec<u64> = (1..=20)
+            .filter(|&tick| condition.evaluate_stateful(&mut state, tick, &partitions[..]))
+            .collect();
+        assert_eq!(fires, [4, 14]);
+    }
+}
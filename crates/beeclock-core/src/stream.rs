@@ -0,0 +1,277 @@
+//! [`Stream`] of tick outcomes for async pipelines (`stream` feature).
+//!
+//! A [`TickStream`] owns a clock and yields one [`TickOutcome`] per tick, so
+//! it composes with `StreamExt` combinators (`filter_map`, `zip`, `take`, or
+//! a runtime's `throttle`). It is paced in one of two ways:
+//!
+//! - [`TickStream::new`] ticks on every poll, leaving the pace to the caller:
+//!   zip it with an interval stream, throttle it, or call
+//!   [`poll_tick`](TickStream::poll_tick) from a hand-written future.
+//! - [`TickStream::every`] (std only) ticks once per period of an internal
+//!   timer, which wakes the task from a helper thread, so it runs on any
+//!   executor.
+//!
+//! Like [`Clock::tick`], each tick notifies subscribers and records metrics.
+//! The stream ends once the clock [halts](Clock::is_halted).
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures_core::{FusedStream, Stream};
+
+use crate::{Clock, TickOutcome};
+
+/// A clock as a [`Stream`] of its tick outcomes.
+///
+/// ```
+/// use beeclock_core::{Clock, TickStream};
+/// use futures::StreamExt;
+///
+/// let clock = Clock::builder()
+///     .least_significant_first()
+///     .partition("sec", 60)
+///     .pulse_every("fifth", 5)
+///     .build()
+///     .unwrap();
+///
+/// let fired: Vec<u64> = futures::executor::block_on(
+///     TickStream::new(clock)
+///         .filter_map(|o| async move { (!o.pulses.is_empty()).then_some(o.snapshot.tick) })
+///         .take(3)
+///         .collect(),
+/// );
+/// assert_eq!(fired, [5, 10, 15]);
+/// ```
+#[derive(Debug)]
+pub struct TickStream {
+    clock: Clock,
+    #[cfg(feature = "std")]
+    timer: Option<timer::Timer>,
+}
+
+impl TickStream {
+    /// A stream that ticks `clock` each time it is polled.
+    pub fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            #[cfg(feature = "std")]
+            timer: None,
+        }
+    }
+
+    /// A stream that ticks `clock` once per `period` of real time, starting
+    /// one period from now (std only).
+    ///
+    /// A consumer that falls behind receives the missed ticks back to back,
+    /// so the clock never skips a tick.
+    #[cfg(feature = "std")]
+    pub fn every(clock: Clock, period: core::time::Duration) -> Self {
+        Self {
+            clock,
+            timer: Some(timer::Timer::new(period)),
+        }
+    }
+
+    /// The clock being streamed.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Mutable access to the clock between ticks, e.g. to set alarms.
+    pub fn clock_mut(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    /// Unwrap the clock.
+    pub fn into_clock(self) -> Clock {
+        self.clock
+    }
+
+    /// Tick once the next tick is due, or `Ready(None)` once the clock has
+    /// halted.
+    ///
+    /// Without a timer the tick is always due, so every call ticks.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Option<TickOutcome>> {
+        if self.clock.is_halted() {
+            return Poll::Ready(None);
+        }
+        #[cfg(feature = "std")]
+        if let Some(timer) = &mut self.timer {
+            if timer.poll_due(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = cx;
+        Poll::Ready(Some(self.clock.tick()))
+    }
+}
+
+impl Stream for TickStream {
+    type Item = TickOutcome;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<TickOutcome>> {
+        self.get_mut().poll_tick(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = match (self.clock.is_halted(), self.clock.run_for()) {
+            (true, _) => Some(0),
+            (false, Some(at)) => usize::try_from(at.saturating_sub(self.clock.tick_count())).ok(),
+            (false, None) => None,
+        };
+        (left.unwrap_or(0), left)
+    }
+}
+
+impl FusedStream for TickStream {
+    fn is_terminated(&self) -> bool {
+        self.clock.is_halted()
+    }
+}
+
+#[cfg(feature = "std")]
+mod timer {
+    use core::task::{Context, Poll, Waker};
+    use core::time::Duration;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::thread;
+    use std::time::Instant;
+
+    /// Deadline tracking plus a helper thread that wakes the polling task
+    /// when the deadline passes.
+    #[derive(Debug)]
+    pub(super) struct Timer {
+        period: Duration,
+        due: Instant,
+        shared: Option<Arc<Shared>>,
+    }
+
+    #[derive(Debug, Default)]
+    struct Shared {
+        state: Mutex<Waiting>,
+        changed: Condvar,
+    }
+
+    #[derive(Debug, Default)]
+    struct Waiting {
+        wake_at: Option<(Instant, Waker)>,
+        closed: bool,
+    }
+
+    impl Timer {
+        pub(super) fn new(period: Duration) -> Self {
+            Self {
+                period,
+                due: Instant::now() + period,
+                shared: None,
+            }
+        }
+
+        pub(super) fn poll_due(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+            if Instant::now() >= self.due {
+                self.due += self.period;
+                return Poll::Ready(());
+            }
+            let shared = self.shared.get_or_insert_with(|| {
+                let shared = Arc::new(Shared::default());
+                let worker = Arc::clone(&shared);
+                thread::spawn(move || worker.run());
+                shared
+            });
+            let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.wake_at = Some((self.due, cx.waker().clone()));
+            shared.changed.notify_one();
+            Poll::Pending
+        }
+    }
+
+    impl Drop for Timer {
+        fn drop(&mut self) {
+            if let Some(shared) = &self.shared {
+                shared
+                    .state
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .closed = true;
+                shared.changed.notify_one();
+            }
+        }
+    }
+
+    impl Shared {
+        /// Sleep until each requested deadline and wake its task, until the
+        /// timer is dropped.
+        fn run(&self) {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            while !state.closed {
+                let Some((at, _)) = &state.wake_at else {
+                    state = self.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+                    continue;
+                };
+                let now = Instant::now();
+                if now < *at {
+                    let wait = *at - now;
+                    state = self
+                        .changed
+                        .wait_timeout(state, wait)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0;
+                    continue;
+                }
+                if let Some((_, waker)) = state.wake_at.take() {
+                    // The task may poll again inline, so wake it unlocked.
+                    drop(state);
+                    waker.wake();
+                    state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::{stream, StreamExt};
+
+    fn clock(run_for: u64) -> Clock {
+        Clock::builder()
+            .least_significant_first()
+            .partition("sec", 4)
+            .pulse_every("even", 2)
+            .run_for(run_for)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn yields_every_tick_until_the_clock_halts() {
+        let mut ticks = TickStream::new(clock(6));
+        assert_eq!(ticks.size_hint(), (6, Some(6)));
+        let labelled: Vec<(char, u64)> = block_on(
+            stream::iter(['a', 'b', 'c'])
+                .cycle()
+                .zip(&mut ticks)
+                .map(|(label, outcome)| (label, outcome.snapshot.tick))
+                .collect(),
+        );
+        assert_eq!(labelled.len(), 6);
+        assert_eq!(labelled[4], ('b', 5));
+        assert!(ticks.is_terminated());
+        assert_eq!(ticks.clock().tick_count(), 6);
+        assert!(block_on(ticks.next()).is_none());
+    }
+
+    #[test]
+    fn timer_paces_ticks() {
+        let period = core::time::Duration::from_millis(5);
+        let started = std::time::Instant::now();
+        let outcomes: Vec<TickOutcome> = block_on(TickStream::every(clock(4), period).collect());
+        assert!(started.elapsed() >= period * 4);
+        let ticks: Vec<u64> = outcomes.iter().map(|o| o.snapshot.tick).collect();
+        assert_eq!(ticks, [1, 2, 3, 4]);
+        assert_eq!(outcomes[1].pulses[0].name, "even");
+    }
+}
@@ -1,8 +1,249 @@
 //! Subscriber types for std environments.
+//!
+//! Everything a clock delivers outcomes to is an [`OutcomeSink`]: channels
+//! from [`Clock::subscribe`](crate::Clock::subscribe), callbacks, writers such
+//! as [`JsonLinesSink`](crate::JsonLinesSink), and user types, all attached
+//! with [`Clock::attach_sink`](crate::Clock::attach_sink). A [`PulseFilter`]
+//! in front of any of them narrows what it sees to pulses matching a name
+//! pattern.
 
+use core::fmt;
+use std::io::Write;
 use std::sync::mpsc::{Sender, SyncSender, TrySendError};
+use std::sync::Arc;
 
-use crate::TickOutcome;
+use crate::router::matches_pattern;
+use crate::{JsonLinesSink, TickOutcome};
+
+/// Whether a sink wants further outcomes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SinkStatus {
+    /// Keep delivering.
+    Open,
+    /// Detach the sink; the clock drops it after this delivery.
+    Closed,
+}
+
+/// Receives tick outcomes from a clock.
+///
+/// Implemented for channel senders, [`Subscriber`], [`JsonLinesSink`], and
+/// closures `FnMut(&TickOutcome) -> SinkStatus`:
+///
+/// ```
+/// use beeclock_core::{Clock, SinkStatus, TickOutcome};
+///
+/// let mut clock = Clock::default();
+/// let mut remaining = 2;
+/// clock.attach_sink(Box::new(move |outcome: &TickOutcome| {
+///     println!("{outcome}");
+///     remaining -= 1;
+///     if remaining == 0 {
+///         SinkStatus::Closed
+///     } else {
+///         SinkStatus::Open
+///     }
+/// }));
+/// assert_eq!(clock.sink_count(), 1);
+/// clock.tick();
+/// clock.tick();
+/// assert_eq!(clock.sink_count(), 0);
+/// ```
+///
+/// Sinks are `Send + Sync` so that clocks holding them remain so.
+pub trait OutcomeSink: Send + Sync {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus;
+}
+
+impl fmt::Debug for dyn OutcomeSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OutcomeSink")
+    }
+}
+
+impl<F> OutcomeSink for F
+where
+    F: FnMut(&TickOutcome) -> SinkStatus + Send + Sync,
+{
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        self(outcome)
+    }
+}
+
+/// Closes once the receiver is dropped.
+impl OutcomeSink for Sender<TickOutcome> {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        match self.send(outcome.clone()) {
+            Ok(()) => SinkStatus::Open,
+            Err(_) => SinkStatus::Closed,
+        }
+    }
+}
+
+/// Drops outcomes while the channel is full; closes once the receiver is
+/// dropped.
+impl OutcomeSink for SyncSender<TickOutcome> {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        match self.try_send(outcome.clone()) {
+            Ok(()) | Err(TrySendError::Full(_)) => SinkStatus::Open,
+            Err(TrySendError::Disconnected(_)) => SinkStatus::Closed,
+        }
+    }
+}
+
+/// Closes on the first write error.
+impl<W: Write + Send + Sync> OutcomeSink for JsonLinesSink<W> {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        match self.write(outcome) {
+            Ok(()) => SinkStatus::Open,
+            Err(_) => SinkStatus::Closed,
+        }
+    }
+}
+
+/// Passes outcomes on to another sink with only the pulses whose names
+/// match a pattern.
+///
+/// Patterns use `*` for any run of characters, as in
+/// [`PulseRouter`](crate::PulseRouter): `"ui.*"` keeps pulses named
+/// `ui.<something>`. Outcomes left with no pulses are skipped unless
+/// [`keep_empty`](Self::keep_empty) is set, and so are outcomes whose only
+/// matches are [ignored](Self::ignoring).
+///
+/// ```
+/// use beeclock_core::{Clock, PulseFilter};
+///
+/// let mut clock = Clock::builder()
+///     .pulse_every("ui.redraw", 2)
+///     .pulse_every("net.poll", 3)
+///     .build()
+///     .unwrap();
+/// let (tx, rx) = std::sync::mpsc::channel();
+/// clock.attach_sink(Box::new(PulseFilter::new("ui.*", tx)));
+/// for _ in 0..6 {
+///     clock.tick();
+/// }
+/// let ticks: Vec<u64> = rx.try_iter().map(|o| o.snapshot.tick).collect();
+/// assert_eq!(ticks, [2, 4, 6]);
+/// ```
+#[derive(Debug)]
+pub struct PulseFilter<S> {
+    pattern: String,
+    keep_empty: bool,
+    /// Pulse that is passed on but does not count as a match.
+    ignore: Option<String>,
+    inner: S,
+}
+
+impl<S: OutcomeSink> PulseFilter<S> {
+    /// Deliver to `inner` only pulses matching `pattern`.
+    pub fn new(pattern: impl Into<String>, inner: S) -> Self {
+        Self {
+            pattern: pattern.into(),
+            keep_empty: false,
+            ignore: None,
+            inner,
+        }
+    }
+
+    /// Also deliver outcomes with no matching pulses, e.g. to keep seeing
+    /// every snapshot.
+    pub fn keep_empty(mut self) -> Self {
+        self.keep_empty = true;
+        self
+    }
+
+    /// Don't deliver an outcome just because pulse `name` fired; it is
+    /// still passed on with other matches. Used to skip ticks where only
+    /// the overflow pulse fired.
+    pub fn ignoring(mut self, name: impl Into<String>) -> Self {
+        self.ignore = Some(name.into());
+        self
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Unwrap the filtered sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: OutcomeSink> OutcomeSink for PulseFilter<S> {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        let matches = |name: &str| matches_pattern(&self.pattern, name);
+        let (mut kept, mut counted) = (0, false);
+        for pulse in outcome.pulses.iter().filter(|p| matches(&p.name)) {
+            kept += 1;
+            counted |= self.ignore.as_deref() != Some(pulse.name.as_str());
+        }
+        if !counted && !self.keep_empty {
+            return SinkStatus::Open;
+        }
+        if kept == outcome.pulses.len() {
+            return self.inner.deliver(outcome);
+        }
+        let filtered = TickOutcome {
+            clock: outcome.clock.clone(),
+            snapshot: outcome.snapshot.clone(),
+            pulses: outcome
+                .pulses
+                .iter()
+                .filter(|p| matches(&p.name))
+                .cloned()
+                .collect(),
+            overflowed: outcome.overflowed,
+            quiet_reports: outcome.quiet_reports.clone(),
+            children: outcome.children.clone(),
+        };
+        self.inner.deliver(&filtered)
+    }
+}
+
+/// A partition's value changing, from
+/// [`Clock::subscribe_partition_changes`](crate::Clock::subscribe_partition_changes).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartitionChange {
+    pub partition: Arc<str>,
+    /// Value on the last tick delivered before this one.
+    pub old: u64,
+    pub new: u64,
+    pub tick: u64,
+    pub epoch: u64,
+    /// Name of the clock, if it has one.
+    pub clock: Option<Arc<str>>,
+}
+
+/// Sends a [`PartitionChange`] when one partition's value differs from the
+/// last outcome's.
+pub(crate) struct ChangeSink {
+    pub index: usize,
+    pub last: u64,
+    pub tx: Sender<PartitionChange>,
+}
+
+impl OutcomeSink for ChangeSink {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        let part = &outcome.snapshot.partitions[self.index];
+        if part.value == self.last {
+            return SinkStatus::Open;
+        }
+        let change = PartitionChange {
+            partition: Arc::clone(&part.name),
+            old: self.last,
+            new: part.value,
+            tick: outcome.snapshot.tick,
+            epoch: outcome.snapshot.epoch,
+            clock: outcome.clock.clone(),
+        };
+        self.last = part.value;
+        match self.tx.send(change) {
+            Ok(()) => SinkStatus::Open,
+            Err(_) => SinkStatus::Closed,
+        }
+    }
+}
 
 /// A subscriber that receives tick outcomes.
 #[derive(Debug)]
@@ -27,3 +268,157 @@ impl Subscriber {
         }
     }
 }
+
+impl OutcomeSink for Subscriber {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        if self.send(outcome) {
+            SinkStatus::Open
+        } else {
+            SinkStatus::Closed
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, ClockError};
+    use std::sync::{mpsc, Mutex};
+
+    /// A user-defined sink.
+    struct Ticks(Arc<Mutex<Vec<u64>>>);
+
+    impl OutcomeSink for Ticks {
+        fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+            self.0.lock().unwrap().push(outcome.snapshot.tick);
+            SinkStatus::Open
+        }
+    }
+
+    #[test]
+    fn delivers_to_every_kind_of_sink() {
+        let mut clock = Clock::default();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        clock.attach_sink(Box::new(Ticks(Arc::clone(&seen))));
+        let rx = clock.subscribe();
+        let bounded = clock.subscribe_bounded(1);
+        clock.attach_sink(Box::new(JsonLinesSink::new(Vec::new())));
+        assert_eq!(clock.sink_count(), 4);
+
+        clock.tick();
+        clock.tick();
+        assert_eq!(*seen.lock().unwrap(), [1, 2]);
+        assert_eq!(rx.try_iter().count(), 2);
+        assert_eq!(bounded.try_iter().count(), 1, "full queue drops");
+
+        drop((rx, bounded));
+        clock.tick();
+        assert_eq!(clock.sink_count(), 2, "closed channels are detached");
+    }
+
+    #[test]
+    fn filters_pulses_by_pattern() {
+        let mut clock = Clock::builder()
+            .pulse_every("ui.redraw", 2)
+            .pulse_every("ui.blink", 3)
+            .pulse_every("net.poll", 2)
+            .build()
+            .unwrap();
+        let matching = clock.subscribe_matching("ui.*");
+        let (tx, every) = mpsc::channel();
+        clock.attach_sink(Box::new(PulseFilter::new("*.poll", tx).keep_empty()));
+        for _ in 0..6 {
+            clock.tick();
+        }
+
+        let names = |outcome: TickOutcome| -> Vec<String> {
+            outcome.pulses.iter().map(|p| p.name.clone()).collect()
+        };
+        let seen: Vec<Vec<String>> = matching.try_iter().map(names).collect();
+        assert_eq!(
+            seen,
+            [
+                vec!["ui.redraw"],
+                vec!["ui.blink"],
+                vec!["ui.redraw"],
+                vec!["ui.redraw", "ui.blink"],
+            ]
+        );
+        let polls: Vec<usize> = every.try_iter().map(|o| o.pulses.len()).collect();
+        assert_eq!(polls, [0, 1, 0, 1, 0, 1]);
+
+        drop(matching);
+        clock.tick();
+        clock.tick();
+        assert_eq!(clock.sink_count(), 1, "closes with its inner sink");
+    }
+
+    #[test]
+    fn fired_subscription_skips_silent_and_overflow_ticks() {
+        let mut clock = Clock::builder().pulse_every("rare", 5).build().unwrap();
+        let fired = clock.subscribe_fired();
+        clock.observe(u64::MAX - 7);
+        clock.observe(u64::MAX - 2);
+        clock.iter_ticks().take(10).for_each(drop);
+
+        let seen: Vec<(u64, usize)> = fired
+            .try_iter()
+            .map(|o| (o.snapshot.tick, o.pulses.len()))
+            .collect();
+        // u64::MAX is a multiple of 5; the wrap to tick 0 fires only
+        // `__overflow__`.
+        assert_eq!(seen, [(u64::MAX, 1), (5, 1)]);
+    }
+
+    #[test]
+    fn reports_partition_changes_only() {
+        let mut clock = Clock::default();
+        clock.set_name("wall");
+        clock.set_partition("min", 58).unwrap();
+        let hours = clock.subscribe_partition_changes("hour").unwrap();
+        let minutes = clock.subscribe_partition_changes("min").unwrap();
+        clock.iter_ticks().take(180).for_each(drop);
+
+        let change = hours.try_recv().unwrap();
+        assert_eq!(
+            change,
+            PartitionChange {
+                partition: Arc::from("hour"),
+                old: 0,
+                new: 1,
+                tick: 120,
+                epoch: 0,
+                clock: Some(Arc::from("wall")),
+            }
+        );
+        assert!(hours.try_recv().is_err());
+        let minutes: Vec<(u64, u64)> = minutes.try_iter().map(|c| (c.old, c.new)).collect();
+        assert_eq!(minutes, [(58, 59), (59, 0), (0, 1)]);
+
+        assert!(matches!(
+            clock.subscribe_partition_changes("day"),
+            Err(ClockError::PartitionNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn failing_writer_closes_its_sink() {
+        struct Broken;
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut clock = Clock::default();
+        clock.attach_sink(Box::new(JsonLinesSink::new(Broken)));
+        let (tx, rx) = mpsc::channel();
+        clock.attach_sink(Box::new(tx));
+        clock.tick();
+        assert_eq!(clock.sink_count(), 1);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,150 @@
+//! Timeline rendering of simulated schedules.
+//!
+//! Draws one row per pulse across the simulated horizon, either as an ASCII
+//! chart for terminals or as a standalone SVG document.
+
+use alloc::string::String;
+use alloc::vec;
+use core::fmt::Write;
+
+use crate::ScheduleReport;
+
+const SVG_LABEL_WIDTH: u64 = 160;
+const SVG_PLOT_WIDTH: u64 = 800;
+const SVG_ROW_HEIGHT: u64 = 24;
+
+impl ScheduleReport {
+    /// Render an ASCII timeline `width` columns wide.
+    ///
+    /// Each column covers an equal share of the horizon and shows `|` for a
+    /// single fire, `#` for several, and `.` for none.
+    pub fn to_ascii(&self, width: usize) -> String {
+        let width = width.max(1);
+        let label = self.pulses.iter().map(|p| p.name.len()).max().unwrap_or(0);
+        let mut out = String::new();
+
+        let first = self.start_tick.saturating_add(1);
+        let last = self.start_tick.saturating_add(self.horizon);
+        let _ = writeln!(out, "{:label$}  ticks {first}..={last}", "");
+        for pulse in &self.pulses {
+            let mut counts = vec![0u32; width];
+            for &tick in &pulse.fire_ticks {
+                counts[self.column(tick, width as u64) as usize] += 1;
+            }
+            let _ = write!(out, "{:label$} [", pulse.name);
+            for count in counts {
+                out.push(match count {
+                    0 => '.',
+                    1 => '|',
+                    _ => '#',
+                });
+            }
+            out.push_str("]\n");
+        }
+        out
+    }
+
+    /// Render an SVG timeline with one labelled row per pulse.
+    pub fn to_svg(&self) -> String {
+        let rows = self.pulses.len() as u64;
+        let width = SVG_LABEL_WIDTH + SVG_PLOT_WIDTH + 20;
+        let height = (rows + 1) * SVG_ROW_HEIGHT;
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="monospace" font-size="12">"#
+        );
+        let axis_y = rows * SVG_ROW_HEIGHT + 4;
+        let _ = writeln!(
+            out,
+            r##"  <line x1="{SVG_LABEL_WIDTH}" y1="{axis_y}" x2="{}" y2="{axis_y}" stroke="#999"/>"##,
+            SVG_LABEL_WIDTH + SVG_PLOT_WIDTH
+        );
+        let _ = writeln!(
+            out,
+            r#"  <text x="{SVG_LABEL_WIDTH}" y="{}">{}</text>"#,
+            axis_y + 14,
+            self.start_tick.saturating_add(1)
+        );
+        let _ = writeln!(
+            out,
+            r#"  <text x="{}" y="{}" text-anchor="end">{}</text>"#,
+            SVG_LABEL_WIDTH + SVG_PLOT_WIDTH,
+            axis_y + 14,
+            self.start_tick.saturating_add(self.horizon)
+        );
+
+        for (row, pulse) in self.pulses.iter().enumerate() {
+            let top = row as u64 * SVG_ROW_HEIGHT;
+            let _ = write!(out, r#"  <g><title>"#);
+            write_xml_escaped(&mut out, &pulse.name);
+            let _ = writeln!(out, " ({} fires)</title>", pulse.count());
+            let _ = write!(out, r#"    <text x="4" y="{}">"#, top + 16);
+            write_xml_escaped(&mut out, &pulse.name);
+            out.push_str("</text>\n");
+            for &tick in &pulse.fire_ticks {
+                let x = SVG_LABEL_WIDTH + self.column(tick, SVG_PLOT_WIDTH);
+                let _ = writeln!(
+                    out,
+                    r##"    <rect x="{x}" y="{}" width="1" height="{}" fill="#d97706"/>"##,
+                    top + 4,
+                    SVG_ROW_HEIGHT - 8
+                );
+            }
+            out.push_str("  </g>\n");
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Column (0..columns) for a tick within the simulated horizon.
+    fn column(&self, tick: u64, columns: u64) -> u64 {
+        let offset = tick.wrapping_sub(self.start_tick).saturating_sub(1);
+        let horizon = self.horizon.max(1);
+        ((offset as u128 * columns as u128) / horizon as u128).min(columns as u128 - 1) as u64
+    }
+}
+
+fn write_xml_escaped(out: &mut String, value: &str) {
+    for ch in value.chars() {
+        match ch {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Clock;
+
+    #[test]
+    fn renders_ascii_rows() {
+        let clock = Clock::builder()
+            .pulse_every("two", 2)
+            .pulse_every("five", 5)
+            .build()
+            .unwrap();
+        let report = clock.simulate(10);
+
+        assert_eq!(
+            report.to_ascii(10),
+            "      ticks 1..=10\ntwo  [.|.|.|.|.|]\nfive [....|....|]\n"
+        );
+        assert_eq!(report.to_ascii(5).lines().nth(1), Some("two  [|||||]"));
+        assert_eq!(report.to_ascii(2).lines().nth(2), Some("five [||]"));
+    }
+
+    #[test]
+    fn renders_svg_marks() {
+        let clock = Clock::builder().pulse_every("a<b", 3).build().unwrap();
+        let svg = clock.simulate(9).to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("a&lt;b"));
+        assert_eq!(svg.matches("<rect").count(), 3);
+    }
+}
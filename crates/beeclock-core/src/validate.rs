@@ -0,0 +1,339 @@
+//! Configuration validation.
+//!
+//! The checks a clock runs on its configuration when it is built, exposed so
+//! editors and bindings can check user input first: [`PulseSpec::validate`]
+//! for one pulse against a set of partitions, and [`ClockConfig::validate`]
+//! for a whole configuration. Both report the first problem found as the
+//! same [`ClockError`] building would.
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+
+use crate::error::closest_name;
+use crate::pulse::PulseState;
+use crate::{
+    ClockConfig, ClockDuration, ClockError, PartitionSpec, PulseCondition, PulseSpec, DAY_PARTITION,
+};
+
+impl PulseSpec {
+    /// Check this pulse's condition and delay against `partitions`.
+    ///
+    /// A delayed pulse's trigger is another pulse, so only
+    /// [`ClockConfig::validate`] checks that it exists.
+    ///
+    /// ```
+    /// use beeclock_core::{ClockError, PartitionSpec, PulseSpec};
+    ///
+    /// let partitions = [PartitionSpec::new("sec", 60), PartitionSpec::new("min", 60)];
+    /// let pulse = PulseSpec::new("top", "mni == 0".parse().unwrap());
+    /// let err = pulse.validate(&partitions).unwrap_err();
+    /// assert!(matches!(err, ClockError::UnknownPartition { suggestion: Some(ref s), .. } if s == "min"));
+    /// ```
+    pub fn validate(&self, partitions: &[PartitionSpec]) -> Result<(), ClockError> {
+        self.check(&partition_names(partitions))
+    }
+
+    fn check(&self, partitions: &BTreeSet<String>) -> Result<(), ClockError> {
+        validate_condition(&self.condition, partitions, &self.name)?;
+        match &self.after {
+            Some(after) if after.delay == 0 => Err(ClockError::ZeroDelay {
+                name: self.name.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ClockConfig {
+    /// Check everything [`build`](Self::build) would, without building.
+    ///
+    /// ```
+    /// use beeclock_core::{Clock, ClockError};
+    ///
+    /// let mut config = Clock::builder().pulse_every("beat", 4).build().unwrap().config();
+    /// assert!(config.validate().is_ok());
+    /// config.run_for = Some(0);
+    /// assert!(matches!(config.validate(), Err(ClockError::ZeroRunLength)));
+    /// ```
+    pub fn validate(&self) -> Result<(), ClockError> {
+        if self.ticks_per_unit == Some(0) {
+            return Err(ClockError::ZeroTicksPerUnit);
+        }
+        validate_layout(&self.partitions, &self.pulses)?;
+        let ticks_of = |duration: &ClockDuration| {
+            let ticks_per_unit = self.ticks_per_unit.unwrap_or(1);
+            duration.ticks_in(self.order, &self.partitions, ticks_per_unit)
+        };
+        for pulse in &self.pulses {
+            PulseState::new(pulse, &ticks_of)?;
+        }
+        let known = partition_names(&self.partitions);
+        for window in &self.quiet_windows {
+            validate_stateless_condition(&window.condition, &known, &window.name)?;
+        }
+        if self.run_for == Some(0) {
+            return Err(ClockError::ZeroRunLength);
+        }
+        if self
+            .tick_duration
+            .is_some_and(|duration| duration.is_zero())
+        {
+            return Err(ClockError::ZeroTickDuration);
+        }
+        validate_partitions(&self.epoch_partitions)
+    }
+}
+
+/// Names of `partitions`, for condition checks.
+pub(crate) fn partition_names(partitions: &[PartitionSpec]) -> BTreeSet<String> {
+    partitions.iter().map(|p| p.name.clone()).collect()
+}
+
+/// Check that every partition has a modulus.
+pub(crate) fn validate_partitions(partitions: &[PartitionSpec]) -> Result<(), ClockError> {
+    match partitions.iter().find(|spec| spec.modulus == 0) {
+        Some(spec) => Err(ClockError::ZeroModulus {
+            name: spec.name.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check a condition that is evaluated without per-pulse state, as quiet
+/// windows and state machine guards are: valid, and not stateful.
+pub(crate) fn validate_stateless_condition(
+    condition: &PulseCondition,
+    partitions: &BTreeSet<String>,
+    name: &str,
+) -> Result<(), ClockError> {
+    validate_condition(condition, partitions, name)?;
+    if condition.is_stateful() {
+        return Err(ClockError::StatefulCondition {
+            name: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Check the partitions, then each pulse against them and its delay trigger
+/// against the other pulses.
+pub(crate) fn validate_layout(
+    partitions: &[PartitionSpec],
+    pulses: &[PulseSpec],
+) -> Result<(), ClockError> {
+    validate_partitions(partitions)?;
+    let known = partition_names(partitions);
+    for pulse in pulses {
+        pulse.check(&known)?;
+        if let Some(after) = &pulse.after {
+            if !pulses.iter().any(|p| p.name == after.pulse) {
+                return Err(ClockError::UnknownTrigger {
+                    pulse: pulse.name.clone(),
+                    trigger: after.pulse.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_condition(
+    condition: &PulseCondition,
+    partitions: &BTreeSet<String>,
+    pulse_name: &str,
+) -> Result<(), ClockError> {
+    match condition {
+        PulseCondition::Every(period) => {
+            if *period == 0 {
+                Err(ClockError::ZeroPeriod {
+                    name: pulse_name.to_string(),
+                })
+            } else {
+                Ok(())
+            }
+        }
+
+        PulseCondition::PartitionEquals { name, .. }
+        | PulseCondition::PartitionChanged { name }
+        | PulseCondition::PartitionDecreased { name } => {
+            if partitions.contains(name) {
+                Ok(())
+            } else {
+                Err(ClockError::UnknownPartition {
+                    pulse: pulse_name.to_string(),
+                    partition: name.clone(),
+                    suggestion: closest_name(name, partitions.iter().map(String::as_str)),
+                })
+            }
+        }
+
+        PulseCondition::PartitionModulo { name, modulus, .. } => {
+            if *modulus == 0 {
+                return Err(ClockError::ZeroConditionModulus {
+                    pulse: pulse_name.to_string(),
+                    partition: name.clone(),
+                });
+            }
+            if partitions.contains(name) {
+                Ok(())
+            } else {
+                Err(ClockError::UnknownPartition {
+                    pulse: pulse_name.to_string(),
+                    partition: name.clone(),
+                    suggestion: closest_name(name, partitions.iter().map(String::as_str)),
+                })
+            }
+        }
+
+        PulseCondition::TickRange { start, end } => {
+            if start > end {
+                Err(ClockError::InvalidTickRange {
+                    pulse: pulse_name.to_string(),
+                    start: *start,
+                    end: *end,
+                })
+            } else {
+                Ok(())
+            }
+        }
+
+        PulseCondition::Not(inner) => validate_condition(inner, partitions, pulse_name),
+
+        PulseCondition::Jitter {
+            window: ticks,
+            inner,
+            ..
+        }
+        | PulseCondition::RateLimit {
+            per_ticks: ticks,
+            inner,
+            ..
+        }
+        | PulseCondition::Debounce { ticks, inner } => {
+            if *ticks == 0 {
+                return Err(ClockError::ZeroWindow {
+                    pulse: pulse_name.to_string(),
+                });
+            }
+            validate_condition(inner, partitions, pulse_name)
+        }
+
+        PulseCondition::ElapsedAtLeast { duration, .. } => {
+            if duration.is_zero() {
+                return Err(ClockError::ZeroWindow {
+                    pulse: pulse_name.to_string(),
+                });
+            }
+            match duration
+                .units()
+                .find(|(name, _)| !partitions.contains(*name))
+            {
+                Some((name, _)) => Err(ClockError::UnknownPartition {
+                    pulse: pulse_name.to_string(),
+                    partition: name.to_string(),
+                    suggestion: closest_name(name, partitions.iter().map(String::as_str)),
+                }),
+                None => Ok(()),
+            }
+        }
+
+        PulseCondition::DateEquals { .. } => {
+            if partitions.contains(DAY_PARTITION) {
+                Ok(())
+            } else {
+                Err(ClockError::UnknownPartition {
+                    pulse: pulse_name.to_string(),
+                    partition: DAY_PARTITION.to_string(),
+                    suggestion: None,
+                })
+            }
+        }
+
+        PulseCondition::And(conditions) | PulseCondition::Or(conditions) => {
+            for c in conditions {
+                validate_condition(c, partitions, pulse_name)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, ElapsedSince, QuietWindow};
+    use alloc::vec;
+    use core::time::Duration;
+
+    #[test]
+    fn validate_agrees_with_build() {
+        let base = || {
+            Clock::builder()
+                .least_significant_first()
+                .partition("sec", 60)
+                .partition("min", 60)
+                .pulse_every("beat", 4)
+                .pulse_after("echo", "beat", 2)
+                .build()
+                .unwrap()
+                .config()
+        };
+        let mut broken = vec![];
+        let mut config = base();
+        config.partitions[1].modulus = 0;
+        broken.push(config);
+        let mut config = base();
+        config.pulses[0].condition = PulseCondition::Every(0);
+        broken.push(config);
+        let mut config = base();
+        config.pulses[1].after.as_mut().unwrap().pulse = String::from("beet");
+        broken.push(config);
+        let mut config = base();
+        config.pulses[1].after.as_mut().unwrap().delay = 0;
+        broken.push(config);
+        let mut config = base();
+        config.pulses[0].condition = PulseCondition::ElapsedAtLeast {
+            duration: ClockDuration::of("min", u64::MAX),
+            since: ElapsedSince::Start,
+        };
+        broken.push(config);
+        let mut config = base();
+        config
+            .quiet_windows
+            .push(QuietWindow::new("night", "hour == 3".parse().unwrap()));
+        broken.push(config);
+        let mut config = base();
+        config.tick_duration = Some(Duration::ZERO);
+        broken.push(config);
+        let mut config = base();
+        config.ticks_per_unit = Some(0);
+        broken.push(config);
+        let mut config = base();
+        config.epoch_partitions.push(PartitionSpec::new("era", 0));
+        broken.push(config);
+
+        assert!(base().validate().is_ok());
+        for config in broken {
+            let expected = config.clone().build().unwrap_err();
+            assert_eq!(config.validate().unwrap_err().code(), expected.code());
+        }
+    }
+
+    #[test]
+    fn pulses_validate_alone() {
+        let partitions = [PartitionSpec::new("sec", 60)];
+        assert!(PulseSpec::new("top", "sec == 0".parse().unwrap())
+            .validate(&partitions)
+            .is_ok());
+        let err = PulseSpec::new("top", "min == 0".parse().unwrap())
+            .validate(&partitions)
+            .unwrap_err();
+        assert!(
+            matches!(err, ClockError::UnknownPartition { ref partition, .. } if partition == "min")
+        );
+        let err = PulseSpec::new("steady", "debounce(0, sec == 0)".parse().unwrap())
+            .validate(&partitions)
+            .unwrap_err();
+        assert!(matches!(err, ClockError::ZeroWindow { ref pulse } if pulse == "steady"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Stall detection for clock loops (std only).
+//!
+//! A [`Watchdog`] is a cloneable handle shared between the loop that ticks a
+//! clock and whatever monitors it. The loop pets it on every tick, either
+//! from a [`drive`](crate::drive) callback or by attaching it as an
+//! [`OutcomeSink`]. The monitor calls [`Watchdog::check`] (for example from a
+//! health endpoint) and, the first time a check finds no tick within the
+//! real-time budget, a [`STALLED_PULSE`] is sent to the watchdog's
+//! subscribers, stamped with the last tick that did happen.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::{Clock, OutcomeSink, PulseFired, SinkStatus, TickOutcome};
+
+/// Name of the pulse a [`Watchdog`] sends when its clock stalls.
+pub const STALLED_PULSE: &str = "__stalled__";
+
+/// Detects a clock loop that stopped ticking.
+///
+/// ```
+/// use std::time::Duration;
+/// use beeclock_core::{Clock, Watchdog};
+///
+/// let watchdog = Watchdog::new(Duration::from_secs(5));
+/// let stalled = watchdog.subscribe();
+/// let mut clock = Clock::default();
+/// clock.attach_sink(Box::new(watchdog.clone()));
+///
+/// clock.tick();
+/// let health = watchdog.check();
+/// assert!(!health.stalled);
+/// assert_eq!(health.last_tick, Some(1));
+/// assert!(stalled.try_recv().is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Watchdog {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    budget: Duration,
+    state: Mutex<WatchState>,
+}
+
+#[derive(Debug)]
+struct WatchState {
+    /// When the watchdog was last petted (or created).
+    last_seen: Instant,
+    /// Tick, epoch and clock name of the last pet.
+    last_tick: Option<(u64, u64, Option<Arc<str>>)>,
+    /// Whether the current stall has been reported.
+    stalled: bool,
+    subscribers: Vec<Sender<PulseFired>>,
+}
+
+/// Result of [`Watchdog::check`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchdogHealth {
+    /// Whether no tick happened within the budget.
+    pub stalled: bool,
+    /// Real time since the last tick (or since the watchdog was created).
+    pub since_last_tick: Duration,
+    /// Last tick seen, if any.
+    pub last_tick: Option<u64>,
+}
+
+impl Watchdog {
+    /// Watch for gaps between ticks longer than `budget`.
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                budget,
+                state: Mutex::new(WatchState {
+                    last_seen: Instant::now(),
+                    last_tick: None,
+                    stalled: false,
+                    subscribers: Vec::new(),
+                }),
+            }),
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.shared.budget
+    }
+
+    /// Record that `clock` just ticked.
+    pub fn pet(&self, clock: &Clock) {
+        let name = clock.name().map(Arc::from);
+        self.record(clock.tick_count(), clock.epoch(), name);
+    }
+
+    /// Receive a [`STALLED_PULSE`] each time a stall is first detected.
+    pub fn subscribe(&self) -> Receiver<PulseFired> {
+        let (tx, rx) = mpsc::channel();
+        self.state().subscribers.push(tx);
+        rx
+    }
+
+    /// Report health, notifying subscribers if this check is the first to
+    /// find the clock stalled. A later tick clears the stall.
+    pub fn check(&self) -> WatchdogHealth {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&self, now: Instant) -> WatchdogHealth {
+        let mut state = self.state();
+        let since_last_tick = now.saturating_duration_since(state.last_seen);
+        let stalled = since_last_tick > self.shared.budget;
+        if stalled && !state.stalled {
+            state.stalled = true;
+            let (tick, epoch, clock) = state.last_tick.clone().unwrap_or_default();
+            let pulse = PulseFired {
+                name: String::from(STALLED_PULSE),
+                tick,
+                epoch,
+                clock,
+            };
+            state
+                .subscribers
+                .retain(|tx| tx.send(pulse.clone()).is_ok());
+        }
+        WatchdogHealth {
+            stalled,
+            since_last_tick,
+            last_tick: state.last_tick.as_ref().map(|&(tick, ..)| tick),
+        }
+    }
+
+    fn record(&self, tick: u64, epoch: u64, clock: Option<Arc<str>>) {
+        let mut state = self.state();
+        state.last_seen = Instant::now();
+        state.last_tick = Some((tick, epoch, clock));
+        state.stalled = false;
+    }
+
+    fn state(&self) -> MutexGuard<'_, WatchState> {
+        // The state stays consistent even if a holder panicked.
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Pets the watchdog on every outcome; never closes.
+impl OutcomeSink for Watchdog {
+    fn deliver(&mut self, outcome: &TickOutcome) -> SinkStatus {
+        let snapshot = &outcome.snapshot;
+        self.record(snapshot.tick, snapshot.epoch, outcome.clock.clone());
+        SinkStatus::Open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_a_stall_once_until_the_next_tick() {
+        let watchdog = Watchdog::new(Duration::from_secs(1));
+        let rx = watchdog.subscribe();
+        let mut clock = Clock::builder().name("loop").build().unwrap();
+        clock.attach_sink(Box::new(watchdog.clone()));
+        clock.tick();
+        clock.tick();
+
+        let later = Instant::now() + Duration::from_secs(2);
+        let health = watchdog.check_at(later);
+        assert!(health.stalled);
+        assert_eq!(health.last_tick, Some(2));
+        let pulse = rx.try_recv().unwrap();
+        assert_eq!(
+            (pulse.name.as_str(), pulse.tick, pulse.clock.as_deref()),
+            (STALLED_PULSE, 2, Some("loop"))
+        );
+
+        assert!(watchdog.check_at(later).stalled);
+        assert!(rx.try_recv().is_err(), "reported once per stall");
+
+        watchdog.pet(&clock);
+        assert!(!watchdog.check().stalled);
+        assert!(watchdog.check_at(later + Duration::from_secs(2)).stalled);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,140 @@
+//! Hierarchical timer wheel for tick-targeted alarms.
+//!
+//! Eleven levels of 64 slots cover the whole `u64` tick range. An entry sits
+//! at the level of the highest bit where its due tick differs from the
+//! current tick; each time the current tick enters a new slot at some level,
+//! that slot's entries cascade down a level. Advancing one tick therefore
+//! touches only the entries that are due or cascading, not every pending one.
+
+use alloc::vec::Vec;
+
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+const LEVELS: usize = 11;
+
+/// `(due tick, id)` pairs bucketed by due tick.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TimerWheel {
+    now: u64,
+    /// `LEVELS * SLOTS` buckets, allocated on first insert.
+    slots: Vec<Vec<(u64, u64)>>,
+    /// Entries already due when inserted.
+    ready: Vec<(u64, u64)>,
+}
+
+impl TimerWheel {
+    /// Schedule `id` for tick `due`, with the clock currently at `now`.
+    pub fn insert(&mut self, now: u64, due: u64, id: u64) {
+        if self.slots.is_empty() {
+            self.slots = (0..LEVELS * SLOTS).map(|_| Vec::new()).collect();
+            self.now = now;
+        } else if now != self.now {
+            self.rebuild(now);
+        }
+        self.place(due, id);
+    }
+
+    /// Drop every entry.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(Vec::clear);
+        self.ready.clear();
+    }
+
+    /// Move to `tick`, reporting every entry due at or before it.
+    ///
+    /// Consecutive ticks cost O(1) amortized; any other jump re-buckets all
+    /// entries.
+    pub fn advance(&mut self, tick: u64, mut due: impl FnMut(u64, u64)) {
+        if self.slots.is_empty() {
+            self.now = tick;
+            return;
+        }
+        if tick == self.now.wrapping_add(1) && tick != 0 {
+            self.now = tick;
+            for level in (1..LEVELS).rev() {
+                let shift = SLOT_BITS * level as u32;
+                if tick & ((1 << shift) - 1) == 0 {
+                    let slot = level * SLOTS + (tick >> shift) as usize % SLOTS;
+                    for (at, id) in core::mem::take(&mut self.slots[slot]) {
+                        self.place(at, id);
+                    }
+                }
+            }
+            let slot = tick as usize % SLOTS;
+            for (at, id) in core::mem::take(&mut self.slots[slot]) {
+                self.place(at, id);
+            }
+        } else if tick != self.now {
+            self.rebuild(tick);
+        }
+        for (at, id) in self.ready.drain(..) {
+            due(at, id);
+        }
+    }
+
+    /// Bucket an entry relative to `self.now`.
+    fn place(&mut self, due: u64, id: u64) {
+        if due <= self.now {
+            self.ready.push((due, id));
+            return;
+        }
+        let level = ((63 - (due ^ self.now).leading_zeros()) / SLOT_BITS) as usize;
+        let slot = (due >> (SLOT_BITS * level as u32)) as usize % SLOTS;
+        self.slots[level * SLOTS + slot].push((due, id));
+    }
+
+    fn rebuild(&mut self, now: u64) {
+        self.now = now;
+        let entries: Vec<(u64, u64)> = self.slots.iter_mut().flat_map(core::mem::take).collect();
+        for (due, id) in entries {
+            self.place(due, id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn run(wheel: &mut TimerWheel, ticks: core::ops::RangeInclusive<u64>) -> Vec<(u64, u64)> {
+        let mut fired = Vec::new();
+        for tick in ticks {
+            wheel.advance(tick, |due, id| {
+                assert!(due <= tick);
+                fired.push((tick, id));
+            });
+        }
+        fired
+    }
+
+    #[test]
+    fn fires_each_entry_on_its_tick() {
+        let mut wheel = TimerWheel::default();
+        let dues = [1u64, 63, 64, 65, 4095, 4096, 4097, 300_000];
+        for (id, &due) in dues.iter().enumerate() {
+            wheel.insert(0, due, id as u64);
+        }
+        let fired = run(&mut wheel, 1..=300_000);
+        let expected: Vec<(u64, u64)> = dues
+            .iter()
+            .enumerate()
+            .map(|(id, &due)| (due, id as u64))
+            .collect();
+        assert_eq!(fired, expected);
+    }
+
+    #[test]
+    fn jumps_rebucket_and_fire_overdue() {
+        let mut wheel = TimerWheel::default();
+        wheel.insert(10, 20, 1);
+        wheel.insert(10, 5000, 2);
+        let mut fired = vec![];
+        wheel.advance(100, |_, id| fired.push(id));
+        assert_eq!(fired, [1]);
+        assert_eq!(run(&mut wheel, 101..=5000), [(5000, 2)]);
+
+        wheel.insert(7000, 7001, 3);
+        assert_eq!(run(&mut wheel, 7001..=7001), [(7001, 3)]);
+    }
+}
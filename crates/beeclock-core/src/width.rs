@@ -0,0 +1,109 @@
+//! Tick counter widths for [`ClockFixed`](crate::ClockFixed).
+//!
+//! [`Clock`](crate::Clock) counts ticks in `u64` and rolls into a new epoch
+//! on overflow. A fixed clock can instead count in `u32`, for smaller state
+//! and native arithmetic on 32-bit MCUs, or in `u128`, which no simulation
+//! will overflow, so the epoch stays 0.
+//!
+//! Conditions keep their `u64` periods and ranges whatever the width.
+
+use core::fmt::Debug;
+use core::hash::Hash;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Integer type a [`ClockFixed`](crate::ClockFixed) counts ticks in:
+/// `u32`, `u64` (the default) or `u128`.
+pub trait TickWidth: sealed::Sealed + Copy + Debug + Eq + Ord + Hash {
+    const ZERO: Self;
+
+    /// Add one, wrapping to zero and reporting `true` on overflow.
+    fn increment(self) -> (Self, bool);
+
+    /// Whether this tick is a multiple of `period` (which is non-zero).
+    fn is_multiple_of(self, period: u64) -> bool;
+
+    /// Whether `start <= self <= end`.
+    fn in_range(self, start: u64, end: u64) -> bool;
+
+    /// Widen losslessly.
+    fn to_u128(self) -> u128;
+}
+
+impl sealed::Sealed for u32 {}
+
+impl TickWidth for u32 {
+    const ZERO: Self = 0;
+
+    #[inline]
+    fn increment(self) -> (Self, bool) {
+        self.overflowing_add(1)
+    }
+
+    #[inline]
+    fn is_multiple_of(self, period: u64) -> bool {
+        // A period past u32::MAX only divides 0.
+        u32::try_from(period).map_or(self == 0, |period| u32::is_multiple_of(self, period))
+    }
+
+    #[inline]
+    fn in_range(self, start: u64, end: u64) -> bool {
+        (start..=end).contains(&u64::from(self))
+    }
+    #[inline]
+    fn to_u128(self) -> u128 {
+        u128::from(self)
+    }
+}
+
+impl sealed::Sealed for u64 {}
+
+impl TickWidth for u64 {
+    const ZERO: Self = 0;
+
+    #[inline]
+    fn increment(self) -> (Self, bool) {
+        self.overflowing_add(1)
+    }
+
+    #[inline]
+    fn is_multiple_of(self, period: u64) -> bool {
+        u64::is_multiple_of(self, period)
+    }
+
+    #[inline]
+    fn in_range(self, start: u64, end: u64) -> bool {
+        (start..=end).contains(&self)
+    }
+    #[inline]
+    fn to_u128(self) -> u128 {
+        u128::from(self)
+    }
+}
+
+impl sealed::Sealed for u128 {}
+
+impl TickWidth for u128 {
+    const ZERO: Self = 0;
+
+    #[inline]
+    fn increment(self) -> (Self, bool) {
+        self.overflowing_add(1)
+    }
+
+    #[inline]
+    fn is_multiple_of(self, period: u64) -> bool {
+        u128::is_multiple_of(self, u128::from(period))
+    }
+
+    #[inline]
+    fn in_range(self, start: u64, end: u64) -> bool {
+        (u128::from(start)..=u128::from(end)).contains(&self)
+    }
+    #[inline]
+    fn to_u128(self) -> u128 {
+        self
+    }
+}
@@ -0,0 +1,375 @@
+//! Compact wire format for snapshots and outcomes (`postcard` feature).
+//!
+//! Messages are [postcard](https://docs.rs/postcard) encodings of a fixed
+//! field sequence, so an embedded [`ClockFixed`] can write them into a stack
+//! buffer and a host reconstructs a [`ClockSnapshot`] or [`TickOutcome`]
+//! from the bytes. The layout below is stable: a change to it bumps
+//! [`WIRE_VERSION`], the first byte of every message.
+//!
+//! Integers are unsigned LEB128 varints, except `u8` (one raw byte).
+//! Strings are a varint byte length followed by UTF-8, sequences a varint
+//! count followed by the items, `Option` a `0`/`1` byte followed by the
+//! value if present, and `bool` a `0`/`1` byte.
+//!
+//! A **snapshot** message:
+//!
+//! | Field | Encoding |
+//! |-------|----------|
+//! | version | `u8`, [`WIRE_VERSION`] |
+//! | tick | varint |
+//! | epoch | varint |
+//! | order | `u8`: 0 least significant first, 1 most significant first |
+//! | partitions | sequence of `name` (string), `value` (varint), `modulus` (varint), in configured order |
+//!
+//! An **outcome** message:
+//!
+//! | Field | Encoding |
+//! |-------|----------|
+//! | version | `u8`, [`WIRE_VERSION`] |
+//! | clock | optional string, the clock name |
+//! | tick, epoch, order, partitions | as in a snapshot |
+//! | overflowed | `bool` |
+//! | pulses | sequence of strings, the fired pulse names in firing order |
+//!
+//! Partition labels, quiet-window reports and child outcomes are not sent;
+//! a decoded snapshot carries no [`PartitionMeta`](crate::PartitionMeta).
+//! A `u128` fixed clock past `u64::MAX` sends its tick as epoch and tick, as
+//! a [`Clock`](crate::Clock) would count it.
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClockFixed, FixedOutcome, PartitionOrder, TickWidth};
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, sync::Arc, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::{ClockSnapshot, PartitionState, PulseFired, PulseList, TickOutcome};
+
+/// Layout version, written as the first byte of every message.
+pub const WIRE_VERSION: u8 = 1;
+
+/// Error encoding or decoding a wire message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WireError {
+    /// Truncated or malformed bytes, or a buffer too small to encode into.
+    Postcard(postcard::Error),
+    /// The message was written with another layout version.
+    Version(u8),
+    /// The fields decoded, but no clock could have produced them.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Postcard(err) => write!(f, "wire message: {err}"),
+            WireError::Version(version) => write!(
+                f,
+                "wire message has layout version {version}, expected {WIRE_VERSION}"
+            ),
+            WireError::Invalid(reason) => write!(f, "wire message is invalid: {reason}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WireError {}
+
+impl From<postcard::Error> for WireError {
+    fn from(err: postcard::Error) -> Self {
+        WireError::Postcard(err)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WirePartition<'a> {
+    name: &'a str,
+    value: u64,
+    modulus: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireSnapshot<P> {
+    tick: u64,
+    epoch: u64,
+    order: u8,
+    partitions: P,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireOutcome<'a, P, N> {
+    #[serde(borrow)]
+    clock: Option<&'a str>,
+    snapshot: WireSnapshot<P>,
+    overflowed: bool,
+    pulses: N,
+}
+
+fn order_tag(order: PartitionOrder) -> u8 {
+    match order {
+        PartitionOrder::LeastSignificantFirst => 0,
+        PartitionOrder::MostSignificantFirst => 1,
+    }
+}
+
+impl<const P: usize, const Q: usize, T: TickWidth> ClockFixed<P, Q, T> {
+    /// Write a snapshot message for the current state into `buf`,
+    /// returning the part written.
+    pub fn snapshot_to_postcard<'b>(&self, buf: &'b mut [u8]) -> Result<&'b mut [u8], WireError> {
+        let partitions = self.wire_partitions(self.values());
+        let snapshot = self.wire_snapshot(self.tick_count(), self.epoch(), &partitions);
+        Ok(postcard::to_slice(&(WIRE_VERSION, snapshot), buf)?)
+    }
+
+    /// Write an outcome message for `outcome`, produced by this clock, into
+    /// `buf`, returning the part written.
+    pub fn outcome_to_postcard<'b>(
+        &self,
+        outcome: &FixedOutcome<P, Q, T>,
+        buf: &'b mut [u8],
+    ) -> Result<&'b mut [u8], WireError> {
+        let mut names = [""; Q];
+        let mut fired = 0;
+        for idx in outcome.fired_indices() {
+            names[fired] = self.pulses()[idx].name;
+            fired += 1;
+        }
+        let partitions = self.wire_partitions(&outcome.values);
+        let message = (
+            WIRE_VERSION,
+            WireOutcome {
+                clock: None,
+                snapshot: self.wire_snapshot(outcome.tick, outcome.epoch, &partitions),
+                overflowed: outcome.overflowed,
+                pulses: &names[..fired],
+            },
+        );
+        Ok(postcard::to_slice(&message, buf)?)
+    }
+
+    fn wire_partitions(&self, values: &[u64; P]) -> [WirePartition<'static>; P] {
+        core::array::from_fn(|idx| WirePartition {
+            name: self.partitions()[idx].name,
+            value: values[idx],
+            modulus: self.partitions()[idx].modulus,
+        })
+    }
+
+    fn wire_snapshot<'p>(
+        &self,
+        tick: T,
+        epoch: u64,
+        partitions: &'p [WirePartition<'static>],
+    ) -> WireSnapshot<&'p [WirePartition<'static>]> {
+        let wide = tick.to_u128();
+        WireSnapshot {
+            tick: wide as u64,
+            epoch: epoch.wrapping_add((wide >> 64) as u64),
+            order: order_tag(self.order()),
+            partitions,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ClockSnapshot {
+    /// Encode as a snapshot message.
+    pub fn to_postcard(&self) -> Vec<u8> {
+        let message = (WIRE_VERSION, wire_snapshot(self));
+        postcard::to_allocvec(&message).expect("wire messages always serialize")
+    }
+
+    /// Decode a snapshot message.
+    pub fn from_postcard(bytes: &[u8]) -> Result<Self, WireError> {
+        let snapshot = postcard::from_bytes(versioned(bytes)?)?;
+        snapshot_from_wire(snapshot)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TickOutcome {
+    /// Encode as an outcome message.
+    pub fn to_postcard(&self) -> Vec<u8> {
+        let pulses: Vec<&str> = self.pulses.iter().map(|p| p.name.as_str()).collect();
+        let message = (
+            WIRE_VERSION,
+            WireOutcome {
+                clock: self.clock.as_deref(),
+                snapshot: wire_snapshot(&self.snapshot),
+                overflowed: self.overflowed,
+                pulses,
+            },
+        );
+        postcard::to_allocvec(&message).expect("wire messages always serialize")
+    }
+
+    /// Decode an outcome message.
+    pub fn from_postcard(bytes: &[u8]) -> Result<Self, WireError> {
+        let message: WireOutcome<'_, Vec<WirePartition<'_>>, Vec<&str>> =
+            postcard::from_bytes(versioned(bytes)?)?;
+        let snapshot = snapshot_from_wire(message.snapshot)?;
+        let clock = message.clock.map(Arc::from);
+        let pulses: PulseList = message
+            .pulses
+            .into_iter()
+            .map(|name| PulseFired {
+                name: String::from(name),
+                tick: snapshot.tick,
+                epoch: snapshot.epoch,
+                clock: clock.clone(),
+            })
+            .collect();
+        Ok(TickOutcome {
+            clock,
+            snapshot: Arc::new(snapshot),
+            pulses,
+            overflowed: message.overflowed,
+            quiet_reports: Vec::new(),
+            children: Vec::new(),
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn wire_snapshot(snapshot: &ClockSnapshot) -> WireSnapshot<Vec<WirePartition<'_>>> {
+    WireSnapshot {
+        tick: snapshot.tick,
+        epoch: snapshot.epoch,
+        order: order_tag(snapshot.order),
+        partitions: snapshot
+            .partitions
+            .iter()
+            .map(|p| WirePartition {
+                name: &p.name,
+                value: p.value,
+                modulus: p.modulus,
+            })
+            .collect(),
+    }
+}
+
+/// The message body after a matching version byte.
+#[cfg(feature = "alloc")]
+fn versioned(bytes: &[u8]) -> Result<&[u8], WireError> {
+    match postcard::take_from_bytes::<u8>(bytes)? {
+        (WIRE_VERSION, body) => Ok(body),
+        (version, _) => Err(WireError::Version(version)),
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn snapshot_from_wire(
+    snapshot: WireSnapshot<Vec<WirePartition<'_>>>,
+) -> Result<ClockSnapshot, WireError> {
+    let order = match snapshot.order {
+        0 => PartitionOrder::LeastSignificantFirst,
+        1 => PartitionOrder::MostSignificantFirst,
+        _ => return Err(WireError::Invalid("unknown partition order")),
+    };
+    let partitions = snapshot
+        .partitions
+        .into_iter()
+        .map(|p| {
+            if p.value >= p.modulus {
+                return Err(WireError::Invalid("partition value not below its modulus"));
+            }
+            Ok(PartitionState {
+                name: Arc::from(p.name),
+                value: p.value,
+                modulus: p.modulus,
+                meta: None,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(ClockSnapshot {
+        tick: snapshot.tick,
+        epoch: snapshot.epoch,
+        partitions,
+        order,
+    })
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{Clock, FixedCondition, FixedPartition, FixedPulse};
+
+    #[test]
+    fn layout_is_stable() {
+        let mut clock = Clock::builder()
+            .least_significant_first()
+            .partition("s", 60)
+            .partition("m", 200)
+            .build()
+            .unwrap();
+        clock.set_partition("m", 130).unwrap();
+        let bytes = clock.tick().snapshot.to_postcard();
+        // Changing these bytes breaks every deployed sender.
+        assert_eq!(
+            bytes,
+            [1, 1, 0, 0, 2, 1, b's', 1, 60, 1, b'm', 130, 1, 200, 1]
+        );
+        assert_eq!(
+            ClockSnapshot::from_postcard(&bytes).unwrap(),
+            clock.snapshot()
+        );
+    }
+
+    #[test]
+    fn fixed_clock_messages_decode_on_the_host() {
+        let partitions = [
+            FixedPartition::new("sec", 60),
+            FixedPartition::new("min", 60),
+        ];
+        let pulses = [
+            FixedPulse::new("quarter", FixedCondition::Every(15)),
+            FixedPulse::new("minute", FixedCondition::Every(60)),
+        ];
+        let order = PartitionOrder::LeastSignificantFirst;
+        let mut fixed = ClockFixed::new(order, partitions, pulses).unwrap();
+        let mut host = Clock::builder()
+            .least_significant_first()
+            .partition("sec", 60)
+            .partition("min", 60)
+            .pulse_every("quarter", 15)
+            .pulse_every("minute", 60)
+            .build()
+            .unwrap();
+
+        let mut buf = [0u8; 64];
+        for _ in 0..60 {
+            let (sent, expected) = (fixed.tick(), host.tick());
+            let bytes = fixed.outcome_to_postcard(&sent, &mut buf).unwrap();
+            let outcome = TickOutcome::from_postcard(bytes).unwrap();
+            assert_eq!(outcome.snapshot, expected.snapshot);
+            assert_eq!(outcome.to_string(), expected.to_string());
+            assert_eq!(outcome.to_postcard(), *bytes);
+        }
+        let bytes = fixed.snapshot_to_postcard(&mut buf).unwrap();
+        assert_eq!(
+            ClockSnapshot::from_postcard(bytes).unwrap(),
+            host.snapshot()
+        );
+
+        assert!(matches!(
+            fixed.snapshot_to_postcard(&mut [0; 4]),
+            Err(WireError::Postcard(_))
+        ));
+        let mut stale = host.snapshot().to_postcard();
+        stale[0] = 0;
+        assert_eq!(
+            ClockSnapshot::from_postcard(&stale),
+            Err(WireError::Version(0))
+        );
+        let mut bad = host.snapshot().to_postcard();
+        bad[3] = 2;
+        assert!(matches!(
+            ClockSnapshot::from_postcard(&bad),
+            Err(WireError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+#ifndef BEECLOCK_H
+#define BEECLOCK_H
+
+/* Generated by cbindgen from crates/beeclock-ffi. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Success.
+#define BEECLOCK_OK 0
+
+// A required pointer argument was NULL.
+#define BEECLOCK_ERR_NULL -1
+
+// An output buffer was shorter than required.
+#define BEECLOCK_ERR_BUFFER_TOO_SMALL -2
+
+// Opaque clock handle.
+typedef struct BeeClock BeeClock;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a default clock (sec/min/hour). Free with `beeclock_clock_free`.
+struct BeeClock *beeclock_clock_new(void);
+
+// Create a clock from a JSON `ClockConfig` document.
+//
+// Returns NULL on parse or validation errors.
+//
+// # Safety
+//
+// `json` must be NULL or a valid NUL-terminated string.
+struct BeeClock *beeclock_clock_from_json(const char *json);
+
+// Free a clock created by this library. NULL is ignored.
+//
+// # Safety
+//
+// `clock` must be NULL or a pointer returned by a constructor in this
+// library that has not been freed yet.
+void beeclock_clock_free(struct BeeClock *clock);
+
+// Message for the last error on this thread, or NULL.
+//
+// The pointer stays valid until the next failing call on this thread.
+const char *beeclock_last_error(void);
+
+// Stable `ClockError` code for the last error on this thread, or 0 if it
+// was not a clock configuration error (or nothing has failed).
+//
+// Codes match `ClockError::code` in beeclock-core; the hundreds digit is the
+// category (1 partition, 2 pulse, 3 state, 4 clock).
+uint32_t beeclock_last_error_code(void);
+
+// Required length (in `uint32_t` words) of a snapshot buffer.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+size_t beeclock_raw_snapshot_len(const struct BeeClock *clock);
+
+// Required length (in `uint32_t` words) of a pulse bit buffer.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+size_t beeclock_raw_pulse_words(const struct BeeClock *clock);
+
+// Advance one tick, writing the snapshot and fired-pulse bits.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer; each output pointer must be valid
+// for writes of its stated length in words.
+int32_t beeclock_tick(struct BeeClock *clock,
+                      uint32_t *snapshot_out,
+                      size_t snapshot_len,
+                      uint32_t *pulse_bits_out,
+                      size_t pulse_bits_len);
+
+// Write the current snapshot without advancing time.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer and `out` valid for `len` words.
+int32_t beeclock_snapshot_raw(const struct BeeClock *clock, uint32_t *out, size_t len);
+
+// Write partition moduli as (lo, hi) word pairs; needs 2 words per partition.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer and `out` valid for `len` words.
+int32_t beeclock_partition_moduli_raw(const struct BeeClock *clock, uint32_t *out, size_t len);
+
+// Current tick count.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+uint64_t beeclock_tick_count(const struct BeeClock *clock);
+
+// Current epoch.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+uint64_t beeclock_epoch(const struct BeeClock *clock);
+
+// Stable fingerprint of the clock's partitions, order, and pulses.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+uint64_t beeclock_config_hash(const struct BeeClock *clock);
+
+// Number of partitions.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+size_t beeclock_partition_count(const struct BeeClock *clock);
+
+// Name of partition `index`, or NULL. Valid until the clock is freed.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+const char *beeclock_partition_name(const struct BeeClock *clock, size_t index);
+
+// Number of pulses (excluding the overflow pulse).
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+size_t beeclock_pulse_count(const struct BeeClock *clock);
+
+// Name of pulse `index` (its bit in the pulse bitset), or NULL.
+// Valid until the clock is freed.
+//
+// # Safety
+//
+// `clock` must be a valid clock pointer.
+const char *beeclock_pulse_name(const struct BeeClock *clock, size_t index);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BEECLOCK_H */
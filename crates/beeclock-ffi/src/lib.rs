@@ -0,0 +1,421 @@
+//! # BeeClock FFI
+//!
+//! C bindings for beeclock-core. The header `include/beeclock.h` is generated
+//! with cbindgen (see `cbindgen.toml`).
+//!
+//! Buffers use the same raw layout as the WASM bindings
+//! (see `beeclock_core::raw`): a snapshot buffer of
+//! `beeclock_raw_snapshot_len()` words and a pulse bitset of
+//! `beeclock_raw_pulse_words()` words.
+//!
+//! Functions returning `int32_t` report `BEECLOCK_OK` or a negative error
+//! code; functions returning pointers return NULL on failure. Either way the
+//! message is available from `beeclock_last_error()`, and for configuration
+//! errors a stable code from `beeclock_last_error_code()`.
+
+use std::cell::{Cell, RefCell};
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::slice;
+
+use beeclock_core::{raw, Clock, ClockConfig, ClockError, TickBuffer};
+
+/// Success.
+pub const BEECLOCK_OK: i32 = 0;
+/// A required pointer argument was NULL.
+pub const BEECLOCK_ERR_NULL: i32 = -1;
+/// An output buffer was shorter than required.
+pub const BEECLOCK_ERR_BUFFER_TOO_SMALL: i32 = -2;
+
+/// Opaque clock handle.
+pub struct BeeClock {
+    inner: Clock,
+    buffer: TickBuffer,
+    moduli: Vec<u64>,
+    partition_names: Vec<CString>,
+    pulse_names: Vec<CString>,
+}
+
+impl BeeClock {
+    fn wrap(inner: Clock) -> Box<Self> {
+        let config = inner.config();
+        Box::new(BeeClock {
+            buffer: inner.tick_buffer(),
+            moduli: config.partitions.iter().map(|p| p.modulus).collect(),
+            partition_names: config
+                .partitions
+                .iter()
+                .map(|p| c_string(&p.name))
+                .collect(),
+            pulse_names: config.pulses.iter().map(|p| c_string(&p.name)).collect(),
+            inner,
+        })
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+    static LAST_ERROR_CODE: Cell<u32> = const { Cell::new(0) };
+}
+
+fn set_error(message: &str) {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(c_string(message)));
+    LAST_ERROR_CODE.with(|code| code.set(0));
+}
+
+fn set_clock_error(err: &ClockError) {
+    set_error(&err.to_string());
+    LAST_ERROR_CODE.with(|code| code.set(err.code()));
+}
+
+fn c_string(value: &str) -> CString {
+    CString::new(value.replace('\0', "")).expect("NUL bytes removed")
+}
+
+fn fail(code: i32, message: &str) -> i32 {
+    set_error(message);
+    code
+}
+
+// ─────────────────────────────────────────────────────────────
+// Lifecycle
+// ─────────────────────────────────────────────────────────────
+
+/// Create a default clock (sec/min/hour). Free with `beeclock_clock_free`.
+#[no_mangle]
+pub extern "C" fn beeclock_clock_new() -> *mut BeeClock {
+    Box::into_raw(BeeClock::wrap(Clock::default()))
+}
+
+/// Create a clock from a JSON `ClockConfig` document.
+///
+/// Returns NULL on parse or validation errors.
+///
+/// # Safety
+///
+/// `json` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_clock_from_json(json: *const c_char) -> *mut BeeClock {
+    if json.is_null() {
+        set_error("json is NULL");
+        return ptr::null_mut();
+    }
+    let json = match CStr::from_ptr(json).to_str() {
+        Ok(json) => json,
+        Err(_) => {
+            set_error("json is not valid UTF-8");
+            return ptr::null_mut();
+        }
+    };
+    let config = match ClockConfig::from_json(json) {
+        Ok(config) => config,
+        Err(err) => {
+            set_error(&err.to_string());
+            return ptr::null_mut();
+        }
+    };
+    match config.build() {
+        Ok(clock) => Box::into_raw(BeeClock::wrap(clock)),
+        Err(err) => {
+            set_clock_error(&err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Free a clock created by this library. NULL is ignored.
+///
+/// # Safety
+///
+/// `clock` must be NULL or a pointer returned by a constructor in this
+/// library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_clock_free(clock: *mut BeeClock) {
+    if !clock.is_null() {
+        drop(Box::from_raw(clock));
+    }
+}
+
+/// Message for the last error on this thread, or NULL.
+///
+/// The pointer stays valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn beeclock_last_error() -> *const c_char {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Stable `ClockError` code for the last error on this thread, or 0 if it
+/// was not a clock configuration error (or nothing has failed).
+///
+/// Codes match `ClockError::code` in beeclock-core; the hundreds digit is the
+/// category (1 partition, 2 pulse, 3 state, 4 clock).
+#[no_mangle]
+pub extern "C" fn beeclock_last_error_code() -> u32 {
+    LAST_ERROR_CODE.with(Cell::get)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Ticking and Raw Buffers
+// ─────────────────────────────────────────────────────────────
+
+/// Required length (in `uint32_t` words) of a snapshot buffer.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_raw_snapshot_len(clock: *const BeeClock) -> usize {
+    clock
+        .as_ref()
+        .map_or(0, |clock| raw::snapshot_len(clock.moduli.len()))
+}
+
+/// Required length (in `uint32_t` words) of a pulse bit buffer.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_raw_pulse_words(clock: *const BeeClock) -> usize {
+    clock
+        .as_ref()
+        .map_or(0, |clock| raw::pulse_words(clock.pulse_names.len()))
+}
+
+/// Advance one tick, writing the snapshot and fired-pulse bits.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer; each output pointer must be valid
+/// for writes of its stated length in words.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_tick(
+    clock: *mut BeeClock,
+    snapshot_out: *mut u32,
+    snapshot_len: usize,
+    pulse_bits_out: *mut u32,
+    pulse_bits_len: usize,
+) -> i32 {
+    let Some(clock) = clock.as_mut() else {
+        return fail(BEECLOCK_ERR_NULL, "clock is NULL");
+    };
+    if snapshot_out.is_null() || pulse_bits_out.is_null() {
+        return fail(BEECLOCK_ERR_NULL, "output buffer is NULL");
+    }
+    if snapshot_len < raw::snapshot_len(clock.moduli.len())
+        || pulse_bits_len < raw::pulse_words(clock.pulse_names.len())
+    {
+        return fail(BEECLOCK_ERR_BUFFER_TOO_SMALL, "output buffer too small");
+    }
+
+    clock.inner.tick_into(&mut clock.buffer);
+    let buffer = &clock.buffer;
+    raw::write_snapshot(
+        slice::from_raw_parts_mut(snapshot_out, snapshot_len),
+        buffer.tick,
+        buffer.epoch,
+        buffer.overflowed,
+        &buffer.values,
+    );
+    raw::write_pulse_bits(
+        slice::from_raw_parts_mut(pulse_bits_out, pulse_bits_len),
+        buffer,
+        clock.pulse_names.len(),
+        clock.inner.overflow_pulse().is_some(),
+    );
+    BEECLOCK_OK
+}
+
+/// Write the current snapshot without advancing time.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer and `out` valid for `len` words.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_snapshot_raw(
+    clock: *const BeeClock,
+    out: *mut u32,
+    len: usize,
+) -> i32 {
+    let Some(clock) = clock.as_ref() else {
+        return fail(BEECLOCK_ERR_NULL, "clock is NULL");
+    };
+    if out.is_null() {
+        return fail(BEECLOCK_ERR_NULL, "output buffer is NULL");
+    }
+    if len < raw::snapshot_len(clock.moduli.len()) {
+        return fail(BEECLOCK_ERR_BUFFER_TOO_SMALL, "output buffer too small");
+    }
+    raw::write_snapshot(
+        slice::from_raw_parts_mut(out, len),
+        clock.inner.tick_count(),
+        clock.inner.epoch(),
+        false,
+        clock.inner.partition_values(),
+    );
+    BEECLOCK_OK
+}
+
+/// Write partition moduli as (lo, hi) word pairs; needs 2 words per partition.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer and `out` valid for `len` words.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_partition_moduli_raw(
+    clock: *const BeeClock,
+    out: *mut u32,
+    len: usize,
+) -> i32 {
+    let Some(clock) = clock.as_ref() else {
+        return fail(BEECLOCK_ERR_NULL, "clock is NULL");
+    };
+    if out.is_null() {
+        return fail(BEECLOCK_ERR_NULL, "output buffer is NULL");
+    }
+    if len < clock.moduli.len() * 2 {
+        return fail(BEECLOCK_ERR_BUFFER_TOO_SMALL, "output buffer too small");
+    }
+    let out = slice::from_raw_parts_mut(out, len);
+    for (idx, &modulus) in clock.moduli.iter().enumerate() {
+        out[idx * 2] = modulus as u32;
+        out[idx * 2 + 1] = (modulus >> 32) as u32;
+    }
+    BEECLOCK_OK
+}
+
+// ─────────────────────────────────────────────────────────────
+// Queries
+// ─────────────────────────────────────────────────────────────
+
+/// Current tick count.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_tick_count(clock: *const BeeClock) -> u64 {
+    clock.as_ref().map_or(0, |clock| clock.inner.tick_count())
+}
+
+/// Current epoch.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_epoch(clock: *const BeeClock) -> u64 {
+    clock.as_ref().map_or(0, |clock| clock.inner.epoch())
+}
+
+/// Stable fingerprint of the clock's partitions, order, and pulses.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_config_hash(clock: *const BeeClock) -> u64 {
+    clock.as_ref().map_or(0, |clock| clock.inner.config_hash())
+}
+
+/// Number of partitions.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_partition_count(clock: *const BeeClock) -> usize {
+    clock.as_ref().map_or(0, |clock| clock.moduli.len())
+}
+
+/// Name of partition `index`, or NULL. Valid until the clock is freed.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_partition_name(
+    clock: *const BeeClock,
+    index: usize,
+) -> *const c_char {
+    name_at(clock.as_ref().map(|clock| &clock.partition_names), index)
+}
+
+/// Number of pulses (excluding the overflow pulse).
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_pulse_count(clock: *const BeeClock) -> usize {
+    clock.as_ref().map_or(0, |clock| clock.pulse_names.len())
+}
+
+/// Name of pulse `index` (its bit in the pulse bitset), or NULL.
+/// Valid until the clock is freed.
+///
+/// # Safety
+///
+/// `clock` must be a valid clock pointer.
+#[no_mangle]
+pub unsafe extern "C" fn beeclock_pulse_name(
+    clock: *const BeeClock,
+    index: usize,
+) -> *const c_char {
+    name_at(clock.as_ref().map(|clock| &clock.pulse_names), index)
+}
+
+fn name_at(names: Option<&Vec<CString>>, index: usize) -> *const c_char {
+    match names.and_then(|names| names.get(index)) {
+        Some(name) => name.as_ptr(),
+        None => {
+            set_error("index out of range");
+            ptr::null()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_through_raw_buffers() {
+        let json = c"{\"order\":\"lsf\",\"partitions\":[{\"name\":\"sec\",\"modulus\":2}],\
+            \"pulses\":[{\"name\":\"wrap\",\"condition\":{\"type\":\"partition_equals\",\"name\":\"sec\",\"value\":0}}]}";
+        unsafe {
+            let clock = beeclock_clock_from_json(json.as_ptr());
+            assert!(!clock.is_null());
+            assert_eq!(beeclock_raw_snapshot_len(clock), 8);
+            assert_eq!(beeclock_raw_pulse_words(clock), 1);
+            assert_eq!(
+                CStr::from_ptr(beeclock_pulse_name(clock, 0)).to_str(),
+                Ok("wrap")
+            );
+
+            let mut snapshot = [0u32; 8];
+            let mut bits = [0u32; 1];
+            for _ in 0..2 {
+                let status = beeclock_tick(clock, snapshot.as_mut_ptr(), 8, bits.as_mut_ptr(), 1);
+                assert_eq!(status, BEECLOCK_OK);
+            }
+            assert_eq!(snapshot, [2, 0, 0, 0, 0, 1, 0, 0]);
+            assert_eq!(bits, [1]);
+
+            let status = beeclock_snapshot_raw(clock, snapshot.as_mut_ptr(), 4);
+            assert_eq!(status, BEECLOCK_ERR_BUFFER_TOO_SMALL);
+            assert!(!beeclock_last_error().is_null());
+            beeclock_clock_free(clock);
+
+            assert!(beeclock_clock_from_json(c"{\"order\":\"sideways\"}".as_ptr()).is_null());
+            assert_eq!(beeclock_last_error_code(), 0);
+            let zero = c"{\"order\":\"lsf\",\"partitions\":[{\"name\":\"sec\",\"modulus\":0}]}";
+            assert!(beeclock_clock_from_json(zero.as_ptr()).is_null());
+            assert_eq!(beeclock_last_error_code(), 101);
+        }
+    }
+}
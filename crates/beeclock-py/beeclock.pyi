@@ -0,0 +1,47 @@
+"""Type stubs for the beeclock extension module."""
+
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional, Union
+
+@dataclass(frozen=True)
+class PulseFired:
+    name: str
+    tick: int
+    epoch: int
+
+class TickOutcome:
+    tick: int
+    epoch: int
+    overflowed: bool
+    partitions: Dict[str, int]
+    pulses: List[PulseFired]
+    def fired(self, name: str) -> bool: ...
+
+class ClockBuilder:
+    def __init__(self) -> None: ...
+    def order(self, order: str) -> ClockBuilder: ...
+    def least_significant_first(self) -> ClockBuilder: ...
+    def most_significant_first(self) -> ClockBuilder: ...
+    def partition(self, name: str, modulus: int) -> ClockBuilder: ...
+    def pulse_every(self, name: str, period: int) -> ClockBuilder: ...
+    def pulse(self, name: str, condition: Union[Dict[str, Any], str]) -> ClockBuilder: ...
+    def build(self) -> Clock: ...
+
+class Clock:
+    tick_count: int
+    epoch: int
+    config_hash: int
+    pulse_names: List[str]
+    def __init__(self) -> None: ...
+    @staticmethod
+    def builder() -> ClockBuilder: ...
+    @staticmethod
+    def from_json(json: str) -> Clock: ...
+    def tick(self) -> TickOutcome: ...
+    def run(self, ticks: int) -> List[TickOutcome]: ...
+    def snapshot(self) -> Dict[str, int]: ...
+    def set_partition(self, name: str, value: int) -> None: ...
+    def simulate(self, horizon: int) -> Dict[str, List[int]]: ...
+    def next_fire(self, pulse: str, horizon: int = 1000000) -> Optional[int]: ...
+    def explain(self, pulse: str) -> Optional[str]: ...
+    def to_json(self) -> str: ...
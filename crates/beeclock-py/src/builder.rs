@@ -0,0 +1,78 @@
+//! Python clock builder.
+
+use beeclock_core::ClockBuilder;
+use pyo3::prelude::*;
+
+use crate::convert::{clock_error, parse_condition, parse_order};
+use crate::PyClock;
+
+/// Chainable clock builder.
+///
+/// Every method returns the builder, so calls can be chained.
+#[pyclass(name = "ClockBuilder", module = "beeclock")]
+#[derive(Default)]
+pub struct PyClockBuilder {
+    inner: ClockBuilder,
+}
+
+impl PyClockBuilder {
+    fn update(
+        mut slf: PyRefMut<'_, Self>,
+        f: impl FnOnce(ClockBuilder) -> ClockBuilder,
+    ) -> PyRefMut<'_, Self> {
+        slf.inner = f(std::mem::take(&mut slf.inner));
+        slf
+    }
+}
+
+#[pymethods]
+impl PyClockBuilder {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the partition order ("lsf" or "msf").
+    fn order<'py>(slf: PyRefMut<'py, Self>, order: &str) -> PyResult<PyRefMut<'py, Self>> {
+        let order = parse_order(order)?;
+        Ok(Self::update(slf, |inner| inner.partition_order(order)))
+    }
+
+    /// Configure least-significant-first order.
+    fn least_significant_first(slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        Self::update(slf, ClockBuilder::least_significant_first)
+    }
+
+    /// Configure most-significant-first order.
+    fn most_significant_first(slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        Self::update(slf, ClockBuilder::most_significant_first)
+    }
+
+    /// Add a partition with the given name and modulus.
+    fn partition(slf: PyRefMut<'_, Self>, name: String, modulus: u64) -> PyRefMut<'_, Self> {
+        Self::update(slf, |inner| inner.partition(name, modulus))
+    }
+
+    /// Add a periodic pulse.
+    fn pulse_every(slf: PyRefMut<'_, Self>, name: String, period: u64) -> PyRefMut<'_, Self> {
+        Self::update(slf, |inner| inner.pulse_every(name, period))
+    }
+
+    /// Add a pulse with a condition dict (or JSON string).
+    fn pulse<'py>(
+        slf: PyRefMut<'py, Self>,
+        name: String,
+        condition: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let condition = parse_condition(condition)?;
+        Ok(Self::update(slf, |inner| inner.pulse_when(name, condition)))
+    }
+
+    /// Build the clock. The builder is reset afterwards.
+    fn build(&mut self) -> PyResult<PyClock> {
+        std::mem::take(&mut self.inner)
+            .build()
+            .map(PyClock::from)
+            .map_err(clock_error)
+    }
+}
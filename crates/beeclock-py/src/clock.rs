@@ -0,0 +1,140 @@
+//! Python Clock wrapper.
+
+use std::collections::BTreeMap;
+
+use beeclock_core::{Clock, ClockConfig};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::convert::{clock_error, partitions_dict};
+use crate::{PyClockBuilder, PyTickOutcome};
+
+/// A partitioned clock.
+///
+/// `Clock()` creates the default sec/min/hour clock; use `Clock.builder()`
+/// or `Clock.from_json()` for custom configurations.
+#[pyclass(name = "Clock", module = "beeclock")]
+pub struct PyClock {
+    inner: Clock,
+}
+
+impl From<Clock> for PyClock {
+    fn from(inner: Clock) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl PyClock {
+    #[new]
+    fn new() -> Self {
+        Clock::default().into()
+    }
+
+    /// Start a new builder.
+    #[staticmethod]
+    fn builder() -> PyClockBuilder {
+        PyClockBuilder::default()
+    }
+
+    /// Build a clock from a JSON `ClockConfig` document.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let config = ClockConfig::from_json(json)
+            .map_err(|err| PyValueError::new_err(format!("invalid clock config: {err}")))?;
+        config.build().map(Self::from).map_err(clock_error)
+    }
+
+    /// Current tick count.
+    #[getter]
+    fn tick_count(&self) -> u64 {
+        self.inner.tick_count()
+    }
+
+    /// Current epoch.
+    #[getter]
+    fn epoch(&self) -> u64 {
+        self.inner.epoch()
+    }
+
+    /// Stable fingerprint of partitions, order, and pulses.
+    #[getter]
+    fn config_hash(&self) -> u64 {
+        self.inner.config_hash()
+    }
+
+    /// Pulse names in declaration order.
+    #[getter]
+    fn pulse_names(&self) -> Vec<String> {
+        (0..self.inner.pulse_count())
+            .filter_map(|index| self.inner.pulse_name(index).map(str::to_string))
+            .collect()
+    }
+
+    /// Advance one tick.
+    fn tick(&mut self, py: Python<'_>) -> PyResult<PyTickOutcome> {
+        let outcome = self.inner.tick();
+        PyTickOutcome::from_outcome(py, &outcome)
+    }
+
+    /// Advance `ticks` ticks, returning every outcome.
+    fn run(&mut self, py: Python<'_>, ticks: u64) -> PyResult<Vec<PyTickOutcome>> {
+        (0..ticks)
+            .map(|_| {
+                let outcome = self.inner.tick();
+                PyTickOutcome::from_outcome(py, &outcome)
+            })
+            .collect()
+    }
+
+    /// Current partition values as `{name: value}`.
+    fn snapshot<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        partitions_dict(py, &self.inner.snapshot())
+    }
+
+    /// Set a partition value; raises `ValueError` for unknown names or
+    /// out-of-range values.
+    fn set_partition(&mut self, name: &str, value: u64) -> PyResult<()> {
+        self.inner.set_partition(name, value).map_err(clock_error)
+    }
+
+    /// Fire ticks per pulse over the next `horizon` ticks, without
+    /// advancing this clock.
+    fn simulate(&self, horizon: u64) -> BTreeMap<String, Vec<u64>> {
+        self.inner
+            .simulate(horizon)
+            .pulses
+            .into_iter()
+            .map(|pulse| (pulse.name, pulse.fire_ticks))
+            .collect()
+    }
+
+    /// Next tick (within `horizon`) on which the pulse fires, or `None`.
+    #[pyo3(signature = (pulse, horizon = 1_000_000))]
+    fn next_fire(&self, pulse: &str, horizon: u64) -> Option<u64> {
+        self.inner.next_fire(pulse, horizon)
+    }
+
+    /// Explain the pulse's condition at the current tick, or `None` for
+    /// unknown pulses.
+    fn explain(&self, pulse: &str) -> Option<String> {
+        self.inner
+            .explain(pulse)
+            .map(|explanation| explanation.to_string())
+    }
+
+    /// Clock configuration as a JSON document.
+    fn to_json(&self) -> String {
+        self.inner.config().to_json()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Clock(tick_count={}, epoch={}, partitions={:?})",
+            self.inner.tick_count(),
+            self.inner.epoch(),
+            self.inner.partition_values()
+        )
+    }
+}
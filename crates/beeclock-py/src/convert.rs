@@ -0,0 +1,45 @@
+//! Python/Rust conversion utilities.
+
+use beeclock_core::{ClockError, ClockSnapshot, PartitionOrder, PulseCondition};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+
+/// Map a core error onto `ValueError`.
+pub(crate) fn clock_error(err: ClockError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// Parse an order string ("lsf", "msf", or the long forms).
+pub(crate) fn parse_order(order: &str) -> PyResult<PartitionOrder> {
+    serde_json::from_value(serde_json::Value::String(order.to_string())).map_err(|_| {
+        PyValueError::new_err("order must be 'lsf' or 'msf' (least/most_significant_first)")
+    })
+}
+
+/// Parse a condition from a dict in the WASM object form, or a JSON string.
+///
+/// `{"type": "every", "period": 5}`,
+/// `{"type": "and", "conditions": [...]}`, etc.
+pub(crate) fn parse_condition(value: &Bound<'_, PyAny>) -> PyResult<PulseCondition> {
+    let json = if let Ok(text) = value.downcast::<PyString>() {
+        text.to_str()?.to_string()
+    } else {
+        let json = value.py().import("json")?;
+        json.call_method1("dumps", (value,))?.extract::<String>()?
+    };
+    serde_json::from_str(&json)
+        .map_err(|err| PyValueError::new_err(format!("invalid pulse condition: {err}")))
+}
+
+/// Partition values as a `{name: value}` dict.
+pub(crate) fn partitions_dict<'py>(
+    py: Python<'py>,
+    snapshot: &ClockSnapshot,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for part in &snapshot.partitions {
+        dict.set_item(&*part.name, part.value)?;
+    }
+    Ok(dict)
+}
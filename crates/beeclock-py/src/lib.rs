@@ -0,0 +1,86 @@
+//! # BeeClock Python Bindings
+//!
+//! PyO3 bindings for beeclock-core, built with maturin:
+//!
+//! ```bash
+//! cd crates/beeclock-py
+//! maturin develop
+//! ```
+//!
+//! ```python
+//! import beeclock
+//!
+//! clock = (beeclock.Clock.builder()
+//!     .least_significant_first()
+//!     .partition("sec", 60)
+//!     .partition("min", 60)
+//!     .pulse_every("tick", 1)
+//!     .pulse("top_of_minute", {"type": "partition_equals", "name": "sec", "value": 0})
+//!     .build())
+//!
+//! outcome = clock.tick()
+//! print(outcome.partitions, outcome.pulses)
+//! ```
+
+use pyo3::prelude::*;
+
+mod builder;
+mod clock;
+mod convert;
+mod outcome;
+
+pub use builder::PyClockBuilder;
+pub use clock::PyClock;
+pub use outcome::PyTickOutcome;
+
+/// The `beeclock` Python module.
+#[pymodule]
+fn beeclock(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyClock>()?;
+    m.add_class::<PyClockBuilder>()?;
+    m.add_class::<PyTickOutcome>()?;
+    m.add("PulseFired", outcome::pulse_fired_class(m.py())?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::py_run;
+    use pyo3::wrap_pymodule;
+
+    use super::*;
+
+    #[test]
+    fn module_ticks_with_dataclass_pulses() {
+        Python::with_gil(|py| {
+            let beeclock = wrap_pymodule!(beeclock)(py);
+            py_run!(
+                py,
+                beeclock,
+                r#"
+import dataclasses
+
+clock = (beeclock.Clock.builder()
+    .least_significant_first()
+    .partition("sec", 2)
+    .pulse("wrap", {"type": "partition_equals", "name": "sec", "value": 0})
+    .build())
+
+first, second = clock.run(2)
+assert first.pulses == []
+assert second.partitions == {"sec": 0}
+assert second.pulses == [beeclock.PulseFired("wrap", 2, 0)]
+assert dataclasses.is_dataclass(second.pulses[0])
+assert second.fired("wrap")
+assert clock.simulate(4) == {"wrap": [4, 6]}
+
+try:
+    clock.set_partition("sec", 5)
+    raise AssertionError("expected ValueError")
+except ValueError:
+    pass
+"#
+            );
+        });
+    }
+}
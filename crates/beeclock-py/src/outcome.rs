@@ -0,0 +1,97 @@
+//! Tick outcomes as Python objects.
+
+use beeclock_core::{PulseFired, TickOutcome};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyInt, PyList, PyString};
+
+use crate::convert::partitions_dict;
+
+static PULSE_FIRED: GILOnceCell<Py<PyAny>> = GILOnceCell::new();
+
+/// The `PulseFired` dataclass: `PulseFired(name: str, tick: int, epoch: int)`.
+pub(crate) fn pulse_fired_class(py: Python<'_>) -> PyResult<&Bound<'_, PyAny>> {
+    PULSE_FIRED
+        .get_or_try_init(py, || {
+            let fields = [
+                ("name", py.get_type::<PyString>()),
+                ("tick", py.get_type::<PyInt>()),
+                ("epoch", py.get_type::<PyInt>()),
+            ];
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("frozen", true)?;
+            let class = py.import("dataclasses")?.call_method(
+                "make_dataclass",
+                ("PulseFired", fields),
+                Some(&kwargs),
+            )?;
+            class.setattr("__module__", "beeclock")?;
+            Ok::<_, PyErr>(class.unbind())
+        })
+        .map(|class| class.bind(py))
+}
+
+fn pulse_fired<'py>(py: Python<'py>, pulse: &PulseFired) -> PyResult<Bound<'py, PyAny>> {
+    pulse_fired_class(py)?.call1((&pulse.name, pulse.tick, pulse.epoch))
+}
+
+/// Result of a single tick.
+#[pyclass(name = "TickOutcome", module = "beeclock", frozen)]
+pub struct PyTickOutcome {
+    /// Tick count after this tick.
+    #[pyo3(get)]
+    tick: u64,
+    /// Epoch after this tick.
+    #[pyo3(get)]
+    epoch: u64,
+    /// Whether the tick counter overflowed into a new epoch on this tick.
+    #[pyo3(get)]
+    overflowed: bool,
+    /// Partition values as `{name: value}`.
+    #[pyo3(get)]
+    partitions: Py<PyDict>,
+    /// Fired pulses as a list of `PulseFired` dataclasses.
+    #[pyo3(get)]
+    pulses: Py<PyList>,
+}
+
+impl PyTickOutcome {
+    pub(crate) fn from_outcome(py: Python<'_>, outcome: &TickOutcome) -> PyResult<Self> {
+        let pulses = outcome
+            .pulses
+            .iter()
+            .map(|pulse| pulse_fired(py, pulse))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Self {
+            tick: outcome.snapshot.tick,
+            epoch: outcome.snapshot.epoch,
+            overflowed: outcome.overflowed,
+            partitions: partitions_dict(py, &outcome.snapshot)?.unbind(),
+            pulses: PyList::new(py, pulses)?.unbind(),
+        })
+    }
+}
+
+#[pymethods]
+impl PyTickOutcome {
+    /// Whether the named pulse fired on this tick.
+    fn fired(&self, py: Python<'_>, name: &str) -> PyResult<bool> {
+        for pulse in self.pulses.bind(py).iter() {
+            if pulse.getattr("name")?.extract::<String>()? == name {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "TickOutcome(tick={}, epoch={}, overflowed={}, partitions={}, pulses={})",
+            self.tick,
+            self.epoch,
+            if self.overflowed { "True" } else { "False" },
+            self.partitions.bind(py).repr()?,
+            self.pulses.bind(py).repr()?,
+        ))
+    }
+}
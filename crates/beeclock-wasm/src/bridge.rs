@@ -2,9 +2,15 @@
 //!
 //! Converts Rust types to JavaScript objects efficiently.
 
-use beeclock_core::{ClockSnapshot, PartitionState, PulseFired, TickOutcome};
-use js_sys::{Array, Object, Reflect, Uint32Array};
+use beeclock_core::{
+    raw, ClockError, ClockSnapshot, ExplainNode, Explanation, PartitionState, PulseFired,
+    TickBuffer, TickOutcome,
+};
+use core::fmt::Write;
+
+use js_sys::{Array, BigInt, BigUint64Array, Error, Object, Reflect, Uint32Array, JSON};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 
 // ─────────────────────────────────────────────────────────────
 // Cached Keys (thread-local for performance)
@@ -13,15 +19,23 @@ use wasm_bindgen::prelude::*;
 pub struct Keys {
     pub tick: JsValue,
     pub tick_str: JsValue,
+    pub tick_big: JsValue,
     pub epoch: JsValue,
     pub epoch_str: JsValue,
+    pub epoch_big: JsValue,
     pub snapshot: JsValue,
     pub partitions: JsValue,
     pub pulses: JsValue,
     pub overflowed: JsValue,
+    pub pulse_bits: JsValue,
+    pub ticks: JsValue,
+    pub alpha: JsValue,
     pub name: JsValue,
     pub value: JsValue,
     pub modulus: JsValue,
+    pub display_name: JsValue,
+    pub unit: JsValue,
+    pub description: JsValue,
 }
 
 impl Keys {
@@ -29,15 +43,23 @@ impl Keys {
         Self {
             tick: JsValue::from_str("tick"),
             tick_str: JsValue::from_str("tick_str"),
+            tick_big: JsValue::from_str("tick_big"),
             epoch: JsValue::from_str("epoch"),
             epoch_str: JsValue::from_str("epoch_str"),
+            epoch_big: JsValue::from_str("epoch_big"),
             snapshot: JsValue::from_str("snapshot"),
             partitions: JsValue::from_str("partitions"),
             pulses: JsValue::from_str("pulses"),
             overflowed: JsValue::from_str("overflowed"),
+            pulse_bits: JsValue::from_str("pulse_bits"),
+            ticks: JsValue::from_str("ticks"),
+            alpha: JsValue::from_str("alpha"),
             name: JsValue::from_str("name"),
             value: JsValue::from_str("value"),
             modulus: JsValue::from_str("modulus"),
+            display_name: JsValue::from_str("display_name"),
+            unit: JsValue::from_str("unit"),
+            description: JsValue::from_str("description"),
         }
     }
 }
@@ -50,11 +72,17 @@ thread_local! {
 // Raw Buffer Layout
 // ─────────────────────────────────────────────────────────────
 
-pub const RAW_HEADER_WORDS: u32 = 6;
-pub const RAW_TICK_LO: u32 = 0;
-pub const RAW_EPOCH_LO: u32 = 2;
-pub const RAW_OVERFLOWED: u32 = 4;
-pub const RAW_PARTITION_COUNT: u32 = 5;
+// Layout is defined in `beeclock_core::raw` and shared with the C bindings.
+pub const RAW_HEADER_WORDS: u32 = raw::HEADER_WORDS as u32;
+pub const RAW_TICK_LO: u32 = raw::TICK_LO as u32;
+pub const RAW_EPOCH_LO: u32 = raw::EPOCH_LO as u32;
+pub const RAW_OVERFLOWED: u32 = raw::OVERFLOWED as u32;
+pub const RAW_PARTITION_COUNT: u32 = raw::PARTITION_COUNT as u32;
+pub const WIDE_HEADER_WORDS: u32 = raw::WIDE_HEADER_WORDS as u32;
+pub const WIDE_TICK: u32 = raw::WIDE_TICK as u32;
+pub const WIDE_EPOCH: u32 = raw::WIDE_EPOCH as u32;
+pub const WIDE_OVERFLOWED: u32 = raw::WIDE_OVERFLOWED as u32;
+pub const WIDE_PARTITION_COUNT: u32 = raw::WIDE_PARTITION_COUNT as u32;
 
 // ─────────────────────────────────────────────────────────────
 // Conversion Functions
@@ -78,6 +106,48 @@ fn outcome_to_js_with_keys(outcome: &TickOutcome, keys: &Keys) -> JsValue {
     obj.into()
 }
 
+/// Explanation tree as `{ pulse, tick, tick_big, fired, text, root }`, with
+/// nodes as `{ summary, met, reason, children }`.
+pub fn explanation_to_js(explanation: &Explanation) -> JsValue {
+    let obj = Object::new();
+    let field = |key: &str, value: &JsValue| set(&obj, &JsValue::from_str(key), value);
+    field("pulse", &JsValue::from_str(&explanation.pulse));
+    field("tick", &JsValue::from_f64(explanation.tick as f64));
+    field("tick_big", &JsValue::from(explanation.tick));
+    field("fired", &JsValue::from_bool(explanation.fired()));
+    field("text", &JsValue::from_str(&explanation.to_string()));
+    field("root", &explain_node_to_js(&explanation.root));
+    obj.into()
+}
+
+fn explain_node_to_js(node: &ExplainNode) -> JsValue {
+    let obj = Object::new();
+    let field = |key: &str, value: &JsValue| set(&obj, &JsValue::from_str(key), value);
+    let children: Array = node.children.iter().map(explain_node_to_js).collect();
+    field("summary", &JsValue::from_str(&node.summary));
+    field("met", &JsValue::from_bool(node.met));
+    field("reason", &JsValue::from_str(&node.reason));
+    field("children", &children);
+    obj.into()
+}
+
+/// Per-frame summary for `start_frames`: ticks run, leftover fraction of a
+/// tick, final snapshot, and the pulses fired over the frame.
+pub fn frame_to_js(ticks: u32, alpha: f64, snapshot: &ClockSnapshot, pulses: &Array) -> JsValue {
+    KEYS.with(|keys| {
+        let obj = Object::new();
+        set(&obj, &keys.ticks, &JsValue::from(ticks));
+        set(&obj, &keys.alpha, &JsValue::from_f64(alpha));
+        set(
+            &obj,
+            &keys.snapshot,
+            &snapshot_to_js_with_keys(snapshot, keys),
+        );
+        set(&obj, &keys.pulses, pulses);
+        obj.into()
+    })
+}
+
 pub fn snapshot_to_js(snapshot: &ClockSnapshot) -> JsValue {
     KEYS.with(|keys| snapshot_to_js_with_keys(snapshot, keys))
 }
@@ -91,8 +161,10 @@ fn snapshot_to_js_with_keys(snapshot: &ClockSnapshot, keys: &Keys) -> JsValue {
 
     set(&obj, &keys.tick, &tick);
     set(&obj, &keys.tick_str, &tick_str);
+    set(&obj, &keys.tick_big, &JsValue::from(snapshot.tick));
     set(&obj, &keys.epoch, &epoch);
     set(&obj, &keys.epoch_str, &epoch_str);
+    set(&obj, &keys.epoch_big, &JsValue::from(snapshot.epoch));
 
     let parts = Array::new();
     for part in &snapshot.partitions {
@@ -110,28 +182,233 @@ fn partition_to_js_with_keys(partition: &PartitionState, keys: &Keys) -> JsValue
     set(&obj, &keys.name, &name);
     set(&obj, &keys.value, &value);
     set(&obj, &keys.modulus, &modulus);
+    if let Some(meta) = &partition.meta {
+        let labels = [
+            (&keys.display_name, &meta.display_name),
+            (&keys.unit, &meta.unit),
+            (&keys.description, &meta.description),
+        ];
+        for (key, label) in labels {
+            if let Some(label) = label {
+                set(&obj, key, &JsValue::from_str(label));
+            }
+        }
+    }
     obj.into()
 }
 
 fn pulses_to_js_with_keys(pulses: &[PulseFired], keys: &Keys) -> JsValue {
     let list = Array::new();
     for pulse in pulses {
-        let obj = Object::new();
-        let name = JsValue::from_str(&pulse.name);
-        let tick = JsValue::from_f64(pulse.tick as f64);
-        let tick_str = JsValue::from_str(&pulse.tick.to_string());
-        let epoch = JsValue::from_f64(pulse.epoch as f64);
-        let epoch_str = JsValue::from_str(&pulse.epoch.to_string());
-        set(&obj, &keys.name, &name);
-        set(&obj, &keys.tick, &tick);
-        set(&obj, &keys.tick_str, &tick_str);
-        set(&obj, &keys.epoch, &epoch);
-        set(&obj, &keys.epoch_str, &epoch_str);
+        let obj = pulse_to_js_with_keys(&pulse.name, pulse.tick, pulse.epoch, keys);
         list.push(&obj);
     }
     list.into()
 }
 
+pub fn pulse_to_js(name: &str, tick: u64, epoch: u64) -> JsValue {
+    KEYS.with(|keys| pulse_to_js_with_keys(name, tick, epoch, keys))
+}
+
+fn pulse_to_js_with_keys(name: &str, tick: u64, epoch: u64, keys: &Keys) -> JsValue {
+    let obj = Object::new();
+    let tick_str = JsValue::from_str(&tick.to_string());
+    let epoch_str = JsValue::from_str(&epoch.to_string());
+    set(&obj, &keys.name, &JsValue::from_str(name));
+    set(&obj, &keys.tick, &JsValue::from_f64(tick as f64));
+    set(&obj, &keys.tick_str, &tick_str);
+    set(&obj, &keys.tick_big, &JsValue::from(tick));
+    set(&obj, &keys.epoch, &JsValue::from_f64(epoch as f64));
+    set(&obj, &keys.epoch_str, &epoch_str);
+    set(&obj, &keys.epoch_big, &JsValue::from(epoch));
+    obj.into()
+}
+
+// ─────────────────────────────────────────────────────────────
+// JSON Conversion
+// ─────────────────────────────────────────────────────────────
+
+/// Like [`outcome_to_js`], but built as JSON text and parsed in one
+/// `JSON.parse` call instead of a `Reflect::set` per field. Faster for
+/// clocks with many partitions.
+pub fn outcome_to_js_json(outcome: &TickOutcome) -> Result<JsValue, JsValue> {
+    let mut json = String::with_capacity(128 + outcome.snapshot.partitions.len() * 48);
+    json.push_str("{\"snapshot\":");
+    write_snapshot_json(&mut json, &outcome.snapshot);
+    let _ = write!(json, ",\"overflowed\":{},\"pulses\":[", outcome.overflowed);
+    for (idx, pulse) in outcome.pulses.iter().enumerate() {
+        if idx > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"name\":");
+        write_json_str(&mut json, &pulse.name);
+        json.push(',');
+        write_tick_json(&mut json, pulse.tick, pulse.epoch);
+        json.push('}');
+    }
+    json.push_str("]}");
+
+    let value = JSON::parse(&json)?;
+    KEYS.with(|keys| {
+        let snapshot = Reflect::get(&value, &keys.snapshot)?;
+        set_bigints(
+            &snapshot,
+            outcome.snapshot.tick,
+            outcome.snapshot.epoch,
+            keys,
+        );
+        let pulses = Array::from(&Reflect::get(&value, &keys.pulses)?);
+        for (obj, pulse) in pulses.iter().zip(outcome.pulses.iter()) {
+            set_bigints(&obj, pulse.tick, pulse.epoch, keys);
+        }
+        Ok(value)
+    })
+}
+
+/// Like [`snapshot_to_js`], through JSON (see [`outcome_to_js_json`]).
+pub fn snapshot_to_js_json(snapshot: &ClockSnapshot) -> Result<JsValue, JsValue> {
+    let mut json = String::with_capacity(96 + snapshot.partitions.len() * 48);
+    write_snapshot_json(&mut json, snapshot);
+    let value = JSON::parse(&json)?;
+    KEYS.with(|keys| set_bigints(&value, snapshot.tick, snapshot.epoch, keys));
+    Ok(value)
+}
+
+fn write_snapshot_json(json: &mut String, snapshot: &ClockSnapshot) {
+    json.push('{');
+    write_tick_json(json, snapshot.tick, snapshot.epoch);
+    json.push_str(",\"partitions\":[");
+    for (idx, part) in snapshot.partitions.iter().enumerate() {
+        if idx > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"name\":");
+        write_json_str(json, &part.name);
+        let _ = write!(
+            json,
+            ",\"value\":{},\"modulus\":{}",
+            part.value, part.modulus
+        );
+        if let Some(meta) = &part.meta {
+            let labels = [
+                ("display_name", &meta.display_name),
+                ("unit", &meta.unit),
+                ("description", &meta.description),
+            ];
+            for (key, label) in labels {
+                if let Some(label) = label {
+                    let _ = write!(json, ",\"{key}\":");
+                    write_json_str(json, label);
+                }
+            }
+        }
+        json.push('}');
+    }
+    json.push_str("]}");
+}
+
+/// Write the `tick` and `epoch` fields, with their string forms.
+fn write_tick_json(json: &mut String, tick: u64, epoch: u64) {
+    let _ = write!(
+        json,
+        "\"tick\":{tick},\"tick_str\":\"{tick}\",\"epoch\":{epoch},\"epoch_str\":\"{epoch}\""
+    );
+}
+
+/// Add the BigInt fields JSON can't carry.
+fn set_bigints(obj: &JsValue, tick: u64, epoch: u64, keys: &Keys) {
+    let obj = obj.unchecked_ref::<Object>();
+    set(obj, &keys.tick_big, &JsValue::from(tick));
+    set(obj, &keys.epoch_big, &JsValue::from(epoch));
+}
+
+fn write_json_str(json: &mut String, text: &str) {
+    json.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            ch if ch < ' ' => {
+                let _ = write!(json, "\\u{:04x}", ch as u32);
+            }
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+}
+
+/// A JS `Error` named `ClockError`, with the stable `code` and `category`
+/// from [`ClockError::code`] alongside the message.
+pub fn clock_error_to_js(err: &ClockError) -> JsValue {
+    let error = Error::new(&err.to_string());
+    error.set_name("ClockError");
+    set(&error, &JsValue::from_str("code"), &err.code().into());
+    let category = JsValue::from_str(err.category().as_str());
+    set(&error, &JsValue::from_str("category"), &category);
+    error.into()
+}
+
+pub fn strings_to_js(strings: &[String]) -> JsValue {
+    strings
+        .iter()
+        .map(|s| JsValue::from_str(s))
+        .collect::<Array>()
+        .into()
+}
+
+/// JSON text of `value`: a string is taken as JSON already, anything else is
+/// converted with [`js_to_json`].
+pub fn json_text(value: &JsValue) -> Result<String, JsValue> {
+    match value.as_string() {
+        Some(text) => Ok(text),
+        None => Ok(js_to_json(value)?.to_string()),
+    }
+}
+
+/// Convert a plain JS value (objects, arrays, strings, numbers, BigInts) to
+/// JSON, keeping BigInts exact so they can feed `u64` fields.
+pub fn js_to_json(value: &JsValue) -> Result<serde_json::Value, JsValue> {
+    use serde_json::Value;
+
+    if value.is_null() || value.is_undefined() {
+        return Ok(Value::Null);
+    }
+    if let Some(flag) = value.as_bool() {
+        return Ok(Value::Bool(flag));
+    }
+    if let Some(number) = value.as_f64() {
+        let integral = number.fract() == 0.0 && number.abs() < 2f64.powi(53);
+        return Ok(match integral {
+            true if number >= 0.0 => Value::from(number as u64),
+            true => Value::from(number as i64),
+            false => Value::from(number),
+        });
+    }
+    if let Some(big) = value.dyn_ref::<BigInt>() {
+        let digits = String::from(big.to_string(10)?);
+        return digits
+            .parse::<u64>()
+            .map(Value::from)
+            .map_err(|_| JsValue::from_str(&format!("BigInt {digits} does not fit in u64")));
+    }
+    if let Some(text) = value.as_string() {
+        return Ok(Value::String(text));
+    }
+    if let Some(array) = value.dyn_ref::<Array>() {
+        return array.iter().map(|item| js_to_json(&item)).collect();
+    }
+    if value.is_object() {
+        let mut map = serde_json::Map::new();
+        for entry in Object::entries(value.unchecked_ref()).iter() {
+            let entry: Array = entry.unchecked_into();
+            let key = entry.get(0).as_string().unwrap_or_default();
+            map.insert(key, js_to_json(&entry.get(1))?);
+        }
+        return Ok(Value::Object(map));
+    }
+    Err(JsValue::from_str("unsupported value in JSON object"))
+}
+
 // ─────────────────────────────────────────────────────────────
 // Raw Buffer Operations
 // ─────────────────────────────────────────────────────────────
@@ -145,16 +422,42 @@ pub fn ensure_len(out: &Uint32Array, required: u32, name: &str) -> Result<(), Js
     Ok(())
 }
 
-pub fn fill_snapshot(out: &Uint32Array, snapshot: &ClockSnapshot, overflowed: bool) {
-    write_u64(out, RAW_TICK_LO, snapshot.tick);
-    write_u64(out, RAW_EPOCH_LO, snapshot.epoch);
+pub fn fill_header(out: &Uint32Array, tick: u64, epoch: u64, overflowed: bool, partitions: u32) {
+    write_u64(out, RAW_TICK_LO, tick);
+    write_u64(out, RAW_EPOCH_LO, epoch);
     out.set_index(RAW_OVERFLOWED, if overflowed { 1 } else { 0 });
-    out.set_index(RAW_PARTITION_COUNT, snapshot.partitions.len() as u32);
+    out.set_index(RAW_PARTITION_COUNT, partitions);
+}
 
-    let mut index = RAW_HEADER_WORDS;
-    for part in &snapshot.partitions {
-        index = write_u64(out, index, part.value);
+/// Copy partition values after the header as little-endian (lo, hi) word pairs.
+pub fn fill_values(out: &Uint32Array, values: &[u64]) {
+    // Split here, independent of host endianness, so the copy into JS stays
+    // a single call.
+    let words: Vec<u32> = values
+        .iter()
+        .flat_map(|&value| [value as u32, (value >> 32) as u32])
+        .collect();
+    out.subarray(RAW_HEADER_WORDS, RAW_HEADER_WORDS + words.len() as u32)
+        .copy_from(&words);
+}
+
+pub fn ensure_wide_len(out: &BigUint64Array, required: u32, name: &str) -> Result<(), JsValue> {
+    if out.length() < required {
+        return Err(JsValue::from_str(&format!(
+            "{name} requires BigUint64Array length >= {required}"
+        )));
     }
+    Ok(())
+}
+
+/// Write a snapshot in the wide (`u64` word) layout.
+pub fn fill_wide(out: &BigUint64Array, tick: u64, epoch: u64, overflowed: bool, values: &[u64]) {
+    out.set_index(WIDE_TICK, tick);
+    out.set_index(WIDE_EPOCH, epoch);
+    out.set_index(WIDE_OVERFLOWED, overflowed as u64);
+    out.set_index(WIDE_PARTITION_COUNT, values.len() as u64);
+    out.subarray(WIDE_HEADER_WORDS, WIDE_HEADER_WORDS + values.len() as u32)
+        .copy_from(values);
 }
 
 pub fn write_u64(out: &Uint32Array, index: u32, value: u64) -> u32 {
@@ -169,20 +472,33 @@ pub fn clear_bits(out: &Uint32Array) {
     }
 }
 
-pub fn fill_pulse_bits(out: &Uint32Array, pulse_names: &[String], outcome: &TickOutcome) {
-    for pulse in &outcome.pulses {
-        if pulse.name == "__overflow__" {
-            continue;
-        }
-        for (idx, name) in pulse_names.iter().enumerate() {
-            if name == &pulse.name {
-                set_bit(out, idx);
-            }
-        }
+/// Set the bits for a tick's fired pulses and, with `overflow_pulse`, its
+/// overflow (see [`raw::write_pulse_bits`]).
+pub fn fill_pulse_bits(
+    out: &Uint32Array,
+    buffer: &TickBuffer,
+    pulse_count: usize,
+    overflow_pulse: bool,
+) {
+    for &index in &buffer.fired {
+        set_bit(out, index);
     }
 
-    if outcome.overflowed {
-        set_bit(out, pulse_names.len());
+    if buffer.overflowed && overflow_pulse {
+        set_bit(out, pulse_count);
+    }
+}
+
+/// OR a tick's pulse bits (same layout as [`fill_pulse_bits`]) into `words`.
+pub fn merge_pulse_bits(
+    words: &mut [u32],
+    buffer: &TickBuffer,
+    pulse_count: usize,
+    overflow_pulse: bool,
+) {
+    let overflow = (buffer.overflowed && overflow_pulse).then_some(pulse_count);
+    for index in buffer.fired.iter().copied().chain(overflow) {
+        words[index / 32] |= 1 << (index % 32);
     }
 }
 
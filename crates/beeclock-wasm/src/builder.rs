@@ -1,19 +1,37 @@
 //! WASM Clock builder.
 
-use beeclock_core::{Clock, PartitionOrder, PartitionSpec, PulseCondition, PulseSpec};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use beeclock_core::{
+    ClockBuilder, ClockDuration, DateField, ElapsedSince, PartitionMeta, PartitionOrder,
+    PartitionSpec, PulseCondition, PulseSpec,
+};
 use js_sys::{Array, Object, Reflect};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
-use crate::bridge::{get_string, get_u64};
+use crate::bridge::{clock_error_to_js, get_string, get_u64};
 use crate::WasmClock;
 
 /// WASM-friendly clock builder.
+///
+/// Each method returns a handle to the same builder, so calls can be
+/// chained (`new WasmClockBuilder().partition('sec', 60n).build()`) or made
+/// one statement at a time. Calls go straight to the core [`ClockBuilder`],
+/// so validation and defaults (such as the partition order of a clock with
+/// no partitions) match it.
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct WasmClockBuilder {
-    partitions: Vec<PartitionSpec>,
-    pulses: Vec<PulseSpec>,
-    order: Option<PartitionOrder>,
+    state: Rc<RefCell<BuilderState>>,
+}
+
+#[derive(Default)]
+struct BuilderState {
+    builder: ClockBuilder,
+    /// Pulses added so far, for error paths.
+    pulse_count: usize,
 }
 
 #[wasm_bindgen]
@@ -21,18 +39,14 @@ impl WasmClockBuilder {
     /// Create a new builder.
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        Self {
-            partitions: Vec::new(),
-            pulses: Vec::new(),
-            order: None,
-        }
+        Self::default()
     }
 
     /// Set the partition order.
     ///
     /// Valid values: "lsf", "least", "least_significant_first",
     ///               "msf", "most", "most_significant_first"
-    pub fn set_partition_order(&mut self, order: String) -> Result<(), JsValue> {
+    pub fn set_partition_order(&self, order: String) -> Result<WasmClockBuilder, JsValue> {
         let order = match order.as_str() {
             "lsf" | "least" | "least_significant_first" => PartitionOrder::LeastSignificantFirst,
             "msf" | "most" | "most_significant_first" => PartitionOrder::MostSignificantFirst,
@@ -42,49 +56,97 @@ impl WasmClockBuilder {
                 ))
             }
         };
-        self.order = Some(order);
-        Ok(())
+        Ok(self.update(|builder| builder.partition_order(order)))
     }
 
     /// Add a partition with the given name and modulus.
-    pub fn partition(&mut self, name: String, modulus: u64) {
-        self.partitions.push(PartitionSpec { name, modulus });
+    pub fn partition(&self, name: String, modulus: u64) -> WasmClockBuilder {
+        self.update(|builder| builder.partition(name, modulus))
+    }
+
+    /// Add a partition with optional labels for display, which snapshots
+    /// carry as `display_name`, `unit` and `description`.
+    pub fn partition_with_labels(
+        &self,
+        name: String,
+        modulus: u64,
+        display_name: Option<String>,
+        unit: Option<String>,
+        description: Option<String>,
+    ) -> WasmClockBuilder {
+        let mut spec = PartitionSpec::new(name, modulus);
+        spec.meta = PartitionMeta {
+            display_name,
+            unit,
+            description,
+        };
+        self.update(|builder| builder.partition_spec(spec))
+    }
+
+    /// Fire the overflow pulse under `name` instead of `__overflow__`.
+    pub fn overflow_pulse(&self, name: String) -> WasmClockBuilder {
+        self.update(|builder| builder.overflow_pulse(name))
+    }
+
+    /// Fire no pulse on tick overflow; outcomes still report `overflowed`.
+    pub fn no_overflow_pulse(&self) -> WasmClockBuilder {
+        self.update(ClockBuilder::no_overflow_pulse)
     }
 
     /// Add a periodic pulse.
-    pub fn pulse_every(&mut self, name: String, period: u64) {
-        self.pulses.push(PulseSpec {
-            name,
-            condition: PulseCondition::Every(period),
-        });
+    pub fn pulse_every(&self, name: String, period: u64) -> WasmClockBuilder {
+        self.add_pulse(PulseSpec::new(name, PulseCondition::Every(period)))
     }
 
-    /// Add a pulse with a custom condition (JS object).
-    pub fn pulse_condition(&mut self, name: String, condition: JsValue) -> Result<(), JsValue> {
-        let condition = parse_condition(&condition)?;
-        self.pulses.push(PulseSpec { name, condition });
-        Ok(())
+    /// Add a pulse with a custom condition: a JS condition object, or a
+    /// string in the core condition syntax (`"hour == 12 && min == 0"`).
+    ///
+    /// Errors name the pulse and where parsing failed, e.g.
+    /// `pulse 'blink': pulses[2].condition.conditions[1].period: expected number`.
+    pub fn pulse_condition(
+        &self,
+        name: String,
+        condition: JsValue,
+    ) -> Result<WasmClockBuilder, JsValue> {
+        let path = format!("pulses[{}].condition", self.state.borrow().pulse_count);
+        let parsed = match condition.as_string() {
+            Some(text) => text
+                .parse::<PulseCondition>()
+                .map_err(|err| at(&path, &err.to_string())),
+            None => parse_condition(&condition, &path),
+        };
+        let condition = parsed.map_err(|err| {
+            let reason = err.as_string().unwrap_or_default();
+            JsValue::from_str(&format!("pulse '{name}': {reason}"))
+        })?;
+        Ok(self.add_pulse(PulseSpec::new(name, condition)))
     }
 
-    /// Build the clock.
-    pub fn build(&mut self) -> Result<WasmClock, JsValue> {
-        let partitions = std::mem::take(&mut self.partitions);
-        let pulses = std::mem::take(&mut self.pulses);
-        let partition_moduli = partitions.iter().map(|part| part.modulus).collect::<Vec<_>>();
-        let pulse_names = pulses.iter().map(|pulse| pulse.name.clone()).collect::<Vec<_>>();
-        let order = self
-            .order
-            .take()
-            .ok_or_else(|| JsValue::from_str("partition order must be set"))?;
-
-        Clock::new(order, partitions, pulses)
-            .map(|inner| WasmClock {
-                inner,
-                partition_count: partition_moduli.len(),
-                partition_moduli,
-                pulse_names,
-            })
-            .map_err(|err| JsValue::from_str(&err.to_string()))
+    /// Build the clock, leaving the builder empty.
+    ///
+    /// The partition order must be set unless there are no partitions.
+    pub fn build(&self) -> Result<WasmClock, JsValue> {
+        let state = std::mem::take(&mut *self.state.borrow_mut());
+        state
+            .builder
+            .build()
+            .map(WasmClock::from_clock)
+            .map_err(|err| clock_error_to_js(&err))
+    }
+}
+
+impl WasmClockBuilder {
+    /// Apply a core builder call, returning another handle to this builder.
+    fn update(&self, apply: impl FnOnce(ClockBuilder) -> ClockBuilder) -> WasmClockBuilder {
+        let mut state = self.state.borrow_mut();
+        state.builder = apply(std::mem::take(&mut state.builder));
+        drop(state);
+        self.clone()
+    }
+
+    fn add_pulse(&self, spec: PulseSpec) -> WasmClockBuilder {
+        self.state.borrow_mut().pulse_count += 1;
+        self.update(|builder| builder.pulse(spec))
     }
 }
 
@@ -92,30 +154,34 @@ impl WasmClockBuilder {
 // Condition Parsing
 // ─────────────────────────────────────────────────────────────
 
-fn parse_condition(value: &JsValue) -> Result<PulseCondition, JsValue> {
-    let obj = Object::from(value.clone());
-    let kind = Reflect::get(&obj, &JsValue::from_str("type"))
-        .map_err(|_| JsValue::from_str("condition.type missing"))?;
-    let kind = kind
-        .as_string()
-        .ok_or_else(|| JsValue::from_str("condition.type must be a string"))?;
+/// Parse a condition object, naming the failing field by its `path` (e.g.
+/// `pulses[2].condition.conditions[1].period`) in errors.
+fn parse_condition(value: &JsValue, path: &str) -> Result<PulseCondition, JsValue> {
+    if !value.is_object() {
+        return Err(at(path, "expected a condition object"));
+    }
+    let obj = value.unchecked_ref::<Object>();
+    let kind = Reflect::get(obj, &JsValue::from_str("type"))
+        .ok()
+        .and_then(|kind| kind.as_string())
+        .ok_or_else(|| at(&format!("{path}.type"), "expected a string"))?;
 
     match kind.as_str() {
         "every" => {
-            let period = get_u64(&obj, "period")?;
+            let period = field_u64(obj, path, "period")?;
             Ok(PulseCondition::Every(period))
         }
 
         "partition_equals" => {
-            let name = get_string(&obj, "name")?;
-            let value = get_u64(&obj, "value")?;
+            let name = field_string(obj, path, "name")?;
+            let value = field_u64(obj, path, "value")?;
             Ok(PulseCondition::PartitionEquals { name, value })
         }
 
         "partition_modulo" => {
-            let name = get_string(&obj, "name")?;
-            let modulus = get_u64(&obj, "modulus")?;
-            let remainder = get_u64(&obj, "remainder")?;
+            let name = field_string(obj, path, "name")?;
+            let modulus = field_u64(obj, path, "modulus")?;
+            let remainder = field_u64(obj, path, "remainder")?;
             Ok(PulseCondition::PartitionModulo {
                 name,
                 modulus,
@@ -124,42 +190,173 @@ fn parse_condition(value: &JsValue) -> Result<PulseCondition, JsValue> {
         }
 
         "tick_range" => {
-            let start = get_u64(&obj, "start")?;
-            let end = get_u64(&obj, "end")?;
+            let start = field_u64(obj, path, "start")?;
+            let end = field_u64(obj, path, "end")?;
             Ok(PulseCondition::TickRange { start, end })
         }
 
+        "partition_changed" => {
+            let name = field_string(obj, path, "name")?;
+            Ok(PulseCondition::PartitionChanged { name })
+        }
+
+        "partition_decreased" => {
+            let name = field_string(obj, path, "name")?;
+            Ok(PulseCondition::PartitionDecreased { name })
+        }
+
         "not" => {
-            let value = Reflect::get(&obj, &JsValue::from_str("condition"))
-                .map_err(|_| JsValue::from_str("condition.condition missing for 'not'"))?;
-            let condition = parse_condition(&value)?;
+            let condition = inner_condition(obj, path)?;
             Ok(PulseCondition::Not(Box::new(condition)))
         }
 
         "and" => {
-            let conditions = get_conditions(&obj)?;
+            let conditions = get_conditions(obj, path)?;
             Ok(PulseCondition::And(conditions))
         }
 
         "or" => {
-            let conditions = get_conditions(&obj)?;
+            let conditions = get_conditions(obj, path)?;
             Ok(PulseCondition::Or(conditions))
         }
 
-        _ => Err(JsValue::from_str("unknown pulse condition type")),
+        "jitter" => {
+            let window = field_u64(obj, path, "window")?;
+            let seed = field_u64(obj, path, "seed")?;
+            let inner = inner_condition(obj, path)?;
+            Ok(PulseCondition::Jitter {
+                window,
+                seed,
+                inner: Box::new(inner),
+            })
+        }
+
+        "rate_limit" => {
+            let max_fires = field_u64(obj, path, "max_fires")?;
+            let per_ticks = field_u64(obj, path, "per_ticks")?;
+            let inner = inner_condition(obj, path)?;
+            Ok(PulseCondition::RateLimit {
+                max_fires,
+                per_ticks,
+                inner: Box::new(inner),
+            })
+        }
+
+        "debounce" => {
+            let ticks = field_u64(obj, path, "ticks")?;
+            let inner = inner_condition(obj, path)?;
+            Ok(PulseCondition::Debounce {
+                ticks,
+                inner: Box::new(inner),
+            })
+        }
+
+        "elapsed_at_least" => {
+            let duration = get_duration(obj, path)?;
+            let since = match Reflect::get(obj, &JsValue::from_str("since"))
+                .ok()
+                .filter(|since| !since.is_undefined())
+                .map(|since| since.as_string())
+            {
+                None => ElapsedSince::Start,
+                Some(Some(since)) if since == "start" => ElapsedSince::Start,
+                Some(Some(since)) if since == "last_fire" => ElapsedSince::LastFire,
+                Some(_) => {
+                    return Err(at(
+                        &format!("{path}.since"),
+                        "expected 'start' or 'last_fire'",
+                    ))
+                }
+            };
+            Ok(PulseCondition::ElapsedAtLeast { duration, since })
+        }
+
+        "date_equals" => {
+            let field = field_string(obj, path, "field")?;
+            let field = DateField::ALL
+                .into_iter()
+                .find(|f| f.name() == field)
+                .ok_or_else(|| {
+                    at(
+                        &format!("{path}.field"),
+                        "expected year, month, day or weekday",
+                    )
+                })?;
+            let value = field_u64(obj, path, "value")?;
+            Ok(PulseCondition::DateEquals { field, value })
+        }
+
+        _ => Err(at(
+            &format!("{path}.type"),
+            &format!("unknown pulse condition type '{kind}'"),
+        )),
     }
 }
 
-fn get_conditions(obj: &Object) -> Result<Vec<PulseCondition>, JsValue> {
+/// The nested `condition` of `not`, `jitter`, `rate_limit` and `debounce`.
+fn inner_condition(obj: &Object, path: &str) -> Result<PulseCondition, JsValue> {
+    let path = format!("{path}.condition");
+    let value = Reflect::get(obj, &JsValue::from_str("condition"))
+        .map_err(|_| at(&path, "expected a condition object"))?;
+    parse_condition(&value, &path)
+}
+
+fn get_conditions(obj: &Object, path: &str) -> Result<Vec<PulseCondition>, JsValue> {
+    let path = format!("{path}.conditions");
     let value = Reflect::get(obj, &JsValue::from_str("conditions"))
-        .map_err(|_| JsValue::from_str("condition.conditions missing"))?;
+        .map_err(|_| at(&path, "expected an array"))?;
     let array = value
         .dyn_ref::<Array>()
-        .ok_or_else(|| JsValue::from_str("condition.conditions must be an array"))?;
+        .ok_or_else(|| at(&path, "expected an array"))?;
 
     let mut conditions = Vec::with_capacity(array.length() as usize);
     for idx in 0..array.length() {
-        conditions.push(parse_condition(&array.get(idx))?);
+        conditions.push(parse_condition(&array.get(idx), &format!("{path}[{idx}]"))?);
     }
     Ok(conditions)
 }
+
+/// The `duration` of `elapsed_at_least`: `[unit, amount]` pairs, as in the
+/// core JSON form.
+fn get_duration(obj: &Object, path: &str) -> Result<ClockDuration, JsValue> {
+    let path = format!("{path}.duration");
+    let value = Reflect::get(obj, &JsValue::from_str("duration"))
+        .map_err(|_| at(&path, "expected an array"))?;
+    let array = value
+        .dyn_ref::<Array>()
+        .ok_or_else(|| at(&path, "expected an array"))?;
+
+    let mut duration = ClockDuration::new();
+    for idx in 0..array.length() {
+        let pair = array.get(idx);
+        let path = format!("{path}[{idx}]");
+        let pair = pair
+            .dyn_ref::<Array>()
+            .filter(|pair| pair.length() == 2)
+            .ok_or_else(|| at(&path, "expected a [unit, amount] pair"))?;
+        let pair = pair.unchecked_ref::<Object>();
+        let unit = field_string(pair, &path, "0")?;
+        let amount = field_u64(pair, &path, "1")?;
+        duration = duration.and(unit, amount);
+    }
+    Ok(duration)
+}
+
+fn field_u64(obj: &Object, path: &str, key: &str) -> Result<u64, JsValue> {
+    get_u64(obj, key).map_err(|err| field_error(path, key, err))
+}
+
+fn field_string(obj: &Object, path: &str, key: &str) -> Result<String, JsValue> {
+    get_string(obj, key).map_err(|err| field_error(path, key, err))
+}
+
+fn field_error(path: &str, key: &str, err: JsValue) -> JsValue {
+    let reason = err
+        .as_string()
+        .unwrap_or_else(|| String::from("invalid value"));
+    at(&format!("{path}.{key}"), &reason)
+}
+
+fn at(path: &str, reason: &str) -> JsValue {
+    JsValue::from_str(&format!("{path}: {reason}"))
+}
@@ -1,21 +1,44 @@
 //! WASM Clock wrapper.
 
-use beeclock_core::Clock;
-use js_sys::Uint32Array;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use beeclock_core::{raw, Clock, ClockConfig, ClockState, TickBuffer, TickOutcome};
+use js_sys::{Array, BigUint64Array, Function, Object, Uint32Array};
 use wasm_bindgen::prelude::*;
 
 use crate::bridge::{
-    clear_bits, ensure_len, fill_pulse_bits, fill_snapshot, outcome_to_js, snapshot_to_js,
-    write_u64, RAW_HEADER_WORDS,
+    clear_bits, clock_error_to_js, ensure_len, ensure_wide_len, explanation_to_js, fill_header,
+    fill_pulse_bits, fill_values, fill_wide, json_text, merge_pulse_bits, outcome_to_js,
+    outcome_to_js_json, pulse_to_js, set, snapshot_to_js, snapshot_to_js_json, strings_to_js,
+    write_u64, KEYS,
+};
+use crate::ticker::Ticker;
+use crate::types::{
+    JsClockSnapshot, JsExplanation, JsFrameCallback, JsPulseCallback, JsStringArray,
+    JsTickCallback, JsTickMany, JsTickOutcome, JsTickOutcomes,
 };
 
+/// Pulse callbacks from `on_pulse`, in registration order.
+pub(crate) type Callbacks = Rc<RefCell<Vec<(String, Function)>>>;
+
 /// WASM-friendly clock wrapper.
 #[wasm_bindgen]
 pub struct WasmClock {
-    pub(crate) inner: Clock,
+    /// Shared with a running ticker.
+    pub(crate) inner: Rc<RefCell<Clock>>,
     pub(crate) partition_count: usize,
+    pub(crate) partition_names: Vec<String>,
     pub(crate) partition_moduli: Vec<u64>,
     pub(crate) pulse_names: Vec<String>,
+    pub(crate) buffer: TickBuffer,
+    /// One `tick_into_ring` slot, assembled before copying out.
+    pub(crate) ring_slot: Vec<u32>,
+    pub(crate) callbacks: Callbacks,
+    /// Interval driving the clock after `start`.
+    pub(crate) ticker: Option<Ticker>,
+    /// Build outcome objects through JSON (see `set_json_outcomes`).
+    pub(crate) json_outcomes: bool,
 }
 
 #[wasm_bindgen]
@@ -23,66 +46,391 @@ impl WasmClock {
     /// Create a default clock (sec/min/hour).
     #[wasm_bindgen(constructor)]
     pub fn new() -> Result<WasmClock, JsValue> {
-        let inner = Clock::default();
-        let snapshot = inner.snapshot();
-        let partition_moduli = snapshot
-            .partitions
-            .iter()
-            .map(|part| part.modulus)
-            .collect::<Vec<_>>();
-        Ok(WasmClock {
-            inner,
-            partition_count: partition_moduli.len(),
-            partition_moduli,
-            pulse_names: Vec::new(),
-        })
+        Ok(WasmClock::from_clock(Clock::default()))
+    }
+
+    /// Build a clock from one configuration object, in the core JSON config
+    /// format: `{ order, partitions, pulses }`, plus optional `name`,
+    /// `quiet_windows`, and `run_for`. Accepts the object itself (numbers
+    /// or BigInts) or its JSON string.
+    pub fn from_config(config: JsValue) -> Result<WasmClock, JsValue> {
+        let config = ClockConfig::from_json(&json_text(&config)?)
+            .map_err(|err| JsValue::from_str(&format!("invalid clock config: {err}")))?;
+        config
+            .build()
+            .map(WasmClock::from_clock)
+            .map_err(|err| clock_error_to_js(&err))
+    }
+
+    /// Check a configuration object the way `from_config` would, without
+    /// building a clock; throws the same errors.
+    pub fn validate_config(config: JsValue) -> Result<(), JsValue> {
+        let config = ClockConfig::from_json(&json_text(&config)?)
+            .map_err(|err| JsValue::from_str(&format!("invalid clock config: {err}")))?;
+        config.validate().map_err(|err| clock_error_to_js(&err))
     }
 
     /// Advance the clock by one tick, returning the outcome as a JS object.
-    pub fn tick(&mut self) -> JsValue {
-        let outcome = self.inner.tick();
-        outcome_to_js(&outcome)
+    ///
+    /// Callbacks registered with `on_pulse` run before it returns; if one
+    /// throws, the error is rethrown after the clock has advanced.
+    pub fn tick(&mut self) -> Result<JsTickOutcome, JsValue> {
+        let outcome = self.inner.borrow_mut().tick();
+        Ok(self.deliver(&outcome)?.unchecked_into())
+    }
+
+    /// Accumulate `delta` ticks, which may be fractional, and perform each
+    /// whole tick crossed, returning their outcomes as from `tick()`. Call
+    /// it every frame with the playback speed (e.g. `1.5` or `0.25`); the
+    /// leftover fraction carries over.
+    pub fn advance_scaled(&mut self, delta: f64) -> Result<JsTickOutcomes, JsValue> {
+        let outcomes = self.inner.borrow_mut().advance_scaled(delta);
+        let list = Array::new_with_length(outcomes.len() as u32);
+        for (index, outcome) in outcomes.iter().enumerate() {
+            list.set(index as u32, self.deliver(outcome)?);
+        }
+        Ok(list.unchecked_into())
+    }
+
+    /// Fraction of a tick accumulated by `advance_scaled` and not yet
+    /// performed.
+    pub fn tick_fraction(&self) -> f64 {
+        self.inner.borrow().tick_fraction()
+    }
+
+    /// Choose how `tick()`, `tick_many()` and `snapshot()` build
+    /// their objects: field by field (the default), or as JSON text parsed
+    /// with one `JSON.parse`, which is faster for clocks with many
+    /// partitions. Both give the same objects.
+    pub fn set_json_outcomes(&mut self, enabled: bool) {
+        self.json_outcomes = enabled;
+    }
+
+    /// Call `callback` with the pulse object (as in `tick().pulses`) each
+    /// time the named pulse fires, from any of the tick methods.
+    ///
+    /// `__overflow__` and alarm names can be registered like pulse names.
+    pub fn on_pulse(&mut self, name: String, callback: JsPulseCallback) {
+        self.callbacks.borrow_mut().push((name, callback.into()));
+    }
+
+    /// Remove every callback registered for `name`, returning whether any
+    /// were.
+    pub fn off_pulse(&mut self, name: &str) -> bool {
+        let mut callbacks = self.callbacks.borrow_mut();
+        let before = callbacks.len();
+        callbacks.retain(|(pulse, _)| pulse != name);
+        callbacks.len() != before
+    }
+
+    /// Tick every `interval_ms` milliseconds on a `setInterval` timer,
+    /// calling `callback` with each tick's outcome (as from `tick()`).
+    ///
+    /// Ticks are paced by wall time, so after the timer is throttled (e.g.
+    /// in a background tab) the missed ticks run on its next firing, up to
+    /// 1000 at once; any further backlog is dropped. Ticking stops once the
+    /// clock halts. Replaces a running ticker.
+    pub fn start(&mut self, interval_ms: f64, callback: JsTickCallback) -> Result<(), JsValue> {
+        if !(interval_ms > 0.0 && interval_ms.is_finite()) {
+            return Err(JsValue::from_str("interval_ms must be a positive number"));
+        }
+        self.ticker = None;
+        self.ticker = Some(Ticker::start(
+            Rc::clone(&self.inner),
+            Rc::clone(&self.callbacks),
+            interval_ms,
+            callback.into(),
+        ));
+        Ok(())
+    }
+
+    /// Tick at `ticks_per_second` from `requestAnimationFrame`, calling
+    /// `callback` once per frame with `{ ticks, alpha, snapshot, pulses }`.
+    ///
+    /// Frame time accumulates and runs as many whole ticks as it covers;
+    /// `alpha` is the fraction of the next tick left over, for
+    /// interpolating between states. After a long pause (e.g. a hidden tab)
+    /// at most 1000 ticks are caught up. Replaces a running ticker.
+    pub fn start_frames(
+        &mut self,
+        ticks_per_second: f64,
+        callback: JsFrameCallback,
+    ) -> Result<(), JsValue> {
+        if !(ticks_per_second > 0.0 && ticks_per_second.is_finite()) {
+            return Err(JsValue::from_str(
+                "ticks_per_second must be a positive number",
+            ));
+        }
+        self.ticker = None;
+        self.ticker = Some(Ticker::start_frames(
+            Rc::clone(&self.inner),
+            Rc::clone(&self.callbacks),
+            ticks_per_second,
+            callback.into(),
+        ));
+        Ok(())
+    }
+
+    /// Stop the ticker started by `start` or `start_frames`, returning
+    /// whether one was running.
+    pub fn stop(&mut self) -> bool {
+        self.ticker.take().is_some()
+    }
+
+    /// Whether a ticker started by `start` or `start_frames` is running.
+    pub fn is_running(&self) -> bool {
+        self.ticker.is_some()
+    }
+
+    /// Advance the clock by `n` ticks in one call.
+    ///
+    /// With `outcomes` set, returns an array of every tick's outcome, as from
+    /// `tick()`. Otherwise returns `{ snapshot, pulse_bits }`: the final
+    /// snapshot and a `Uint32Array` OR of every tick's pulse bits, in the
+    /// `tick_raw` layout.
+    pub fn tick_many(&mut self, n: u32, outcomes: bool) -> Result<JsTickMany, JsValue> {
+        if outcomes {
+            let list = Array::new_with_length(n);
+            for index in 0..n {
+                list.set(index, self.tick()?.into());
+            }
+            return Ok(list.unchecked_into());
+        }
+
+        let mut words = vec![0u32; self.raw_pulse_words() as usize];
+        for _ in 0..n {
+            self.inner.borrow_mut().tick_into(&mut self.buffer);
+            let overflow_pulse = self.has_overflow_pulse();
+            merge_pulse_bits(
+                &mut words,
+                &self.buffer,
+                self.pulse_names.len(),
+                overflow_pulse,
+            );
+            self.notify_buffer()?;
+        }
+        let snapshot = self.snapshot()?;
+        let obj = Object::new();
+        KEYS.with(|keys| {
+            set(&obj, &keys.snapshot, &snapshot);
+            set(&obj, &keys.pulse_bits, &Uint32Array::from(&words[..]));
+        });
+        Ok(obj.unchecked_into())
+    }
+
+    /// Save the clock's full state as a JSON string (e.g. for
+    /// `localStorage`).
+    pub fn save_state(&self) -> JsValue {
+        JsValue::from_str(&self.inner.borrow().save_state().to_json())
+    }
+
+    /// Resume from a state saved with `save_state`, given as the JSON string
+    /// or the parsed object. Pulse callbacks and a running ticker carry on
+    /// with the restored clock.
+    pub fn restore_state(&mut self, state: JsValue) -> Result<(), JsValue> {
+        let state = ClockState::from_json(&json_text(&state)?)
+            .map_err(|err| JsValue::from_str(&format!("invalid clock state: {err}")))?;
+        let inner = Clock::from_state(state).map_err(|err| clock_error_to_js(&err))?;
+        self.inner.replace(inner);
+        self.cache_layout();
+        Ok(())
+    }
+
+    /// Tick at which `name` next fires, searching at most `horizon` ticks
+    /// ahead, or `undefined`. Runs on a copy; the clock is not advanced.
+    pub fn next_fire(&self, name: &str, horizon: u64) -> Option<u64> {
+        self.inner.borrow().next_fire(name, horizon)
+    }
+
+    /// Ticks of the next `count` fires of `name` within `horizon` ticks, as
+    /// a `BigUint64Array`. Runs on a copy; the clock is not advanced.
+    pub fn fire_schedule(&self, name: &str, count: u32, horizon: u64) -> BigUint64Array {
+        let fires = self
+            .inner
+            .borrow()
+            .fire_schedule(name, count as usize, horizon);
+        BigUint64Array::from(&fires[..])
+    }
+
+    /// Evaluation tree of the named pulse's condition at the current tick
+    /// (met or not per node, with reasons), or `undefined` for an unknown
+    /// pulse. `text` holds the tree formatted for the console.
+    pub fn explain(&self, name: &str) -> Option<JsExplanation> {
+        let explanation = self.inner.borrow().explain(name)?;
+        Some(explanation_to_js(&explanation).unchecked_into())
+    }
+
+    /// Stable fingerprint of partitions, order, and pulses, as a `bigint`.
+    /// Equal across Rust, WASM, and C for the same clock shape.
+    pub fn config_hash(&self) -> u64 {
+        self.inner.borrow().config_hash()
     }
 
     /// Get the current snapshot as a JS object.
-    pub fn snapshot(&self) -> JsValue {
-        snapshot_to_js(&self.inner.snapshot())
+    pub fn snapshot(&self) -> Result<JsClockSnapshot, JsValue> {
+        let snapshot = self.inner.borrow().snapshot();
+        let snapshot = match self.json_outcomes {
+            true => snapshot_to_js_json(&snapshot)?,
+            false => snapshot_to_js(&snapshot),
+        };
+        Ok(snapshot.unchecked_into())
     }
 
     /// Get the required length for raw snapshot buffer.
     pub fn raw_snapshot_len(&self) -> u32 {
-        RAW_HEADER_WORDS + (self.partition_count as u32) * 2
+        raw::snapshot_len(self.partition_count) as u32
     }
 
     /// Get the number of u32 words needed for pulse bits.
     pub fn raw_pulse_words(&self) -> u32 {
-        let bits = self.pulse_names.len() as u32 + 1;
-        (bits + 31) / 32
+        raw::pulse_words(self.pulse_names.len()) as u32
     }
 
     /// Write snapshot to a raw Uint32Array (zero-copy path).
     pub fn snapshot_raw(&self, out: &Uint32Array) -> Result<(), JsValue> {
-        let snapshot = self.inner.snapshot();
         ensure_len(out, self.raw_snapshot_len(), "snapshot_raw")?;
-        fill_snapshot(out, &snapshot, false);
+        let inner = self.inner.borrow();
+        fill_header(
+            out,
+            inner.tick_count(),
+            inner.epoch(),
+            false,
+            self.partition_count as u32,
+        );
+        fill_values(out, inner.partition_values());
         Ok(())
     }
 
-    /// Tick and write results to raw buffers (zero-copy path).
+    /// Tick and write results to raw buffers (zero-copy path). Throws
+    /// without ticking if either buffer is too short.
     pub fn tick_raw(
         &mut self,
         snapshot_out: &Uint32Array,
         pulse_bits_out: &Uint32Array,
     ) -> Result<(), JsValue> {
-        let outcome = self.inner.tick();
         ensure_len(snapshot_out, self.raw_snapshot_len(), "tick_raw")?;
         ensure_len(pulse_bits_out, self.raw_pulse_words(), "tick_raw")?;
-        fill_snapshot(snapshot_out, &outcome.snapshot, outcome.overflowed);
+        self.inner.borrow_mut().tick_into(&mut self.buffer);
+        let buffer = &self.buffer;
+        fill_header(
+            snapshot_out,
+            buffer.tick,
+            buffer.epoch,
+            buffer.overflowed,
+            self.partition_count as u32,
+        );
+        fill_values(snapshot_out, &buffer.values);
         clear_bits(pulse_bits_out);
-        fill_pulse_bits(pulse_bits_out, &self.pulse_names, &outcome);
+        let overflow_pulse = self.has_overflow_pulse();
+        fill_pulse_bits(
+            pulse_bits_out,
+            buffer,
+            self.pulse_names.len(),
+            overflow_pulse,
+        );
+        self.notify_buffer()
+    }
+
+    /// Get the required length for the wide (`BigUint64Array`) snapshot
+    /// buffer.
+    pub fn raw_snapshot_len_wide(&self) -> u32 {
+        raw::wide_snapshot_len(self.partition_count) as u32
+    }
+
+    /// Write snapshot to a raw BigUint64Array, keeping tick, epoch and
+    /// values exact past 2^53.
+    pub fn snapshot_raw_wide(&self, out: &BigUint64Array) -> Result<(), JsValue> {
+        ensure_wide_len(out, self.raw_snapshot_len_wide(), "snapshot_raw_wide")?;
+        let inner = self.inner.borrow();
+        fill_wide(
+            out,
+            inner.tick_count(),
+            inner.epoch(),
+            false,
+            inner.partition_values(),
+        );
         Ok(())
     }
 
+    /// Like `tick_raw`, with the snapshot written in the wide layout.
+    pub fn tick_raw_wide(
+        &mut self,
+        snapshot_out: &BigUint64Array,
+        pulse_bits_out: &Uint32Array,
+    ) -> Result<(), JsValue> {
+        ensure_wide_len(snapshot_out, self.raw_snapshot_len_wide(), "tick_raw_wide")?;
+        ensure_len(pulse_bits_out, self.raw_pulse_words(), "tick_raw_wide")?;
+        self.inner.borrow_mut().tick_into(&mut self.buffer);
+        let buffer = &self.buffer;
+        fill_wide(
+            snapshot_out,
+            buffer.tick,
+            buffer.epoch,
+            buffer.overflowed,
+            &buffer.values,
+        );
+        clear_bits(pulse_bits_out);
+        let overflow_pulse = self.has_overflow_pulse();
+        fill_pulse_bits(
+            pulse_bits_out,
+            buffer,
+            self.pulse_names.len(),
+            overflow_pulse,
+        );
+        self.notify_buffer()
+    }
+
+    /// Words per `tick_into_ring` slot: a raw snapshot followed by the
+    /// pulse bits.
+    pub fn raw_ring_slot_words(&self) -> u32 {
+        self.raw_snapshot_len() + self.raw_pulse_words()
+    }
+
+    /// Tick once and write the outcome into slot `head` of a ring of
+    /// `capacity` slots in `buffer`, returning the next head.
+    ///
+    /// Each slot is `raw_ring_slot_words()` words: the `snapshot_raw` layout,
+    /// then the `tick_raw` pulse bits. Lets a worker tick into a shared
+    /// buffer that the main thread drains in batches, with no per-tick
+    /// objects built.
+    pub fn tick_into_ring(
+        &mut self,
+        buffer: &Uint32Array,
+        capacity: u32,
+        head: u32,
+    ) -> Result<u32, JsValue> {
+        let slot_words = self.raw_ring_slot_words();
+        if head >= capacity {
+            return Err(JsValue::from_str(&format!(
+                "ring head {head} out of range for capacity {capacity}"
+            )));
+        }
+        let required = capacity
+            .checked_mul(slot_words)
+            .ok_or_else(|| JsValue::from_str("ring capacity too large"))?;
+        ensure_len(buffer, required, "tick_into_ring")?;
+
+        let snapshot_len = self.raw_snapshot_len() as usize;
+        self.inner.borrow_mut().tick_into(&mut self.buffer);
+        let (snapshot, bits) = self.ring_slot.split_at_mut(snapshot_len);
+        let tick = &self.buffer;
+        raw::write_snapshot(
+            snapshot,
+            tick.tick,
+            tick.epoch,
+            tick.overflowed,
+            &tick.values,
+        );
+        let overflow_pulse = self.inner.borrow().overflow_pulse().is_some();
+        raw::write_pulse_bits(bits, tick, self.pulse_names.len(), overflow_pulse);
+        let start = head * slot_words;
+        buffer
+            .subarray(start, start + slot_words)
+            .copy_from(&self.ring_slot);
+        self.notify_buffer()?;
+        Ok((head + 1) % capacity)
+    }
+
     /// Write partition moduli to a raw buffer.
     pub fn partition_moduli_raw(&self, out: &Uint32Array) -> Result<(), JsValue> {
         let required = (self.partition_count as u32) * 2;
@@ -93,4 +441,129 @@ impl WasmClock {
         }
         Ok(())
     }
+
+    /// Partition names, in the order of the raw snapshot values.
+    pub fn partition_names(&self) -> JsStringArray {
+        strings_to_js(&self.partition_names).unchecked_into()
+    }
+
+    /// Pulse names; pulse `i` sets bit `i` of the raw pulse bits.
+    pub fn pulse_names(&self) -> JsStringArray {
+        strings_to_js(&self.pulse_names).unchecked_into()
+    }
+
+    /// Bit a pulse sets in the raw pulse bits, by name. The overflow pulse
+    /// (`__overflow__` unless renamed) maps to the bit after the last pulse,
+    /// which is set on overflow even when the pulse is disabled.
+    pub fn pulse_bit(&self, name: &str) -> Option<u32> {
+        if self.inner.borrow().overflow_pulse() == Some(name) {
+            return Some(self.pulse_names.len() as u32);
+        }
+        self.pulse_names
+            .iter()
+            .position(|pulse| pulse == name)
+            .map(|idx| idx as u32)
+    }
+}
+
+impl WasmClock {
+    /// Wrap a core clock, caching its layout for the raw paths.
+    pub(crate) fn from_clock(inner: Clock) -> WasmClock {
+        let mut clock = WasmClock {
+            inner: Rc::new(RefCell::new(inner)),
+            partition_count: 0,
+            partition_names: Vec::new(),
+            partition_moduli: Vec::new(),
+            pulse_names: Vec::new(),
+            buffer: TickBuffer::default(),
+            ring_slot: Vec::new(),
+            callbacks: Callbacks::default(),
+            ticker: None,
+            json_outcomes: false,
+        };
+        clock.cache_layout();
+        clock
+    }
+
+    /// Refresh the layout cached from the inner clock.
+    fn cache_layout(&mut self) {
+        let inner = self.inner.borrow();
+        (self.partition_names, self.partition_moduli) = inner
+            .snapshot()
+            .partitions
+            .into_iter()
+            .map(|part| (String::from(&*part.name), part.modulus))
+            .unzip();
+        self.partition_count = self.partition_moduli.len();
+        self.pulse_names = (0..inner.pulse_count())
+            .filter_map(|idx| inner.pulse_name(idx).map(String::from))
+            .collect();
+        self.buffer = inner.tick_buffer();
+        self.ring_slot = vec![0; self.raw_ring_slot_words() as usize];
+    }
+
+    /// Whether overflow sets the pulse bit after the last pulse.
+    fn has_overflow_pulse(&self) -> bool {
+        self.inner.borrow().overflow_pulse().is_some()
+    }
+
+    /// Invoke callbacks for everything that fired into `self.buffer`.
+    fn notify_buffer(&self) -> Result<(), JsValue> {
+        if self.callbacks.borrow().is_empty() {
+            return Ok(());
+        }
+        let buffer = &self.buffer;
+        let fired = buffer
+            .fired
+            .iter()
+            .map(|&idx| self.pulse_names[idx].as_str());
+        let alarms = buffer.alarms.iter().map(|name| &**name);
+        // Copied out so callbacks can use the clock.
+        let overflow = match buffer.overflowed {
+            true => self.inner.borrow().overflow_pulse().map(String::from),
+            false => None,
+        };
+        let halted = buffer.halted.then_some("__halted__");
+        for name in fired.chain(alarms).chain(overflow.as_deref()).chain(halted) {
+            notify(&self.callbacks, name, buffer.tick, buffer.epoch)?;
+        }
+        Ok(())
+    }
+
+    /// Invoke callbacks for `outcome`'s pulses and convert it for JS.
+    fn deliver(&self, outcome: &TickOutcome) -> Result<JsValue, JsValue> {
+        for pulse in outcome.pulses.iter() {
+            notify(&self.callbacks, &pulse.name, pulse.tick, pulse.epoch)?;
+        }
+        match self.json_outcomes {
+            true => outcome_to_js_json(outcome),
+            false => Ok(outcome_to_js(outcome)),
+        }
+    }
+}
+
+/// Invoke the callbacks registered for `name`.
+///
+/// The list is not borrowed while they run, so callbacks may register or
+/// remove others.
+pub(crate) fn notify(
+    callbacks: &Callbacks,
+    name: &str,
+    tick: u64,
+    epoch: u64,
+) -> Result<(), JsValue> {
+    let matching: Vec<Function> = callbacks
+        .borrow()
+        .iter()
+        .filter(|(pulse, _)| pulse == name)
+        .map(|(_, callback)| callback.clone())
+        .collect();
+    if matching.is_empty() {
+        return Ok(());
+    }
+    let pulse = pulse_to_js(name, tick, epoch);
+    for callback in &matching {
+        callback.call1(&JsValue::NULL, &pulse)?;
+    }
+    Ok(())
 }
@@ -7,6 +7,8 @@
 mod bridge;
 mod builder;
 mod clock;
+mod ticker;
+mod types;
 
 pub use builder::WasmClockBuilder;
 pub use clock::WasmClock;
@@ -0,0 +1,153 @@
+//! Wall-clock driven ticking for `WasmClock::start` and
+//! `WasmClock::start_frames`.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use beeclock_core::{Clock, TickOutcome};
+use js_sys::{Array, Date, Function};
+use wasm_bindgen::prelude::*;
+
+use crate::bridge::{frame_to_js, outcome_to_js, pulse_to_js};
+use crate::clock::{notify, Callbacks};
+
+/// Most ticks run on one timer firing or frame; a longer backlog is dropped.
+const MAX_CATCH_UP: u64 = 1000;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setInterval)]
+    fn set_interval(handler: &Function, timeout: f64) -> JsValue;
+
+    #[wasm_bindgen(js_name = clearInterval)]
+    fn clear_interval(id: &JsValue);
+
+    #[wasm_bindgen(js_name = requestAnimationFrame)]
+    fn request_animation_frame(handler: &Function) -> i32;
+
+    #[wasm_bindgen(js_name = cancelAnimationFrame)]
+    fn cancel_animation_frame(id: i32);
+}
+
+type FrameHandler = Closure<dyn FnMut(f64) -> Result<(), JsValue>>;
+
+/// A running timer driving a clock, cancelled on drop.
+pub(crate) enum Ticker {
+    Interval {
+        id: JsValue,
+        _handler: Closure<dyn FnMut() -> Result<(), JsValue>>,
+    },
+    /// The handler reschedules itself each frame through a weak handle.
+    Frame {
+        id: Rc<Cell<i32>>,
+        _handler: Rc<RefCell<Option<FrameHandler>>>,
+    },
+}
+
+impl Ticker {
+    /// Tick `clock` once per `interval_ms` of wall time, calling `on_tick`
+    /// with each outcome.
+    pub fn start(
+        clock: Rc<RefCell<Clock>>,
+        callbacks: Callbacks,
+        interval_ms: f64,
+        on_tick: Function,
+    ) -> Ticker {
+        let started = Date::now();
+        let mut ran: u64 = 0;
+        let handler = Closure::<dyn FnMut() -> Result<(), JsValue>>::new(move || {
+            let due = ((Date::now() - started) / interval_ms) as u64;
+            let behind = due.saturating_sub(ran);
+            ran = due - behind.min(MAX_CATCH_UP);
+            for _ in 0..behind.min(MAX_CATCH_UP) {
+                ran += 1;
+                let Some(outcome) = tick(&clock, &callbacks)? else {
+                    break;
+                };
+                on_tick.call1(&JsValue::NULL, &outcome_to_js(&outcome))?;
+            }
+            Ok(())
+        });
+        let id = set_interval(handler.as_ref().unchecked_ref(), interval_ms);
+        Ticker::Interval {
+            id,
+            _handler: handler,
+        }
+    }
+
+    /// Tick `clock` `ticks_per_second` times per second of frame time,
+    /// calling `on_frame` once per animation frame with the frame summary.
+    pub fn start_frames(
+        clock: Rc<RefCell<Clock>>,
+        callbacks: Callbacks,
+        ticks_per_second: f64,
+        on_frame: Function,
+    ) -> Ticker {
+        let step = 1000.0 / ticks_per_second;
+        let id = Rc::new(Cell::new(0));
+        let handler: Rc<RefCell<Option<FrameHandler>>> = Rc::default();
+        let mut last: Option<f64> = None;
+        let mut accumulated = 0.0;
+
+        let (frame_id, this) = (Rc::clone(&id), Rc::downgrade(&handler));
+        let frame = FrameHandler::new(move |now: f64| {
+            // Schedule the next frame first, so `stop()` from the callback
+            // cancels it.
+            if let Some(this) = this.upgrade() {
+                if let Some(next) = this.borrow().as_ref() {
+                    frame_id.set(request_animation_frame(next.as_ref().unchecked_ref()));
+                }
+            }
+            accumulated += last.map_or(0.0, |last| now - last);
+            accumulated = accumulated.min(step * MAX_CATCH_UP as f64);
+            last = Some(now);
+
+            let pulses = Array::new();
+            let mut ticks = 0u32;
+            while accumulated >= step {
+                accumulated -= step;
+                let Some(outcome) = tick(&clock, &callbacks)? else {
+                    accumulated = 0.0;
+                    break;
+                };
+                for pulse in outcome.pulses.iter() {
+                    pulses.push(&pulse_to_js(&pulse.name, pulse.tick, pulse.epoch));
+                }
+                ticks += 1;
+            }
+            let snapshot = clock.borrow().snapshot();
+            let frame = frame_to_js(ticks, accumulated / step, &snapshot, &pulses);
+            on_frame.call1(&JsValue::NULL, &frame)?;
+            Ok(())
+        });
+        id.set(request_animation_frame(frame.as_ref().unchecked_ref()));
+        *handler.borrow_mut() = Some(frame);
+        Ticker::Frame {
+            id,
+            _handler: handler,
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        match self {
+            Ticker::Interval { id, .. } => clear_interval(id),
+            Ticker::Frame { id, .. } => cancel_animation_frame(id.get()),
+        }
+    }
+}
+
+/// Tick `clock` and run its pulse callbacks, or `None` once it has halted.
+///
+/// The clock is released before calling out, so JS can use it.
+fn tick(clock: &RefCell<Clock>, callbacks: &Callbacks) -> Result<Option<TickOutcome>, JsValue> {
+    if clock.borrow().is_halted() {
+        return Ok(None);
+    }
+    let outcome = clock.borrow_mut().tick();
+    for pulse in outcome.pulses.iter() {
+        notify(callbacks, &pulse.name, pulse.tick, pulse.epoch)?;
+    }
+    Ok(Some(outcome))
+}
@@ -0,0 +1,120 @@
+//! TypeScript definitions for the objects built in `bridge`.
+//!
+//! The interfaces are emitted into the generated `.d.ts`; the extern types
+//! below name them in method signatures. Keep both in step with the keys set
+//! in `bridge`.
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface PartitionState {
+    name: string;
+    value: number;
+    modulus: number;
+    /** Labels, present only when set on the partition. */
+    display_name?: string;
+    unit?: string;
+    description?: string;
+}
+
+export interface ClockSnapshot {
+    /** Rounded past 2^53; see `tick_big`. */
+    tick: number;
+    tick_str: string;
+    tick_big: bigint;
+    /** Rounded past 2^53; see `epoch_big`. */
+    epoch: number;
+    epoch_str: string;
+    epoch_big: bigint;
+    partitions: PartitionState[];
+}
+
+export interface PulseFired {
+    name: string;
+    tick: number;
+    tick_str: string;
+    tick_big: bigint;
+    epoch: number;
+    epoch_str: string;
+    epoch_big: bigint;
+}
+
+export interface TickOutcome {
+    snapshot: ClockSnapshot;
+    overflowed: boolean;
+    pulses: PulseFired[];
+}
+
+export interface FrameInfo {
+    /** Ticks run this frame. */
+    ticks: number;
+    /** Fraction of the next tick already elapsed, for interpolation. */
+    alpha: number;
+    snapshot: ClockSnapshot;
+    /** Pulses fired over this frame's ticks. */
+    pulses: PulseFired[];
+}
+
+export interface ExplainNode {
+    /** What the node tests, e.g. `hour == 12`. */
+    summary: string;
+    met: boolean;
+    /** Why, e.g. `hour is 11`. */
+    reason: string;
+    children: ExplainNode[];
+}
+
+export interface Explanation {
+    pulse: string;
+    tick: number;
+    tick_big: bigint;
+    fired: boolean;
+    /** The tree as indented text, for the console. */
+    text: string;
+    root: ExplainNode;
+}
+
+/** Thrown when a clock cannot be built or restored. */
+export interface ClockError extends Error {
+    name: "ClockError";
+    /** Stable across releases; the hundreds digit is the category. */
+    code: number;
+    category: "partition" | "pulse" | "state" | "clock";
+}
+
+export interface TickManySummary {
+    snapshot: ClockSnapshot;
+    pulse_bits: Uint32Array;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "TickOutcome")]
+    pub type JsTickOutcome;
+
+    #[wasm_bindgen(typescript_type = "ClockSnapshot")]
+    pub type JsClockSnapshot;
+
+    #[wasm_bindgen(typescript_type = "TickOutcome[]")]
+    pub type JsTickOutcomes;
+
+    #[wasm_bindgen(typescript_type = "TickOutcome[] | TickManySummary")]
+    pub type JsTickMany;
+
+    #[wasm_bindgen(typescript_type = "Explanation")]
+    pub type JsExplanation;
+
+    #[wasm_bindgen(typescript_type = "string[]")]
+    pub type JsStringArray;
+
+    #[wasm_bindgen(extends = js_sys::Function, typescript_type = "(pulse: PulseFired) => void")]
+    pub type JsPulseCallback;
+
+    #[wasm_bindgen(extends = js_sys::Function, typescript_type = "(outcome: TickOutcome) => void")]
+    pub type JsTickCallback;
+
+    #[wasm_bindgen(extends = js_sys::Function, typescript_type = "(frame: FrameInfo) => void")]
+    pub type JsFrameCallback;
+}
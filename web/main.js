@@ -45,6 +45,7 @@ const params = new URLSearchParams(window.location.search);
 const profileEnabled = params.has("profile");
 const traceEnabled = params.has("trace");
 const traceEvery = Number(params.get("trace_every")) || 60;
+const benchTicks = Number(params.get("bench")) || 0;
 let traceFrame = 0;
 let resizeObserver = null;
 
@@ -286,9 +287,33 @@ async function start() {
   if (profileEnabled && perfEl) {
     perf = new PerfMeter(perfEl);
   }
+  if (benchTicks > 0) {
+    benchOutcomes(benchTicks);
+  }
   requestAnimationFrame(frame);
 }
 
+// Compare field-by-field and JSON outcome building on a wide clock.
+function benchOutcomes(ticks) {
+  const results = [];
+  for (const json of [false, true]) {
+    const builder = new WasmClockBuilder().set_partition_order("lsf");
+    for (let i = 0; i < 256; i += 1) {
+      builder.partition(`p${i}`, 2n);
+    }
+    const bench = builder.pulse_every("beat", 1n).build();
+    bench.set_json_outcomes(json);
+    const begin = performance.now();
+    for (let i = 0; i < ticks; i += 1) {
+      bench.tick();
+    }
+    const ms = performance.now() - begin;
+    results.push({ mode: json ? "json" : "objects", ticks, ms, usPerTick: (ms * 1000) / ticks });
+    bench.free();
+  }
+  console.table(results);
+}
+
 class PerfMeter {
   constructor(el) {
     this.el = el;